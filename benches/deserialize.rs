@@ -0,0 +1,69 @@
+//! Measures deserializing record-heavy documents into `Obj`
+//!
+//! Run with `cargo bench --bench deserialize`. The documents are lists of small records, the
+//! typical shape of API responses and log files, so most maps are stored in the compact
+//! representation of `ObjMap`. `serde_json::Value`, whose maps are `BTreeMap`s, is measured as
+//! reference.
+
+extern crate serde;
+extern crate serde_json;
+extern crate rmp_serde;
+extern crate serde_utils;
+
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+use serde_utils::Obj;
+
+const RECORDS: usize = 10_000;
+const ROUNDS: usize = 20;
+
+fn document() -> serde_json::Value {
+    let records: Vec<serde_json::Value> = (0..RECORDS).map(|i| serde_json::json!({
+        "id": i,
+        "name": format!("user{}", i),
+        "email": format!("user{}@example.com", i),
+        "active": i % 3 != 0,
+        "score": i as f64 / 7.0,
+        "tags": ["a", "b"],
+        "address": {"city": "Berlin", "zip": format!("{:05}", i % 100_000)},
+        "created": 1_600_000_000 + i as u64
+    })).collect();
+    serde_json::Value::Array(records)
+}
+
+fn measure<F: FnMut()>(name: &str, bytes: usize, mut f: F) {
+    f();
+    let mut best = Duration::MAX;
+    for _ in 0..ROUNDS {
+        let start = Instant::now();
+        f();
+        best = best.min(start.elapsed());
+    }
+    let mbps = bytes as f64 / best.as_secs_f64() / 1e6;
+    println!("{:<28} {:>10.3} ms {:>10.1} MB/s", name, best.as_secs_f64() * 1e3, mbps);
+}
+
+fn main() {
+    let doc = document();
+    let json = serde_json::to_vec(&doc).unwrap();
+    let msgpack = rmp_serde::to_vec(&doc).unwrap();
+    println!("{} records, {} bytes JSON, {} bytes MessagePack", RECORDS, json.len(), msgpack.len());
+    measure("json -> Obj", json.len(), || {
+        let obj: Obj = serde_json::from_slice(&json).unwrap();
+        assert_eq!(obj.pointer("0/address/city").as_deref(), Some(&Obj::Str("Berlin".to_string())));
+    });
+    measure("json -> serde_json::Value", json.len(), || {
+        let val: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(val[0]["address"]["city"], "Berlin");
+    });
+    measure("msgpack -> Obj", msgpack.len(), || {
+        let obj = Obj::deserialize(&mut rmp_serde::Deserializer::new(&msgpack[..])).unwrap();
+        assert_eq!(obj.pointer("0/address/city").as_deref(), Some(&Obj::Str("Berlin".to_string())));
+    });
+    measure("lookups in Obj records", json.len(), || {
+        let obj: Obj = serde_json::from_slice(&json).unwrap();
+        let active = (0..RECORDS).filter(|i| obj.pointer(&format!("{}/active", i)).as_deref() == Some(&Obj::Bool(true))).count();
+        assert!(active > 0);
+    });
+}
@@ -0,0 +1,275 @@
+//! Derive macro for `serde_utils`
+//!
+//! This crate provides `#[derive(SerdeImpl)]` which expands to the same implementations as the
+//! `serde_impl!` macro of `serde_utils`, but reads the field list and keys from the type
+//! definition itself. It should be used via the `serde_utils_derive` feature of `serde_utils`.
+
+extern crate proc_macro;
+extern crate proc_macro2;
+#[macro_use] extern crate syn;
+#[macro_use] extern crate quote;
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use syn::{token, Attribute, Data, DeriveInput, Error, Expr, ExprLit, Fields, Ident, Lit, LitStr, Path};
+
+/// The options that can only be used on the type
+const TYPE_OPTIONS: &[&str] = &["crate", "key_type", "compressed", "required", "strict", "tuple", "roundtrip"];
+
+/// The options that can only be used on fields, `key` is also used on variants
+const FIELD_OPTIONS: &[&str] = &["key", "skip", "is_default"];
+
+/// Derives `Serialize` and `Deserialize` like `serde_impl!`
+///
+/// See the documentation of `serde_utils` for the options of the `#[serde_utils(..)]` attribute.
+#[proc_macro_derive(SerdeImpl, attributes(serde_utils))]
+pub fn derive_serde_impl(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    match expand(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into()
+    }
+}
+
+fn expand(input: &DeriveInput) -> Result<TokenStream2, Error> {
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(&input.generics, "SerdeImpl does not support parametrized data types"));
+    }
+    let opts = options(&input.attrs)?;
+    check_no_opt(&opts, FIELD_OPTIONS)?;
+    let body = match input.data {
+        Data::Struct(ref data) => expand_struct(input, &opts, &data.fields)?,
+        Data::Enum(ref data) => {
+            let mut variants = Vec::new();
+            for variant in &data.variants {
+                let vopts = options(&variant.attrs)?;
+                check_no_opt(&vopts, TYPE_OPTIONS)?;
+                check_no_opt(&vopts, &["skip", "is_default"])?;
+                variants.push((&variant.ident, &variant.fields, find_key(&vopts)?));
+            }
+            expand_enum(input, &opts, variants)?
+        },
+        Data::Union(_) => return Err(Error::new_spanned(&input.ident, "SerdeImpl does not support unions"))
+    };
+    let krate = match find_opt(&opts, "crate") {
+        Some(opt) => {
+            let path: Path = opt_value::<LitStr>(opt)?.parse()?;
+            quote!(#path)
+        },
+        None => quote!(::serde_utils)
+    };
+    if has_flag(&opts, "roundtrip")? {
+        return Ok(quote!( #krate::serde_impl!(roundtrip; #body); ));
+    }
+    Ok(quote!( #krate::serde_impl!(#body); ))
+}
+
+fn expand_struct(input: &DeriveInput, opts: &[Opt], fields: &Fields) -> Result<TokenStream2, Error> {
+    let name = &input.ident;
+    let fields = match *fields {
+        Fields::Named(ref fields) => &fields.named,
+        _ => return Err(Error::new_spanned(name, "SerdeImpl only supports structs with named fields"))
+    };
+    let compressed = has_flag(opts, "compressed")?;
+    let required = has_flag(opts, "required")?;
+    if has_flag(opts, "tuple")? {
+        check_no_opt(opts, &["compressed", "required", "strict", "key_type"])?;
+        let mut defs = Vec::new();
+        for field in fields {
+            let fopts = options(&field.attrs)?;
+            check_no_opt(&fopts, TYPE_OPTIONS)?;
+            check_no_opt(&fopts, FIELD_OPTIONS)?;
+            let (fname, ftype) = (&field.ident, &field.ty);
+            defs.push(quote!( #fname: #ftype ));
+        }
+        return Ok(quote!( #name { #(#defs),* } ));
+    }
+    if compressed && required {
+        return Err(Error::new_spanned(name, "compressed and required can not be combined"));
+    }
+    let mut entries = Vec::new();
+    for field in fields {
+        let fopts = options(&field.attrs)?;
+        check_no_opt(&fopts, TYPE_OPTIONS)?;
+        if has_flag(&fopts, "skip")? {
+            if required {
+                return Err(Error::new_spanned(field, "skip can not be used with required"));
+            }
+            continue
+        }
+        let fname = field.ident.as_ref().unwrap();
+        let pred = match find_opt(&fopts, "is_default") {
+            Some(opt) if !compressed => return Err(Error::new_spanned(&opt.name, "is_default(..) requires compressed")),
+            Some(opt) => {
+                let expr: Expr = opt_args(opt)?;
+                Some(quote!( #[is_default(#expr)] ))
+            },
+            None => None
+        };
+        entries.push((fname, &field.ty, find_key(&fopts)?, pred));
+    }
+    if entries.is_empty() {
+        return Err(Error::new_spanned(name, "SerdeImpl needs at least one field that is not skipped"));
+    }
+    let ktype = key_type(opts, entries.iter().map(|&(fname, _, ref key, _)| (fname, key.as_ref())))?;
+    let mut defs = Vec::new();
+    for (fname, ftype, key, pred) in entries {
+        let key = key_or_name(key, fname, &ktype)?;
+        defs.push(quote!( #pred #fname: #ftype => #key ));
+    }
+    let strict = if has_flag(opts, "strict")? {
+        quote!(strict)
+    } else {
+        quote!()
+    };
+    let flag = if compressed {
+        quote!(?)
+    } else if required {
+        quote!(!)
+    } else {
+        quote!()
+    };
+    Ok(quote!( #name(#ktype #flag) #strict { #(#defs),* } ))
+}
+
+fn expand_enum(input: &DeriveInput, opts: &[Opt], variants: Vec<(&Ident, &Fields, Option<Expr>)>) -> Result<TokenStream2, Error> {
+    let name = &input.ident;
+    check_no_opt(opts, &["compressed", "required", "strict", "tuple"])?;
+    if variants.is_empty() {
+        return Err(Error::new_spanned(name, "SerdeImpl does not support empty enums"));
+    }
+    let ktype = key_type(opts, variants.iter().map(|&(vname, _, ref key)| (vname, key.as_ref())))?;
+    let with_params = !matches!(*variants[0].1, Fields::Unit);
+    let mut defs = Vec::new();
+    for (vname, fields, key) in variants {
+        let key = key_or_name(key, vname, &ktype)?;
+        match (with_params, fields) {
+            (false, Fields::Unit) => defs.push(quote!( #vname => #key )),
+            (true, Fields::Unnamed(fields)) if fields.unnamed.len() == 1 => {
+                let ftype = &fields.unnamed[0].ty;
+                defs.push(quote!( #vname(#ftype) => #key ))
+            },
+            _ => return Err(Error::new_spanned(vname,
+                "SerdeImpl requires all variants to be either unit variants or to have exactly one unnamed parameter"))
+        }
+    }
+    Ok(quote!( #name(#ktype) { #(#defs),* } ))
+}
+
+/// An option of a `#[serde_utils(..)]` attribute like `skip`, `key = 1` or `key_type(u8)`
+struct Opt {
+    name: Ident,
+    value: OptValue
+}
+
+enum OptValue {
+    Flag,
+    Value(Expr),
+    Args(TokenStream2)
+}
+
+/// Collects the options of all `#[serde_utils(..)]` attributes
+fn options(attrs: &[Attribute]) -> Result<Vec<Opt>, Error> {
+    let mut opts = Vec::new();
+    for attr in attrs {
+        if !attr.path().is_ident("serde_utils") {
+            continue
+        }
+        attr.parse_nested_meta(|meta| {
+            let name = match meta.path.get_ident() {
+                Some(name) if TYPE_OPTIONS.iter().chain(FIELD_OPTIONS).any(|known| name == known) => name.clone(),
+                _ => return Err(meta.error("unknown serde_utils option"))
+            };
+            let value = if meta.input.peek(Token![=]) {
+                OptValue::Value(meta.value()?.parse()?)
+            } else if meta.input.peek(token::Paren) {
+                let args;
+                parenthesized!(args in meta.input);
+                OptValue::Args(args.parse()?)
+            } else {
+                OptValue::Flag
+            };
+            opts.push(Opt { name, value });
+            Ok(())
+        })?;
+    }
+    Ok(opts)
+}
+
+fn find_opt<'a>(opts: &'a [Opt], name: &str) -> Option<&'a Opt> {
+    opts.iter().find(|opt| opt.name == name)
+}
+
+fn has_flag(opts: &[Opt], name: &str) -> Result<bool, Error> {
+    match find_opt(opts, name) {
+        Some(&Opt { value: OptValue::Flag, .. }) => Ok(true),
+        Some(opt) => Err(Error::new_spanned(&opt.name, format!("{} does not take a value", name))),
+        None => Ok(false)
+    }
+}
+
+fn check_no_opt(opts: &[Opt], names: &[&str]) -> Result<(), Error> {
+    for opt in opts {
+        if names.iter().any(|name| opt.name == name) {
+            return Err(Error::new_spanned(&opt.name, format!("#[serde_utils({})] can not be used here", opt.name)));
+        }
+    }
+    Ok(())
+}
+
+/// Parses the value of an option like `key = ..`
+fn opt_value<T: syn::parse::Parse>(opt: &Opt) -> Result<T, Error> {
+    match opt.value {
+        OptValue::Value(ref value) => syn::parse2(quote!(#value)),
+        _ => Err(Error::new_spanned(&opt.name, format!("expected {} = ..", opt.name)))
+    }
+}
+
+/// Parses the arguments of an option like `key_type(..)`
+fn opt_args<T: syn::parse::Parse>(opt: &Opt) -> Result<T, Error> {
+    match opt.value {
+        OptValue::Args(ref args) => syn::parse2(args.clone()),
+        _ => Err(Error::new_spanned(&opt.name, format!("expected {}(..)", opt.name)))
+    }
+}
+
+fn find_key(opts: &[Opt]) -> Result<Option<Expr>, Error> {
+    find_opt(opts, "key").map(opt_value).transpose()
+}
+
+/// Determines the key type from `key_type(..)` or from the kind of the key literals
+fn key_type<'a, I: Iterator<Item=(&'a Ident, Option<&'a Expr>)>>(opts: &[Opt], keys: I) -> Result<Ident, Error> {
+    if let Some(opt) = find_opt(opts, "key_type") {
+        return opt_args(opt);
+    }
+    let mut ktype = None;
+    for (name, key) in keys {
+        let this = match key {
+            None => Ident::new("String", Span::call_site()),
+            Some(&Expr::Lit(ExprLit { lit: Lit::Str(_), .. })) => Ident::new("String", Span::call_site()),
+            Some(&Expr::Lit(ExprLit { lit: Lit::Int(ref lit), .. })) => match lit.suffix() {
+                "" => Ident::new("u64", Span::call_site()),
+                suffix => Ident::new(suffix, Span::call_site())
+            },
+            Some(key) => return Err(Error::new_spanned(key,
+                "the key type can not be inferred from this key, use #[serde_utils(key_type(..))] on the type")),
+        };
+        match ktype {
+            None => ktype = Some(this),
+            Some(ref ktype) if *ktype == this => (),
+            Some(_) => return Err(Error::new_spanned(name, "keys of different types, use #[serde_utils(key_type(..))] on the type"))
+        }
+    }
+    Ok(ktype.unwrap())
+}
+
+fn key_or_name(key: Option<Expr>, name: &Ident, ktype: &Ident) -> Result<TokenStream2, Error> {
+    match key {
+        Some(key) => Ok(quote!(#key)),
+        None if ktype == "String" => {
+            let key = LitStr::new(&name.to_string(), name.span());
+            Ok(quote!(#key))
+        },
+        None => Err(Error::new_spanned(name, format!("missing #[serde_utils(key = ..)] for {} keys", ktype)))
+    }
+}
@@ -0,0 +1,155 @@
+//! Base64 encoding for byte buffers
+//!
+//! In human-readable formats like JSON, the bytes are encoded as a base64 string using the
+//! standard alphabet with padding. Deserialization also accepts unpadded strings. In binary
+//! formats, the bytes are (de-)serialized as raw bytes.
+//!
+//! The functions can be used with `#[serde(with = "serde_utils::adapters::base64")]` on fields of
+//! type `Vec<u8>`, `ByteBuf`, `Box<[u8]>` and `[u8; N]`. The wrapper type
+//! [`Base64`](struct.Base64.html) can be used as field type instead.
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Test {
+//!     #[serde(with = "serde_utils::adapters::base64")]
+//!     data: Vec<u8>,
+//!     hash: Base64<[u8; 16]>
+//! }
+//! ```
+
+use std::ops::{Deref, DerefMut};
+
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+
+use super::{FromBytes, BytesVisitor};
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encodes the bytes as base64 string with padding
+pub fn encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.chunks(3).len() * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+fn decode_char(c: u8) -> Option<u32> {
+    match c {
+        b'A'..=b'Z' => Some(u32::from(c - b'A')),
+        b'a'..=b'z' => Some(u32::from(c - b'a') + 26),
+        b'0'..=b'9' => Some(u32::from(c - b'0') + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None
+    }
+}
+
+/// Decodes a base64 string, padding is optional
+///
+/// On failure, a description of the problem is returned.
+pub fn decode(text: &str) -> Result<Vec<u8>, &'static str> {
+    let mut data = text.as_bytes();
+    if data.len() % 4 == 0 {
+        for _ in 0..2 {
+            if let Some((&b'=', rest)) = data.split_last() {
+                data = rest;
+            }
+        }
+    }
+    if data.len() % 4 == 1 {
+        return Err("invalid base64 length");
+    }
+    let mut out = Vec::with_capacity(data.len() * 3 / 4);
+    for chunk in data.chunks(4) {
+        let mut n = 0;
+        for (i, &c) in chunk.iter().enumerate() {
+            n |= decode_char(c).ok_or("invalid base64 character")? << (18 - 6 * i);
+        }
+        let bytes = [(n >> 16) as u8, (n >> 8) as u8, n as u8];
+        out.extend_from_slice(&bytes[..chunk.len() - 1]);
+    }
+    Ok(out)
+}
+
+/// Serializes the bytes as base64 string in human-readable formats and as bytes otherwise
+#[inline]
+pub fn serialize<T: AsRef<[u8]> + ?Sized, S: Serializer>(bytes: &T, ser: S) -> Result<S::Ok, S::Error> {
+    if ser.is_human_readable() {
+        ser.serialize_str(&encode(bytes.as_ref()))
+    } else {
+        ser.serialize_bytes(bytes.as_ref())
+    }
+}
+
+/// Deserializes bytes from a base64 string in human-readable formats and from bytes otherwise
+///
+/// Fixed-size arrays fail to deserialize if the number of bytes does not match.
+#[inline]
+pub fn deserialize<'de, T: FromBytes, D: Deserializer<'de>>(de: D) -> Result<T, D::Error> {
+    let bytes = if de.is_human_readable() {
+        de.deserialize_str(BytesVisitor::new("base64 string or bytes", decode))?
+    } else {
+        de.deserialize_bytes(BytesVisitor::new("base64 string or bytes", decode))?
+    };
+    super::from_bytes(bytes)
+}
+
+/// A wrapper type that (de-)serializes its bytes as base64 string in human-readable formats
+///
+/// See the [module documentation](index.html) for details.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Base64<T = Vec<u8>>(pub T);
+
+impl<T> Base64<T> {
+    /// Returns the wrapped value
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for Base64<T> {
+    #[inline]
+    fn from(val: T) -> Self {
+        Base64(val)
+    }
+}
+
+impl<T> Deref for Base64<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Base64<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: AsRef<[u8]>> Serialize for Base64<T> {
+    #[inline]
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        serialize(&self.0, ser)
+    }
+}
+
+impl<'de, T: FromBytes> Deserialize<'de> for Base64<T> {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        deserialize(de).map(Base64)
+    }
+}
@@ -0,0 +1,111 @@
+//! Falling back to the default value on errors
+//!
+//! A single malformed value in otherwise valid data normally makes the whole deserialization
+//! fail. This module deserializes the value into an [`Obj`](../../enum.Obj.html) first and then
+//! interprets it as the target type. If that fails, the default value of the type is used instead.
+//! The data must still be syntactically valid and a self-describing format is required.
+//!
+//! The functions can be used with `#[serde(with = "serde_utils::adapters::default_on_error")]` on
+//! fields of all types implementing `Default`. The wrapper type
+//! [`DefaultOnError`](struct.DefaultOnError.html) can be used as field type instead, it also
+//! records the error.
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Record {
+//!     #[serde(with = "serde_utils::adapters::default_on_error")]
+//!     rating: Option<u8>,
+//!     tags: DefaultOnError<Vec<String>>
+//! }
+//! ```
+
+use std::ops::{Deref, DerefMut};
+
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+
+use generic::Obj;
+use deserializer::from_obj_untracked;
+
+/// Serializes the value unchanged
+#[inline]
+pub fn serialize<T: Serialize, S: Serializer>(val: &T, ser: S) -> Result<S::Ok, S::Error> {
+    val.serialize(ser)
+}
+
+/// Deserializes the value, using the default value if it can not be interpreted
+#[inline]
+pub fn deserialize<'de, T, D>(de: D) -> Result<T, D::Error> where
+    T: for<'a> Deserialize<'a> + Default, D: Deserializer<'de>
+{
+    DefaultOnError::deserialize(de).map(DefaultOnError::into_inner)
+}
+
+/// A wrapper type that falls back to the default value if deserialization fails
+///
+/// The message of the error that caused the fallback is kept and can be retrieved with
+/// [`error`](#method.error). See the [module documentation](index.html) for details.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DefaultOnError<T> {
+    value: T,
+    error: Option<String>
+}
+
+impl<T> DefaultOnError<T> {
+    /// Wraps the value without any error
+    #[inline]
+    pub fn new(value: T) -> Self {
+        DefaultOnError { value, error: None }
+    }
+
+    /// Returns the error message if the value was replaced by its default
+    #[inline]
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Returns the wrapped value
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> From<T> for DefaultOnError<T> {
+    #[inline]
+    fn from(value: T) -> Self {
+        DefaultOnError::new(value)
+    }
+}
+
+impl<T> Deref for DefaultOnError<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for DefaultOnError<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+impl<T: Serialize> Serialize for DefaultOnError<T> {
+    #[inline]
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        self.value.serialize(ser)
+    }
+}
+
+impl<'de, T: for<'a> Deserialize<'a> + Default> Deserialize<'de> for DefaultOnError<T> {
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        let obj = Obj::deserialize(de)?;
+        Ok(match from_obj_untracked(obj) {
+            Ok(value) => DefaultOnError::new(value),
+            Err(err) => DefaultOnError { value: T::default(), error: Some(err.to_string()) }
+        })
+    }
+}
@@ -0,0 +1,186 @@
+//! Human-readable strings for `Duration`
+//!
+//! In human-readable formats like JSON, durations are encoded as strings like `"1h30m"` or
+//! `"250ms"`, consisting of integer amounts with one of the units `d`, `h`, `m`, `s`, `ms`, `us`
+//! (or `µs`) and `ns`. Serialization emits the non-zero components from the largest to the
+//! smallest unit, a zero duration is encoded as `"0s"`. Deserialization accepts the components in
+//! any order and whitespace between them.
+//!
+//! In binary formats, durations are encoded as a tuple of seconds and nanoseconds like
+//! [`timestamp::secs_nanos`](../timestamp/secs_nanos/index.html).
+//!
+//! The functions can be used with `#[serde(with = "serde_utils::adapters::duration")]` on fields
+//! of type `Duration`. The wrapper type [`HumanDuration`](struct.HumanDuration.html) can be used
+//! as field type instead.
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Config {
+//!     #[serde(with = "serde_utils::adapters::duration")]
+//!     timeout: Duration,
+//!     interval: HumanDuration
+//! }
+//! ```
+
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::time::Duration;
+
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::de::{self, Visitor, Error};
+
+use super::timestamp::secs_nanos;
+
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+const UNITS: &[(&str, u128)] = &[
+    ("d", 86_400 * NANOS_PER_SEC),
+    ("h", 3_600 * NANOS_PER_SEC),
+    ("m", 60 * NANOS_PER_SEC),
+    ("s", NANOS_PER_SEC),
+    ("ms", 1_000_000),
+    ("us", 1_000),
+    ("ns", 1)
+];
+
+/// Formats the duration as string like `"1h30m"`
+pub fn format(duration: Duration) -> String {
+    let mut nanos = duration.as_nanos();
+    if nanos == 0 {
+        return "0s".to_string();
+    }
+    let mut out = String::new();
+    for &(unit, factor) in UNITS {
+        if nanos >= factor {
+            out.push_str(&(nanos / factor).to_string());
+            out.push_str(unit);
+            nanos %= factor;
+        }
+    }
+    out
+}
+
+/// Parses a duration from a string like `"1h30m"`
+///
+/// On failure, a description of the problem is returned.
+pub fn parse(text: &str) -> Result<Duration, &'static str> {
+    let mut rest = text.trim_start();
+    if rest.is_empty() {
+        return Err("empty duration");
+    }
+    let mut nanos: u128 = 0;
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+        if digits == 0 {
+            return Err("expected a number");
+        }
+        let amount: u128 = rest[..digits].parse().map_err(|_| "duration out of range")?;
+        rest = &rest[digits..];
+        let letters = rest.find(|c: char| !c.is_alphabetic() && c != 'µ').unwrap_or(rest.len());
+        let factor = match &rest[..letters] {
+            "µs" => 1_000,
+            "" => return Err("missing unit"),
+            unit => match UNITS.iter().find(|&&(name, _)| name == unit) {
+                Some(&(_, factor)) => factor,
+                None => return Err("unknown unit")
+            }
+        };
+        rest = rest[letters..].trim_start();
+        nanos = amount.checked_mul(factor).and_then(|n| n.checked_add(nanos)).ok_or("duration out of range")?;
+    }
+    if nanos / NANOS_PER_SEC > u128::from(u64::MAX) {
+        return Err("duration out of range");
+    }
+    Ok(Duration::new((nanos / NANOS_PER_SEC) as u64, (nanos % NANOS_PER_SEC) as u32))
+}
+
+/// Serializes the duration as string in human-readable formats and as tuple otherwise
+#[inline]
+pub fn serialize<S: Serializer>(duration: &Duration, ser: S) -> Result<S::Ok, S::Error> {
+    if ser.is_human_readable() {
+        ser.serialize_str(&format(*duration))
+    } else {
+        secs_nanos::serialize(duration, ser)
+    }
+}
+
+/// Deserializes the duration from a string in human-readable formats and from a tuple otherwise
+#[inline]
+pub fn deserialize<'de, D: Deserializer<'de>>(de: D) -> Result<Duration, D::Error> {
+    if de.is_human_readable() {
+        de.deserialize_str(DurationVisitor)
+    } else {
+        secs_nanos::deserialize(de)
+    }
+}
+
+struct DurationVisitor;
+
+impl<'de> Visitor<'de> for DurationVisitor {
+    type Value = Duration;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("duration string like \"1h30m\"")
+    }
+
+    fn visit_str<E: Error>(self, val: &str) -> Result<Self::Value, E> {
+        parse(val).map_err(|_| E::invalid_value(de::Unexpected::Str(val), &self))
+    }
+}
+
+/// A wrapper type that (de-)serializes a duration as human-readable string
+///
+/// See the [module documentation](index.html) for details.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct HumanDuration(pub Duration);
+
+impl HumanDuration {
+    /// Returns the wrapped duration
+    #[inline]
+    pub fn into_inner(self) -> Duration {
+        self.0
+    }
+}
+
+impl From<Duration> for HumanDuration {
+    #[inline]
+    fn from(val: Duration) -> Self {
+        HumanDuration(val)
+    }
+}
+
+impl Deref for HumanDuration {
+    type Target = Duration;
+
+    #[inline]
+    fn deref(&self) -> &Duration {
+        &self.0
+    }
+}
+
+impl DerefMut for HumanDuration {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Duration {
+        &mut self.0
+    }
+}
+
+impl fmt::Display for HumanDuration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&format(self.0))
+    }
+}
+
+impl Serialize for HumanDuration {
+    #[inline]
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        serialize(&self.0, ser)
+    }
+}
+
+impl<'de> Deserialize<'de> for HumanDuration {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        deserialize(de).map(HumanDuration)
+    }
+}
@@ -0,0 +1,138 @@
+//! Empty strings as `None`
+//!
+//! Some data sources emit empty strings instead of null for missing values. This module
+//! deserializes `Option<T>` from strings, treating both null and `""` as `None` and parsing all
+//! other strings with `FromStr`. Serialization writes `None` as `""` and all other values via
+//! `Display`, so that `Option<String>` and e.g. `Option<u32>` are both encoded as strings.
+//!
+//! The functions can be used with `#[serde(with = "serde_utils::adapters::empty_none")]` on fields
+//! of type `Option<T>`. To keep serializing `None` as null, use only the deserialization with
+//! `#[serde(deserialize_with = "serde_utils::adapters::empty_none::deserialize")]` instead. The
+//! wrapper type [`EmptyNone`](struct.EmptyNone.html) can be used as field type as well.
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Contact {
+//!     #[serde(with = "serde_utils::adapters::empty_none")]
+//!     phone: Option<String>,
+//!     age: EmptyNone<u32>
+//! }
+//! ```
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+use std::str::FromStr;
+
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::de::{Visitor, Error};
+
+/// Serializes `None` as `""` and other values as string via `Display`
+#[inline]
+pub fn serialize<T: fmt::Display, S: Serializer>(val: &Option<T>, ser: S) -> Result<S::Ok, S::Error> {
+    match *val {
+        Some(ref val) => ser.collect_str(val),
+        None => ser.serialize_str("")
+    }
+}
+
+/// Deserializes null and `""` as `None` and parses other strings with `FromStr`
+#[inline]
+pub fn deserialize<'de, T, D>(de: D) -> Result<Option<T>, D::Error> where
+    T: FromStr, T::Err: fmt::Display, D: Deserializer<'de>
+{
+    de.deserialize_option(EmptyNoneVisitor(PhantomData))
+}
+
+struct EmptyNoneVisitor<T>(PhantomData<T>);
+
+impl<'de, T: FromStr> Visitor<'de> for EmptyNoneVisitor<T> where T::Err: fmt::Display {
+    type Value = Option<T>;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("string or null")
+    }
+
+    #[inline]
+    fn visit_none<E: Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    #[inline]
+    fn visit_unit<E: Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    #[inline]
+    fn visit_some<D: Deserializer<'de>>(self, de: D) -> Result<Self::Value, D::Error> {
+        de.deserialize_str(self)
+    }
+
+    fn visit_str<E: Error>(self, val: &str) -> Result<Self::Value, E> {
+        if val.is_empty() {
+            return Ok(None);
+        }
+        match val.parse() {
+            Ok(val) => Ok(Some(val)),
+            Err(err) => Err(E::custom(format_args!("invalid value {:?}: {}", val, err)))
+        }
+    }
+}
+
+/// A wrapper type that (de-)serializes `None` as empty string
+///
+/// See the [module documentation](index.html) for details.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct EmptyNone<T>(pub Option<T>);
+
+impl<T> Default for EmptyNone<T> {
+    #[inline]
+    fn default() -> Self {
+        EmptyNone(None)
+    }
+}
+
+impl<T> EmptyNone<T> {
+    /// Returns the wrapped option
+    #[inline]
+    pub fn into_inner(self) -> Option<T> {
+        self.0
+    }
+}
+
+impl<T> From<Option<T>> for EmptyNone<T> {
+    #[inline]
+    fn from(val: Option<T>) -> Self {
+        EmptyNone(val)
+    }
+}
+
+impl<T> Deref for EmptyNone<T> {
+    type Target = Option<T>;
+
+    #[inline]
+    fn deref(&self) -> &Option<T> {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for EmptyNone<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Option<T> {
+        &mut self.0
+    }
+}
+
+impl<T: fmt::Display> Serialize for EmptyNone<T> {
+    #[inline]
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        serialize(&self.0, ser)
+    }
+}
+
+impl<'de, T: FromStr> Deserialize<'de> for EmptyNone<T> where T::Err: fmt::Display {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        deserialize(de).map(EmptyNone)
+    }
+}
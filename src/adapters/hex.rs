@@ -0,0 +1,129 @@
+//! Hex encoding for byte buffers
+//!
+//! The bytes are encoded as lowercase hex string in all formats, so that e.g. hashes look the same
+//! everywhere. Deserialization also accepts uppercase digits.
+//!
+//! The functions can be used with `#[serde(with = "serde_utils::adapters::hex")]` on fields of
+//! type `Vec<u8>`, `ByteBuf`, `Box<[u8]>` and `[u8; N]`. The wrapper type [`Hex`](struct.Hex.html)
+//! can be used as field type instead. For `[u8; N]`, strings that do not decode to exactly `N`
+//! bytes are rejected.
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Test {
+//!     #[serde(with = "serde_utils::adapters::hex")]
+//!     id: Vec<u8>,
+//!     hash: Hex<[u8; 32]>
+//! }
+//! ```
+
+use std::ops::{Deref, DerefMut};
+
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+
+use super::{FromBytes, BytesVisitor};
+
+const DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+/// Encodes the bytes as lowercase hex string
+pub fn encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len() * 2);
+    for &b in bytes {
+        out.push(DIGITS[(b >> 4) as usize] as char);
+        out.push(DIGITS[(b & 0x0f) as usize] as char);
+    }
+    out
+}
+
+fn decode_digit(c: u8) -> Option<u8> {
+    match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        b'A'..=b'F' => Some(c - b'A' + 10),
+        _ => None
+    }
+}
+
+/// Decodes a hex string, both lowercase and uppercase digits are accepted
+///
+/// On failure, a description of the problem is returned.
+pub fn decode(text: &str) -> Result<Vec<u8>, &'static str> {
+    let data = text.as_bytes();
+    if data.len() % 2 != 0 {
+        return Err("odd hex length");
+    }
+    let mut out = Vec::with_capacity(data.len() / 2);
+    for pair in data.chunks(2) {
+        match (decode_digit(pair[0]), decode_digit(pair[1])) {
+            (Some(high), Some(low)) => out.push(high << 4 | low),
+            _ => return Err("invalid hex digit")
+        }
+    }
+    Ok(out)
+}
+
+/// Serializes the bytes as lowercase hex string
+#[inline]
+pub fn serialize<T: AsRef<[u8]> + ?Sized, S: Serializer>(bytes: &T, ser: S) -> Result<S::Ok, S::Error> {
+    ser.serialize_str(&encode(bytes.as_ref()))
+}
+
+/// Deserializes bytes from a hex string
+///
+/// Fixed-size arrays fail to deserialize if the number of bytes does not match.
+#[inline]
+pub fn deserialize<'de, T: FromBytes, D: Deserializer<'de>>(de: D) -> Result<T, D::Error> {
+    let bytes = de.deserialize_str(BytesVisitor::new("hex string", decode))?;
+    super::from_bytes(bytes)
+}
+
+/// A wrapper type that (de-)serializes its bytes as lowercase hex string
+///
+/// See the [module documentation](index.html) for details.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Hex<T = Vec<u8>>(pub T);
+
+impl<T> Hex<T> {
+    /// Returns the wrapped value
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for Hex<T> {
+    #[inline]
+    fn from(val: T) -> Self {
+        Hex(val)
+    }
+}
+
+impl<T> Deref for Hex<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Hex<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: AsRef<[u8]>> Serialize for Hex<T> {
+    #[inline]
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        serialize(&self.0, ser)
+    }
+}
+
+impl<'de, T: FromBytes> Deserialize<'de> for Hex<T> {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        deserialize(de).map(Hex)
+    }
+}
@@ -0,0 +1,134 @@
+//! Integers as decimal strings
+//!
+//! JavaScript represents all numbers as doubles, so integers beyond 2^53 lose precision when JSON
+//! is consumed there. This module serializes integers as decimal strings like `"9007199254740993"`
+//! in human-readable formats and as native integers otherwise. When deserializing from
+//! human-readable formats, both strings and numbers are accepted, which requires a self-describing
+//! format.
+//!
+//! The functions can be used with `#[serde(with = "serde_utils::adapters::int_string")]` on fields
+//! of all integer types, although they are mostly useful for 64 and 128 bit integers. The wrapper
+//! type [`IntString`](struct.IntString.html) can be used as field type instead.
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Account {
+//!     #[serde(with = "serde_utils::adapters::int_string")]
+//!     id: u64,
+//!     balance: IntString<i64>
+//! }
+//! ```
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+use std::str::FromStr;
+
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::de::{self, Visitor, Error};
+
+/// Integer types supported by this module
+pub trait Integer: Sized + fmt::Display + FromStr + TryFrom<i64> + TryFrom<u64> + Serialize + for<'de> Deserialize<'de> {}
+
+macro_rules! impl_integer(
+    ( $( $ty:ty ),* ) => {
+        $( impl Integer for $ty {} )*
+    };
+);
+
+impl_integer!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// Serializes the integer as decimal string in human-readable formats and natively otherwise
+#[inline]
+pub fn serialize<T: Integer, S: Serializer>(val: &T, ser: S) -> Result<S::Ok, S::Error> {
+    if ser.is_human_readable() {
+        ser.collect_str(val)
+    } else {
+        val.serialize(ser)
+    }
+}
+
+/// Deserializes the integer from a decimal string or number in human-readable formats and natively
+/// otherwise
+#[inline]
+pub fn deserialize<'de, T: Integer, D: Deserializer<'de>>(de: D) -> Result<T, D::Error> {
+    if de.is_human_readable() {
+        de.deserialize_any(IntVisitor(PhantomData))
+    } else {
+        T::deserialize(de)
+    }
+}
+
+struct IntVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Integer> Visitor<'de> for IntVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("integer or decimal string")
+    }
+
+    fn visit_i64<E: Error>(self, val: i64) -> Result<Self::Value, E> {
+        T::try_from(val).map_err(|_| E::invalid_value(de::Unexpected::Signed(val), &self))
+    }
+
+    fn visit_u64<E: Error>(self, val: u64) -> Result<Self::Value, E> {
+        T::try_from(val).map_err(|_| E::invalid_value(de::Unexpected::Unsigned(val), &self))
+    }
+
+    fn visit_str<E: Error>(self, val: &str) -> Result<Self::Value, E> {
+        val.parse().map_err(|_| E::invalid_value(de::Unexpected::Str(val), &self))
+    }
+}
+
+/// A wrapper type that (de-)serializes an integer as decimal string
+///
+/// See the [module documentation](index.html) for details.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct IntString<T>(pub T);
+
+impl<T> IntString<T> {
+    /// Returns the wrapped integer
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for IntString<T> {
+    #[inline]
+    fn from(val: T) -> Self {
+        IntString(val)
+    }
+}
+
+impl<T> Deref for IntString<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for IntString<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: Integer> Serialize for IntString<T> {
+    #[inline]
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        serialize(&self.0, ser)
+    }
+}
+
+impl<'de, T: Integer> Deserialize<'de> for IntString<T> {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        deserialize(de).map(IntString)
+    }
+}
@@ -0,0 +1,263 @@
+//! Lenient decoding of numbers and booleans
+//!
+//! Some data sources encode numbers inconsistently, sometimes as numbers and sometimes as strings
+//! like `"42"` or `"3.5"`. This module accepts both when deserializing into integers and floats.
+//! Floats without fractional part are also accepted for integers, as long as they are in range.
+//!
+//! Booleans are accepted as actual booleans, as the integers `0` and `1` and as the strings
+//! `"true"`, `"false"`, `"yes"`, `"no"`, `"1"` and `"0"` (ignoring case).
+//!
+//! Serialization always emits the value in its native form.
+//!
+//! Since the representation is detected from the data, this requires a self-describing format
+//! like JSON or MessagePack.
+//!
+//! The functions can be used with `#[serde(with = "serde_utils::adapters::lenient")]` on fields of
+//! type `bool` and all integer and float types. The wrapper type [`Lenient`](struct.Lenient.html)
+//! can be used as field type instead.
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Payment {
+//!     #[serde(with = "serde_utils::adapters::lenient")]
+//!     amount: f64,
+//!     count: Lenient<u32>,
+//!     #[serde(with = "serde_utils::adapters::lenient")]
+//!     active: bool
+//! }
+//! ```
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::de::{self, Visitor, Error};
+
+/// Types that can be decoded leniently
+pub trait FromLenient: Sized {
+    /// A description of the accepted values for error messages
+    const EXPECTING: &'static str;
+
+    /// Converts a signed integer, `None` if it is out of range
+    fn from_i64(val: i64) -> Option<Self>;
+
+    /// Converts an unsigned integer, `None` if it is out of range
+    fn from_u64(val: u64) -> Option<Self>;
+
+    /// Converts a float, `None` if it can not be represented
+    fn from_f64(val: f64) -> Option<Self>;
+
+    /// Parses a string, `None` if it is not valid
+    fn from_str(val: &str) -> Option<Self>;
+
+    /// Converts a boolean, by default booleans are rejected
+    #[inline]
+    fn from_bool(_val: bool) -> Option<Self> {
+        None
+    }
+}
+
+macro_rules! impl_lenient_int(
+    ( $( $ty:ty ),* ) => {
+        $(
+            impl FromLenient for $ty {
+                const EXPECTING: &'static str = "integer or numeric string";
+
+                #[inline]
+                fn from_i64(val: i64) -> Option<Self> {
+                    <$ty>::try_from(val).ok()
+                }
+
+                #[inline]
+                fn from_u64(val: u64) -> Option<Self> {
+                    <$ty>::try_from(val).ok()
+                }
+
+                #[inline]
+                fn from_f64(val: f64) -> Option<Self> {
+                    if val.fract() != 0.0 || val < <$ty>::MIN as f64 || val > <$ty>::MAX as f64 {
+                        return None;
+                    }
+                    Some(val as $ty)
+                }
+
+                #[inline]
+                fn from_str(val: &str) -> Option<Self> {
+                    let val = val.trim();
+                    val.parse().ok().or_else(|| val.parse().ok().and_then(Self::from_f64))
+                }
+            }
+        )*
+    };
+);
+
+impl_lenient_int!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+macro_rules! impl_lenient_float(
+    ( $( $ty:ty ),* ) => {
+        $(
+            impl FromLenient for $ty {
+                const EXPECTING: &'static str = "number or numeric string";
+
+                #[inline]
+                fn from_i64(val: i64) -> Option<Self> {
+                    Some(val as $ty)
+                }
+
+                #[inline]
+                fn from_u64(val: u64) -> Option<Self> {
+                    Some(val as $ty)
+                }
+
+                #[inline]
+                fn from_f64(val: f64) -> Option<Self> {
+                    Some(val as $ty)
+                }
+
+                #[inline]
+                fn from_str(val: &str) -> Option<Self> {
+                    val.trim().parse().ok()
+                }
+            }
+        )*
+    };
+);
+
+impl_lenient_float!(f32, f64);
+
+impl FromLenient for bool {
+    const EXPECTING: &'static str = "boolean, 0, 1 or boolean string";
+
+    #[inline]
+    fn from_i64(val: i64) -> Option<Self> {
+        match val {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None
+        }
+    }
+
+    #[inline]
+    fn from_u64(val: u64) -> Option<Self> {
+        match val {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None
+        }
+    }
+
+    #[inline]
+    fn from_f64(_val: f64) -> Option<Self> {
+        None
+    }
+
+    fn from_str(val: &str) -> Option<Self> {
+        let val = val.trim();
+        for &(text, res) in &[("true", true), ("false", false), ("yes", true), ("no", false), ("1", true), ("0", false)] {
+            if val.eq_ignore_ascii_case(text) {
+                return Some(res);
+            }
+        }
+        None
+    }
+
+    #[inline]
+    fn from_bool(val: bool) -> Option<Self> {
+        Some(val)
+    }
+}
+
+/// Serializes the value in its native form
+#[inline]
+pub fn serialize<T: Serialize, S: Serializer>(val: &T, ser: S) -> Result<S::Ok, S::Error> {
+    val.serialize(ser)
+}
+
+/// Deserializes the value from its native form or from a string
+#[inline]
+pub fn deserialize<'de, T: FromLenient, D: Deserializer<'de>>(de: D) -> Result<T, D::Error> {
+    de.deserialize_any(LenientVisitor(PhantomData))
+}
+
+struct LenientVisitor<T>(PhantomData<T>);
+
+impl<'de, T: FromLenient> Visitor<'de> for LenientVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str(T::EXPECTING)
+    }
+
+    fn visit_bool<E: Error>(self, val: bool) -> Result<Self::Value, E> {
+        T::from_bool(val).ok_or_else(|| E::invalid_type(de::Unexpected::Bool(val), &self))
+    }
+
+    fn visit_i64<E: Error>(self, val: i64) -> Result<Self::Value, E> {
+        T::from_i64(val).ok_or_else(|| E::invalid_value(de::Unexpected::Signed(val), &self))
+    }
+
+    fn visit_u64<E: Error>(self, val: u64) -> Result<Self::Value, E> {
+        T::from_u64(val).ok_or_else(|| E::invalid_value(de::Unexpected::Unsigned(val), &self))
+    }
+
+    fn visit_f64<E: Error>(self, val: f64) -> Result<Self::Value, E> {
+        T::from_f64(val).ok_or_else(|| E::invalid_value(de::Unexpected::Float(val), &self))
+    }
+
+    fn visit_str<E: Error>(self, val: &str) -> Result<Self::Value, E> {
+        T::from_str(val).ok_or_else(|| E::invalid_value(de::Unexpected::Str(val), &self))
+    }
+}
+
+/// A wrapper type that deserializes its value leniently
+///
+/// See the [module documentation](index.html) for details.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Lenient<T>(pub T);
+
+impl<T> Lenient<T> {
+    /// Returns the wrapped value
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for Lenient<T> {
+    #[inline]
+    fn from(val: T) -> Self {
+        Lenient(val)
+    }
+}
+
+impl<T> Deref for Lenient<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Lenient<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: Serialize> Serialize for Lenient<T> {
+    #[inline]
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(ser)
+    }
+}
+
+impl<'de, T: FromLenient> Deserialize<'de> for Lenient<T> {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        deserialize(de).map(Lenient)
+    }
+}
@@ -0,0 +1,132 @@
+//! Sequences that skip undecodable elements
+//!
+//! This module deserializes a sequence element by element, dropping elements that can not be
+//! interpreted as the element type instead of failing for the whole sequence. Like
+//! [`default_on_error`](../default_on_error/index.html), each element is deserialized into an
+//! [`Obj`](../../enum.Obj.html) first, so the data must still be syntactically valid and a
+//! self-describing format is required.
+//!
+//! The functions can be used with `#[serde(with = "serde_utils::adapters::lossy_vec")]` on fields
+//! of type `Vec<T>`. The wrapper type [`LossyVec`](struct.LossyVec.html) can be used as field type
+//! instead, it also counts the dropped elements.
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Batch {
+//!     #[serde(with = "serde_utils::adapters::lossy_vec")]
+//!     events: Vec<Event>,
+//!     records: LossyVec<Record>
+//! }
+//! ```
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::de::{Visitor, SeqAccess};
+
+use generic::Obj;
+use deserializer::from_obj_untracked;
+
+/// Serializes the elements as sequence
+#[inline]
+pub fn serialize<T: Serialize, S: Serializer>(val: &[T], ser: S) -> Result<S::Ok, S::Error> {
+    ser.collect_seq(val)
+}
+
+/// Deserializes the sequence, dropping all elements that can not be interpreted
+#[inline]
+pub fn deserialize<'de, T, D>(de: D) -> Result<Vec<T>, D::Error> where
+    T: for<'a> Deserialize<'a>, D: Deserializer<'de>
+{
+    LossyVec::deserialize(de).map(LossyVec::into_inner)
+}
+
+/// A wrapper type that drops undecodable elements when deserializing a sequence
+///
+/// The number of dropped elements can be retrieved with [`dropped`](#method.dropped). See the
+/// [module documentation](index.html) for details.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LossyVec<T> {
+    items: Vec<T>,
+    dropped: usize
+}
+
+impl<T> LossyVec<T> {
+    /// Wraps the elements without any dropped elements
+    #[inline]
+    pub fn new(items: Vec<T>) -> Self {
+        LossyVec { items, dropped: 0 }
+    }
+
+    /// Returns the number of elements that were dropped when deserializing
+    #[inline]
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    /// Returns the wrapped elements
+    #[inline]
+    pub fn into_inner(self) -> Vec<T> {
+        self.items
+    }
+}
+
+impl<T> From<Vec<T>> for LossyVec<T> {
+    #[inline]
+    fn from(items: Vec<T>) -> Self {
+        LossyVec::new(items)
+    }
+}
+
+impl<T> Deref for LossyVec<T> {
+    type Target = Vec<T>;
+
+    #[inline]
+    fn deref(&self) -> &Vec<T> {
+        &self.items
+    }
+}
+
+impl<T> DerefMut for LossyVec<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Vec<T> {
+        &mut self.items
+    }
+}
+
+impl<T: Serialize> Serialize for LossyVec<T> {
+    #[inline]
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        serialize(&self.items, ser)
+    }
+}
+
+impl<'de, T: for<'a> Deserialize<'a>> Deserialize<'de> for LossyVec<T> {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        de.deserialize_seq(LossyVecVisitor(PhantomData))
+    }
+}
+
+struct LossyVecVisitor<T>(PhantomData<T>);
+
+impl<'de, T: for<'a> Deserialize<'a>> Visitor<'de> for LossyVecVisitor<T> {
+    type Value = LossyVec<T>;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("sequence")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut res = LossyVec::new(Vec::with_capacity(seq.size_hint().unwrap_or(0)));
+        while let Some(obj) = seq.next_element::<Obj>()? {
+            match from_obj_untracked(obj) {
+                Ok(item) => res.items.push(item),
+                Err(_) => res.dropped += 1
+            }
+        }
+        Ok(res)
+    }
+}
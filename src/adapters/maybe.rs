@@ -0,0 +1,194 @@
+//! Distinguishing missing fields from null values
+//!
+//! `Option` fields are `None` both when the field is missing and when it is explicitly null. For
+//! e.g. partial updates, these cases mean different things: a missing field should stay unchanged
+//! while a null field should be cleared. The enum [`Maybe`](enum.Maybe.html) keeps them apart.
+//!
+//! In maps implemented with [`serde_impl!`](../../macro.serde_impl!.html), missing `Maybe` fields
+//! are set to `Maybe::Missing`, which is also the default value. This works with plain maps,
+//! [compressed maps](../../macro.serde_impl!.html#compressed-maps) and
+//! [required fields](../../macro.serde_impl!.html#required-fields). When serializing, `Missing`
+//! and `Null` are both written as null, so compressed maps should be used to omit missing fields.
+//!
+//! Note that Serde's buffering for untagged enums and flattened fields reports null values in a
+//! way that is indistinguishable from missing fields, so `Maybe` should not be used there.
+//!
+//! For Serde's derive, this module provides `serialize` and `deserialize` functions for
+//! `Option<Option<T>>` where the outer `Option` is `None` if the field is missing. They must be
+//! used together with `#[serde(default)]`, since the functions are not called for missing fields.
+//!
+//! ```ignore
+//! #[derive(Default, Debug, PartialEq)]
+//! struct Update {
+//!     name: Maybe<String>,
+//!     email: Maybe<String>
+//! }
+//! serde_impl!(Update(String?) {
+//!     name: Maybe<String> => "name",
+//!     email: Maybe<String> => "email"
+//! });
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct DerivedUpdate {
+//!     #[serde(default, with = "serde_utils::adapters::maybe", skip_serializing_if = "Option::is_none")]
+//!     name: Option<Option<String>>
+//! }
+//! ```
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::de::{Visitor, Error};
+
+/// A value that can be missing, null or present
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Maybe<T> {
+    /// The field is missing
+    Missing,
+    /// The field is explicitly null
+    Null,
+    /// The field has a value
+    Value(T)
+}
+
+impl<T> Default for Maybe<T> {
+    #[inline]
+    fn default() -> Self {
+        Maybe::Missing
+    }
+}
+
+impl<T> Maybe<T> {
+    /// Returns whether the field is missing
+    #[inline]
+    pub fn is_missing(&self) -> bool {
+        matches!(*self, Maybe::Missing)
+    }
+
+    /// Returns whether the field is explicitly null
+    #[inline]
+    pub fn is_null(&self) -> bool {
+        matches!(*self, Maybe::Null)
+    }
+
+    /// Returns whether the field has a value
+    #[inline]
+    pub fn is_value(&self) -> bool {
+        matches!(*self, Maybe::Value(_))
+    }
+
+    /// Returns a reference to the value if there is one
+    #[inline]
+    pub fn as_ref(&self) -> Maybe<&T> {
+        match *self {
+            Maybe::Missing => Maybe::Missing,
+            Maybe::Null => Maybe::Null,
+            Maybe::Value(ref val) => Maybe::Value(val)
+        }
+    }
+
+    /// Returns the value, treating `Missing` and `Null` both as `None`
+    #[inline]
+    pub fn into_option(self) -> Option<T> {
+        match self {
+            Maybe::Value(val) => Some(val),
+            _ => None
+        }
+    }
+
+    /// Applies an update to an existing value
+    ///
+    /// `Missing` keeps the value unchanged, `Null` clears it and `Value` replaces it.
+    #[inline]
+    pub fn apply_to(self, target: &mut Option<T>) {
+        match self {
+            Maybe::Missing => (),
+            Maybe::Null => *target = None,
+            Maybe::Value(val) => *target = Some(val)
+        }
+    }
+}
+
+impl<T> From<Option<Option<T>>> for Maybe<T> {
+    #[inline]
+    fn from(val: Option<Option<T>>) -> Self {
+        match val {
+            None => Maybe::Missing,
+            Some(None) => Maybe::Null,
+            Some(Some(val)) => Maybe::Value(val)
+        }
+    }
+}
+
+impl<T> From<Maybe<T>> for Option<Option<T>> {
+    #[inline]
+    fn from(val: Maybe<T>) -> Self {
+        match val {
+            Maybe::Missing => None,
+            Maybe::Null => Some(None),
+            Maybe::Value(val) => Some(Some(val))
+        }
+    }
+}
+
+impl<T: Serialize> Serialize for Maybe<T> {
+    #[inline]
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        match *self {
+            Maybe::Value(ref val) => ser.serialize_some(val),
+            _ => ser.serialize_none()
+        }
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Maybe<T> {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        de.deserialize_option(MaybeVisitor(PhantomData))
+    }
+}
+
+struct MaybeVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for MaybeVisitor<T> {
+    type Value = Maybe<T>;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("optional value")
+    }
+
+    /// Only used by `serde_impl!` for missing fields
+    #[inline]
+    fn visit_unit<E: Error>(self) -> Result<Self::Value, E> {
+        Ok(Maybe::Missing)
+    }
+
+    #[inline]
+    fn visit_none<E: Error>(self) -> Result<Self::Value, E> {
+        Ok(Maybe::Null)
+    }
+
+    #[inline]
+    fn visit_some<D: Deserializer<'de>>(self, de: D) -> Result<Self::Value, D::Error> {
+        T::deserialize(de).map(Maybe::Value)
+    }
+}
+
+/// Serializes a present field as optional value
+///
+/// Missing fields should be skipped with `#[serde(skip_serializing_if = "Option::is_none")]`,
+/// otherwise they are serialized as null.
+#[inline]
+pub fn serialize<T: Serialize, S: Serializer>(val: &Option<Option<T>>, ser: S) -> Result<S::Ok, S::Error> {
+    match *val {
+        Some(Some(ref val)) => ser.serialize_some(val),
+        _ => ser.serialize_none()
+    }
+}
+
+/// Deserializes a present field, null becomes `Some(None)`
+#[inline]
+pub fn deserialize<'de, T: Deserialize<'de>, D: Deserializer<'de>>(de: D) -> Result<Option<Option<T>>, D::Error> {
+    Option::deserialize(de).map(Some)
+}
@@ -0,0 +1,129 @@
+//! Adapters for (de-)serializing common types in a specific representation
+//!
+//! Each adapter module provides `serialize` and `deserialize` functions that can be used with
+//! `#[serde(with = "...")]`, and a wrapper type that can be used directly as field type, e.g. in
+//! [`serde_impl!`](../macro.serde_impl!.html).
+//!
+//! Adapters for types of other crates require the feature of the same name: the `chrono` and
+//! `time` features add the [`rfc3339`](rfc3339/index.html) module and support for their types in
+//! the [`timestamp`](timestamp/index.html) module, the `uuid` feature adds the
+//! [`uuid`](uuid/index.html) module.
+
+use std::convert::TryInto;
+use std::fmt;
+
+use serde::de::{self, Expected, Error, Visitor, SeqAccess};
+use serde_bytes::ByteBuf;
+
+pub mod base64;
+pub mod default_on_error;
+pub mod duration;
+pub mod empty_none;
+pub mod hex;
+pub mod int_string;
+pub mod lenient;
+pub mod lossy_vec;
+pub mod maybe;
+pub mod net;
+pub mod non_finite;
+pub mod os_string;
+pub mod pairs;
+pub mod path;
+#[cfg(any(feature = "chrono", feature = "time"))] pub mod rfc3339;
+pub mod shared;
+pub mod timestamp;
+#[cfg(feature = "uuid")] pub mod uuid;
+pub mod zero_none;
+
+/// Byte containers that adapters can decode into
+pub trait FromBytes: Sized {
+    /// Creates the container from the decoded bytes
+    ///
+    /// On failure, the expected length is returned.
+    fn from_bytes(bytes: Vec<u8>) -> Result<Self, usize>;
+}
+
+impl FromBytes for Vec<u8> {
+    #[inline]
+    fn from_bytes(bytes: Vec<u8>) -> Result<Self, usize> {
+        Ok(bytes)
+    }
+}
+
+impl FromBytes for ByteBuf {
+    #[inline]
+    fn from_bytes(bytes: Vec<u8>) -> Result<Self, usize> {
+        Ok(ByteBuf::from(bytes))
+    }
+}
+
+impl FromBytes for Box<[u8]> {
+    #[inline]
+    fn from_bytes(bytes: Vec<u8>) -> Result<Self, usize> {
+        Ok(bytes.into_boxed_slice())
+    }
+}
+
+impl<const N: usize> FromBytes for [u8; N] {
+    #[inline]
+    fn from_bytes(bytes: Vec<u8>) -> Result<Self, usize> {
+        bytes.try_into().map_err(|_| N)
+    }
+}
+
+struct LengthExpected(usize);
+
+impl Expected for LengthExpected {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{} bytes", self.0)
+    }
+}
+
+/// Converts decoded bytes into the container, reporting length mismatches as error
+fn from_bytes<T: FromBytes, E: Error>(bytes: Vec<u8>) -> Result<T, E> {
+    let len = bytes.len();
+    T::from_bytes(bytes).map_err(|expected| E::invalid_length(len, &LengthExpected(expected)))
+}
+
+/// Visitor for bytes that are encoded as string in human-readable formats
+struct BytesVisitor {
+    expecting: &'static str,
+    decode: fn(&str) -> Result<Vec<u8>, &'static str>
+}
+
+impl BytesVisitor {
+    #[inline]
+    fn new(expecting: &'static str, decode: fn(&str) -> Result<Vec<u8>, &'static str>) -> Self {
+        BytesVisitor { expecting, decode }
+    }
+}
+
+impl<'de> Visitor<'de> for BytesVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str(self.expecting)
+    }
+
+    fn visit_str<E: Error>(self, val: &str) -> Result<Self::Value, E> {
+        (self.decode)(val).map_err(|_| E::invalid_value(de::Unexpected::Str(val), &self))
+    }
+
+    #[inline]
+    fn visit_bytes<E: Error>(self, val: &[u8]) -> Result<Self::Value, E> {
+        Ok(val.to_vec())
+    }
+
+    #[inline]
+    fn visit_byte_buf<E: Error>(self, val: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(val)
+    }
+
+    fn visit_seq<V: SeqAccess<'de>>(self, mut seq: V) -> Result<Self::Value, V::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(byte) = seq.next_element()? {
+            bytes.push(byte);
+        }
+        Ok(bytes)
+    }
+}
@@ -0,0 +1,280 @@
+//! String encodings for IP and socket addresses
+//!
+//! The functions of this module (de-)serialize `IpAddr`, `Ipv4Addr`, `Ipv6Addr`, `SocketAddr`,
+//! `SocketAddrV4` and `SocketAddrV6` as their canonical string forms like `"10.0.0.1"` or
+//! `"[::1]:80"` in all formats. Serde's own implementations use strings only in human-readable
+//! formats and enums otherwise.
+//!
+//! The submodule [`compact`](compact/index.html) uses strings in human-readable formats and raw
+//! bytes otherwise: 4 or 16 bytes for IP addresses and additionally 2 bytes for the port in big
+//! endian for socket addresses. The flow info and scope id of `SocketAddrV6` are not encoded. The
+//! length of the bytes determines the address family.
+//!
+//! The modules can be used with `#[serde(with = "serde_utils::adapters::net")]` and
+//! `#[serde(with = "serde_utils::adapters::net::compact")]` on fields of the address types. The
+//! wrapper types [`Addr`](struct.Addr.html) and [`CompactAddr`](struct.CompactAddr.html) can be
+//! used as field types instead.
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Config {
+//!     #[serde(with = "serde_utils::adapters::net")]
+//!     listen: SocketAddr,
+//!     peers: Vec<CompactAddr<IpAddr>>
+//! }
+//! ```
+
+use std::convert::TryInto;
+use std::fmt;
+use std::marker::PhantomData;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use std::ops::{Deref, DerefMut};
+use std::str::FromStr;
+
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::de::{self, Visitor, Error};
+
+/// Address types supported by this module
+pub trait NetAddr: Sized + fmt::Display + FromStr {
+    /// A description of the address type for error messages
+    const EXPECTING: &'static str;
+
+    /// Returns the compact binary form of the address
+    fn to_bytes(&self) -> Vec<u8>;
+
+    /// Parses the compact binary form of the address, `None` if it is not valid
+    fn from_bytes(bytes: &[u8]) -> Option<Self>;
+}
+
+impl NetAddr for Ipv4Addr {
+    const EXPECTING: &'static str = "IPv4 address";
+
+    #[inline]
+    fn to_bytes(&self) -> Vec<u8> {
+        self.octets().to_vec()
+    }
+
+    #[inline]
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        TryInto::<[u8; 4]>::try_into(bytes).ok().map(Ipv4Addr::from)
+    }
+}
+
+impl NetAddr for Ipv6Addr {
+    const EXPECTING: &'static str = "IPv6 address";
+
+    #[inline]
+    fn to_bytes(&self) -> Vec<u8> {
+        self.octets().to_vec()
+    }
+
+    #[inline]
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        TryInto::<[u8; 16]>::try_into(bytes).ok().map(Ipv6Addr::from)
+    }
+}
+
+impl NetAddr for IpAddr {
+    const EXPECTING: &'static str = "IP address";
+
+    #[inline]
+    fn to_bytes(&self) -> Vec<u8> {
+        match *self {
+            IpAddr::V4(ref addr) => addr.to_bytes(),
+            IpAddr::V6(ref addr) => addr.to_bytes()
+        }
+    }
+
+    #[inline]
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        match bytes.len() {
+            4 => Ipv4Addr::from_bytes(bytes).map(IpAddr::V4),
+            16 => Ipv6Addr::from_bytes(bytes).map(IpAddr::V6),
+            _ => None
+        }
+    }
+}
+
+fn with_port(mut bytes: Vec<u8>, port: u16) -> Vec<u8> {
+    bytes.extend_from_slice(&port.to_be_bytes());
+    bytes
+}
+
+fn split_port(bytes: &[u8]) -> Option<(&[u8], u16)> {
+    if bytes.len() < 2 {
+        return None;
+    }
+    let (ip, port) = bytes.split_at(bytes.len() - 2);
+    Some((ip, u16::from_be_bytes(port.try_into().ok()?)))
+}
+
+impl NetAddr for SocketAddrV4 {
+    const EXPECTING: &'static str = "IPv4 socket address";
+
+    #[inline]
+    fn to_bytes(&self) -> Vec<u8> {
+        with_port(self.ip().to_bytes(), self.port())
+    }
+
+    #[inline]
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let (ip, port) = split_port(bytes)?;
+        Ipv4Addr::from_bytes(ip).map(|ip| SocketAddrV4::new(ip, port))
+    }
+}
+
+impl NetAddr for SocketAddrV6 {
+    const EXPECTING: &'static str = "IPv6 socket address";
+
+    #[inline]
+    fn to_bytes(&self) -> Vec<u8> {
+        with_port(self.ip().to_bytes(), self.port())
+    }
+
+    #[inline]
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let (ip, port) = split_port(bytes)?;
+        Ipv6Addr::from_bytes(ip).map(|ip| SocketAddrV6::new(ip, port, 0, 0))
+    }
+}
+
+impl NetAddr for SocketAddr {
+    const EXPECTING: &'static str = "socket address";
+
+    #[inline]
+    fn to_bytes(&self) -> Vec<u8> {
+        with_port(self.ip().to_bytes(), self.port())
+    }
+
+    #[inline]
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let (ip, port) = split_port(bytes)?;
+        IpAddr::from_bytes(ip).map(|ip| SocketAddr::new(ip, port))
+    }
+}
+
+struct AddrVisitor<T>(PhantomData<T>);
+
+impl<'de, T: NetAddr> Visitor<'de> for AddrVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str(T::EXPECTING)
+    }
+
+    fn visit_str<E: Error>(self, val: &str) -> Result<Self::Value, E> {
+        val.parse().map_err(|_| E::invalid_value(de::Unexpected::Str(val), &self))
+    }
+
+    fn visit_bytes<E: Error>(self, val: &[u8]) -> Result<Self::Value, E> {
+        T::from_bytes(val).ok_or_else(|| E::invalid_value(de::Unexpected::Bytes(val), &self))
+    }
+}
+
+/// Serializes the address as string
+#[inline]
+pub fn serialize<T: NetAddr, S: Serializer>(addr: &T, ser: S) -> Result<S::Ok, S::Error> {
+    ser.collect_str(addr)
+}
+
+/// Deserializes the address from a string
+#[inline]
+pub fn deserialize<'de, T: NetAddr, D: Deserializer<'de>>(de: D) -> Result<T, D::Error> {
+    de.deserialize_str(AddrVisitor(PhantomData))
+}
+
+/// Strings in human-readable formats and raw bytes otherwise
+pub mod compact {
+    use std::marker::PhantomData;
+
+    use serde::{Serializer, Deserializer};
+
+    use super::{NetAddr, AddrVisitor};
+
+    /// Serializes the address as string in human-readable formats and as bytes otherwise
+    #[inline]
+    pub fn serialize<T: NetAddr, S: Serializer>(addr: &T, ser: S) -> Result<S::Ok, S::Error> {
+        if ser.is_human_readable() {
+            ser.collect_str(addr)
+        } else {
+            ser.serialize_bytes(&addr.to_bytes())
+        }
+    }
+
+    /// Deserializes the address from a string in human-readable formats and from bytes otherwise
+    #[inline]
+    pub fn deserialize<'de, T: NetAddr, D: Deserializer<'de>>(de: D) -> Result<T, D::Error> {
+        if de.is_human_readable() {
+            de.deserialize_str(AddrVisitor(PhantomData))
+        } else {
+            de.deserialize_bytes(AddrVisitor(PhantomData))
+        }
+    }
+}
+
+macro_rules! addr_wrapper(
+    ( $(#[$doc:meta])* $wrapper:ident, $serialize:path, $deserialize:path ) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub struct $wrapper<T>(pub T);
+
+        impl<T> $wrapper<T> {
+            /// Returns the wrapped address
+            #[inline]
+            pub fn into_inner(self) -> T {
+                self.0
+            }
+        }
+
+        impl<T> From<T> for $wrapper<T> {
+            #[inline]
+            fn from(val: T) -> Self {
+                $wrapper(val)
+            }
+        }
+
+        impl<T> Deref for $wrapper<T> {
+            type Target = T;
+
+            #[inline]
+            fn deref(&self) -> &T {
+                &self.0
+            }
+        }
+
+        impl<T> DerefMut for $wrapper<T> {
+            #[inline]
+            fn deref_mut(&mut self) -> &mut T {
+                &mut self.0
+            }
+        }
+
+        impl<T: NetAddr> Serialize for $wrapper<T> {
+            #[inline]
+            fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+                $serialize(&self.0, ser)
+            }
+        }
+
+        impl<'de, T: NetAddr> Deserialize<'de> for $wrapper<T> {
+            #[inline]
+            fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+                $deserialize(de).map($wrapper)
+            }
+        }
+    };
+);
+
+addr_wrapper!(
+    /// A wrapper type that (de-)serializes an address as string
+    ///
+    /// See the [module documentation](index.html) for details.
+    Addr, serialize, deserialize
+);
+
+addr_wrapper!(
+    /// A wrapper type that (de-)serializes an address as string or as raw bytes
+    ///
+    /// See the [module documentation](index.html) for details.
+    CompactAddr, compact::serialize, compact::deserialize
+);
@@ -0,0 +1,182 @@
+//! Non-finite floats as strings
+//!
+//! JSON and similar formats can not represent `NaN` and infinite values, so serializing them
+//! either fails or silently emits `null`. This module serializes these values as the strings
+//! `"NaN"`, `"Infinity"` and `"-Infinity"` in human-readable formats and parses them back. Finite
+//! values are serialized as numbers. Binary formats can represent all floats, so the values are
+//! (de-)serialized natively there.
+//!
+//! Deserializing from human-readable formats detects the representation from the data, so a
+//! self-describing format is required.
+//!
+//! The functions can be used with `#[serde(with = "serde_utils::adapters::non_finite")]` on fields
+//! of type `f32` and `f64`. The wrapper type [`NonFinite`](struct.NonFinite.html) can be used as
+//! field type instead.
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Stats {
+//!     #[serde(with = "serde_utils::adapters::non_finite")]
+//!     mean: f64,
+//!     max: NonFinite<f32>
+//! }
+//! ```
+
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::de::{self, Visitor, Error};
+
+/// Float types supported by this module
+pub trait Float: Copy + Serialize + for<'de> Deserialize<'de> {
+    /// Converts the value to `f64`
+    fn to_f64(self) -> f64;
+
+    /// Converts the value from `f64`
+    fn from_f64(val: f64) -> Self;
+}
+
+impl Float for f32 {
+    #[inline]
+    fn to_f64(self) -> f64 {
+        f64::from(self)
+    }
+
+    #[inline]
+    fn from_f64(val: f64) -> Self {
+        val as f32
+    }
+}
+
+impl Float for f64 {
+    #[inline]
+    fn to_f64(self) -> f64 {
+        self
+    }
+
+    #[inline]
+    fn from_f64(val: f64) -> Self {
+        val
+    }
+}
+
+fn name(val: f64) -> Option<&'static str> {
+    if val.is_nan() {
+        Some("NaN")
+    } else if val == f64::INFINITY {
+        Some("Infinity")
+    } else if val == f64::NEG_INFINITY {
+        Some("-Infinity")
+    } else {
+        None
+    }
+}
+
+fn parse(val: &str) -> Option<f64> {
+    match val {
+        "NaN" => Some(f64::NAN),
+        "Infinity" => Some(f64::INFINITY),
+        "-Infinity" => Some(f64::NEG_INFINITY),
+        _ => None
+    }
+}
+
+/// Serializes non-finite values as strings in human-readable formats and as numbers otherwise
+#[inline]
+pub fn serialize<T: Float, S: Serializer>(val: &T, ser: S) -> Result<S::Ok, S::Error> {
+    if ser.is_human_readable() {
+        if let Some(name) = name(val.to_f64()) {
+            return ser.serialize_str(name);
+        }
+    }
+    val.serialize(ser)
+}
+
+/// Deserializes numbers and the strings for non-finite values in human-readable formats and
+/// numbers otherwise
+#[inline]
+pub fn deserialize<'de, T: Float, D: Deserializer<'de>>(de: D) -> Result<T, D::Error> {
+    if de.is_human_readable() {
+        de.deserialize_any(FloatVisitor).map(T::from_f64)
+    } else {
+        T::deserialize(de)
+    }
+}
+
+struct FloatVisitor;
+
+impl<'de> Visitor<'de> for FloatVisitor {
+    type Value = f64;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("number, \"NaN\", \"Infinity\" or \"-Infinity\"")
+    }
+
+    fn visit_i64<E: Error>(self, val: i64) -> Result<Self::Value, E> {
+        Ok(val as f64)
+    }
+
+    fn visit_u64<E: Error>(self, val: u64) -> Result<Self::Value, E> {
+        Ok(val as f64)
+    }
+
+    fn visit_f64<E: Error>(self, val: f64) -> Result<Self::Value, E> {
+        Ok(val)
+    }
+
+    fn visit_str<E: Error>(self, val: &str) -> Result<Self::Value, E> {
+        parse(val).ok_or_else(|| E::invalid_value(de::Unexpected::Str(val), &self))
+    }
+}
+
+/// A wrapper type that (de-)serializes non-finite floats as strings
+///
+/// See the [module documentation](index.html) for details.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub struct NonFinite<T>(pub T);
+
+impl<T> NonFinite<T> {
+    /// Returns the wrapped value
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for NonFinite<T> {
+    #[inline]
+    fn from(val: T) -> Self {
+        NonFinite(val)
+    }
+}
+
+impl<T> Deref for NonFinite<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for NonFinite<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: Float> Serialize for NonFinite<T> {
+    #[inline]
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        serialize(&self.0, ser)
+    }
+}
+
+impl<'de, T: Float> Deserialize<'de> for NonFinite<T> {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        deserialize(de).map(NonFinite)
+    }
+}
@@ -0,0 +1,198 @@
+//! Lossless encoding for OS strings
+//!
+//! This module (de-)serializes `OsString` without losing data that is not valid unicode. The value
+//! is encoded as enum tagged with the platform it was serialized on:
+//!
+//! * `Unix(bytes)` - the raw bytes of the string on Unix and other non-Windows platforms
+//! * `Windows([u16])` - the UTF-16 code units of the string on Windows, possibly containing
+//!   unpaired surrogates
+//!
+//! Values are restored exactly on the same platform family. When deserializing values from the
+//! other family, they are only accepted if they are valid unicode.
+//!
+//! The functions can be used with `#[serde(with = "serde_utils::adapters::os_string")]` on fields of
+//! type `OsString`. The wrapper type [`LosslessOsString`](struct.LosslessOsString.html) can be used
+//! as field type instead.
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Snapshot {
+//!     #[serde(with = "serde_utils::adapters::os_string")]
+//!     cwd: OsString,
+//!     env: Vec<(LosslessOsString, LosslessOsString)>
+//! }
+//! ```
+
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::de::{self, Visitor, EnumAccess, VariantAccess, Error};
+use serde_bytes::{Bytes, ByteBuf};
+
+const VARIANTS: &[&str] = &["Unix", "Windows"];
+
+enum Platform {
+    Unix,
+    Windows
+}
+
+impl<'de> Deserialize<'de> for Platform {
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        de.deserialize_identifier(PlatformVisitor)
+    }
+}
+
+struct PlatformVisitor;
+
+impl<'de> Visitor<'de> for PlatformVisitor {
+    type Value = Platform;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("`Unix` or `Windows`")
+    }
+
+    fn visit_u64<E: Error>(self, val: u64) -> Result<Self::Value, E> {
+        match val {
+            0 => Ok(Platform::Unix),
+            1 => Ok(Platform::Windows),
+            _ => Err(E::invalid_value(de::Unexpected::Unsigned(val), &"variant index 0 <= i < 2"))
+        }
+    }
+
+    fn visit_str<E: Error>(self, val: &str) -> Result<Self::Value, E> {
+        match val {
+            "Unix" => Ok(Platform::Unix),
+            "Windows" => Ok(Platform::Windows),
+            _ => Err(E::unknown_variant(val, VARIANTS))
+        }
+    }
+}
+
+#[cfg(unix)]
+fn from_unix(bytes: Vec<u8>) -> Option<OsString> {
+    use std::os::unix::ffi::OsStringExt;
+    Some(OsString::from_vec(bytes))
+}
+
+#[cfg(not(unix))]
+fn from_unix(bytes: Vec<u8>) -> Option<OsString> {
+    String::from_utf8(bytes).ok().map(OsString::from)
+}
+
+#[cfg(windows)]
+fn from_windows(wide: Vec<u16>) -> Option<OsString> {
+    use std::os::windows::ffi::OsStringExt;
+    Some(OsString::from_wide(&wide))
+}
+
+#[cfg(not(windows))]
+fn from_windows(wide: Vec<u16>) -> Option<OsString> {
+    String::from_utf16(&wide).ok().map(OsString::from)
+}
+
+/// Serializes the string tagged with the platform family
+#[cfg(unix)]
+#[inline]
+pub fn serialize<T: AsRef<OsStr>, S: Serializer>(val: &T, ser: S) -> Result<S::Ok, S::Error> {
+    use std::os::unix::ffi::OsStrExt;
+    ser.serialize_newtype_variant("OsString", 0, "Unix", Bytes::new(val.as_ref().as_bytes()))
+}
+
+/// Serializes the string tagged with the platform family
+#[cfg(windows)]
+#[inline]
+pub fn serialize<T: AsRef<OsStr>, S: Serializer>(val: &T, ser: S) -> Result<S::Ok, S::Error> {
+    use std::os::windows::ffi::OsStrExt;
+    let wide: Vec<u16> = val.as_ref().encode_wide().collect();
+    ser.serialize_newtype_variant("OsString", 1, "Windows", &wide)
+}
+
+/// Serializes the string tagged with the platform family
+///
+/// On platforms other than Unix and Windows, only strings that are valid unicode are supported.
+#[cfg(not(any(unix, windows)))]
+#[inline]
+pub fn serialize<T: AsRef<OsStr>, S: Serializer>(val: &T, ser: S) -> Result<S::Ok, S::Error> {
+    match val.as_ref().to_str() {
+        Some(val) => ser.serialize_newtype_variant("OsString", 0, "Unix", Bytes::new(val.as_bytes())),
+        None => Err(::serde::ser::Error::custom("string is not valid unicode"))
+    }
+}
+
+/// Deserializes the string, converting strings of the other platform family if they are valid
+/// unicode
+#[inline]
+pub fn deserialize<'de, D: Deserializer<'de>>(de: D) -> Result<OsString, D::Error> {
+    de.deserialize_enum("OsString", VARIANTS, OsStringVisitor)
+}
+
+struct OsStringVisitor;
+
+impl<'de> Visitor<'de> for OsStringVisitor {
+    type Value = OsString;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("os string")
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Self::Value, A::Error> {
+        let res = match data.variant()? {
+            (Platform::Unix, variant) => from_unix(variant.newtype_variant::<ByteBuf>()?.into_vec()),
+            (Platform::Windows, variant) => from_windows(variant.newtype_variant()?)
+        };
+        res.ok_or_else(|| A::Error::custom("string of other platform is not valid unicode"))
+    }
+}
+
+/// A wrapper type that (de-)serializes an OS string losslessly
+///
+/// See the [module documentation](index.html) for details.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct LosslessOsString(pub OsString);
+
+impl LosslessOsString {
+    /// Returns the wrapped string
+    #[inline]
+    pub fn into_inner(self) -> OsString {
+        self.0
+    }
+}
+
+impl From<OsString> for LosslessOsString {
+    #[inline]
+    fn from(val: OsString) -> Self {
+        LosslessOsString(val)
+    }
+}
+
+impl Deref for LosslessOsString {
+    type Target = OsString;
+
+    #[inline]
+    fn deref(&self) -> &OsString {
+        &self.0
+    }
+}
+
+impl DerefMut for LosslessOsString {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut OsString {
+        &mut self.0
+    }
+}
+
+impl Serialize for LosslessOsString {
+    #[inline]
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        serialize(&self.0, ser)
+    }
+}
+
+impl<'de> Deserialize<'de> for LosslessOsString {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        deserialize(de).map(LosslessOsString)
+    }
+}
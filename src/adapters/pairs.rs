@@ -0,0 +1,133 @@
+//! Maps encoded as sequences of key-value pairs
+//!
+//! Some formats like JSON only allow strings as map keys. This module encodes maps as a sequence
+//! of `[key, value]` pairs instead, so that maps with integer or struct keys can be used in all
+//! formats.
+//!
+//! The functions can be used with `#[serde(with = "serde_utils::adapters::pairs")]` on fields of
+//! type `BTreeMap<K, V>`, `HashMap<K, V>` and other map types that can be iterated by reference
+//! and implement `Default` and `Extend`. The wrapper type [`Pairs`](struct.Pairs.html) can be
+//! used as field type for `BTreeMap` and `HashMap` instead.
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Test {
+//!     #[serde(with = "serde_utils::adapters::pairs")]
+//!     ports: BTreeMap<u16, String>,
+//!     names: Pairs<HashMap<Id, String>>
+//! }
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::de::{Visitor, SeqAccess};
+
+/// Serializes the map as a sequence of key-value pairs
+#[inline]
+pub fn serialize<'a, M, K, V, S>(map: &'a M, ser: S) -> Result<S::Ok, S::Error> where
+    &'a M: IntoIterator<Item=(&'a K, &'a V)>, K: Serialize + 'a, V: Serialize + 'a, S: Serializer
+{
+    ser.collect_seq(map)
+}
+
+/// Deserializes the map from a sequence of key-value pairs
+///
+/// If a key occurs multiple times, the map type decides which value is kept.
+#[inline]
+pub fn deserialize<'de, M, K, V, D>(de: D) -> Result<M, D::Error> where
+    M: Default + Extend<(K, V)>, K: Deserialize<'de>, V: Deserialize<'de>, D: Deserializer<'de>
+{
+    de.deserialize_seq(PairsVisitor(PhantomData))
+}
+
+struct PairsVisitor<M, K, V>(PhantomData<(M, K, V)>);
+
+impl<'de, M, K, V> Visitor<'de> for PairsVisitor<M, K, V> where
+    M: Default + Extend<(K, V)>, K: Deserialize<'de>, V: Deserialize<'de>
+{
+    type Value = M;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("sequence of key-value pairs")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut map = M::default();
+        while let Some(pair) = seq.next_element::<(K, V)>()? {
+            map.extend(Some(pair));
+        }
+        Ok(map)
+    }
+}
+
+/// A wrapper type that (de-)serializes a map as a sequence of key-value pairs
+///
+/// See the [module documentation](index.html) for details.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Pairs<M>(pub M);
+
+impl<M> Pairs<M> {
+    /// Returns the wrapped map
+    #[inline]
+    pub fn into_inner(self) -> M {
+        self.0
+    }
+}
+
+impl<M> From<M> for Pairs<M> {
+    #[inline]
+    fn from(val: M) -> Self {
+        Pairs(val)
+    }
+}
+
+impl<M> Deref for Pairs<M> {
+    type Target = M;
+
+    #[inline]
+    fn deref(&self) -> &M {
+        &self.0
+    }
+}
+
+impl<M> DerefMut for Pairs<M> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut M {
+        &mut self.0
+    }
+}
+
+impl<K: Serialize, V: Serialize> Serialize for Pairs<BTreeMap<K, V>> {
+    #[inline]
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        serialize(&self.0, ser)
+    }
+}
+
+impl<'de, K: Deserialize<'de> + Ord, V: Deserialize<'de>> Deserialize<'de> for Pairs<BTreeMap<K, V>> {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        deserialize(de).map(Pairs)
+    }
+}
+
+impl<K: Serialize, V: Serialize, H> Serialize for Pairs<HashMap<K, V, H>> {
+    #[inline]
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        serialize(&self.0, ser)
+    }
+}
+
+impl<'de, K, V, H> Deserialize<'de> for Pairs<HashMap<K, V, H>> where
+    K: Deserialize<'de> + Eq + Hash, V: Deserialize<'de>, H: BuildHasher + Default
+{
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        deserialize(de).map(Pairs)
+    }
+}
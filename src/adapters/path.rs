@@ -0,0 +1,152 @@
+//! Portable strings for paths
+//!
+//! This module (de-)serializes `PathBuf` as UTF-8 string with forward slashes as separators in all
+//! formats, so that paths written on Windows can be read on other systems and vice versa. Backslashes
+//! are converted to forward slashes in both directions, so they can not be used in file names.
+//!
+//! Paths that are not valid unicode can not be serialized with this module. The submodule
+//! [`lossy`](lossy/index.html) replaces invalid sequences with `U+FFFD` instead.
+//!
+//! The modules can be used with `#[serde(with = "serde_utils::adapters::path")]` and
+//! `#[serde(with = "serde_utils::adapters::path::lossy")]` on fields of type `PathBuf`. The wrapper
+//! types [`PortablePath`](struct.PortablePath.html) and [`LossyPath`](struct.LossyPath.html) can be
+//! used as field types instead.
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Config {
+//!     #[serde(with = "serde_utils::adapters::path")]
+//!     data_dir: PathBuf,
+//!     log_file: LossyPath
+//! }
+//! ```
+
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
+
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::de::{Visitor, Error};
+use serde::ser::Error as SerError;
+
+#[inline]
+fn portable(path: &str) -> String {
+    path.replace('\\', "/")
+}
+
+struct PathVisitor;
+
+impl<'de> Visitor<'de> for PathVisitor {
+    type Value = PathBuf;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("path string")
+    }
+
+    fn visit_str<E: Error>(self, val: &str) -> Result<Self::Value, E> {
+        Ok(PathBuf::from(portable(val)))
+    }
+}
+
+/// Serializes the path as string with forward slashes, failing if it is not valid unicode
+#[inline]
+pub fn serialize<P: AsRef<Path>, S: Serializer>(path: &P, ser: S) -> Result<S::Ok, S::Error> {
+    match path.as_ref().to_str() {
+        Some(path) => ser.serialize_str(&portable(path)),
+        None => Err(S::Error::custom(format_args!("path is not valid unicode: {:?}", path.as_ref())))
+    }
+}
+
+/// Deserializes the path from a string with forward slashes or backslashes
+#[inline]
+pub fn deserialize<'de, D: Deserializer<'de>>(de: D) -> Result<PathBuf, D::Error> {
+    de.deserialize_str(PathVisitor)
+}
+
+/// Lossy conversion of paths that are not valid unicode
+pub mod lossy {
+    use std::path::{Path, PathBuf};
+
+    use serde::{Serializer, Deserializer};
+
+    use super::portable;
+
+    /// Serializes the path as string with forward slashes, replacing invalid unicode
+    #[inline]
+    pub fn serialize<P: AsRef<Path>, S: Serializer>(path: &P, ser: S) -> Result<S::Ok, S::Error> {
+        ser.serialize_str(&portable(&path.as_ref().to_string_lossy()))
+    }
+
+    /// Deserializes the path from a string with forward slashes or backslashes
+    #[inline]
+    pub fn deserialize<'de, D: Deserializer<'de>>(de: D) -> Result<PathBuf, D::Error> {
+        super::deserialize(de)
+    }
+}
+
+macro_rules! path_wrapper(
+    ( $(#[$doc:meta])* $wrapper:ident, $serialize:path, $deserialize:path ) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+        pub struct $wrapper(pub PathBuf);
+
+        impl $wrapper {
+            /// Returns the wrapped path
+            #[inline]
+            pub fn into_inner(self) -> PathBuf {
+                self.0
+            }
+        }
+
+        impl From<PathBuf> for $wrapper {
+            #[inline]
+            fn from(val: PathBuf) -> Self {
+                $wrapper(val)
+            }
+        }
+
+        impl Deref for $wrapper {
+            type Target = PathBuf;
+
+            #[inline]
+            fn deref(&self) -> &PathBuf {
+                &self.0
+            }
+        }
+
+        impl DerefMut for $wrapper {
+            #[inline]
+            fn deref_mut(&mut self) -> &mut PathBuf {
+                &mut self.0
+            }
+        }
+
+        impl Serialize for $wrapper {
+            #[inline]
+            fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+                $serialize(&self.0, ser)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $wrapper {
+            #[inline]
+            fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+                $deserialize(de).map($wrapper)
+            }
+        }
+    };
+);
+
+path_wrapper!(
+    /// A wrapper type that (de-)serializes a path as portable string
+    ///
+    /// See the [module documentation](index.html) for details.
+    PortablePath, serialize, deserialize
+);
+
+path_wrapper!(
+    /// A wrapper type that (de-)serializes a path as portable string, replacing invalid unicode
+    ///
+    /// See the [module documentation](index.html) for details.
+    LossyPath, lossy::serialize, lossy::deserialize
+);
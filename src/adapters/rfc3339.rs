@@ -0,0 +1,178 @@
+//! RFC 3339 strings for calendar types
+//!
+//! This module (de-)serializes date and time values as RFC 3339 strings like
+//! `"2020-01-02T03:04:05Z"` or `"2020-01-02T03:04:05.5+01:00"` in all formats. Fractional seconds
+//! are only written if they are not zero.
+//!
+//! The supported types depend on the enabled features:
+//!
+//! * `chrono` - `chrono::DateTime<Utc>`, `chrono::DateTime<FixedOffset>` and
+//!   `chrono::NaiveDateTime`. Naive values are written in UTC and other offsets are converted to
+//!   UTC when reading them.
+//! * `time` - `time::OffsetDateTime`
+//!
+//! For integer timestamps, the same types can be used with the
+//! [`timestamp`](../timestamp/index.html) adapters.
+//!
+//! The functions can be used with `#[serde(with = "serde_utils::adapters::rfc3339")]` on fields of
+//! the supported types. The wrapper type [`Rfc3339`](struct.Rfc3339.html) can be used as field type
+//! instead.
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Event {
+//!     #[serde(with = "serde_utils::adapters::rfc3339")]
+//!     created: DateTime<Utc>,
+//!     modified: Rfc3339<OffsetDateTime>
+//! }
+//! ```
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::de::{Visitor, Error};
+use serde::ser::Error as SerError;
+
+/// Calendar types that can be encoded as RFC 3339 string
+pub trait CalendarTime: Sized {
+    /// Formats the value as RFC 3339 string
+    ///
+    /// On failure, a description of the problem is returned.
+    fn to_rfc3339(&self) -> Result<String, String>;
+
+    /// Parses the value from an RFC 3339 string
+    ///
+    /// On failure, a description of the problem is returned.
+    fn from_rfc3339(val: &str) -> Result<Self, String>;
+}
+
+#[cfg(feature = "chrono")]
+impl CalendarTime for ::chrono::DateTime<::chrono::Utc> {
+    #[inline]
+    fn to_rfc3339(&self) -> Result<String, String> {
+        Ok(self.to_rfc3339_opts(::chrono::SecondsFormat::AutoSi, true))
+    }
+
+    #[inline]
+    fn from_rfc3339(val: &str) -> Result<Self, String> {
+        ::chrono::DateTime::parse_from_rfc3339(val).map(|val| val.with_timezone(&::chrono::Utc)).map_err(|err| err.to_string())
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl CalendarTime for ::chrono::DateTime<::chrono::FixedOffset> {
+    #[inline]
+    fn to_rfc3339(&self) -> Result<String, String> {
+        Ok(self.to_rfc3339_opts(::chrono::SecondsFormat::AutoSi, true))
+    }
+
+    #[inline]
+    fn from_rfc3339(val: &str) -> Result<Self, String> {
+        ::chrono::DateTime::parse_from_rfc3339(val).map_err(|err| err.to_string())
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl CalendarTime for ::chrono::NaiveDateTime {
+    #[inline]
+    fn to_rfc3339(&self) -> Result<String, String> {
+        CalendarTime::to_rfc3339(&self.and_utc())
+    }
+
+    #[inline]
+    fn from_rfc3339(val: &str) -> Result<Self, String> {
+        ::chrono::DateTime::<::chrono::Utc>::from_rfc3339(val).map(|val| val.naive_utc())
+    }
+}
+
+#[cfg(feature = "time")]
+impl CalendarTime for ::time::OffsetDateTime {
+    #[inline]
+    fn to_rfc3339(&self) -> Result<String, String> {
+        self.format(&::time::format_description::well_known::Rfc3339).map_err(|err| err.to_string())
+    }
+
+    #[inline]
+    fn from_rfc3339(val: &str) -> Result<Self, String> {
+        ::time::OffsetDateTime::parse(val, &::time::format_description::well_known::Rfc3339).map_err(|err| err.to_string())
+    }
+}
+
+/// Serializes the value as RFC 3339 string
+#[inline]
+pub fn serialize<T: CalendarTime, S: Serializer>(val: &T, ser: S) -> Result<S::Ok, S::Error> {
+    ser.serialize_str(&val.to_rfc3339().map_err(S::Error::custom)?)
+}
+
+/// Deserializes the value from an RFC 3339 string
+#[inline]
+pub fn deserialize<'de, T: CalendarTime, D: Deserializer<'de>>(de: D) -> Result<T, D::Error> {
+    de.deserialize_str(Rfc3339Visitor(PhantomData))
+}
+
+struct Rfc3339Visitor<T>(PhantomData<T>);
+
+impl<'de, T: CalendarTime> Visitor<'de> for Rfc3339Visitor<T> {
+    type Value = T;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("RFC 3339 date and time string")
+    }
+
+    fn visit_str<E: Error>(self, val: &str) -> Result<Self::Value, E> {
+        T::from_rfc3339(val).map_err(|err| E::custom(format_args!("invalid date and time {:?}: {}", val, err)))
+    }
+}
+
+/// A wrapper type that (de-)serializes its value as RFC 3339 string
+///
+/// See the [module documentation](index.html) for details.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Rfc3339<T>(pub T);
+
+impl<T> Rfc3339<T> {
+    /// Returns the wrapped value
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for Rfc3339<T> {
+    #[inline]
+    fn from(val: T) -> Self {
+        Rfc3339(val)
+    }
+}
+
+impl<T> Deref for Rfc3339<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Rfc3339<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: CalendarTime> Serialize for Rfc3339<T> {
+    #[inline]
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        serialize(&self.0, ser)
+    }
+}
+
+impl<'de, T: CalendarTime> Deserialize<'de> for Rfc3339<T> {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        deserialize(de).map(Rfc3339)
+    }
+}
@@ -0,0 +1,235 @@
+//! Shared values that are written only once
+//!
+//! Values behind an `Rc` or `Arc` are serialized like the value itself, which writes a value that
+//! is referenced from many places once per reference and creates independent copies when reading
+//! it back. This module writes every distinct value only once and refers back to it afterwards,
+//! so the sharing survives a round trip.
+//!
+//! Deduplication happens within a [`scope`](fn.scope.html): the first occurrence of a value is
+//! written as `[id, value]`, later occurrences of the same pointer as `[id, null]`. Reading
+//! resolves these back-references within a scope as well, so all occurrences of a value share
+//! the same pointer again. Outside of a scope, every value is written in full with the id 0. The
+//! representation is a plain tuple, so it works with every format, including non-self-describing
+//! ones.
+//!
+//! The functions can be used with `#[serde(with = "serde_utils::adapters::shared")]` on fields of
+//! type `Rc<T>` or `Arc<T>`. The wrapper type [`Shared`](struct.Shared.html) can be used as field
+//! type instead, e.g. in [`serde_impl!`](../../macro.serde_impl!.html).
+//!
+//! ```ignore
+//! serde_impl!(Scene(String) {
+//!     nodes: Vec<Shared<Rc<Node>>> => "nodes"
+//! });
+//!
+//! let bytes = shared::scope(|| rmp_serde::to_vec(&scene))?;
+//! let scene: Scene = shared::scope(|| rmp_serde::from_slice(&bytes))?;
+//! ```
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::ser::SerializeTuple;
+use serde::de::{Error, Visitor, SeqAccess};
+
+/// Pointers to values that can be shared, implemented for `Rc` and `Arc`
+pub trait SharedPointer: Clone + 'static {
+    /// The type of the shared value
+    type Value;
+
+    /// Moves the value into a new pointer
+    fn share(val: Self::Value) -> Self;
+
+    /// Returns the shared value
+    fn value(&self) -> &Self::Value;
+}
+
+impl<T: 'static> SharedPointer for Rc<T> {
+    type Value = T;
+
+    #[inline]
+    fn share(val: T) -> Self {
+        Rc::new(val)
+    }
+
+    #[inline]
+    fn value(&self) -> &T {
+        self
+    }
+}
+
+impl<T: 'static> SharedPointer for Arc<T> {
+    type Value = T;
+
+    #[inline]
+    fn share(val: T) -> Self {
+        Arc::new(val)
+    }
+
+    #[inline]
+    fn value(&self) -> &T {
+        self
+    }
+}
+
+#[derive(Default)]
+struct Table {
+    /// Ids of the written values by address, the pointers keep the addresses from being reused
+    written: HashMap<usize, (u64, Box<dyn Any>)>,
+    /// Pointers of the read values by id
+    read: HashMap<u64, Box<dyn Any>>
+}
+
+thread_local! {
+    static TABLE: RefCell<Option<Table>> = const { RefCell::new(None) };
+}
+
+/// Removes the table of the outermost scope, also if the closure panics
+struct ScopeGuard;
+
+impl Drop for ScopeGuard {
+    fn drop(&mut self) {
+        TABLE.with(|table| table.borrow_mut().take());
+    }
+}
+
+/// Runs the closure in a scope that deduplicates shared values
+///
+/// All values written within the scope are written only once and all back-references read
+/// within the scope are resolved, also across several calls of the serializer or deserializer.
+/// The scope holds references to the written and read values until it ends. Nested scopes share
+/// the table of the outermost scope.
+///
+/// Serializers that visit the value more than once, e.g. `bincode::serialize` to compute the
+/// size first, would only write back-references in the later passes. They need to be called in a
+/// scope of their own per pass or replaced by a single pass like `bincode::serialize_into`.
+pub fn scope<R, F: FnOnce() -> R>(f: F) -> R {
+    let outermost = TABLE.with(|table| {
+        let mut table = table.borrow_mut();
+        if table.is_some() {
+            return false
+        }
+        *table = Some(Table::default());
+        true
+    });
+    let _guard = if outermost { Some(ScopeGuard) } else { None };
+    f()
+}
+
+/// Returns the id of the value and whether it has been written before in the current scope
+fn written_id<P: SharedPointer>(val: &P) -> (u64, bool) {
+    let addr = val.value() as *const P::Value as usize;
+    TABLE.with(|table| match *table.borrow_mut() {
+        Some(ref mut table) => {
+            let next = table.written.len() as u64;
+            if let Some(entry) = table.written.get(&addr) {
+                return (entry.0, true)
+            }
+            table.written.insert(addr, (next, Box::new(val.clone())));
+            (next, false)
+        },
+        None => (0, false)
+    })
+}
+
+/// Serializes the value as `[id, value]` the first time and as `[id, null]` afterwards
+pub fn serialize<P: SharedPointer, S: Serializer>(val: &P, ser: S) -> Result<S::Ok, S::Error> where
+    P::Value: Serialize
+{
+    let (id, written) = written_id(val);
+    let mut tuple = ser.serialize_tuple(2)?;
+    tuple.serialize_element(&id)?;
+    if written {
+        tuple.serialize_element(&None::<&P::Value>)?;
+    } else {
+        tuple.serialize_element(&Some(val.value()))?;
+    }
+    tuple.end()
+}
+
+/// Deserializes the value or resolves a back-reference to a value read before in the current scope
+#[inline]
+pub fn deserialize<'de, P: SharedPointer, D: Deserializer<'de>>(de: D) -> Result<P, D::Error> where
+    P::Value: Deserialize<'de>
+{
+    de.deserialize_tuple(2, SharedVisitor(PhantomData))
+}
+
+struct SharedVisitor<P>(PhantomData<P>);
+
+impl<'de, P: SharedPointer> Visitor<'de> for SharedVisitor<P> where P::Value: Deserialize<'de> {
+    type Value = P;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("a shared value as [id, value]")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let id: u64 = seq.next_element()?.ok_or_else(|| A::Error::invalid_length(0, &self))?;
+        let val: Option<P::Value> = seq.next_element()?.ok_or_else(|| A::Error::invalid_length(1, &self))?;
+        TABLE.with(|table| match (val, table.borrow_mut().as_mut()) {
+            (Some(val), Some(table)) => {
+                let shared = P::share(val);
+                table.read.insert(id, Box::new(shared.clone()));
+                Ok(shared)
+            },
+            (Some(val), None) => Ok(P::share(val)),
+            (None, table) => match table.and_then(|table| table.read.get(&id)) {
+                Some(shared) => shared.downcast_ref::<P>().cloned().ok_or_else(|| {
+                    A::Error::custom(format_args!("shared value {} has a different type", id))
+                }),
+                None => Err(A::Error::custom(format_args!("unknown shared value {}", id)))
+            }
+        })
+    }
+}
+
+/// A wrapper type that writes the shared value only once within a [`scope`](fn.scope.html)
+///
+/// See the [module documentation](index.html) for details.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Shared<P>(pub P);
+
+impl<P> Shared<P> {
+    /// Returns the wrapped pointer
+    #[inline]
+    pub fn into_inner(self) -> P {
+        self.0
+    }
+}
+
+impl<P> From<P> for Shared<P> {
+    #[inline]
+    fn from(val: P) -> Self {
+        Shared(val)
+    }
+}
+
+impl<P> Deref for Shared<P> {
+    type Target = P;
+
+    #[inline]
+    fn deref(&self) -> &P {
+        &self.0
+    }
+}
+
+impl<P: SharedPointer> Serialize for Shared<P> where P::Value: Serialize {
+    #[inline]
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        serialize(&self.0, ser)
+    }
+}
+
+impl<'de, P: SharedPointer> Deserialize<'de> for Shared<P> where P::Value: Deserialize<'de> {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        deserialize(de).map(Shared)
+    }
+}
@@ -0,0 +1,265 @@
+//! Epoch-based encodings for `SystemTime` and `Duration`
+//!
+//! This module contains one submodule per representation:
+//!
+//! * [`secs`](secs/index.html) - whole seconds as unsigned integer, sub-second precision is lost
+//! * [`millis`](millis/index.html) - whole milliseconds as unsigned integer
+//! * [`secs_nanos`](secs_nanos/index.html) - a tuple of seconds and nanoseconds without any loss
+//!
+//! `SystemTime` values are encoded as time since the Unix epoch, times before the epoch can not be
+//! serialized. `Duration` values are encoded directly. With the `chrono` and `time` features,
+//! `chrono::DateTime<Utc>`, `chrono::NaiveDateTime` (interpreted as UTC) and
+//! `time::OffsetDateTime` are supported like `SystemTime`.
+//!
+//! The submodules can be used with e.g. `#[serde(with = "serde_utils::adapters::timestamp::millis")]`
+//! on fields of both types. The wrapper types [`Secs`](struct.Secs.html),
+//! [`Millis`](struct.Millis.html) and [`SecsNanos`](struct.SecsNanos.html) can be used as field
+//! types instead.
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Test {
+//!     #[serde(with = "serde_utils::adapters::timestamp::secs")]
+//!     created: SystemTime,
+//!     timeout: Millis<Duration>
+//! }
+//! ```
+
+#[cfg(feature = "chrono")] use std::convert::TryFrom;
+use std::ops::{Deref, DerefMut};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+
+/// Types that can be encoded as time since the Unix epoch
+pub trait Timestamp: Sized {
+    /// Returns the time since the Unix epoch
+    ///
+    /// On failure, a description of the problem is returned.
+    fn to_epoch(&self) -> Result<Duration, &'static str>;
+
+    /// Creates the value from the time since the Unix epoch
+    ///
+    /// `None` is returned if the value is out of range.
+    fn from_epoch(epoch: Duration) -> Option<Self>;
+}
+
+impl Timestamp for Duration {
+    #[inline]
+    fn to_epoch(&self) -> Result<Duration, &'static str> {
+        Ok(*self)
+    }
+
+    #[inline]
+    fn from_epoch(epoch: Duration) -> Option<Self> {
+        Some(epoch)
+    }
+}
+
+impl Timestamp for SystemTime {
+    #[inline]
+    fn to_epoch(&self) -> Result<Duration, &'static str> {
+        self.duration_since(UNIX_EPOCH).map_err(|_| "time is before the unix epoch")
+    }
+
+    #[inline]
+    fn from_epoch(epoch: Duration) -> Option<Self> {
+        UNIX_EPOCH.checked_add(epoch)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl Timestamp for ::chrono::DateTime<::chrono::Utc> {
+    #[inline]
+    fn to_epoch(&self) -> Result<Duration, &'static str> {
+        if self.timestamp() < 0 {
+            return Err("time is before the unix epoch");
+        }
+        Ok(Duration::new(self.timestamp() as u64, self.timestamp_subsec_nanos()))
+    }
+
+    #[inline]
+    fn from_epoch(epoch: Duration) -> Option<Self> {
+        let secs = TryFrom::try_from(epoch.as_secs()).ok()?;
+        ::chrono::DateTime::from_timestamp(secs, epoch.subsec_nanos())
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl Timestamp for ::chrono::NaiveDateTime {
+    #[inline]
+    fn to_epoch(&self) -> Result<Duration, &'static str> {
+        self.and_utc().to_epoch()
+    }
+
+    #[inline]
+    fn from_epoch(epoch: Duration) -> Option<Self> {
+        ::chrono::DateTime::from_epoch(epoch).map(|val| val.naive_utc())
+    }
+}
+
+#[cfg(feature = "time")]
+impl Timestamp for ::time::OffsetDateTime {
+    #[inline]
+    fn to_epoch(&self) -> Result<Duration, &'static str> {
+        if self.unix_timestamp() < 0 {
+            return Err("time is before the unix epoch");
+        }
+        Ok(Duration::new(self.unix_timestamp() as u64, self.nanosecond()))
+    }
+
+    #[inline]
+    fn from_epoch(epoch: Duration) -> Option<Self> {
+        ::time::OffsetDateTime::from_unix_timestamp_nanos(epoch.as_nanos() as i128).ok()
+    }
+}
+
+fn from_epoch<T: Timestamp, E: ::serde::de::Error>(epoch: Duration) -> Result<T, E> {
+    T::from_epoch(epoch).ok_or_else(|| E::custom("timestamp out of range"))
+}
+
+macro_rules! timestamp_repr(
+    ( $(#[$doc:meta])* $module:ident, $wrapper:ident ) => {
+        $(#[$doc])*
+        pub mod $module {
+            use serde::{Serializer, Deserializer};
+            use serde::ser::Error as SerError;
+
+            use super::Timestamp;
+
+            /// Serializes the value in this representation
+            #[inline]
+            pub fn serialize<T: Timestamp, S: Serializer>(val: &T, ser: S) -> Result<S::Ok, S::Error> {
+                let epoch = val.to_epoch().map_err(S::Error::custom)?;
+                super::encode::$module(epoch, ser)
+            }
+
+            /// Deserializes the value from this representation
+            #[inline]
+            pub fn deserialize<'de, T: Timestamp, D: Deserializer<'de>>(de: D) -> Result<T, D::Error> {
+                super::from_epoch(super::decode::$module(de)?)
+            }
+        }
+
+        /// A wrapper type that (de-)serializes its value in the
+        #[doc = concat!("[`", stringify!($module), "`](", stringify!($module), "/index.html)")]
+        /// representation
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub struct $wrapper<T = SystemTime>(pub T);
+
+        impl<T> $wrapper<T> {
+            /// Returns the wrapped value
+            #[inline]
+            pub fn into_inner(self) -> T {
+                self.0
+            }
+        }
+
+        impl<T> From<T> for $wrapper<T> {
+            #[inline]
+            fn from(val: T) -> Self {
+                $wrapper(val)
+            }
+        }
+
+        impl<T> Deref for $wrapper<T> {
+            type Target = T;
+
+            #[inline]
+            fn deref(&self) -> &T {
+                &self.0
+            }
+        }
+
+        impl<T> DerefMut for $wrapper<T> {
+            #[inline]
+            fn deref_mut(&mut self) -> &mut T {
+                &mut self.0
+            }
+        }
+
+        impl<T: Timestamp> Serialize for $wrapper<T> {
+            #[inline]
+            fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+                $module::serialize(&self.0, ser)
+            }
+        }
+
+        impl<'de, T: Timestamp> Deserialize<'de> for $wrapper<T> {
+            #[inline]
+            fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+                $module::deserialize(de).map($wrapper)
+            }
+        }
+    };
+);
+
+mod encode {
+    use std::time::Duration;
+
+    use serde::{Serialize, Serializer};
+    use serde::ser::Error;
+
+    #[inline]
+    pub fn secs<S: Serializer>(epoch: Duration, ser: S) -> Result<S::Ok, S::Error> {
+        ser.serialize_u64(epoch.as_secs())
+    }
+
+    #[inline]
+    pub fn millis<S: Serializer>(epoch: Duration, ser: S) -> Result<S::Ok, S::Error> {
+        if epoch.as_millis() > u128::from(u64::MAX) {
+            return Err(S::Error::custom("timestamp out of range"));
+        }
+        ser.serialize_u64(epoch.as_millis() as u64)
+    }
+
+    #[inline]
+    pub fn secs_nanos<S: Serializer>(epoch: Duration, ser: S) -> Result<S::Ok, S::Error> {
+        (epoch.as_secs(), epoch.subsec_nanos()).serialize(ser)
+    }
+}
+
+mod decode {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer};
+    use serde::de::{Error, Unexpected};
+
+    #[inline]
+    pub fn secs<'de, D: Deserializer<'de>>(de: D) -> Result<Duration, D::Error> {
+        u64::deserialize(de).map(Duration::from_secs)
+    }
+
+    #[inline]
+    pub fn millis<'de, D: Deserializer<'de>>(de: D) -> Result<Duration, D::Error> {
+        u64::deserialize(de).map(Duration::from_millis)
+    }
+
+    #[inline]
+    pub fn secs_nanos<'de, D: Deserializer<'de>>(de: D) -> Result<Duration, D::Error> {
+        let (secs, nanos) = <(u64, u32)>::deserialize(de)?;
+        if nanos >= 1_000_000_000 {
+            return Err(D::Error::invalid_value(Unexpected::Unsigned(u64::from(nanos)), &"less than 1000000000 nanoseconds"));
+        }
+        Ok(Duration::new(secs, nanos))
+    }
+}
+
+timestamp_repr!(
+    /// Whole seconds since the epoch as unsigned integer
+    ///
+    /// Sub-second precision is lost when serializing.
+    secs, Secs
+);
+
+timestamp_repr!(
+    /// Whole milliseconds since the epoch as unsigned integer
+    ///
+    /// Sub-millisecond precision is lost when serializing.
+    millis, Millis
+);
+
+timestamp_repr!(
+    /// A tuple of whole seconds and nanoseconds since the epoch
+    secs_nanos, SecsNanos
+);
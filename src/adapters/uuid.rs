@@ -0,0 +1,103 @@
+//! Compact encoding for UUIDs
+//!
+//! This module requires the `uuid` feature. It (de-)serializes `uuid::Uuid` as hyphenated string
+//! like `"67e55044-10b1-426f-9247-bb680e5fe0c8"` in human-readable formats and as 16 raw bytes
+//! otherwise. When deserializing strings, all formats accepted by `Uuid::parse_str` are supported.
+//!
+//! The functions can be used with `#[serde(with = "serde_utils::adapters::uuid")]` on fields of type
+//! `Uuid`. The wrapper type [`CompactUuid`](struct.CompactUuid.html) can be used as field type
+//! instead.
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Message {
+//!     #[serde(with = "serde_utils::adapters::uuid")]
+//!     id: Uuid,
+//!     parent: CompactUuid
+//! }
+//! ```
+
+use std::ops::{Deref, DerefMut};
+
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+
+use uuid::Uuid;
+
+use super::{BytesVisitor, from_bytes};
+
+fn decode(val: &str) -> Result<Vec<u8>, &'static str> {
+    Uuid::parse_str(val).map(|val| val.as_bytes().to_vec()).map_err(|_| "invalid uuid")
+}
+
+/// Serializes the UUID as hyphenated string in human-readable formats and as bytes otherwise
+#[inline]
+pub fn serialize<S: Serializer>(val: &Uuid, ser: S) -> Result<S::Ok, S::Error> {
+    if ser.is_human_readable() {
+        ser.collect_str(&val.hyphenated())
+    } else {
+        ser.serialize_bytes(val.as_bytes())
+    }
+}
+
+/// Deserializes the UUID from a string in human-readable formats and from bytes otherwise
+#[inline]
+pub fn deserialize<'de, D: Deserializer<'de>>(de: D) -> Result<Uuid, D::Error> {
+    let visitor = BytesVisitor::new("UUID string or 16 bytes", decode);
+    let bytes = if de.is_human_readable() {
+        de.deserialize_str(visitor)?
+    } else {
+        de.deserialize_bytes(visitor)?
+    };
+    from_bytes(bytes).map(Uuid::from_bytes)
+}
+
+/// A wrapper type that (de-)serializes a UUID as string or as raw bytes
+///
+/// See the [module documentation](index.html) for details.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct CompactUuid(pub Uuid);
+
+impl CompactUuid {
+    /// Returns the wrapped UUID
+    #[inline]
+    pub fn into_inner(self) -> Uuid {
+        self.0
+    }
+}
+
+impl From<Uuid> for CompactUuid {
+    #[inline]
+    fn from(val: Uuid) -> Self {
+        CompactUuid(val)
+    }
+}
+
+impl Deref for CompactUuid {
+    type Target = Uuid;
+
+    #[inline]
+    fn deref(&self) -> &Uuid {
+        &self.0
+    }
+}
+
+impl DerefMut for CompactUuid {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Uuid {
+        &mut self.0
+    }
+}
+
+impl Serialize for CompactUuid {
+    #[inline]
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        serialize(&self.0, ser)
+    }
+}
+
+impl<'de> Deserialize<'de> for CompactUuid {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        deserialize(de).map(CompactUuid)
+    }
+}
@@ -0,0 +1,130 @@
+//! Zero as `None` for non-zero integers
+//!
+//! Some protocols use zero as sentinel value for "unset". This module (de-)serializes
+//! `Option<NonZeroU64>` and the other non-zero integer types as plain integers, where `0` stands
+//! for `None`.
+//!
+//! The functions can be used with `#[serde(with = "serde_utils::adapters::zero_none")]` on fields
+//! of type `Option<NonZeroU64>` etc. The wrapper type [`ZeroNone`](struct.ZeroNone.html) can be
+//! used as field type instead.
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Header {
+//!     #[serde(with = "serde_utils::adapters::zero_none")]
+//!     parent: Option<NonZeroU64>,
+//!     session: ZeroNone<NonZeroU32>
+//! }
+//! ```
+
+use std::num::{NonZeroU8, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU128, NonZeroUsize};
+use std::num::{NonZeroI8, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI128, NonZeroIsize};
+use std::ops::{Deref, DerefMut};
+
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+
+/// Non-zero integer types supported by this module
+pub trait NonZero: Copy {
+    /// The corresponding integer type that includes zero
+    type Int: Copy + Default + Serialize + for<'de> Deserialize<'de>;
+
+    /// Returns the value as integer
+    fn get(self) -> Self::Int;
+
+    /// Creates the value from an integer, `None` if it is zero
+    fn new(val: Self::Int) -> Option<Self>;
+}
+
+macro_rules! impl_non_zero(
+    ( $( $ty:ident($int:ty) ),* ) => {
+        $(
+            impl NonZero for $ty {
+                type Int = $int;
+
+                #[inline]
+                fn get(self) -> $int {
+                    $ty::get(self)
+                }
+
+                #[inline]
+                fn new(val: $int) -> Option<Self> {
+                    $ty::new(val)
+                }
+            }
+        )*
+    };
+);
+
+impl_non_zero!(NonZeroU8(u8), NonZeroU16(u16), NonZeroU32(u32), NonZeroU64(u64), NonZeroU128(u128),
+    NonZeroUsize(usize), NonZeroI8(i8), NonZeroI16(i16), NonZeroI32(i32), NonZeroI64(i64),
+    NonZeroI128(i128), NonZeroIsize(isize));
+
+/// Serializes `None` as `0` and other values as their integer
+#[inline]
+pub fn serialize<T: NonZero, S: Serializer>(val: &Option<T>, ser: S) -> Result<S::Ok, S::Error> {
+    val.map_or_else(T::Int::default, T::get).serialize(ser)
+}
+
+/// Deserializes `0` as `None` and other integers as their non-zero value
+#[inline]
+pub fn deserialize<'de, T: NonZero, D: Deserializer<'de>>(de: D) -> Result<Option<T>, D::Error> {
+    T::Int::deserialize(de).map(T::new)
+}
+
+/// A wrapper type that (de-)serializes `None` as zero
+///
+/// See the [module documentation](index.html) for details.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ZeroNone<T>(pub Option<T>);
+
+impl<T> Default for ZeroNone<T> {
+    #[inline]
+    fn default() -> Self {
+        ZeroNone(None)
+    }
+}
+
+impl<T> ZeroNone<T> {
+    /// Returns the wrapped option
+    #[inline]
+    pub fn into_inner(self) -> Option<T> {
+        self.0
+    }
+}
+
+impl<T> From<Option<T>> for ZeroNone<T> {
+    #[inline]
+    fn from(val: Option<T>) -> Self {
+        ZeroNone(val)
+    }
+}
+
+impl<T> Deref for ZeroNone<T> {
+    type Target = Option<T>;
+
+    #[inline]
+    fn deref(&self) -> &Option<T> {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for ZeroNone<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Option<T> {
+        &mut self.0
+    }
+}
+
+impl<T: NonZero> Serialize for ZeroNone<T> {
+    #[inline]
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        serialize(&self.0, ser)
+    }
+}
+
+impl<'de, T: NonZero> Deserialize<'de> for ZeroNone<T> {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        deserialize(de).map(ZeroNone)
+    }
+}
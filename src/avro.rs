@@ -0,0 +1,507 @@
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+
+use generic::Obj;
+use map::MapBuilder;
+use path::{ObjPath, PathSegment};
+use extract::ExtractError;
+
+const PRIMITIVES: [&str; 8] = ["null", "boolean", "int", "long", "float", "double", "bytes", "string"];
+const COMPLEX: [&str; 6] = ["record", "error", "enum", "fixed", "array", "map"];
+
+/// A schema inferred from a value, rendered as Avro schema JSON by `render`
+#[derive(Clone, PartialEq)]
+enum Inferred {
+    Primitive(&'static str),
+    Array(Box<Inferred>),
+    Map(Box<Inferred>),
+    /// Name and fields, optional fields are missing in some of the values
+    Record(String, Vec<Field>),
+    Union(Vec<Inferred>)
+}
+
+#[derive(Clone, PartialEq)]
+struct Field {
+    name: String,
+    ty: Inferred,
+    optional: bool
+}
+
+fn is_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => chars.all(|c| c.is_ascii_alphanumeric() || c == '_'),
+        _ => false
+    }
+}
+
+fn error(path: &ObjPath, obj: &Obj, message: &str) -> ExtractError {
+    ExtractError::new(path.clone(), "Avro", Some(obj.kind()), message)
+}
+
+fn infer(obj: &Obj, name: &str, path: &mut ObjPath) -> Result<Inferred, ExtractError> {
+    Ok(match *obj {
+        Obj::Null => Inferred::Primitive("null"),
+        Obj::Bool(_) => Inferred::Primitive("boolean"),
+        Obj::Signed(_) => Inferred::Primitive("long"),
+        Obj::Unsigned(val) if val <= i64::MAX as u64 => Inferred::Primitive("long"),
+        Obj::Float(_) => Inferred::Primitive("double"),
+        Obj::Str(_) => Inferred::Primitive("string"),
+        Obj::Bin(_) => Inferred::Primitive("bytes"),
+        Obj::F64Array(_) => Inferred::Array(Box::new(Inferred::Primitive("double"))),
+        Obj::U64Array(ref list) if list.iter().all(|&val| val <= i64::MAX as u64) => {
+            Inferred::Array(Box::new(Inferred::Primitive("long")))
+        },
+        Obj::List(ref list) => {
+            let name = format!("{}_item", name);
+            let mut items: Option<Inferred> = None;
+            for (index, val) in list.iter().enumerate() {
+                path.push(PathSegment::Index(index));
+                let ty = infer(val, &name, path)?;
+                path.pop();
+                items = Some(match items {
+                    Some(items) => merge(items, ty),
+                    None => ty
+                });
+            }
+            Inferred::Array(Box::new(items.unwrap_or(Inferred::Primitive("null"))))
+        },
+        Obj::Map(ref entries) => {
+            let mut keys = Vec::with_capacity(entries.len());
+            for (key, _) in entries.iter() {
+                match *key {
+                    Obj::Str(ref key) => keys.push(key.as_str()),
+                    _ => return Err(error(path, obj, &format!("map key {} is not a string", key)))
+                }
+            }
+            if keys.iter().all(|key| is_name(key)) {
+                let mut fields = Vec::with_capacity(entries.len());
+                for (key, val) in entries.iter() {
+                    path.push(PathSegment::Key(key.clone()));
+                    let field = keys[fields.len()];
+                    let ty = infer(val, &format!("{}_{}", name, field), path)?;
+                    path.pop();
+                    fields.push(Field { name: field.to_string(), ty, optional: false });
+                }
+                Inferred::Record(name.to_string(), fields)
+            } else {
+                let name = format!("{}_value", name);
+                let mut values: Option<Inferred> = None;
+                for (key, val) in entries.iter() {
+                    path.push(PathSegment::Key(key.clone()));
+                    let ty = infer(val, &name, path)?;
+                    path.pop();
+                    values = Some(match values {
+                        Some(values) => merge(values, ty),
+                        None => ty
+                    });
+                }
+                Inferred::Map(Box::new(values.unwrap_or(Inferred::Primitive("null"))))
+            }
+        },
+        #[cfg(feature = "rust_decimal")]
+        Obj::Decimal(_) => Inferred::Primitive("string"),
+        _ => return Err(error(path, obj, "integer is too large for Avro"))
+    })
+}
+
+/// Merges two schemas into one that accepts the values of both
+///
+/// Avro unions may only contain one array, one map and one record of each name, so those are
+/// merged recursively. Fields that are missing on one side become optional.
+fn merge(a: Inferred, b: Inferred) -> Inferred {
+    if a == b {
+        return a
+    }
+    let mut branches = match a {
+        Inferred::Union(branches) => branches,
+        a => vec![a]
+    };
+    let others = match b {
+        Inferred::Union(branches) => branches,
+        b => vec![b]
+    };
+    for other in others {
+        match branches.iter().position(|branch| same_branch(branch, &other)) {
+            Some(pos) => {
+                let branch = branches.remove(pos);
+                branches.insert(pos, merge_branch(branch, other));
+            },
+            None => branches.push(other)
+        }
+    }
+    if branches.len() == 1 {
+        return branches.remove(0)
+    }
+    // Null first so that optional fields can default to null
+    branches.sort_by_key(|branch| *branch != Inferred::Primitive("null"));
+    Inferred::Union(branches)
+}
+
+fn same_branch(a: &Inferred, b: &Inferred) -> bool {
+    match (a, b) {
+        (Inferred::Primitive(a), Inferred::Primitive(b)) => a == b,
+        (Inferred::Array(_), Inferred::Array(_)) | (Inferred::Map(_), Inferred::Map(_)) => true,
+        (Inferred::Record(a, _), Inferred::Record(b, _)) => a == b,
+        _ => false
+    }
+}
+
+fn merge_branch(a: Inferred, b: Inferred) -> Inferred {
+    match (a, b) {
+        (Inferred::Array(a), Inferred::Array(b)) => Inferred::Array(Box::new(merge(*a, *b))),
+        (Inferred::Map(a), Inferred::Map(b)) => Inferred::Map(Box::new(merge(*a, *b))),
+        (Inferred::Record(name, mut fields), Inferred::Record(_, others)) => {
+            for field in &mut fields {
+                if !others.iter().any(|other| other.name == field.name) {
+                    field.optional = true;
+                }
+            }
+            for other in others {
+                match fields.iter().position(|field| field.name == other.name) {
+                    Some(pos) => {
+                        let field = &mut fields[pos];
+                        field.ty = merge(field.ty.clone(), other.ty);
+                        field.optional |= other.optional;
+                    },
+                    None => fields.push(Field { optional: true, ..other })
+                }
+            }
+            Inferred::Record(name, fields)
+        },
+        (a, _) => a
+    }
+}
+
+fn schema(entries: Vec<(&'static str, Obj)>) -> Obj {
+    let mut map = MapBuilder::new(entries.len());
+    for (key, value) in entries {
+        map.insert(Obj::Str(key.to_string()), value);
+    }
+    map.finish()
+}
+
+fn render(ty: Inferred, names: &mut HashSet<String>) -> Obj {
+    match ty {
+        Inferred::Primitive(name) => Obj::Str(name.to_string()),
+        Inferred::Array(items) => schema(vec![
+            ("type", Obj::Str("array".to_string())), ("items", render(*items, names))
+        ]),
+        Inferred::Map(values) => schema(vec![
+            ("type", Obj::Str("map".to_string())), ("values", render(*values, names))
+        ]),
+        Inferred::Record(name, fields) => {
+            // Names are derived from the keys, so they can collide, e.g. `a_item` and the items of `a`
+            let mut unique = name.clone();
+            let mut counter = 1;
+            while !names.insert(unique.clone()) {
+                counter += 1;
+                unique = format!("{}_{}", name, counter);
+            }
+            let fields = fields.into_iter().map(|field| {
+                let mut entries = vec![("name", Obj::Str(field.name))];
+                if field.optional {
+                    entries.push(("type", render(merge(Inferred::Primitive("null"), field.ty), names)));
+                    entries.push(("default", Obj::Null));
+                } else {
+                    entries.push(("type", render(field.ty, names)));
+                }
+                schema(entries)
+            }).collect();
+            schema(vec![
+                ("type", Obj::Str("record".to_string())), ("name", Obj::Str(unique)), ("fields", Obj::List(fields))
+            ])
+        },
+        Inferred::Union(branches) => Obj::List(branches.into_iter().map(|branch| render(branch, names)).collect())
+    }
+}
+
+fn str_entry<'a>(schema: &'a Obj, key: &str) -> Option<&'a str> {
+    match schema.entries()?.get(&Obj::Str(key.to_string())) {
+        Some(Obj::Str(val)) => Some(val),
+        _ => None
+    }
+}
+
+fn entry<'a>(schema: &'a Obj, key: &str) -> Option<&'a Obj> {
+    schema.entries()?.get(&Obj::Str(key.to_string()))
+}
+
+fn is_symbol(symbol: &Obj, val: &str) -> bool {
+    match *symbol {
+        Obj::Str(ref symbol) => symbol == val,
+        _ => false
+    }
+}
+
+/// Registers all named types of the schema under their name and full name
+fn collect_names<'a>(schema: &'a Obj, namespace: Option<&'a str>, names: &mut HashMap<String, &'a Obj>) {
+    match *schema {
+        Obj::List(ref branches) => for branch in branches {
+            collect_names(branch, namespace, names)
+        },
+        Obj::Map(_) => {
+            let namespace = str_entry(schema, "namespace").or(namespace);
+            if let (Some("record"), Some(name)) | (Some("error"), Some(name)) | (Some("enum"), Some(name))
+                | (Some("fixed"), Some(name)) = (str_entry(schema, "type"), str_entry(schema, "name")) {
+                let short = name.rsplit('.').next().unwrap_or(name);
+                names.insert(short.to_string(), schema);
+                match namespace {
+                    Some(namespace) if !name.contains('.') => names.insert(format!("{}.{}", namespace, name), schema),
+                    _ => names.insert(name.to_string(), schema)
+                };
+            }
+            if let Some(Obj::List(fields)) = entry(schema, "fields") {
+                for field in fields {
+                    if let Some(ty) = entry(field, "type") {
+                        collect_names(ty, namespace, names);
+                    }
+                }
+            }
+            for key in &["items", "values", "type"] {
+                if let Some(nested) = entry(schema, key) {
+                    collect_names(nested, namespace, names);
+                }
+            }
+        },
+        _ => ()
+    }
+}
+
+struct Writer<'a> {
+    names: HashMap<String, &'a Obj>,
+    out: Vec<u8>
+}
+
+impl<'a> Writer<'a> {
+    fn long(&mut self, val: i64) {
+        let mut n = ((val << 1) ^ (val >> 63)) as u64;
+        while n >= 0x80 {
+            self.out.push((n as u8) | 0x80);
+            n >>= 7;
+        }
+        self.out.push(n as u8);
+    }
+
+    fn bytes(&mut self, val: &[u8]) {
+        self.long(val.len() as i64);
+        self.out.extend_from_slice(val);
+    }
+
+    /// Resolves references to named types and returns the type name and the schema object
+    fn resolve(&self, schema: &'a Obj, obj: &Obj, path: &ObjPath) -> Result<(&'a str, &'a Obj), ExtractError> {
+        match *schema {
+            Obj::Str(ref name) if PRIMITIVES.contains(&name.as_str()) => Ok((name, schema)),
+            Obj::Str(ref name) => match self.names.get(name.as_str()) {
+                Some(named) => self.resolve(named, obj, path),
+                None => Err(error(path, obj, &format!("invalid Avro schema: unknown type {}", name)))
+            },
+            Obj::List(_) => Ok(("union", schema)),
+            _ => match entry(schema, "type") {
+                Some(Obj::Str(ty)) if PRIMITIVES.contains(&ty.as_str()) || COMPLEX.contains(&ty.as_str()) => {
+                    Ok((ty, schema))
+                },
+                Some(nested) => self.resolve(nested, obj, path),
+                None => Err(error(path, obj, "invalid Avro schema: missing type"))
+            }
+        }
+    }
+
+    /// Checks whether the value can be written with the schema, used to select union branches
+    ///
+    /// In strict mode, integers only match integer types and floats only floating point types.
+    fn matches(&self, schema: &'a Obj, obj: &Obj, strict: bool) -> bool {
+        let (ty, schema) = match self.resolve(schema, obj, &ObjPath::new()) {
+            Ok(res) => res,
+            Err(_) => return false
+        };
+        match (ty, obj) {
+            ("null", &Obj::Null) | ("boolean", &Obj::Bool(_)) | ("bytes", &Obj::Bin(_)) | ("string", &Obj::Str(_)) => true,
+            ("int", &Obj::Signed(val)) => i32::try_from(val).is_ok(),
+            ("int", &Obj::Unsigned(val)) => i32::try_from(val).is_ok(),
+            ("long", &Obj::Signed(_)) => true,
+            ("long", &Obj::Unsigned(val)) => i64::try_from(val).is_ok(),
+            ("float", &Obj::Float(_)) | ("double", &Obj::Float(_)) => true,
+            ("float", &Obj::Signed(_)) | ("float", &Obj::Unsigned(_)) | ("double", &Obj::Signed(_))
+                | ("double", &Obj::Unsigned(_)) => !strict,
+            #[cfg(feature = "rust_decimal")]
+            ("string", &Obj::Decimal(_)) => true,
+            ("enum", Obj::Str(val)) => match entry(schema, "symbols") {
+                Some(Obj::List(symbols)) => symbols.iter().any(|symbol| is_symbol(symbol, val)),
+                _ => false
+            },
+            ("fixed", Obj::Bin(val)) => entry(schema, "size").and_then(Obj::as_u64) == Some(val.len() as u64),
+            ("array", &Obj::List(_)) | ("array", &Obj::U64Array(_)) | ("array", &Obj::F64Array(_)) => true,
+            ("map", &Obj::Map(_)) => true,
+            ("record", Obj::Map(entries)) | ("error", Obj::Map(entries)) => {
+                let fields = match entry(schema, "fields") {
+                    Some(Obj::List(fields)) => fields,
+                    _ => return false
+                };
+                let known = entries.iter().all(|(key, _)| fields.iter().any(|field| entry(field, "name") == Some(key)));
+                known && fields.iter().all(|field| entry(field, "default").is_some()
+                    || entry(field, "name").and_then(|name| entries.get(name)).is_some())
+            },
+            _ => false
+        }
+    }
+
+    fn write(&mut self, schema: &'a Obj, obj: &Obj, path: &mut ObjPath) -> Result<(), ExtractError> {
+        let (ty, schema) = self.resolve(schema, obj, path)?;
+        match (ty, obj) {
+            ("null", &Obj::Null) => (),
+            ("boolean", &Obj::Bool(val)) => self.out.push(val as u8),
+            ("int", _) | ("long", _) => {
+                let val = match *obj {
+                    Obj::Signed(val) => Some(val),
+                    Obj::Unsigned(val) => i64::try_from(val).ok(),
+                    _ => return Err(error(path, obj, &format!("expected {}", ty)))
+                };
+                match val {
+                    Some(val) if ty == "long" || i32::try_from(val).is_ok() => self.long(val),
+                    _ => return Err(error(path, obj, &format!("integer is too large for Avro {}", ty)))
+                }
+            },
+            ("float", _) | ("double", _) => {
+                let val = match *obj {
+                    Obj::Float(val) => val,
+                    Obj::Signed(val) => val as f64,
+                    Obj::Unsigned(val) => val as f64,
+                    _ => return Err(error(path, obj, &format!("expected {}", ty)))
+                };
+                if ty == "float" {
+                    self.out.extend_from_slice(&(val as f32).to_le_bytes());
+                } else {
+                    self.out.extend_from_slice(&val.to_le_bytes());
+                }
+            },
+            ("bytes", Obj::Bin(val)) => self.bytes(val),
+            ("string", Obj::Str(val)) => self.bytes(val.as_bytes()),
+            #[cfg(feature = "rust_decimal")]
+            ("string", Obj::Decimal(val)) => self.bytes(val.to_string().as_bytes()),
+            ("union", _) => {
+                let branches = match *schema {
+                    Obj::List(ref branches) => branches,
+                    _ => unreachable!()
+                };
+                let pos = branches.iter().position(|branch| self.matches(branch, obj, true))
+                    .or_else(|| branches.iter().position(|branch| self.matches(branch, obj, false)));
+                match pos {
+                    Some(pos) => {
+                        self.long(pos as i64);
+                        self.write(&branches[pos], obj, path)?;
+                    },
+                    None => return Err(error(path, obj, "value does not match any branch of the union"))
+                }
+            },
+            ("enum", Obj::Str(val)) => {
+                let pos = match entry(schema, "symbols") {
+                    Some(Obj::List(symbols)) => symbols.iter().position(|symbol| is_symbol(symbol, val)),
+                    _ => return Err(error(path, obj, "invalid Avro schema: enum without symbols"))
+                };
+                match pos {
+                    Some(pos) => self.long(pos as i64),
+                    None => return Err(error(path, obj, &format!("{} is not a symbol of the enum", val)))
+                }
+            },
+            ("fixed", Obj::Bin(val)) => {
+                if entry(schema, "size").and_then(Obj::as_u64) != Some(val.len() as u64) {
+                    return Err(error(path, obj, "binary value does not have the size of the fixed type"))
+                }
+                self.out.extend_from_slice(val);
+            },
+            ("array", &Obj::List(_)) | ("array", &Obj::U64Array(_)) | ("array", &Obj::F64Array(_)) => {
+                let items = entry(schema, "items")
+                    .ok_or_else(|| error(path, obj, "invalid Avro schema: array without items"))?;
+                let list = obj.clone().into_list().unwrap_or_default();
+                if !list.is_empty() {
+                    self.long(list.len() as i64);
+                    for (index, val) in list.iter().enumerate() {
+                        path.push(PathSegment::Index(index));
+                        self.write(items, val, path)?;
+                        path.pop();
+                    }
+                }
+                self.long(0);
+            },
+            ("map", Obj::Map(entries)) => {
+                let values = entry(schema, "values")
+                    .ok_or_else(|| error(path, obj, "invalid Avro schema: map without values"))?;
+                if !entries.is_empty() {
+                    self.long(entries.len() as i64);
+                    for (key, val) in entries.iter() {
+                        match *key {
+                            Obj::Str(ref key) => self.bytes(key.as_bytes()),
+                            _ => return Err(error(path, obj, &format!("map key {} is not a string", key)))
+                        }
+                        path.push(PathSegment::Key(key.clone()));
+                        self.write(values, val, path)?;
+                        path.pop();
+                    }
+                }
+                self.long(0);
+            },
+            ("record", Obj::Map(entries)) | ("error", Obj::Map(entries)) => {
+                let fields = match entry(schema, "fields") {
+                    Some(Obj::List(fields)) => fields,
+                    _ => return Err(error(path, obj, "invalid Avro schema: record without fields"))
+                };
+                for field in fields {
+                    let (name, ty) = match (entry(field, "name"), entry(field, "type")) {
+                        (Some(name), Some(ty)) => (name, ty),
+                        _ => return Err(error(path, obj, "invalid Avro schema: field without name or type"))
+                    };
+                    path.push(PathSegment::Key(name.clone()));
+                    match entries.get(name).or_else(|| entry(field, "default")) {
+                        Some(val) => self.write(ty, val, path)?,
+                        None => return Err(ExtractError::new(path.clone(), "Avro", None, "missing field without default"))
+                    }
+                    path.pop();
+                }
+            },
+            _ => return Err(error(path, obj, &format!("expected {}", ty)))
+        }
+        Ok(())
+    }
+}
+
+impl Obj {
+    /// Infers an [Avro](https://avro.apache.org/) schema that describes this value
+    ///
+    /// The schema is returned as schema JSON in an `Obj`, so it can be serialized as JSON.
+    /// Integers become `long`, floats `double`, `Bin` values `bytes` and maps with string keys
+    /// that are valid Avro names become records. The top-level record is called `name`, nested
+    /// records are named after their path, e.g. `name_field_item` for the elements of a list in
+    /// `field`. Other maps become Avro maps. The elements of lists and the values of maps are
+    /// merged into a union, fields that are missing in some of the merged records become nullable
+    /// with `null` as default.
+    ///
+    /// Maps with non-string keys and integers outside of the range of `i64` cannot be described
+    /// and are reported as error with the path of the value.
+    pub fn avro_schema(&self, name: &str) -> Result<Obj, ExtractError> {
+        let mut path = ObjPath::new();
+        if !is_name(name) {
+            return Err(ExtractError::new(path, "Avro", Some(self.kind()), format_args!("{} is not a valid Avro name", name)))
+        }
+        let ty = infer(self, name, &mut path)?;
+        Ok(render(ty, &mut HashSet::new()))
+    }
+
+    /// Encodes this value in the binary encoding of [Avro](https://avro.apache.org/) according
+    /// to the schema
+    ///
+    /// The schema is given as schema JSON, e.g. from [`avro_schema`](#method.avro_schema) or
+    /// deserialized from a file. All types of the specification are supported, logical types are
+    /// encoded as their underlying types. Missing record fields are filled with their default
+    /// value and union branches are selected by the first branch that matches the value, preferring
+    /// branches of the exact number type. Only the encoded value is returned, without a container
+    /// file header or the framing of a schema registry.
+    ///
+    /// If the value does not match the schema, an error with the path of the value is returned.
+    pub fn to_avro(&self, schema: &Obj) -> Result<Vec<u8>, ExtractError> {
+        let mut writer = Writer { names: HashMap::new(), out: Vec::new() };
+        collect_names(schema, None, &mut writer.names);
+        writer.write(schema, self, &mut ObjPath::new())?;
+        Ok(writer.out)
+    }
+}
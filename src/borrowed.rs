@@ -0,0 +1,252 @@
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::fmt;
+
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::de::{Visitor, SeqAccess, MapAccess, Error};
+
+use adapters;
+use generic::{Obj, ObjKind, ListBuilder};
+use map::MapBuilder;
+
+/// A generic object that borrows strings and bytes from the input it was deserialized from
+///
+/// This is the zero-copy counterpart of [`Obj`](enum.Obj.html) for inspecting large documents
+/// without copying their contents. Strings and bytes are borrowed if the deserializer supports it
+/// and copied otherwise, e.g. for JSON strings with escape sequences. Maps keep the entries in
+/// the order of the input, including duplicate keys.
+///
+/// Integers outside of the range of `i64` and `u64` can not be stored. Unlike `Obj`, the type is
+/// meant for reading, it can be converted into an owned `Obj` via `From` for further processing.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ObjRef<'a> {
+    /// `None` / `null` / `nil`
+    Null,
+
+    /// Boolean value
+    Bool(bool),
+
+    /// Signed integer
+    Signed(i64),
+
+    /// Unsigned integer
+    Unsigned(u64),
+
+    /// Floating-point value
+    Float(f64),
+
+    /// Utf-8 string
+    Str(Cow<'a, str>),
+
+    /// Byte sequence
+    Bin(Cow<'a, [u8]>),
+
+    /// List / tuple / sequence
+    List(Vec<ObjRef<'a>>),
+
+    /// Mapping / object with the entries in input order
+    Map(Vec<(ObjRef<'a>, ObjRef<'a>)>)
+}
+
+impl<'a> ObjRef<'a> {
+    /// Returns the kind of the value
+    pub fn kind(&self) -> ObjKind {
+        match *self {
+            ObjRef::Null => ObjKind::Null,
+            ObjRef::Bool(_) => ObjKind::Bool,
+            ObjRef::Signed(_) => ObjKind::Signed,
+            ObjRef::Unsigned(_) => ObjKind::Unsigned,
+            ObjRef::Float(_) => ObjKind::Float,
+            ObjRef::Str(_) => ObjKind::Str,
+            ObjRef::Bin(_) => ObjKind::Bin,
+            ObjRef::List(_) => ObjKind::List,
+            ObjRef::Map(_) => ObjKind::Map
+        }
+    }
+
+    /// Returns the value of the first entry with the given string key, or `None` if the value is
+    /// not a map or the key does not exist
+    pub fn get(&self, key: &str) -> Option<&ObjRef<'a>> {
+        match *self {
+            ObjRef::Map(ref entries) => entries.iter().find(|entry| match entry.0 {
+                ObjRef::Str(ref name) => name == key,
+                _ => false
+            }).map(|entry| &entry.1),
+            _ => None
+        }
+    }
+
+    /// Returns the string if the value is a string
+    pub fn as_str(&self) -> Option<&str> {
+        match *self {
+            ObjRef::Str(ref val) => Some(val),
+            _ => None
+        }
+    }
+
+    /// Returns whether the value borrows any string or bytes from the input
+    pub fn is_borrowed(&self) -> bool {
+        match *self {
+            ObjRef::Str(Cow::Borrowed(_)) | ObjRef::Bin(Cow::Borrowed(_)) => true,
+            ObjRef::List(ref list) => list.iter().any(ObjRef::is_borrowed),
+            ObjRef::Map(ref entries) => entries.iter().any(|entry| entry.0.is_borrowed() || entry.1.is_borrowed()),
+            _ => false
+        }
+    }
+}
+
+/// Copies the value into an `Obj`, later entries of duplicate map keys take precedence
+impl<'a> From<ObjRef<'a>> for Obj {
+    fn from(val: ObjRef<'a>) -> Obj {
+        match val {
+            ObjRef::Null => Obj::Null,
+            ObjRef::Bool(val) => Obj::Bool(val),
+            ObjRef::Signed(val) => Obj::Signed(val),
+            ObjRef::Unsigned(val) => Obj::Unsigned(val),
+            ObjRef::Float(val) => Obj::Float(val),
+            ObjRef::Str(val) => Obj::Str(val.into_owned()),
+            ObjRef::Bin(val) => Obj::from_bytes(val.into_owned()),
+            ObjRef::List(list) => {
+                let mut builder = ListBuilder::new(list.len());
+                for val in list {
+                    builder.push(Obj::from(val));
+                }
+                builder.finish()
+            },
+            ObjRef::Map(entries) => {
+                let mut map = MapBuilder::new(entries.len());
+                for (key, val) in entries {
+                    map.insert(Obj::from(key), Obj::from(val));
+                }
+                map.finish()
+            }
+        }
+    }
+}
+
+impl<'a> Serialize for ObjRef<'a> {
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        match *self {
+            ObjRef::Null => ser.serialize_none(),
+            ObjRef::Bool(val) => ser.serialize_bool(val),
+            ObjRef::Signed(val) => ser.serialize_i64(val),
+            ObjRef::Unsigned(val) => ser.serialize_u64(val),
+            ObjRef::Float(val) => ser.serialize_f64(val),
+            ObjRef::Str(ref val) => ser.serialize_str(val),
+            ObjRef::Bin(ref val) => adapters::base64::serialize(&val[..], ser),
+            ObjRef::List(ref val) => val.serialize(ser),
+            ObjRef::Map(ref val) => ser.collect_map(val.iter().map(|entry| (&entry.0, &entry.1)))
+        }
+    }
+}
+
+struct RefVisitor;
+
+impl<'de> Visitor<'de> for RefVisitor {
+    type Value = ObjRef<'de>;
+
+    #[inline]
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "any value")
+    }
+
+    #[inline]
+    fn visit_none<E: Error>(self) -> Result<Self::Value, E> {
+        Ok(ObjRef::Null)
+    }
+
+    #[inline]
+    fn visit_unit<E: Error>(self) -> Result<Self::Value, E> {
+        Ok(ObjRef::Null)
+    }
+
+    #[inline]
+    fn visit_bool<E: Error>(self, val: bool) -> Result<Self::Value, E> {
+        Ok(ObjRef::Bool(val))
+    }
+
+    #[inline]
+    fn visit_u64<E: Error>(self, val: u64) -> Result<Self::Value, E> {
+        Ok(ObjRef::Unsigned(val))
+    }
+
+    #[inline]
+    fn visit_i64<E: Error>(self, val: i64) -> Result<Self::Value, E> {
+        Ok(ObjRef::Signed(val))
+    }
+
+    fn visit_i128<E: Error>(self, val: i128) -> Result<Self::Value, E> {
+        if let Ok(val) = i64::try_from(val) {
+            Ok(ObjRef::Signed(val))
+        } else if let Ok(val) = u64::try_from(val) {
+            Ok(ObjRef::Unsigned(val))
+        } else {
+            Err(E::custom(format_args!("integer {} is out of range", val)))
+        }
+    }
+
+    fn visit_u128<E: Error>(self, val: u128) -> Result<Self::Value, E> {
+        match u64::try_from(val) {
+            Ok(val) => Ok(ObjRef::Unsigned(val)),
+            Err(_) => Err(E::custom(format_args!("integer {} is out of range", val)))
+        }
+    }
+
+    #[inline]
+    fn visit_f64<E: Error>(self, val: f64) -> Result<Self::Value, E> {
+        Ok(ObjRef::Float(val))
+    }
+
+    #[inline]
+    fn visit_borrowed_str<E: Error>(self, val: &'de str) -> Result<Self::Value, E> {
+        Ok(ObjRef::Str(Cow::Borrowed(val)))
+    }
+
+    #[inline]
+    fn visit_str<E: Error>(self, val: &str) -> Result<Self::Value, E> {
+        Ok(ObjRef::Str(Cow::Owned(val.to_owned())))
+    }
+
+    #[inline]
+    fn visit_string<E: Error>(self, val: String) -> Result<Self::Value, E> {
+        Ok(ObjRef::Str(Cow::Owned(val)))
+    }
+
+    #[inline]
+    fn visit_borrowed_bytes<E: Error>(self, val: &'de [u8]) -> Result<Self::Value, E> {
+        Ok(ObjRef::Bin(Cow::Borrowed(val)))
+    }
+
+    #[inline]
+    fn visit_bytes<E: Error>(self, val: &[u8]) -> Result<Self::Value, E> {
+        Ok(ObjRef::Bin(Cow::Owned(val.to_vec())))
+    }
+
+    #[inline]
+    fn visit_byte_buf<E: Error>(self, val: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(ObjRef::Bin(Cow::Owned(val)))
+    }
+
+    fn visit_seq<V: SeqAccess<'de>>(self, mut visitor: V) -> Result<Self::Value, V::Error> {
+        let mut list = Vec::with_capacity(visitor.size_hint().unwrap_or(0));
+        while let Some(value) = visitor.next_element()? {
+            list.push(value);
+        }
+        Ok(ObjRef::List(list))
+    }
+
+    fn visit_map<V: MapAccess<'de>>(self, mut visitor: V) -> Result<Self::Value, V::Error> {
+        let mut entries = Vec::with_capacity(visitor.size_hint().unwrap_or(0));
+        while let Some(entry) = visitor.next_entry()? {
+            entries.push(entry);
+        }
+        Ok(ObjRef::Map(entries))
+    }
+}
+
+impl<'de> Deserialize<'de> for ObjRef<'de> {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        de.deserialize_any(RefVisitor)
+    }
+}
@@ -0,0 +1,41 @@
+use std::borrow::Cow;
+
+use serde::{Serialize, Serializer};
+
+use generic::Obj;
+use adapters::base64;
+
+fn key_string(key: &Obj) -> Cow<'_, str> {
+    match *key {
+        Obj::Str(ref key) => Cow::Borrowed(key),
+        Obj::Bin(ref key) => Cow::Owned(base64::encode(key)),
+        ref key => Cow::Owned(key.to_string())
+    }
+}
+
+/// A wrapper that serializes an [`Obj`](enum.Obj.html) for formats that only support string keys
+///
+/// Formats like JSON require map keys to be strings, so documents deserialized from formats like
+/// msgpack can not be serialized there directly if they contain other keys. This wrapper converts
+/// map keys at any depth on the fly: strings are kept, byte sequences are encoded as base64 and
+/// all other keys are converted via their `Display` implementation, e.g. `Unsigned(1)` as `"1"`.
+/// Keys that become equal through the conversion are all emitted, it depends on the format how
+/// duplicate keys are handled.
+///
+/// ```ignore
+/// let json = serde_json::to_string(&JsonCompat(&obj))?;
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct JsonCompat<'a>(pub &'a Obj);
+
+impl<'a> Serialize for JsonCompat<'a> {
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        match *self.0 {
+            Obj::List(ref list) => ser.collect_seq(list.iter().map(JsonCompat)),
+            ref obj => match obj.entries() {
+                Some(map) => ser.collect_map(map.iter().map(|(key, val)| (key_string(key), JsonCompat(val)))),
+                None => obj.serialize(ser)
+            }
+        }
+    }
+}
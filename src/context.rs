@@ -0,0 +1,108 @@
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde::de::{Deserializer, DeserializeSeed, Visitor, SeqAccess};
+
+/// Deserialization that needs access to a context, e.g. to resolve ids against a table
+///
+/// This trait is implemented by [`serde_impl!`](macro.serde_impl!.html#deserializing-with-a-context)
+/// for structs with a `context(..)` clause and can be implemented manually for the types of the
+/// fields that need the context. Values are deserialized via [`ContextSeed`](struct.ContextSeed.html).
+///
+/// ```ignore
+/// impl<'de> DeserializeWith<'de, Session> for UserRef {
+///     fn deserialize_with<D: Deserializer<'de>>(session: &mut Session, de: D) -> Result<Self, D::Error> {
+///         let id = u64::deserialize(de)?;
+///         session.user(id).ok_or_else(|| D::Error::custom(format_args!("unknown user {}", id)))
+///     }
+/// }
+/// ```
+pub trait DeserializeWith<'de, C: ?Sized>: Sized {
+    /// Deserializes the value using the context
+    fn deserialize_with<D: Deserializer<'de>>(context: &mut C, de: D) -> Result<Self, D::Error>;
+}
+
+impl<'de, C: ?Sized, T: DeserializeWith<'de, C>> DeserializeWith<'de, C> for Option<T> {
+    fn deserialize_with<D: Deserializer<'de>>(context: &mut C, de: D) -> Result<Self, D::Error> {
+        de.deserialize_option(OptionVisitor(ContextSeed::new(context)))
+    }
+}
+
+impl<'de, C: ?Sized, T: DeserializeWith<'de, C>> DeserializeWith<'de, C> for Vec<T> {
+    fn deserialize_with<D: Deserializer<'de>>(context: &mut C, de: D) -> Result<Self, D::Error> {
+        de.deserialize_seq(VecVisitor(ContextSeed::new(context)))
+    }
+}
+
+/// A `DeserializeSeed` that deserializes a [`DeserializeWith`](trait.DeserializeWith.html) type
+/// with a context
+///
+/// ```ignore
+/// let mut de = serde_json::Deserializer::from_str(&json);
+/// let message: Message = ContextSeed::new(&mut session).deserialize(&mut de)?;
+/// ```
+pub struct ContextSeed<'a, T, C: ?Sized> {
+    context: &'a mut C,
+    marker: PhantomData<T>
+}
+
+impl<'a, T, C: ?Sized> ContextSeed<'a, T, C> {
+    /// Creates a seed using the context
+    #[inline]
+    pub fn new(context: &'a mut C) -> Self {
+        ContextSeed { context, marker: PhantomData }
+    }
+}
+
+impl<'a, 'de, T: DeserializeWith<'de, C>, C: ?Sized> DeserializeSeed<'de> for ContextSeed<'a, T, C> {
+    type Value = T;
+
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(self, de: D) -> Result<T, D::Error> {
+        T::deserialize_with(self.context, de)
+    }
+}
+
+struct OptionVisitor<'a, T, C: ?Sized>(ContextSeed<'a, T, C>);
+
+impl<'a, 'de, T: DeserializeWith<'de, C>, C: ?Sized> Visitor<'de> for OptionVisitor<'a, T, C> {
+    type Value = Option<T>;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("option")
+    }
+
+    #[inline]
+    fn visit_none<E>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    #[inline]
+    fn visit_unit<E>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    #[inline]
+    fn visit_some<D: Deserializer<'de>>(self, de: D) -> Result<Self::Value, D::Error> {
+        self.0.deserialize(de).map(Some)
+    }
+}
+
+struct VecVisitor<'a, T, C: ?Sized>(ContextSeed<'a, T, C>);
+
+impl<'a, 'de, T: DeserializeWith<'de, C>, C: ?Sized> Visitor<'de> for VecVisitor<'a, T, C> {
+    type Value = Vec<T>;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("sequence")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let context = self.0.context;
+        let mut res = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(val) = seq.next_element_seed(ContextSeed::new(&mut *context))? {
+            res.push(val);
+        }
+        Ok(res)
+    }
+}
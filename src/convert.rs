@@ -0,0 +1,114 @@
+//! Conversions between serialization formats
+//!
+//! The conversions deserialize the input into an [`Obj`](../enum.Obj.html) and serialize it in the
+//! target format. [`convert`](fn.convert.html) works with any pair of deserializer and serializer,
+//! the other functions are shortcuts for the formats enabled via features: `msgpack` (using
+//! `rmp-serde`), `json` (using `serde_json`) and `cbor` (using `serde_cbor`).
+//!
+//! The formats differ in what they can represent, so the conversions follow these policies:
+//!
+//! * Byte sequences are written as base64 strings to human-readable formats like JSON and as bytes
+//!   to binary formats. Text formats can not distinguish base64 strings from other strings, so
+//!   they are read as strings and stay strings in binary formats.
+//! * Map keys that are not strings are converted to strings for JSON, see
+//!   [`JsonCompat`](../struct.JsonCompat.html). For other serializers, [`KeyPolicy`](enum.KeyPolicy.html)
+//!   selects whether keys are kept.
+//!
+//! ```ignore
+//! let json = serde_utils::convert::msgpack_to_json(&bytes)?;
+//! ```
+
+use std::error;
+use std::fmt;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use generic::Obj;
+use compat::JsonCompat;
+
+/// How map keys are written by [`convert`](fn.convert.html)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyPolicy {
+    /// Keys are written unchanged, for formats that support keys of any kind
+    Keep,
+    /// Keys that are not strings are converted to strings like
+    /// [`JsonCompat`](../struct.JsonCompat.html) does, for formats that only support string keys
+    Stringify
+}
+
+/// An error of a conversion, either when reading the input or when writing the output
+#[derive(Debug)]
+pub enum Error<D, S> {
+    /// The input could not be deserialized
+    Deserialize(D),
+    /// The output could not be serialized
+    Serialize(S)
+}
+
+impl<D: fmt::Display, S: fmt::Display> fmt::Display for Error<D, S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Deserialize(ref err) => write!(f, "failed to read input: {}", err),
+            Error::Serialize(ref err) => write!(f, "failed to write output: {}", err)
+        }
+    }
+}
+
+impl<D: error::Error + 'static, S: error::Error + 'static> error::Error for Error<D, S> {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Deserialize(ref err) => Some(err),
+            Error::Serialize(ref err) => Some(err)
+        }
+    }
+}
+
+/// Converts the value read by the deserializer into the format of the serializer
+///
+/// The value is deserialized into an [`Obj`](../enum.Obj.html) first, so the conversion needs
+/// memory for the whole value and the deserializer must be self-describing.
+pub fn convert<'de, D: Deserializer<'de>, S: Serializer>(de: D, ser: S, keys: KeyPolicy)
+    -> Result<S::Ok, Error<D::Error, S::Error>>
+{
+    let obj = Obj::deserialize(de).map_err(Error::Deserialize)?;
+    match keys {
+        KeyPolicy::Keep => obj.serialize(ser),
+        KeyPolicy::Stringify => JsonCompat(&obj).serialize(ser)
+    }.map_err(Error::Serialize)
+}
+
+/// Converts MessagePack to JSON
+#[cfg(all(feature = "msgpack", feature = "json"))]
+pub fn msgpack_to_json(bytes: &[u8]) -> Result<String, ::rmp_serde::decode::Error> {
+    Ok(Obj::from_msgpack_slice(bytes)?.to_json_string())
+}
+
+/// Converts JSON to MessagePack
+#[cfg(all(feature = "msgpack", feature = "json"))]
+pub fn json_to_msgpack(json: &str) -> Result<Vec<u8>, Error<::serde_json::Error, ::rmp_serde::encode::Error>> {
+    Obj::from_json_str(json).map_err(Error::Deserialize)?.to_msgpack_vec().map_err(Error::Serialize)
+}
+
+/// Converts CBOR to JSON
+#[cfg(all(feature = "cbor", feature = "json"))]
+pub fn cbor_to_json(bytes: &[u8]) -> Result<String, ::serde_cbor::Error> {
+    Ok(Obj::from_cbor_slice(bytes)?.to_json_string())
+}
+
+/// Converts JSON to CBOR
+#[cfg(all(feature = "cbor", feature = "json"))]
+pub fn json_to_cbor(json: &str) -> Result<Vec<u8>, Error<::serde_json::Error, ::serde_cbor::Error>> {
+    Obj::from_json_str(json).map_err(Error::Deserialize)?.to_cbor_vec().map_err(Error::Serialize)
+}
+
+/// Converts MessagePack to CBOR
+#[cfg(all(feature = "msgpack", feature = "cbor"))]
+pub fn msgpack_to_cbor(bytes: &[u8]) -> Result<Vec<u8>, Error<::rmp_serde::decode::Error, ::serde_cbor::Error>> {
+    Obj::from_msgpack_slice(bytes).map_err(Error::Deserialize)?.to_cbor_vec().map_err(Error::Serialize)
+}
+
+/// Converts CBOR to MessagePack
+#[cfg(all(feature = "msgpack", feature = "cbor"))]
+pub fn cbor_to_msgpack(bytes: &[u8]) -> Result<Vec<u8>, Error<::serde_cbor::Error, ::rmp_serde::encode::Error>> {
+    Obj::from_cbor_slice(bytes).map_err(Error::Deserialize)?.to_msgpack_vec().map_err(Error::Serialize)
+}
@@ -0,0 +1,104 @@
+use std::fmt;
+use std::ops::Deref;
+use std::sync::Arc;
+
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+
+use generic::Obj;
+
+/// A shared [`Obj`](enum.Obj.html) that is cloned in constant time and copied on mutation
+///
+/// Cloning an `Obj` copies the whole tree. A `CowObj` instead shares the tree between all of its
+/// clones, also across threads, and only copies it when one of them is mutated via
+/// [`make_mut`](#method.make_mut) while others still exist. Read access is available via `Deref`.
+///
+/// ```ignore
+/// let doc = CowObj::new(obj);
+/// for rule in &rules {
+///     rule.process(doc.clone());
+/// }
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct CowObj(Arc<Obj>);
+
+impl CowObj {
+    /// Wraps the value for sharing
+    #[inline]
+    pub fn new(obj: Obj) -> Self {
+        CowObj(Arc::new(obj))
+    }
+
+    /// Returns a mutable reference to the value, copying it first if it is shared with other clones
+    #[inline]
+    pub fn make_mut(&mut self) -> &mut Obj {
+        Arc::make_mut(&mut self.0)
+    }
+
+    /// Returns the value, copying it if it is shared with other clones
+    #[inline]
+    pub fn into_inner(self) -> Obj {
+        Arc::try_unwrap(self.0).unwrap_or_else(|shared| (*shared).clone())
+    }
+
+    /// Returns whether both values share the same tree
+    #[inline]
+    pub fn ptr_eq(&self, other: &CowObj) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl From<Obj> for CowObj {
+    #[inline]
+    fn from(obj: Obj) -> Self {
+        CowObj::new(obj)
+    }
+}
+
+impl From<CowObj> for Obj {
+    #[inline]
+    fn from(obj: CowObj) -> Self {
+        obj.into_inner()
+    }
+}
+
+impl Deref for CowObj {
+    type Target = Obj;
+
+    #[inline]
+    fn deref(&self) -> &Obj {
+        &self.0
+    }
+}
+
+impl AsRef<Obj> for CowObj {
+    #[inline]
+    fn as_ref(&self) -> &Obj {
+        &self.0
+    }
+}
+
+impl fmt::Debug for CowObj {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&*self.0, f)
+    }
+}
+
+impl fmt::Display for CowObj {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&*self.0, f)
+    }
+}
+
+impl Serialize for CowObj {
+    #[inline]
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(ser)
+    }
+}
+
+impl<'de> Deserialize<'de> for CowObj {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        Obj::deserialize(de).map(CowObj::new)
+    }
+}
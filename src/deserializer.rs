@@ -0,0 +1,349 @@
+#[cfg(feature = "num-bigint")] use std::convert::TryFrom;
+use std::any;
+use std::cell::RefCell;
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem;
+use std::rc::Rc;
+
+use serde::de::{self, Visitor, IntoDeserializer, SeqAccess, MapAccess, EnumAccess, VariantAccess, DeserializeSeed, Error};
+
+use generic::{Obj, ObjKind};
+use path::{ObjPath, PathSegment};
+use extract::ExtractError;
+
+/// Keeps track of the current path while deserializing and of the location of the last error
+#[derive(Default)]
+struct Track {
+    path: RefCell<ObjPath>,
+    error: RefCell<Option<(ObjPath, ObjKind)>>
+}
+
+impl Track {
+    fn fail(&self, kind: ObjKind) {
+        let path = self.path.borrow();
+        let mut error = self.error.borrow_mut();
+        // An error passes all enclosing values, the innermost location is kept
+        match *error {
+            Some((ref inner, _)) if inner.segments().starts_with(path.segments()) => (),
+            _ => *error = Some((path.clone(), kind))
+        }
+    }
+}
+
+#[inline]
+fn record<T, E>(track: &Option<Rc<Track>>, kind: ObjKind, res: Result<T, E>) -> Result<T, E> {
+    if let (Some(track), Err(_)) = (track, &res) {
+        track.fail(kind);
+    }
+    res
+}
+
+fn nested<T, E, F>(track: &Option<Rc<Track>>, segment: F, obj: Obj, f: impl FnOnce(ObjDeserializer<E>) -> Result<T, E>
+) -> Result<T, E> where F: FnOnce() -> PathSegment {
+    match *track {
+        Some(ref track) => {
+            track.path.borrow_mut().push(segment());
+            let res = f(ObjDeserializer::tracked(obj, Some(track.clone())));
+            track.path.borrow_mut().pop();
+            res
+        },
+        None => f(ObjDeserializer::new(obj))
+    }
+}
+
+/// A deserializer that reads values from an [`Obj`](enum.Obj.html).
+///
+/// This makes it possible to first deserialize unknown data into an `Obj` and later interpret it
+/// as a specific type. The error type `E` can be chosen freely so that errors can be passed on
+/// from within other deserializers.
+pub struct ObjDeserializer<E> {
+    obj: Obj,
+    track: Option<Rc<Track>>,
+    marker: PhantomData<E>
+}
+
+impl<E> ObjDeserializer<E> {
+    /// Creates a new deserializer reading from the given object
+    #[inline]
+    pub fn new(obj: Obj) -> Self {
+        ObjDeserializer { obj, track: None, marker: PhantomData }
+    }
+
+    #[inline]
+    fn tracked(obj: Obj, track: Option<Rc<Track>>) -> Self {
+        ObjDeserializer { obj, track, marker: PhantomData }
+    }
+}
+
+impl<'de, E: Error> IntoDeserializer<'de, E> for Obj {
+    type Deserializer = ObjDeserializer<E>;
+
+    #[inline]
+    fn into_deserializer(self) -> Self::Deserializer {
+        ObjDeserializer::new(self)
+    }
+}
+
+/// Interprets an object as a specific type
+///
+/// This deserializes the type from an [`ObjDeserializer`](struct.ObjDeserializer.html). On
+/// failure, the error contains the path and kind of the innermost value that could not be
+/// interpreted, e.g. `servers/2/port`.
+pub fn from_obj<'de, T: de::Deserialize<'de>>(obj: Obj) -> Result<T, ExtractError> {
+    let kind = obj.kind();
+    let track = Rc::new(Track::default());
+    T::deserialize(ObjDeserializer::<de::value::Error>::tracked(obj, Some(track.clone()))).map_err(|err| {
+        let (path, kind) = track.error.borrow_mut().take().unwrap_or((ObjPath::new(), kind));
+        ExtractError::new(path, any::type_name::<T>(), Some(kind), err)
+    })
+}
+
+/// Interprets an object as a specific type without tracking the location of errors
+#[inline]
+pub(crate) fn from_obj_untracked<'de, T: de::Deserialize<'de>>(obj: Obj) -> Result<T, de::value::Error> {
+    T::deserialize(ObjDeserializer::new(obj))
+}
+
+struct ExpectedLen(usize, &'static str);
+
+impl de::Expected for ExpectedLen {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} elements in {}", self.0, self.1)
+    }
+}
+
+struct ObjSeqAccess<I, E> {
+    iter: I,
+    index: usize,
+    track: Option<Rc<Track>>,
+    marker: PhantomData<E>
+}
+
+impl<I: ExactSizeIterator<Item=Obj>, E: Error> ObjSeqAccess<I, E> {
+    fn visit<'de, V: Visitor<'de>>(iter: I, track: Option<Rc<Track>>, visitor: V) -> Result<V::Value, E> {
+        let mut seq = ObjSeqAccess { iter, index: 0, track, marker: PhantomData };
+        let res = visitor.visit_seq(&mut seq)?;
+        match seq.iter.len() {
+            0 => Ok(res),
+            rest => Err(E::invalid_length(seq.index + rest, &ExpectedLen(seq.index, "sequence")))
+        }
+    }
+}
+
+impl<'de, I: ExactSizeIterator<Item=Obj>, E: Error> SeqAccess<'de> for ObjSeqAccess<I, E> {
+    type Error = E;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, E> {
+        match self.iter.next() {
+            Some(val) => {
+                let index = self.index;
+                self.index += 1;
+                nested(&self.track, || PathSegment::Index(index), val, |de| seed.deserialize(de)).map(Some)
+            },
+            None => Ok(None)
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+struct ObjMapAccess<I, E> {
+    iter: I,
+    count: usize,
+    value: Option<(Obj, Obj)>,
+    track: Option<Rc<Track>>,
+    marker: PhantomData<E>
+}
+
+impl<I: ExactSizeIterator<Item=(Obj, Obj)>, E: Error> ObjMapAccess<I, E> {
+    fn visit<'de, V: Visitor<'de>>(iter: I, track: Option<Rc<Track>>, visitor: V) -> Result<V::Value, E> {
+        let mut map = ObjMapAccess { iter, count: 0, value: None, track, marker: PhantomData };
+        let res = visitor.visit_map(&mut map)?;
+        match map.iter.len() {
+            0 => Ok(res),
+            rest => Err(E::invalid_length(map.count + rest, &ExpectedLen(map.count, "map")))
+        }
+    }
+}
+
+impl<'de, I: ExactSizeIterator<Item=(Obj, Obj)>, E: Error> MapAccess<'de> for ObjMapAccess<I, E> {
+    type Error = E;
+
+    fn next_key_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, E> {
+        match self.iter.next() {
+            Some((key, value)) => {
+                self.count += 1;
+                // The key is only needed for the path of the value when tracking
+                let path_key = match self.track {
+                    Some(_) => key.clone(),
+                    None => Obj::Null
+                };
+                self.value = Some((path_key, value));
+                seed.deserialize(ObjDeserializer::tracked(key, self.track.clone())).map(Some)
+            },
+            None => Ok(None)
+        }
+    }
+
+    fn next_value_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<T::Value, E> {
+        match self.value.take() {
+            Some((key, value)) => nested(&self.track, || PathSegment::Key(key), value, |de| seed.deserialize(de)),
+            None => Err(E::custom("value is missing"))
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+impl<'de, E: Error> de::Deserializer<'de> for ObjDeserializer<E> {
+    type Error = E;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, E> {
+        let (track, kind) = (self.track.clone(), self.obj.kind());
+        record(&track, kind, self.visit_any(visitor))
+    }
+
+    #[inline]
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, E> {
+        match self.obj {
+            Obj::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self)
+        }
+    }
+
+    #[inline]
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, E> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(self, _name: &'static str, _variants: &'static [&'static str], visitor: V) -> Result<V::Value, E> {
+        let (track, kind) = (self.track.clone(), self.obj.kind());
+        record(&track, kind, self.visit_enum(visitor))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf unit
+        unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+impl<'de, E: Error> ObjDeserializer<E> {
+    fn visit_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, E> {
+        let track = self.track;
+        match self.obj {
+            Obj::Null => visitor.visit_unit(),
+            Obj::Bool(val) => visitor.visit_bool(val),
+            Obj::Signed(val) => visitor.visit_i64(val),
+            Obj::Unsigned(val) => visitor.visit_u64(val),
+            Obj::Float(val) => visitor.visit_f64(val),
+            #[cfg(feature = "num-bigint")]
+            Obj::BigInt(val) => if let Ok(val) = i64::try_from(&val) {
+                visitor.visit_i64(val)
+            } else if let Ok(val) = u64::try_from(&val) {
+                visitor.visit_u64(val)
+            } else if let Ok(val) = i128::try_from(&val) {
+                visitor.visit_i128(val)
+            } else if let Ok(val) = u128::try_from(&val) {
+                visitor.visit_u128(val)
+            } else {
+                visitor.visit_string(val.to_string())
+            },
+            #[cfg(feature = "rust_decimal")]
+            Obj::Decimal(val) => visitor.visit_string(val.to_string()),
+            Obj::Str(val) => visitor.visit_string(val),
+            Obj::Bin(mut val) => visitor.visit_byte_buf(mem::take(&mut *val)),
+            Obj::List(val) => ObjSeqAccess::visit(val.into_iter(), track, visitor),
+            Obj::U64Array(val) => ObjSeqAccess::visit(val.into_iter().map(Obj::Unsigned), track, visitor),
+            Obj::F64Array(val) => ObjSeqAccess::visit(val.into_iter().map(Obj::Float), track, visitor),
+            Obj::Map(val) => ObjMapAccess::visit(val.into_iter(), track, visitor)
+        }
+    }
+
+    fn visit_enum<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, E> {
+        let track = self.track;
+        let (variant, value) = match self.obj {
+            Obj::Map(val) => {
+                if val.len() != 1 {
+                    return Err(E::invalid_length(val.len(), &"map with a single key"));
+                }
+                let (variant, value) = val.into_iter().next().unwrap();
+                (variant, Some(value))
+            },
+            variant => (variant, None)
+        };
+        visitor.visit_enum(ObjEnumAccess { variant, value, track, marker: PhantomData })
+    }
+}
+
+struct ObjEnumAccess<E> {
+    variant: Obj,
+    value: Option<Obj>,
+    track: Option<Rc<Track>>,
+    marker: PhantomData<E>
+}
+
+impl<'de, E: Error> EnumAccess<'de> for ObjEnumAccess<E> {
+    type Error = E;
+    type Variant = ObjVariantAccess<E>;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self::Variant), E> {
+        // The variant is only needed for the path of the value when tracking
+        let key = match (&self.track, &self.value) {
+            (&Some(_), &Some(_)) => self.variant.clone(),
+            _ => Obj::Null
+        };
+        let variant = seed.deserialize(ObjDeserializer::tracked(self.variant, self.track.clone()))?;
+        Ok((variant, ObjVariantAccess { key, value: self.value, track: self.track, marker: PhantomData }))
+    }
+}
+
+struct ObjVariantAccess<E> {
+    key: Obj,
+    value: Option<Obj>,
+    track: Option<Rc<Track>>,
+    marker: PhantomData<E>
+}
+
+impl<'de, E: Error> VariantAccess<'de> for ObjVariantAccess<E> {
+    type Error = E;
+
+    fn unit_variant(self) -> Result<(), E> {
+        match self.value {
+            None | Some(Obj::Null) => Ok(()),
+            Some(_) => Err(E::invalid_type(de::Unexpected::Map, &"unit variant"))
+        }
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, E> {
+        let key = self.key;
+        match self.value {
+            Some(value) => nested(&self.track, || PathSegment::Key(key), value, |de| seed.deserialize(de)),
+            None => Err(E::invalid_type(de::Unexpected::UnitVariant, &"newtype variant"))
+        }
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, E> {
+        let key = self.key;
+        match self.value {
+            Some(value) => nested(&self.track, || PathSegment::Key(key), value,
+                |de| de::Deserializer::deserialize_any(de, visitor)),
+            None => Err(E::invalid_type(de::Unexpected::UnitVariant, &"tuple variant"))
+        }
+    }
+
+    fn struct_variant<V: Visitor<'de>>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value, E> {
+        let key = self.key;
+        match self.value {
+            Some(value) => nested(&self.track, || PathSegment::Key(key), value,
+                |de| de::Deserializer::deserialize_any(de, visitor)),
+            None => Err(E::invalid_type(de::Unexpected::UnitVariant, &"struct variant"))
+        }
+    }
+}
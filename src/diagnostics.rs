@@ -0,0 +1,83 @@
+use std::fmt;
+use std::sync::{Arc, RwLock};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+type Hook = Arc<dyn Fn(&Diagnostic) + Send + Sync>;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static HOOK: RwLock<Option<Hook>> = RwLock::new(None);
+
+/// An event reported by the map deserializers generated by [`serde_impl!`](macro.serde_impl!.html)
+///
+/// The events point out differences between the data and the declared fields that are otherwise
+/// handled silently, e.g. to detect schema drift in production. See
+/// [`set_diagnostics_hook`](fn.set_diagnostics_hook.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Diagnostic<'a> {
+    /// An unknown key has been skipped
+    ///
+    /// The key is given in debug formatting if the key type implements `Debug`.
+    UnknownKey {
+        /// Name of the type being deserialized
+        ty: &'static str,
+        /// The skipped key
+        key: Option<&'a str>
+    },
+    /// A field was missing in the data and has been set to its default value
+    DefaultedField {
+        /// Name of the type being deserialized
+        ty: &'static str,
+        /// Name of the field
+        field: &'static str,
+        /// Key of the field as written in the macro invocation
+        key: &'a str
+    }
+}
+
+impl<'a> fmt::Display for Diagnostic<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Diagnostic::UnknownKey { ty, key: Some(key) } => write!(f, "{}: skipped unknown key {}", ty, key),
+            Diagnostic::UnknownKey { ty, key: None } => write!(f, "{}: skipped unknown key", ty),
+            Diagnostic::DefaultedField { ty, field, key } => {
+                write!(f, "{}.{} (key {}): missing, using default value", ty, field, key)
+            }
+        }
+    }
+}
+
+/// Installs a hook that is called for every [`Diagnostic`](enum.Diagnostic.html) event
+///
+/// The hook is global and replaces any previously installed hook. It is called on the thread that
+/// deserializes the value, so it should be cheap, e.g. forward the event to a logging framework:
+///
+/// ```ignore
+/// serde_utils::set_diagnostics_hook(|event| log::warn!("{}", event));
+/// ```
+///
+/// As long as no hook is installed, the deserializers only pay for checking a flag.
+pub fn set_diagnostics_hook<F: Fn(&Diagnostic) + Send + Sync + 'static>(hook: F) {
+    let mut lock = HOOK.write().unwrap_or_else(|err| err.into_inner());
+    *lock = Some(Arc::new(hook));
+    ENABLED.store(true, Ordering::Release);
+}
+
+/// Removes the hook installed via [`set_diagnostics_hook`](fn.set_diagnostics_hook.html)
+pub fn clear_diagnostics_hook() {
+    let mut lock = HOOK.write().unwrap_or_else(|err| err.into_inner());
+    ENABLED.store(false, Ordering::Release);
+    *lock = None;
+}
+
+#[inline]
+pub(crate) fn enabled() -> bool {
+    ENABLED.load(Ordering::Acquire)
+}
+
+pub(crate) fn report(event: &Diagnostic) {
+    // The lock is released before calling the hook so that the hook can replace itself
+    let hook = HOOK.read().unwrap_or_else(|err| err.into_inner()).clone();
+    if let Some(hook) = hook {
+        hook(event);
+    }
+}
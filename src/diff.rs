@@ -0,0 +1,49 @@
+use generic::Obj;
+use path::{ObjPath, PathSegment};
+
+fn diff_at(path: &mut ObjPath, expected: &Obj, actual: &Obj, out: &mut Vec<String>) {
+    if expected == actual {
+        return;
+    }
+    let location = if path.is_empty() { "(root)".to_string() } else { path.to_string() };
+    if let (Some(emap), Some(amap)) = (expected.entries(), actual.entries()) {
+        for (key, eval) in emap.iter() {
+            path.push(PathSegment::Key(key.clone()));
+            match amap.get(key) {
+                Some(aval) => diff_at(path, eval, aval, out),
+                None => out.push(format!("{}: missing, expected {:?}", path, eval))
+            }
+            path.pop();
+        }
+        for (key, aval) in amap.iter() {
+            if emap.get(key).is_none() {
+                path.push(PathSegment::Key(key.clone()));
+                out.push(format!("{}: unexpected {:?}", path, aval));
+                path.pop();
+            }
+        }
+        return;
+    }
+    if let (Some(elist), Some(alist)) = (expected.clone().into_list(), actual.clone().into_list()) {
+        for (i, (eval, aval)) in elist.iter().zip(&alist).enumerate() {
+            path.push(PathSegment::Index(i));
+            diff_at(path, eval, aval, out);
+            path.pop();
+        }
+        if elist.len() != alist.len() {
+            out.push(format!("{}: expected {} elements, found {}", location, elist.len(), alist.len()));
+        }
+        return;
+    }
+    out.push(format!("{}: expected {:?}, found {:?}", location, expected, actual));
+}
+
+/// Lists the differences between two values, one line per differing path
+///
+/// Each line has the form `<path>: <description>`, the root is shown as `(root)`. The result is
+/// empty if the values are equal.
+pub(crate) fn diff(expected: &Obj, actual: &Obj) -> Vec<String> {
+    let mut out = Vec::new();
+    diff_at(&mut ObjPath::new(), expected, actual, &mut out);
+    out
+}
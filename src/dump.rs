@@ -0,0 +1,122 @@
+use std::fmt::Write;
+
+use generic::Obj;
+
+#[derive(Clone, Copy)]
+struct Limits {
+    depth: usize,
+    len: usize
+}
+
+fn write_more(total: usize, shown: usize, out: &mut String) {
+    if total > shown {
+        if shown > 0 {
+            out.push_str(", ");
+        }
+        write!(out, "... {} more", total - shown).unwrap();
+    }
+}
+
+fn write_elements<T, F: FnMut(&T, &mut String)>(list: &[T], limits: Limits, out: &mut String, mut f: F) {
+    if limits.depth == 0 {
+        return write!(out, "List({})[...]", list.len()).unwrap()
+    }
+    write!(out, "List({})[", list.len()).unwrap();
+    let shown = list.len().min(limits.len);
+    for (i, val) in list[..shown].iter().enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        f(val, out);
+    }
+    write_more(list.len(), shown, out);
+    out.push(']');
+}
+
+impl Obj {
+    /// Returns a single-line description of the value that names the kind of every value
+    ///
+    /// Lists and maps are annotated with their number of elements and entries, e.g.
+    /// `Map(2) { "id": Unsigned(7), "tags": List(1)[Str("a")] }`. Compact representations are
+    /// described as the kind they represent, so `U64Array` and `F64Array` values appear as `List`.
+    /// Byte sequences are written in hex.
+    ///
+    /// Use [`dump_typed_limited`](#method.dump_typed_limited) for large documents.
+    pub fn dump_typed(&self) -> String {
+        self.dump_typed_limited(usize::MAX, usize::MAX)
+    }
+
+    /// Like [`dump_typed`](#method.dump_typed), but truncates deep and long values
+    ///
+    /// Lists and maps nested deeper than `max_depth` are written as `List(3)[...]` and
+    /// `Map(2) {...}`, the value itself is at depth 0. Of longer lists, maps, strings and byte
+    /// sequences, only the first `max_len` elements, entries, characters and bytes are written,
+    /// followed by the number of omitted ones.
+    pub fn dump_typed_limited(&self, max_depth: usize, max_len: usize) -> String {
+        let mut out = String::new();
+        self.write_typed(Limits { depth: max_depth, len: max_len }, &mut out);
+        out
+    }
+
+    fn write_typed(&self, limits: Limits, out: &mut String) {
+        let nested = Limits { depth: limits.depth.saturating_sub(1), ..limits };
+        match *self {
+            Obj::Null => out.push_str("Null"),
+            Obj::Bool(val) => write!(out, "Bool({})", val).unwrap(),
+            Obj::Signed(val) => write!(out, "Signed({})", val).unwrap(),
+            Obj::Unsigned(val) => write!(out, "Unsigned({})", val).unwrap(),
+            Obj::Float(val) => write!(out, "Float({:?})", val).unwrap(),
+            #[cfg(feature = "num-bigint")]
+            Obj::BigInt(ref val) => write!(out, "BigInt({})", val).unwrap(),
+            #[cfg(feature = "rust_decimal")]
+            Obj::Decimal(ref val) => write!(out, "Decimal({})", val).unwrap(),
+            Obj::Str(ref val) => match val.char_indices().nth(limits.len) {
+                Some((end, _)) => {
+                    write!(out, "Str({:?}... {} chars)", &val[..end], val.chars().count()).unwrap()
+                },
+                None => write!(out, "Str({:?})", val).unwrap()
+            },
+            Obj::Bin(ref val) => {
+                write!(out, "Bin({})[", val.len()).unwrap();
+                let shown = val.len().min(limits.len);
+                for (i, byte) in val[..shown].iter().enumerate() {
+                    if i > 0 {
+                        out.push(' ');
+                    }
+                    write!(out, "{:02x}", byte).unwrap();
+                }
+                if val.len() > shown {
+                    write!(out, "{}... {} more", if shown > 0 { " " } else { "" }, val.len() - shown).unwrap();
+                }
+                out.push(']');
+            },
+            Obj::List(ref list) => write_elements(list, limits, out, |val, out| val.write_typed(nested, out)),
+            Obj::U64Array(ref list) => write_elements(list, limits, out, |val, out| {
+                write!(out, "Unsigned({})", val).unwrap()
+            }),
+            Obj::F64Array(ref list) => write_elements(list, limits, out, |val, out| {
+                write!(out, "Float({:?})", val).unwrap()
+            }),
+            Obj::Map(ref entries) => {
+                if limits.depth == 0 {
+                    return write!(out, "Map({}) {{...}}", entries.len()).unwrap()
+                }
+                write!(out, "Map({}) {{", entries.len()).unwrap();
+                let shown = entries.len().min(limits.len);
+                for (i, (key, val)) in entries.iter().take(shown).enumerate() {
+                    out.push_str(if i > 0 { ", " } else { " " });
+                    match *key {
+                        Obj::Str(ref key) => write!(out, "{:?}", key).unwrap(),
+                        ref key => key.write_typed(nested, out)
+                    }
+                    out.push_str(": ");
+                    val.write_typed(nested, out);
+                }
+                if entries.len() > shown {
+                    write!(out, "{}... {} more", if shown > 0 { ", " } else { " " }, entries.len() - shown).unwrap();
+                }
+                out.push_str(if !entries.is_empty() { " }" } else { "}" });
+            }
+        }
+    }
+}
@@ -0,0 +1,198 @@
+use std::any;
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::error;
+use std::fmt;
+
+use serde::de::{self, Deserialize, Error};
+
+use generic::{Obj, ObjKind};
+use path::{ObjPath, PathSegment};
+use deserializer::from_obj;
+
+/// An error when extracting a typed value from an [`Obj`](enum.Obj.html)
+///
+/// The error is returned by [`from_obj`](fn.from_obj.html), [`get_as`](enum.Obj.html#method.get_as)
+/// and the `TryFrom<Obj>` implementations. It contains the path and kind of the value that could
+/// not be interpreted and the name of the requested type. The methods modifying a value at a path
+/// like [`set_path`](enum.Obj.html#method.set_path) report the parent that was missing or of the
+/// wrong kind the same way.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtractError {
+    path: ObjPath,
+    expected: &'static str,
+    found: Option<ObjKind>,
+    message: String
+}
+
+impl ExtractError {
+    /// Creates a new error
+    #[inline]
+    pub fn new<M: fmt::Display>(path: ObjPath, expected: &'static str, found: Option<ObjKind>, message: M) -> Self {
+        ExtractError { path, expected, found, message: message.to_string() }
+    }
+
+    /// Returns the path of the value
+    ///
+    /// If the value was not found, this is the path up to the first segment that does not exist.
+    #[inline]
+    pub fn path(&self) -> &ObjPath {
+        &self.path
+    }
+
+    /// Returns the name of the requested type
+    ///
+    /// This is the type that was extracted as a whole, the error may concern a nested value of it.
+    #[inline]
+    pub fn expected(&self) -> &'static str {
+        self.expected
+    }
+
+    /// Returns the kind of the value at the path, or `None` if the value was not found
+    #[inline]
+    pub fn found(&self) -> Option<ObjKind> {
+        self.found
+    }
+
+    /// Returns the description of the problem
+    #[inline]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for ExtractError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at path \"{}\"", self.message, self.path)
+    }
+}
+
+impl error::Error for ExtractError {}
+
+impl From<ExtractError> for de::value::Error {
+    #[inline]
+    fn from(err: ExtractError) -> Self {
+        de::value::Error::custom(err)
+    }
+}
+
+macro_rules! impl_try_from(
+    ( $( $ty:ty ),* ) => {
+        $(
+            impl TryFrom<Obj> for $ty {
+                type Error = ExtractError;
+
+                #[inline]
+                fn try_from(obj: Obj) -> Result<Self, ExtractError> {
+                    from_obj(obj)
+                }
+            }
+        )*
+    };
+);
+
+impl_try_from!(bool, u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, f32, f64, char, String);
+
+fn child<'a>(obj: &'a Obj, segment: &str) -> Option<(PathSegment, Cow<'a, Obj>)> {
+    match *obj {
+        Obj::List(ref list) => {
+            let index = segment.parse().ok()?;
+            list.get(index).map(|val| (PathSegment::Index(index), Cow::Borrowed(val)))
+        },
+        Obj::U64Array(ref list) => {
+            let index = segment.parse().ok()?;
+            list.get(index).map(|&val| (PathSegment::Index(index), Cow::Owned(Obj::Unsigned(val))))
+        },
+        Obj::F64Array(ref list) => {
+            let index = segment.parse().ok()?;
+            list.get(index).map(|&val| (PathSegment::Index(index), Cow::Owned(Obj::Float(val))))
+        },
+        Obj::Map(ref map) => {
+            let key = Obj::Str(segment.to_string());
+            if let Some(val) = map.get(&key) {
+                return Some((PathSegment::Key(key), Cow::Borrowed(val)));
+            }
+            let key = match segment.parse() {
+                Ok(num) => Obj::Unsigned(num),
+                Err(_) => Obj::Signed(segment.parse().ok()?)
+            };
+            map.get(&key).map(|val| (PathSegment::Key(key), Cow::Borrowed(val)))
+        },
+        _ => None
+    }
+}
+
+impl Obj {
+    /// Returns the value at the given path, or `None` if it does not exist
+    ///
+    /// Elements of `U64Array` and `F64Array` values are not stored as `Obj`, so they are returned
+    /// as owned `Unsigned` and `Float` values. All other values are borrowed.
+    pub fn get_path(&self, path: &ObjPath) -> Option<Cow<'_, Obj>> {
+        let mut obj = Cow::Borrowed(self);
+        for segment in path.segments() {
+            let parent = match obj {
+                Cow::Borrowed(parent) => parent,
+                Cow::Owned(_) => return None
+            };
+            obj = match (parent, segment) {
+                (Obj::List(list), PathSegment::Index(index)) => Cow::Borrowed(list.get(*index)?),
+                (Obj::U64Array(list), PathSegment::Index(index)) => Cow::Owned(Obj::Unsigned(*list.get(*index)?)),
+                (Obj::F64Array(list), PathSegment::Index(index)) => Cow::Owned(Obj::Float(*list.get(*index)?)),
+                (Obj::Map(map), PathSegment::Key(key)) => Cow::Borrowed(map.get(key)?),
+                _ => return None
+            };
+        }
+        Some(obj)
+    }
+
+    /// Returns the value at the given path string, or `None` if it does not exist
+    ///
+    /// The path consists of segments separated by slashes like `users/0/name`, the empty string
+    /// refers to the value itself. In lists, segments are interpreted as positions. In maps, they
+    /// are looked up as string keys first and as integer keys if no string key matches. Like in
+    /// [`get_path`](#method.get_path), elements of `U64Array` and `F64Array` values are returned
+    /// as owned values.
+    #[inline]
+    pub fn pointer(&self, path: &str) -> Option<Cow<'_, Obj>> {
+        self.resolve(path).ok().map(|(_, obj)| obj)
+    }
+
+    fn resolve(&self, path: &str) -> Result<(ObjPath, Cow<'_, Obj>), ObjPath> {
+        let mut obj = Cow::Borrowed(self);
+        let mut resolved = ObjPath::new();
+        if path.is_empty() {
+            return Ok((resolved, obj));
+        }
+        for segment in path.split('/') {
+            let next = match obj {
+                Cow::Borrowed(obj) => child(obj, segment),
+                Cow::Owned(_) => None
+            };
+            match next {
+                Some((segment, val)) => {
+                    resolved.push(segment);
+                    obj = val;
+                },
+                None => return Err(resolved)
+            }
+        }
+        Ok((resolved, obj))
+    }
+
+    /// Interprets the value at the given path string as a specific type
+    ///
+    /// The path is interpreted like in [`pointer`](#method.pointer). The value is cloned and
+    /// deserialized like with [`from_obj`](fn.from_obj.html). Missing values and values that can
+    /// not be interpreted as the type are reported with their path.
+    pub fn get_as<'de, T: Deserialize<'de>>(&self, path: &str) -> Result<T, ExtractError> {
+        let expected = any::type_name::<T>();
+        match self.resolve(path) {
+            Ok((mut path, obj)) => from_obj(obj.into_owned()).map_err(|mut err| {
+                path.extend(err.path.segments().iter().cloned());
+                err.path = path;
+                err
+            }),
+            Err(path) => Err(ExtractError::new(path, expected, None, "value not found"))
+        }
+    }
+}
@@ -0,0 +1,24 @@
+use serde_cbor::Error;
+
+use generic::Obj;
+
+impl Obj {
+    /// Decodes a value from CBOR
+    ///
+    /// Requires the `cbor` feature. Byte strings are decoded as `Bin`, tags are ignored and only
+    /// their content is decoded.
+    #[inline]
+    pub fn from_cbor_slice(bytes: &[u8]) -> Result<Obj, Error> {
+        ::serde_cbor::from_slice(bytes)
+    }
+
+    /// Encodes the value as CBOR
+    ///
+    /// Requires the `cbor` feature. `Bin` values are written as byte strings and map keys of any
+    /// kind are kept. `BigInt` and `Decimal` values are written as strings like when serializing
+    /// them.
+    #[inline]
+    pub fn to_cbor_vec(&self) -> Result<Vec<u8>, Error> {
+        ::serde_cbor::to_vec(self)
+    }
+}
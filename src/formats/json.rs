@@ -0,0 +1,33 @@
+use serde_json::Error;
+
+use generic::Obj;
+use compat::JsonCompat;
+
+impl Obj {
+    /// Parses a value from a JSON string
+    ///
+    /// Requires the `json` feature. JSON has no binary type, so base64 strings stay `Str` values,
+    /// see the [`base64`](adapters/base64/index.html) adapter for extracting them as bytes.
+    #[inline]
+    pub fn from_json_str(json: &str) -> Result<Obj, Error> {
+        ::serde_json::from_str(json)
+    }
+
+    /// Encodes the value as compact JSON
+    ///
+    /// Requires the `json` feature. Values without an equivalent in JSON are converted like
+    /// [`JsonCompat`](struct.JsonCompat.html) does: `Bin` values become base64 strings and map keys
+    /// that are not strings are converted to strings, e.g. `Unsigned(1)` to `"1"`. Non-finite
+    /// floats become `null`. So the conversion never fails for values of `Obj` and parsing the
+    /// result does not necessarily return an equal value.
+    pub fn to_json_string(&self) -> String {
+        ::serde_json::to_string(&JsonCompat(self)).expect("values of Obj can always be encoded as JSON")
+    }
+
+    /// Encodes the value as indented JSON, like [`to_json_string`](#method.to_json_string)
+    ///
+    /// Requires the `json` feature.
+    pub fn to_json_string_pretty(&self) -> String {
+        ::serde_json::to_string_pretty(&JsonCompat(self)).expect("values of Obj can always be encoded as JSON")
+    }
+}
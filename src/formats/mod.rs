@@ -0,0 +1,8 @@
+//! Helpers for reading and writing `Obj` values in common formats.
+//!
+//! Each format is behind the feature of the crate implementing it. The helpers are methods of
+//! `Obj` and document how values without a direct equivalent in the format are encoded.
+
+#[cfg(feature = "msgpack")] mod msgpack;
+#[cfg(feature = "json")] mod json;
+#[cfg(feature = "cbor")] mod cbor;
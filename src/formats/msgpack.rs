@@ -0,0 +1,34 @@
+use rmp_serde::{decode, encode::Error};
+
+use generic::Obj;
+use borrowed::ObjRef;
+
+impl Obj {
+    /// Decodes a value from MessagePack
+    ///
+    /// Requires the `msgpack` feature. Binary values are decoded as `Bin`, strings as `Str`.
+    #[inline]
+    pub fn from_msgpack_slice(bytes: &[u8]) -> Result<Obj, decode::Error> {
+        ::rmp_serde::from_slice(bytes)
+    }
+
+    /// Encodes the value as MessagePack
+    ///
+    /// Requires the `msgpack` feature. `Bin` values are written as MessagePack binaries, `BigInt`
+    /// and `Decimal` values as strings like when serializing them. Integers use the smallest
+    /// possible encoding.
+    #[inline]
+    pub fn to_msgpack_vec(&self) -> Result<Vec<u8>, Error> {
+        ::rmp_serde::to_vec(self)
+    }
+}
+
+impl<'a> ObjRef<'a> {
+    /// Decodes a value from MessagePack, borrowing strings and binaries from the slice
+    ///
+    /// Requires the `msgpack` feature.
+    #[inline]
+    pub fn from_msgpack_slice(bytes: &'a [u8]) -> Result<ObjRef<'a>, decode::Error> {
+        ::rmp_serde::from_slice(bytes)
+    }
+}
@@ -1,13 +1,20 @@
+use std::borrow::Cow;
 use std::collections::BTreeMap;
 use std::hash::{Hash, Hasher};
-use std::mem::transmute;
 use std::fmt;
 use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::mem;
+use std::ops::Index;
 
 use serde_bytes::ByteBuf;
 use serde::{Serialize, Serializer, Deserialize, Deserializer};
 use serde::de::{Visitor, SeqAccess, MapAccess, Error};
 
+use adapters;
+use policy::{FloatPolicy, NanGreatest};
+use map::{ObjMap, MapBuilder};
+
 /// A generic object that can hold any value deserialized via Serde.
 ///
 /// The important aspect of this generic object enum is that it can consume all possible values and
@@ -18,11 +25,29 @@ use serde::de::{Visitor, SeqAccess, MapAccess, Error};
 ///              recursion depth of the process and potentially crash it (although in a safe way).
 ///
 /// Note: The implementations of `PartialEq`, `PartialOrd`, and `Ord` traits treat `NAN` floats as
-///       equal.
+///       equal and greater than all other floats, see [`NanGreatest`](struct.NanGreatest.html).
+///       [`PolicyObj`](struct.PolicyObj.html) can be used to compare with another policy.
 ///       The implementations of `Hash`, `PartialEq`, `PartialOrd`, and `Ord` traits treat
 ///       non-negative `Signed` objects like `Unsigned` objects with the same value.
+///
+/// Sequences that only contain unsigned integers or only floats are deserialized as the compact
+/// variants `U64Array` and `F64Array` instead of `List`. These variants behave like the
+/// corresponding `List` in all trait implementations, i.e. `U64Array(vec![1])` is equal to
+/// `List(vec![Unsigned(1)])`. [`into_list`](#method.into_list) and
+/// [`from_list`](#method.from_list) convert between both representations.
+///
+/// Byte sequences are serialized as base64 strings in human-readable formats like JSON and as raw
+/// bytes in binary formats. Text formats can not distinguish these strings from other strings, so
+/// they are deserialized as `Str`. Typed values can still be extracted as bytes via the
+/// [`base64`](adapters/base64/index.html) adapter, which accepts both forms. Note that some
+/// serializers of binary formats, e.g. the one of `rmp-serde` before version 0.14, claim to be
+/// human-readable.
+///
+/// Some variants are only available with the corresponding features, so the enum is
+/// non-exhaustive and matches need a wildcard arm.
 #[allow(dead_code)]
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub enum Obj {
     /// `None` / `null` / `nil`
     Null,
@@ -39,17 +64,90 @@ pub enum Obj {
     /// Floating-point value
     Float(f64),
 
+    /// Integer outside of the range of `Signed` and `Unsigned`, requires the `num-bigint` feature
+    ///
+    /// It is serialized as decimal string. Use [`from_bigint`](#method.from_bigint) to create
+    /// values, it only uses this variant if necessary.
+    #[cfg(feature = "num-bigint")]
+    BigInt(::num_bigint::BigInt),
+
+    /// Exact decimal number, requires the `rust_decimal` feature
+    ///
+    /// It is serialized as decimal string and can be extracted via `from_obj` into types that
+    /// accept decimal strings.
+    #[cfg(feature = "rust_decimal")]
+    Decimal(::rust_decimal::Decimal),
+
     /// Utf-8 string
     Str(String),
 
-    /// Byte sequence
+    /// Byte sequence, serialized as base64 string in human-readable formats
     Bin(ByteBuf),
 
     /// List / tuple / sequence
     List(Vec<Obj>),
 
+    /// List of unsigned integers, compact form of a `List` of `Unsigned` values
+    U64Array(Vec<u64>),
+
+    /// List of floats, compact form of a `List` of `Float` values
+    F64Array(Vec<f64>),
+
     /// Mapping / object
-    Map(BTreeMap<Obj, Obj>)
+    Map(ObjMap)
+}
+
+/// The kind of value stored in an [`Obj`](enum.Obj.html)
+///
+/// Compact representations are reported as the kind they represent, e.g. `U64Array` as `List`.
+/// Like `Obj`, the enum is non-exhaustive as some kinds depend on features.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ObjKind {
+    /// `Null`
+    Null,
+    /// `Bool`
+    Bool,
+    /// `Signed`
+    Signed,
+    /// `Unsigned`
+    Unsigned,
+    /// `Float`
+    Float,
+    /// `BigInt`, requires the `num-bigint` feature
+    #[cfg(feature = "num-bigint")]
+    BigInt,
+    /// `Decimal`, requires the `rust_decimal` feature
+    #[cfg(feature = "rust_decimal")]
+    Decimal,
+    /// `Str`
+    Str,
+    /// `Bin`
+    Bin,
+    /// `List`, `U64Array` or `F64Array`
+    List,
+    /// `Map`
+    Map
+}
+
+impl fmt::Display for ObjKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            ObjKind::Null => "null",
+            ObjKind::Bool => "bool",
+            ObjKind::Signed => "signed integer",
+            ObjKind::Unsigned => "unsigned integer",
+            ObjKind::Float => "float",
+            #[cfg(feature = "num-bigint")]
+            ObjKind::BigInt => "big integer",
+            #[cfg(feature = "rust_decimal")]
+            ObjKind::Decimal => "decimal",
+            ObjKind::Str => "string",
+            ObjKind::Bin => "bytes",
+            ObjKind::List => "list",
+            ObjKind::Map => "map"
+        })
+    }
 }
 
 impl Obj {
@@ -62,8 +160,200 @@ impl Obj {
             Obj::Float(_) => 4,
             Obj::Str(_) => 5,
             Obj::Bin(_) => 6,
-            Obj::List(_) => 7,
+            Obj::List(_) | Obj::U64Array(_) | Obj::F64Array(_) => 7,
             Obj::Map(_) => 8,
+            #[cfg(feature = "num-bigint")]
+            Obj::BigInt(_) => 9,
+            #[cfg(feature = "rust_decimal")]
+            Obj::Decimal(_) => 10,
+        }
+    }
+
+    /// Returns the kind of the value
+    pub fn kind(&self) -> ObjKind {
+        match *self {
+            Obj::Null => ObjKind::Null,
+            Obj::Bool(_) => ObjKind::Bool,
+            Obj::Signed(_) => ObjKind::Signed,
+            Obj::Unsigned(_) => ObjKind::Unsigned,
+            Obj::Float(_) => ObjKind::Float,
+            #[cfg(feature = "num-bigint")]
+            Obj::BigInt(_) => ObjKind::BigInt,
+            #[cfg(feature = "rust_decimal")]
+            Obj::Decimal(_) => ObjKind::Decimal,
+            Obj::Str(_) => ObjKind::Str,
+            Obj::Bin(_) => ObjKind::Bin,
+            Obj::List(_) | Obj::U64Array(_) | Obj::F64Array(_) => ObjKind::List,
+            Obj::Map(_) => ObjKind::Map
+        }
+    }
+
+    /// Creates an integer, using `Signed` or `Unsigned` if the value is in their range
+    #[cfg(feature = "num-bigint")]
+    pub fn from_bigint(val: ::num_bigint::BigInt) -> Obj {
+        if let Ok(val) = i64::try_from(&val) {
+            Obj::Signed(val)
+        } else if let Ok(val) = u64::try_from(&val) {
+            Obj::Unsigned(val)
+        } else {
+            Obj::BigInt(val)
+        }
+    }
+
+    #[inline]
+    pub(crate) fn entries(&self) -> Option<&ObjMap> {
+        match *self {
+            Obj::Map(ref val) => Some(val),
+            _ => None
+        }
+    }
+
+    /// Returns the entries of a map as `BTreeMap`, `None` for other values
+    pub fn into_map(self) -> Option<BTreeMap<Obj, Obj>> {
+        match self {
+            Obj::Map(val) => Some(val.into()),
+            _ => None
+        }
+    }
+
+    /// Creates a map from the entries of a `BTreeMap`
+    #[inline]
+    pub fn from_map(map: BTreeMap<Obj, Obj>) -> Obj {
+        Obj::Map(map.into())
+    }
+
+    fn elements(&self) -> Option<Elements<'_>> {
+        match *self {
+            Obj::List(ref val) => Some(Elements::List(val)),
+            Obj::U64Array(ref val) => Some(Elements::U64(val)),
+            Obj::F64Array(ref val) => Some(Elements::F64(val)),
+            _ => None
+        }
+    }
+
+    /// Returns the elements of a list in either representation, `None` for other values
+    pub fn into_list(self) -> Option<Vec<Obj>> {
+        match self {
+            Obj::List(val) => Some(val),
+            Obj::U64Array(val) => Some(val.into_iter().map(Obj::Unsigned).collect()),
+            Obj::F64Array(val) => Some(val.into_iter().map(Obj::Float).collect()),
+            _ => None
+        }
+    }
+
+    /// Creates a list, using a compact representation if all elements are unsigned integers or all
+    /// are floats
+    pub fn from_list(list: Vec<Obj>) -> Obj {
+        let mut builder = ListBuilder::new(list.len());
+        for val in list {
+            builder.push(val);
+        }
+        builder.finish()
+    }
+
+    /// Creates a `Bin` value from the bytes
+    #[inline]
+    pub fn from_bytes(bytes: Vec<u8>) -> Obj {
+        Obj::Bin(ByteBuf::from(bytes))
+    }
+}
+
+/// The elements of a list in any of its representations
+enum Elements<'a> {
+    List(&'a [Obj]),
+    U64(&'a [u64]),
+    F64(&'a [f64])
+}
+
+impl<'a> Elements<'a> {
+    fn len(&self) -> usize {
+        match *self {
+            Elements::List(val) => val.len(),
+            Elements::U64(val) => val.len(),
+            Elements::F64(val) => val.len()
+        }
+    }
+
+    fn get(&self, index: usize) -> Cow<'a, Obj> {
+        match *self {
+            Elements::List(val) => Cow::Borrowed(&val[index]),
+            Elements::U64(val) => Cow::Owned(Obj::Unsigned(val[index])),
+            Elements::F64(val) => Cow::Owned(Obj::Float(val[index]))
+        }
+    }
+
+    fn eq_with<P: FloatPolicy>(&self, other: &Elements) -> bool {
+        match (self, other) {
+            (Elements::U64(val), Elements::U64(oval)) => val == oval,
+            _ => self.len() == other.len() && (0..self.len()).all(|i| self.get(i).eq_with::<P>(&other.get(i)))
+        }
+    }
+
+    fn cmp_with<P: FloatPolicy>(&self, other: &Elements) -> Ordering {
+        for i in 0..self.len().min(other.len()) {
+            match self.get(i).cmp_with::<P>(&other.get(i)) {
+                Ordering::Equal => (),
+                res => return res
+            }
+        }
+        self.len().cmp(&other.len())
+    }
+
+    fn hash_with<P: FloatPolicy, H: Hasher>(&self, state: &mut H) {
+        // Same as hashing a `Vec<Obj>`
+        state.write_usize(self.len());
+        for i in 0..self.len() {
+            self.get(i).hash_with::<P, H>(state);
+        }
+    }
+}
+
+/// Collects list elements, using a compact representation as long as possible
+pub(crate) enum ListBuilder {
+    Empty(usize),
+    U64(Vec<u64>),
+    F64(Vec<f64>),
+    List(Vec<Obj>)
+}
+
+impl ListBuilder {
+    #[inline]
+    pub(crate) fn new(capacity: usize) -> Self {
+        ListBuilder::Empty(capacity)
+    }
+
+    pub(crate) fn push(&mut self, val: Obj) {
+        match (&mut *self, val) {
+            (&mut ListBuilder::U64(ref mut list), Obj::Unsigned(val)) => list.push(val),
+            (&mut ListBuilder::F64(ref mut list), Obj::Float(val)) => list.push(val),
+            (&mut ListBuilder::List(ref mut list), val) => list.push(val),
+            (&mut ListBuilder::Empty(capacity), Obj::Unsigned(val)) => {
+                let mut list = Vec::with_capacity(capacity);
+                list.push(val);
+                *self = ListBuilder::U64(list);
+            },
+            (&mut ListBuilder::Empty(capacity), Obj::Float(val)) => {
+                let mut list = Vec::with_capacity(capacity);
+                list.push(val);
+                *self = ListBuilder::F64(list);
+            },
+            (_, val) => {
+                let mut list = match mem::replace(self, ListBuilder::Empty(0)) {
+                    ListBuilder::Empty(capacity) => Vec::with_capacity(capacity),
+                    builder => builder.finish().into_list().unwrap_or_default()
+                };
+                list.push(val);
+                *self = ListBuilder::List(list);
+            }
+        }
+    }
+
+    pub(crate) fn finish(self) -> Obj {
+        match self {
+            ListBuilder::Empty(_) => Obj::List(Vec::new()),
+            ListBuilder::U64(list) => Obj::U64Array(list),
+            ListBuilder::F64(list) => Obj::F64Array(list),
+            ListBuilder::List(list) => Obj::List(list)
         }
     }
 }
@@ -75,52 +365,49 @@ impl Default for Obj {
     }
 }
 
-impl PartialEq for Obj {
-    fn eq(&self, other: &Self) -> bool {
+impl Obj {
+    /// Compares two values, treating floats according to the policy
+    pub(crate) fn eq_with<P: FloatPolicy>(&self, other: &Obj) -> bool {
         if let Obj::Signed(val) = *self {
             if val >= 0 {
-                return &Obj::Unsigned(val as u64) == other;
+                return Obj::Unsigned(val as u64).eq_with::<P>(other);
             }
         }
         if let Obj::Signed(val) = *other {
             if val >= 0 {
-                return self == &Obj::Unsigned(val as u64);
+                return self.eq_with::<P>(&Obj::Unsigned(val as u64));
             }
         }
         match *self {
-            Obj::Null => if let Obj::Null = *other { true } else { false },
+            Obj::Null => matches!(*other, Obj::Null),
             Obj::Bool(val) => if let Obj::Bool(oval) = *other { val == oval } else { false },
             Obj::Unsigned(val) => if let Obj::Unsigned(oval) = *other { val == oval } else { false },
             Obj::Signed(val) => if let Obj::Signed(oval) = *other { val == oval } else { false },
-            Obj::Float(val) => if let Obj::Float(oval) = *other {
-                if val.is_nan() && oval.is_nan() { true } else { val == oval }
-            } else { false },
+            Obj::Float(val) => if let Obj::Float(oval) = *other { P::eq(val, oval) } else { false },
+            #[cfg(feature = "num-bigint")]
+            Obj::BigInt(ref val) => if let Obj::BigInt(ref oval) = *other { val == oval } else { false },
+            #[cfg(feature = "rust_decimal")]
+            Obj::Decimal(ref val) => if let Obj::Decimal(ref oval) = *other { val == oval } else { false },
             Obj::Str(ref val) => if let Obj::Str(ref oval) = *other { val == oval } else { false },
             Obj::Bin(ref val) => if let Obj::Bin(ref oval) = *other { val == oval } else { false },
-            Obj::List(ref val) => if let Obj::List(ref oval) = *other { val == oval } else { false },
-            Obj::Map(ref val) => if let Obj::Map(ref oval) = *other { val == oval } else { false },
+            Obj::List(_) | Obj::U64Array(_) | Obj::F64Array(_) => match (self.elements(), other.elements()) {
+                (Some(val), Some(oval)) => val.eq_with::<P>(&oval),
+                _ => false
+            },
+            Obj::Map(ref val) => if let Obj::Map(ref oval) = *other { val.eq_with::<P>(oval) } else { false },
         }
     }
-}
 
-impl Eq for Obj {}
-
-impl PartialOrd for Obj {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
-impl Ord for Obj {
-    fn cmp(&self, other: &Self) -> Ordering {
+    /// Orders two values, treating floats according to the policy
+    pub(crate) fn cmp_with<P: FloatPolicy>(&self, other: &Obj) -> Ordering {
         if let Obj::Signed(val) = *self {
             if val >= 0 {
-                return Obj::Unsigned(val as u64).cmp(other);
+                return Obj::Unsigned(val as u64).cmp_with::<P>(other);
             }
         }
         if let Obj::Signed(val) = *other {
             if val >= 0 {
-                return self.cmp(&Obj::Unsigned(val as u64));
+                return self.cmp_with::<P>(&Obj::Unsigned(val as u64));
             }
         }
         let stype = self.type_num();
@@ -146,45 +433,49 @@ impl Ord for Obj {
                 unreachable!()
             },
             Obj::Float(val) => if let Obj::Float(oval) = *other {
-                if !val.is_nan() && !oval.is_nan() {
-                    val.partial_cmp(&oval).unwrap()
-                } else if val.is_nan() {
-                    Ordering::Greater
-                } else {
-                    Ordering::Less
-                }
+                P::cmp(val, oval)
             } else {
                 unreachable!()
             },
-            Obj::Str(ref val) => if let Obj::Str(ref oval) = *other {
+            #[cfg(feature = "num-bigint")]
+            Obj::BigInt(ref val) => if let Obj::BigInt(ref oval) = *other {
                 val.cmp(oval)
             } else {
                 unreachable!()
             },
-            Obj::Bin(ref val) => if let Obj::Bin(ref oval) = *other {
+            #[cfg(feature = "rust_decimal")]
+            Obj::Decimal(ref val) => if let Obj::Decimal(ref oval) = *other {
                 val.cmp(oval)
             } else {
                 unreachable!()
             },
-            Obj::List(ref val) => if let Obj::List(ref oval) = *other {
+            Obj::Str(ref val) => if let Obj::Str(ref oval) = *other {
                 val.cmp(oval)
             } else {
                 unreachable!()
             },
-            Obj::Map(ref val) => if let Obj::Map(ref oval) = *other {
+            Obj::Bin(ref val) => if let Obj::Bin(ref oval) = *other {
                 val.cmp(oval)
             } else {
                 unreachable!()
             },
+            Obj::List(_) | Obj::U64Array(_) | Obj::F64Array(_) => match (self.elements(), other.elements()) {
+                (Some(val), Some(oval)) => val.cmp_with::<P>(&oval),
+                _ => unreachable!()
+            },
+            Obj::Map(ref val) => if let Obj::Map(ref oval) = *other {
+                val.cmp_with::<P>(oval)
+            } else {
+                unreachable!()
+            },
         }
     }
-}
 
-impl Hash for Obj {
-    fn hash<H>(&self, state: &mut H) where H: Hasher {
+    /// Hashes the value, treating floats according to the policy
+    pub(crate) fn hash_with<P: FloatPolicy, H: Hasher>(&self, state: &mut H) {
         if let Obj::Signed(val) = *self {
             if val >= 0 {
-                return Obj::Unsigned(val as u64).hash(state);
+                return Obj::Unsigned(val as u64).hash_with::<P, H>(state);
             }
         }
         state.write_u8(self.type_num());
@@ -193,15 +484,50 @@ impl Hash for Obj {
             Obj::Bool(val) => state.write_u8(if val { 1 } else { 0 }),
             Obj::Unsigned(val) => state.write_u64(val),
             Obj::Signed(val) => state.write_i64(val),
-            Obj::Float(val) => state.write_u64(unsafe { transmute(val) }),
+            Obj::Float(val) => P::hash(val, state),
+            #[cfg(feature = "num-bigint")]
+            Obj::BigInt(ref val) => val.hash(state),
+            #[cfg(feature = "rust_decimal")]
+            Obj::Decimal(ref val) => val.hash(state),
             Obj::Str(ref val) => val.hash(state),
             Obj::Bin(ref val) => val.hash(state),
-            Obj::List(ref val) => val.hash(state),
-            Obj::Map(ref val) => val.hash(state),
+            Obj::List(_) | Obj::U64Array(_) | Obj::F64Array(_) => if let Some(val) = self.elements() {
+                val.hash_with::<P, H>(state)
+            },
+            Obj::Map(ref val) => val.hash_with::<P, H>(state),
         }
     }
 }
 
+impl PartialEq for Obj {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.eq_with::<NanGreatest>(other)
+    }
+}
+
+impl Eq for Obj {}
+
+impl PartialOrd for Obj {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Obj {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.cmp_with::<NanGreatest>(other)
+    }
+}
+
+impl Hash for Obj {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.hash_with::<NanGreatest, H>(state)
+    }
+}
+
 impl Serialize for Obj {
     #[inline]
     fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
@@ -211,15 +537,31 @@ impl Serialize for Obj {
             Obj::Unsigned(val) => ser.serialize_u64(val),
             Obj::Signed(val) => ser.serialize_i64(val),
             Obj::Float(val) => ser.serialize_f64(val),
+            #[cfg(feature = "num-bigint")]
+            Obj::BigInt(ref val) => ser.collect_str(val),
+            #[cfg(feature = "rust_decimal")]
+            Obj::Decimal(ref val) => ser.collect_str(val),
             Obj::Str(ref val) => ser.serialize_str(val),
-            Obj::Bin(ref val) => ser.serialize_bytes(val),
+            Obj::Bin(ref val) => adapters::base64::serialize(&val[..], ser),
             Obj::List(ref val) => val.serialize(ser),
+            Obj::U64Array(ref val) => val.serialize(ser),
+            Obj::F64Array(ref val) => val.serialize(ser),
             Obj::Map(ref val) => val.serialize(ser)
         }
     }
 }
 
-struct GenericVisitor;
+#[cfg(feature = "num-bigint")]
+fn big_int<T: Into<::num_bigint::BigInt>, E: Error>(val: T) -> Result<Obj, E> {
+    Ok(Obj::BigInt(val.into()))
+}
+
+#[cfg(not(feature = "num-bigint"))]
+fn big_int<T: fmt::Display, E: Error>(val: T) -> Result<Obj, E> {
+    Err(E::custom(format_args!("integer {} is out of range, the num-bigint feature is required", val)))
+}
+
+pub(crate) struct GenericVisitor;
 
 impl<'a> Visitor<'a> for GenericVisitor {
     type Value = Obj;
@@ -249,6 +591,25 @@ impl<'a> Visitor<'a> for GenericVisitor {
         Ok(Obj::Signed(val))
     }
 
+    #[inline]
+    fn visit_i128<E: Error>(self, val: i128) -> Result<Self::Value, E> {
+        if let Ok(val) = i64::try_from(val) {
+            Ok(Obj::Signed(val))
+        } else if let Ok(val) = u64::try_from(val) {
+            Ok(Obj::Unsigned(val))
+        } else {
+            big_int(val)
+        }
+    }
+
+    #[inline]
+    fn visit_u128<E: Error>(self, val: u128) -> Result<Self::Value, E> {
+        match u64::try_from(val) {
+            Ok(val) => Ok(Obj::Unsigned(val)),
+            Err(_) => big_int(val)
+        }
+    }
+
     #[inline]
     fn visit_f64<E: Error>(self, val: f64) -> Result<Self::Value, E> {
         Ok(Obj::Float(val))
@@ -283,24 +644,20 @@ impl<'a> Visitor<'a> for GenericVisitor {
 
     #[inline]
     fn visit_seq<V: SeqAccess<'a>>(self, mut visitor: V) -> Result<Self::Value, V::Error> {
-        let mut list = if let Some(len) = visitor.size_hint() {
-            Vec::with_capacity(len)
-        } else {
-            Vec::new()
-        };
+        let mut list = ListBuilder::new(visitor.size_hint().unwrap_or(0));
         while let Some(value) = visitor.next_element()? {
             list.push(value);
         }
-        Ok(Obj::List(list))
+        Ok(list.finish())
     }
 
     #[inline]
     fn visit_map<V: MapAccess<'a>>(self, mut visitor: V) -> Result<Self::Value, V::Error> {
-        let mut map = BTreeMap::new();
+        let mut map = MapBuilder::new(visitor.size_hint().unwrap_or(0));
         while let Some((key, value)) = visitor.next_entry()? {
             map.insert(key, value);
         }
-        Ok(Obj::Map(map))
+        Ok(map.finish())
     }
 }
 
@@ -319,10 +676,85 @@ impl fmt::Display for Obj {
             Obj::Unsigned(val) => write!(f, "{}", val),
             Obj::Signed(val) => write!(f, "{}", val),
             Obj::Float(val) => write!(f, "{}", val),
+            #[cfg(feature = "num-bigint")]
+            Obj::BigInt(ref val) => write!(f, "{}", val),
+            #[cfg(feature = "rust_decimal")]
+            Obj::Decimal(ref val) => write!(f, "{}", val),
             Obj::Str(ref val) => write!(f, "{}", val),
             Obj::Bin(ref val) => write!(f, "{:?}", val),
             Obj::List(ref val) => write!(f, "{:?}", val),
+            Obj::U64Array(ref val) => write!(f, "{:?}", val),
+            Obj::F64Array(ref val) => write!(f, "{:?}", val),
             Obj::Map(ref val) => write!(f, "{:?}", val),
         }
     }
 }
+
+static NULL: Obj = Obj::Null;
+
+/// Looks up a string key in a map
+///
+/// `Null` is returned if the key does not exist or the value is not a map. This allows chained
+/// lookups like `obj["user"]["name"]`.
+impl Index<&str> for Obj {
+    type Output = Obj;
+
+    fn index(&self, key: &str) -> &Obj {
+        self.entries().and_then(|map| map.get(&Obj::Str(key.to_string()))).unwrap_or(&NULL)
+    }
+}
+
+macro_rules! impl_partial_eq(
+    ( $( $ty:ty ),* => |$val:ident, $other:ident| $eq:expr ) => {
+        $(
+            impl PartialEq<$ty> for Obj {
+                #[inline]
+                fn eq(&self, $other: &$ty) -> bool {
+                    let $val = self;
+                    $eq
+                }
+            }
+
+            impl PartialEq<Obj> for $ty {
+                #[inline]
+                fn eq(&self, other: &Obj) -> bool {
+                    other == self
+                }
+            }
+        )*
+    };
+);
+
+impl_partial_eq!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize => |val, other| match *val {
+    Obj::Unsigned(val) => i128::from(val) == *other as i128,
+    Obj::Signed(val) => i128::from(val) == *other as i128,
+    _ => false
+});
+
+impl_partial_eq!(f64 => |val, other| match *val {
+    Obj::Float(val) => <NanGreatest as FloatPolicy>::eq(val, *other),
+    _ => false
+});
+
+impl_partial_eq!(f32 => |val, other| *val == f64::from(*other));
+
+impl_partial_eq!(bool => |val, other| *val == Obj::Bool(*other));
+
+impl_partial_eq!(str, String => |val, other| match *val {
+    Obj::Str(ref val) => val == other,
+    _ => false
+});
+
+impl<'a> PartialEq<&'a str> for Obj {
+    #[inline]
+    fn eq(&self, other: &&'a str) -> bool {
+        *self == **other
+    }
+}
+
+impl PartialEq<Obj> for &str {
+    #[inline]
+    fn eq(&self, other: &Obj) -> bool {
+        *other == **self
+    }
+}
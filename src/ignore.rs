@@ -0,0 +1,107 @@
+use std::fmt;
+
+use serde::{Deserialize, Deserializer};
+use serde::de::{Visitor, SeqAccess, MapAccess, EnumAccess, VariantAccess, Error};
+
+/// A value that deserializes from anything and discards it
+///
+/// Unlike deserializing into an [`Obj`](enum.Obj.html), no strings, lists or maps are allocated.
+/// Formats that can skip values without parsing them are asked to do so via
+/// `deserialize_ignored_any`. This is used by [`serde_impl!`](macro.serde_impl!.html) to skip
+/// values of unknown keys.
+///
+/// ```ignore
+/// let _: Ignore = visitor.next_value()?;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Ignore;
+
+impl<'de> Visitor<'de> for Ignore {
+    type Value = Ignore;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("anything")
+    }
+
+    #[inline]
+    fn visit_bool<E: Error>(self, _: bool) -> Result<Self::Value, E> {
+        Ok(Ignore)
+    }
+
+    #[inline]
+    fn visit_i64<E: Error>(self, _: i64) -> Result<Self::Value, E> {
+        Ok(Ignore)
+    }
+
+    #[inline]
+    fn visit_i128<E: Error>(self, _: i128) -> Result<Self::Value, E> {
+        Ok(Ignore)
+    }
+
+    #[inline]
+    fn visit_u64<E: Error>(self, _: u64) -> Result<Self::Value, E> {
+        Ok(Ignore)
+    }
+
+    #[inline]
+    fn visit_u128<E: Error>(self, _: u128) -> Result<Self::Value, E> {
+        Ok(Ignore)
+    }
+
+    #[inline]
+    fn visit_f64<E: Error>(self, _: f64) -> Result<Self::Value, E> {
+        Ok(Ignore)
+    }
+
+    #[inline]
+    fn visit_str<E: Error>(self, _: &str) -> Result<Self::Value, E> {
+        Ok(Ignore)
+    }
+
+    #[inline]
+    fn visit_bytes<E: Error>(self, _: &[u8]) -> Result<Self::Value, E> {
+        Ok(Ignore)
+    }
+
+    #[inline]
+    fn visit_none<E: Error>(self) -> Result<Self::Value, E> {
+        Ok(Ignore)
+    }
+
+    #[inline]
+    fn visit_some<D: Deserializer<'de>>(self, de: D) -> Result<Self::Value, D::Error> {
+        Ignore::deserialize(de)
+    }
+
+    #[inline]
+    fn visit_newtype_struct<D: Deserializer<'de>>(self, de: D) -> Result<Self::Value, D::Error> {
+        Ignore::deserialize(de)
+    }
+
+    #[inline]
+    fn visit_unit<E: Error>(self) -> Result<Self::Value, E> {
+        Ok(Ignore)
+    }
+
+    fn visit_seq<V: SeqAccess<'de>>(self, mut visitor: V) -> Result<Self::Value, V::Error> {
+        while visitor.next_element::<Ignore>()?.is_some() {}
+        Ok(Ignore)
+    }
+
+    fn visit_map<V: MapAccess<'de>>(self, mut visitor: V) -> Result<Self::Value, V::Error> {
+        while visitor.next_entry::<Ignore, Ignore>()?.is_some() {}
+        Ok(Ignore)
+    }
+
+    fn visit_enum<V: EnumAccess<'de>>(self, visitor: V) -> Result<Self::Value, V::Error> {
+        let (Ignore, variant) = visitor.variant::<Ignore>()?;
+        variant.newtype_variant::<Ignore>()
+    }
+}
+
+impl<'de> Deserialize<'de> for Ignore {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        de.deserialize_ignored_any(Ignore)
+    }
+}
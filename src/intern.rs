@@ -0,0 +1,102 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::rc::Rc;
+
+use serde::de::{Deserializer, Visitor, Error};
+
+use context::DeserializeWith;
+
+/// Statistics of an [`Interner`](struct.Interner.html)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct InternerStats {
+    /// Number of distinct strings stored
+    pub strings: usize,
+    /// Total length of the distinct strings in bytes
+    pub bytes: usize,
+    /// Number of lookups that returned an existing string
+    pub hits: u64,
+    /// Number of lookups that stored a new string
+    pub misses: u64
+}
+
+/// A string interner that shares the storage of equal strings
+///
+/// Interned strings are returned as `Rc<str>`, so repeated strings across many deserialized
+/// documents only allocate once. The interner implements
+/// [`DeserializeWith`](trait.DeserializeWith.html) for `Rc<str>`, so it can be used as the context
+/// of [`serde_impl!`](macro.serde_impl!.html#deserializing-with-a-context) for fields marked with
+/// `#[context]`. Serializing `Rc<str>` fields requires the `rc` feature.
+///
+/// ```ignore
+/// serde_impl!(Record(String) context(Interner) {
+///     #[context]
+///     host: Rc<str> => "host",
+///     value: f64 => "value"
+/// });
+///
+/// let mut interner = Interner::new();
+/// let record: Record = ContextSeed::new(&mut interner).deserialize(&mut de)?;
+/// ```
+#[derive(Debug, Default)]
+pub struct Interner {
+    strings: HashSet<Rc<str>>,
+    bytes: usize,
+    hits: u64,
+    misses: u64
+}
+
+impl Interner {
+    /// Creates an empty interner
+    #[inline]
+    pub fn new() -> Self {
+        Interner::default()
+    }
+
+    /// Returns the shared copy of the string, storing it first if it is new
+    pub fn intern(&mut self, val: &str) -> Rc<str> {
+        if let Some(shared) = self.strings.get(val) {
+            self.hits += 1;
+            return shared.clone()
+        }
+        self.misses += 1;
+        self.bytes += val.len();
+        let shared: Rc<str> = Rc::from(val);
+        self.strings.insert(shared.clone());
+        shared
+    }
+
+    /// Returns a snapshot of the current statistics
+    pub fn stats(&self) -> InternerStats {
+        InternerStats { strings: self.strings.len(), bytes: self.bytes, hits: self.hits, misses: self.misses }
+    }
+
+    /// Removes all strings that are not used outside of the interner
+    ///
+    /// The statistics on hits and misses are kept.
+    pub fn shrink(&mut self) {
+        self.strings.retain(|shared| Rc::strong_count(shared) > 1);
+        self.bytes = self.strings.iter().map(|shared| shared.len()).sum();
+    }
+}
+
+struct InternVisitor<'a>(&'a mut Interner);
+
+impl<'a, 'de> Visitor<'de> for InternVisitor<'a> {
+    type Value = Rc<str>;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("string")
+    }
+
+    #[inline]
+    fn visit_str<E: Error>(self, val: &str) -> Result<Self::Value, E> {
+        Ok(self.0.intern(val))
+    }
+}
+
+impl<'de> DeserializeWith<'de, Interner> for Rc<str> {
+    #[inline]
+    fn deserialize_with<D: Deserializer<'de>>(interner: &mut Interner, de: D) -> Result<Self, D::Error> {
+        de.deserialize_str(InternVisitor(interner))
+    }
+}
@@ -0,0 +1,158 @@
+use std::convert::TryFrom;
+
+use bson::{Bson, Document};
+use bson::spec::BinarySubtype;
+use bson::Binary;
+
+use generic::{Obj, ListBuilder};
+use map::MapBuilder;
+use path::{ObjPath, PathSegment};
+use extract::ExtractError;
+use serializer::to_obj;
+
+use super::{unsupported, convert_list, string_key};
+
+/// Keys of the MongoDB Extended JSON representations that are converted back into BSON types
+const EXTJSON_KEYS: &[&str] = &[
+    "$oid", "$date", "$binary", "$uuid", "$numberDecimal", "$numberLong", "$numberInt", "$numberDouble",
+    "$regularExpression", "$timestamp", "$code", "$scope", "$symbol", "$dbPointer", "$minKey", "$maxKey",
+    "$undefined"
+];
+
+/// Converts a BSON value into an object
+///
+/// Types with a direct equivalent are converted directly: integers become `Unsigned` if they are
+/// not negative and `Signed` otherwise (like when reading JSON), `Binary` values with the generic
+/// subtype become `Bin`. All other BSON types like `ObjectId`, `DateTime`, `Decimal128` and
+/// `Binary` values with other subtypes are converted into their
+/// [relaxed MongoDB Extended JSON](https://www.mongodb.com/docs/manual/reference/mongodb-extended-json/)
+/// form, e.g. `{"$oid": "..."}` or `{"$date": "2020-01-01T00:00:00Z"}`. These maps are converted
+/// back into the BSON types by `TryFrom<Obj> for Bson`. The order of document fields is not
+/// preserved since maps are sorted by key.
+impl From<Bson> for Obj {
+    fn from(val: Bson) -> Obj {
+        match val {
+            Bson::Null => Obj::Null,
+            Bson::Boolean(val) => Obj::Bool(val),
+            Bson::Int32(val) => int(i64::from(val)),
+            Bson::Int64(val) => int(val),
+            Bson::Double(val) => Obj::Float(val),
+            Bson::String(val) => Obj::Str(val),
+            Bson::Binary(Binary { subtype: BinarySubtype::Generic, bytes }) => Obj::from_bytes(bytes),
+            Bson::Array(list) => {
+                let mut builder = ListBuilder::new(list.len());
+                for val in list {
+                    builder.push(Obj::from(val));
+                }
+                builder.finish()
+            },
+            Bson::Document(doc) => Obj::from(doc),
+            val => to_obj(&val.into_relaxed_extjson()).unwrap_or(Obj::Null)
+        }
+    }
+}
+
+impl From<Document> for Obj {
+    fn from(doc: Document) -> Obj {
+        let mut map = MapBuilder::new(doc.len());
+        for (key, val) in doc {
+            map.insert(Obj::Str(key), Obj::from(val));
+        }
+        map.finish()
+    }
+}
+
+#[inline]
+fn int(val: i64) -> Obj {
+    if val >= 0 {
+        Obj::Unsigned(val as u64)
+    } else {
+        Obj::Signed(val)
+    }
+}
+
+/// Converts an object into a BSON value
+///
+/// Integers become `Int32` if they fit and `Int64` otherwise, larger integers can not be
+/// converted. `Bin` becomes `Binary` with the generic subtype. Maps with keys that are not
+/// strings can not be converted. Maps that consist of the keys of an Extended JSON representation
+/// (e.g. `{"$oid": "..."}`) are converted into the BSON type they represent, see
+/// `From<Bson> for Obj`. The error contains the path of the value that could not be converted.
+impl TryFrom<Obj> for Bson {
+    type Error = ExtractError;
+
+    #[inline]
+    fn try_from(obj: Obj) -> Result<Bson, ExtractError> {
+        to_bson(obj, &mut ObjPath::new())
+    }
+}
+
+/// Converts a map object into a BSON document, see `TryFrom<Obj> for Bson`
+impl TryFrom<Obj> for Document {
+    type Error = ExtractError;
+
+    fn try_from(obj: Obj) -> Result<Document, ExtractError> {
+        match to_bson(obj, &mut ObjPath::new())? {
+            Bson::Document(doc) => Ok(doc),
+            val => Err(ExtractError::new(ObjPath::new(), "Document", Some(Obj::from(val).kind()), "expected a map"))
+        }
+    }
+}
+
+fn to_bson(obj: Obj, path: &mut ObjPath) -> Result<Bson, ExtractError> {
+    Ok(match obj {
+        Obj::Null => Bson::Null,
+        Obj::Bool(val) => Bson::Boolean(val),
+        Obj::Signed(val) => match i32::try_from(val) {
+            Ok(val) => Bson::Int32(val),
+            Err(_) => Bson::Int64(val)
+        },
+        Obj::Unsigned(val) => match (i32::try_from(val), i64::try_from(val)) {
+            (Ok(val), _) => Bson::Int32(val),
+            (_, Ok(val)) => Bson::Int64(val),
+            _ => return Err(unsupported(path, "Bson", &obj, "integer is too large for BSON"))
+        },
+        Obj::Float(val) => Bson::Double(val),
+        Obj::Str(val) => Bson::String(val),
+        Obj::Bin(val) => Bson::Binary(Binary { subtype: BinarySubtype::Generic, bytes: val.into_vec() }),
+        Obj::List(_) | Obj::U64Array(_) | Obj::F64Array(_) => {
+            Bson::Array(convert_list(obj.into_list().unwrap_or_default(), path, to_bson)?)
+        },
+        Obj::Map(_) => {
+            if is_extjson(&obj) {
+                let json = ::serde_json::to_value(&obj)
+                    .map_err(|err| unsupported(path, "Bson", &obj, &err.to_string()))?;
+                return Bson::try_from(json).map_err(|err| unsupported(path, "Bson", &obj, &err.to_string()));
+            }
+            let map = obj.into_map().unwrap_or_default();
+            let mut doc = Document::new();
+            for (key, val) in map {
+                let key = string_key(key, path, "Bson")?;
+                path.push(PathSegment::Key(Obj::Str(key.clone())));
+                let val = to_bson(val, path)?;
+                path.pop();
+                doc.insert(key, val);
+            }
+            Bson::Document(doc)
+        },
+        #[cfg(feature = "num-bigint")]
+        Obj::BigInt(_) => return Err(unsupported(path, "Bson", &obj, "integer is too large for BSON")),
+        #[cfg(feature = "rust_decimal")]
+        Obj::Decimal(ref val) => {
+            let mut json = ::serde_json::Map::new();
+            json.insert("$numberDecimal".to_string(), ::serde_json::Value::String(val.to_string()));
+            let json = ::serde_json::Value::Object(json);
+            return Bson::try_from(json).map_err(|err| unsupported(path, "Bson", &obj, &err.to_string()));
+        }
+    })
+}
+
+fn is_extjson(obj: &Obj) -> bool {
+    match obj.entries() {
+        Some(entries) => !entries.is_empty() && entries.iter().all(|(key, _)| match *key {
+            Obj::Str(ref key) => EXTJSON_KEYS.contains(&key.as_str()),
+            _ => false
+        }),
+        None => false
+    }
+}
@@ -0,0 +1,146 @@
+use std::convert::TryFrom;
+
+use ciborium::value::{Value, Integer};
+
+use generic::{Obj, ListBuilder};
+use map::MapBuilder;
+
+/// Tags of positive and negative bignums (RFC 8949, section 3.4.3)
+const TAG_BIGNUM: u64 = 2;
+const TAG_NEGATIVE_BIGNUM: u64 = 3;
+
+const TAG_KEY: &str = "$tag";
+const TAG_VALUE_KEY: &str = "$value";
+
+/// Converts a CBOR value into an object
+///
+/// Byte strings become `Bin` and integers become `Unsigned` if they are not negative and `Signed`
+/// otherwise. Negative integers below the range of `i64` become `BigInt` with the `num-bigint`
+/// feature and a negative bignum tag otherwise. Tagged values become a map with the two entries
+/// `"$tag"` (the tag number) and `"$value"` (the tagged value), which is converted back into a tag
+/// by `From<Obj> for Value`. So the conversion is lossless except that maps are sorted by key and
+/// of duplicate keys only the last value is kept.
+impl From<Value> for Obj {
+    fn from(val: Value) -> Obj {
+        match val {
+            Value::Null => Obj::Null,
+            Value::Bool(val) => Obj::Bool(val),
+            Value::Integer(val) => int(i128::from(val)),
+            Value::Float(val) => Obj::Float(val),
+            Value::Text(val) => Obj::Str(val),
+            Value::Bytes(val) => Obj::from_bytes(val),
+            Value::Tag(tag, val) => tagged(tag, Obj::from(*val)),
+            Value::Array(list) => {
+                let mut builder = ListBuilder::new(list.len());
+                for val in list {
+                    builder.push(Obj::from(val));
+                }
+                builder.finish()
+            },
+            Value::Map(entries) => {
+                let mut map = MapBuilder::new(entries.len());
+                for (key, val) in entries {
+                    map.insert(Obj::from(key), Obj::from(val));
+                }
+                map.finish()
+            },
+            // Value is non-exhaustive
+            _ => Obj::Null
+        }
+    }
+}
+
+fn tagged(tag: u64, val: Obj) -> Obj {
+    let mut map = MapBuilder::new(2);
+    map.insert(Obj::Str(TAG_KEY.to_string()), Obj::Unsigned(tag));
+    map.insert(Obj::Str(TAG_VALUE_KEY.to_string()), val);
+    map.finish()
+}
+
+fn int(val: i128) -> Obj {
+    if let Ok(val) = u64::try_from(val) {
+        Obj::Unsigned(val)
+    } else if let Ok(val) = i64::try_from(val) {
+        Obj::Signed(val)
+    } else {
+        big_int(val)
+    }
+}
+
+#[cfg(feature = "num-bigint")]
+fn big_int(val: i128) -> Obj {
+    Obj::from_bigint(::num_bigint::BigInt::from(val))
+}
+
+#[cfg(not(feature = "num-bigint"))]
+fn big_int(val: i128) -> Obj {
+    // Only negative values are outside of the range of `u64` and `i64`
+    let magnitude = (-1 - val) as u128;
+    let bytes = magnitude.to_be_bytes();
+    let start = bytes.iter().position(|&b| b != 0).unwrap_or(bytes.len());
+    tagged(TAG_NEGATIVE_BIGNUM, Obj::from_bytes(bytes[start..].to_vec()))
+}
+
+/// Converts an object into a CBOR value
+///
+/// This is the reverse of `From<Value> for Obj`: `Bin` becomes a byte string and maps with
+/// exactly the entries `"$tag"` and `"$value"` become tagged values. Bignum tags whose value fits
+/// into a CBOR integer become integers. Values of the `BigInt` variant become integers or bignums
+/// and `Decimal` values become text like when serializing them.
+impl From<Obj> for Value {
+    fn from(obj: Obj) -> Value {
+        match obj {
+            Obj::Null => Value::Null,
+            Obj::Bool(val) => Value::Bool(val),
+            Obj::Signed(val) => Value::Integer(Integer::from(val)),
+            Obj::Unsigned(val) => Value::Integer(Integer::from(val)),
+            Obj::Float(val) => Value::Float(val),
+            Obj::Str(val) => Value::Text(val),
+            Obj::Bin(val) => Value::Bytes(val.into_vec()),
+            Obj::List(_) | Obj::U64Array(_) | Obj::F64Array(_) => {
+                Value::Array(obj.into_list().unwrap_or_default().into_iter().map(Value::from).collect())
+            },
+            Obj::Map(_) => {
+                let map = obj.into_map().unwrap_or_default();
+                if map.len() == 2 {
+                    if let (Some(&Obj::Unsigned(tag)), Some(val)) = (map.get(&Obj::Str(TAG_KEY.to_string())),
+                        map.get(&Obj::Str(TAG_VALUE_KEY.to_string()))) {
+                        return from_tag(tag, val.clone());
+                    }
+                }
+                Value::Map(map.into_iter().map(|(key, val)| (Value::from(key), Value::from(val))).collect())
+            },
+            #[cfg(feature = "num-bigint")]
+            Obj::BigInt(val) => {
+                use num_bigint::Sign;
+                if let Some(val) = i128::try_from(&val).ok().and_then(|val| Integer::try_from(val).ok()) {
+                    return Value::Integer(val);
+                }
+                let (tag, magnitude) = match val.sign() {
+                    Sign::Minus => (TAG_NEGATIVE_BIGNUM, -1 - val),
+                    _ => (TAG_BIGNUM, val)
+                };
+                Value::Tag(tag, Box::new(Value::Bytes(magnitude.to_bytes_be().1)))
+            },
+            #[cfg(feature = "rust_decimal")]
+            Obj::Decimal(val) => Value::Text(val.to_string())
+        }
+    }
+}
+
+fn from_tag(tag: u64, val: Obj) -> Value {
+    if let (TAG_BIGNUM, &Obj::Bin(ref bytes)) | (TAG_NEGATIVE_BIGNUM, &Obj::Bin(ref bytes)) = (tag, &val) {
+        if bytes.len() <= 8 {
+            let magnitude = bytes.iter().fold(0u64, |acc, &b| (acc << 8) | u64::from(b));
+            let val = if tag == TAG_BIGNUM {
+                i128::from(magnitude)
+            } else {
+                -1 - i128::from(magnitude)
+            };
+            if let Ok(val) = Integer::try_from(val) {
+                return Value::Integer(val);
+            }
+        }
+    }
+    Value::Tag(tag, Box::new(Value::from(val)))
+}
@@ -0,0 +1,43 @@
+//! Conversions between `Obj` and the value types of other serialization crates.
+//!
+//! Each conversion is behind the feature of the respective crate. The conversions are implemented
+//! as `From` and `TryFrom` on `Obj` and the foreign types, their policies are documented there.
+
+use generic::{Obj, ObjKind};
+use path::{ObjPath, PathSegment};
+use extract::ExtractError;
+
+#[cfg(feature = "bson")] mod bson;
+#[cfg(feature = "ciborium")] mod ciborium;
+#[cfg(feature = "ron")] mod ron;
+#[cfg(feature = "pickle")] mod pickle;
+
+/// Creates the error for a value that has no equivalent in the target type
+#[allow(dead_code)]
+fn unsupported(path: &ObjPath, expected: &'static str, obj: &Obj, reason: &str) -> ExtractError {
+    ExtractError::new(path.clone(), expected, Some(obj.kind()), reason)
+}
+
+/// Converts the elements of a list, extending the path by their positions
+#[allow(dead_code)]
+fn convert_list<T, F>(list: Vec<Obj>, path: &mut ObjPath, mut f: F) -> Result<Vec<T>, ExtractError>
+    where F: FnMut(Obj, &mut ObjPath) -> Result<T, ExtractError>
+{
+    let mut res = Vec::with_capacity(list.len());
+    for (index, obj) in list.into_iter().enumerate() {
+        path.push(PathSegment::Index(index));
+        res.push(f(obj, path)?);
+        path.pop();
+    }
+    Ok(res)
+}
+
+/// Returns the key as string for formats that only support string keys
+#[allow(dead_code)]
+fn string_key(key: Obj, path: &ObjPath, expected: &'static str) -> Result<String, ExtractError> {
+    match key {
+        Obj::Str(key) => Ok(key),
+        key => Err(ExtractError::new(path.clone(), expected, Some(ObjKind::Map),
+            format_args!("map key {} is not a string", key)))
+    }
+}
@@ -0,0 +1,170 @@
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+
+use serde_pickle::{Value, HashableValue};
+
+use generic::{Obj, ListBuilder};
+use map::MapBuilder;
+use path::{ObjPath, PathSegment};
+use extract::ExtractError;
+
+use super::{unsupported, convert_list};
+
+/// Converts a pickle value into an object
+///
+/// Integers become `Unsigned` if they are not negative and `Signed` otherwise. Integers outside of
+/// the range of `u64` and `i64` become `BigInt` with the `num-bigint` feature and decimal strings
+/// otherwise, like when serializing a `BigInt`. Bytes become `Bin`. Tuples, sets and frozen sets
+/// become lists, sets in their sorted order. Map keys are converted the same way.
+impl From<Value> for Obj {
+    fn from(val: Value) -> Obj {
+        match val {
+            Value::None => Obj::Null,
+            Value::Bool(val) => Obj::Bool(val),
+            Value::I64(val) => int(val),
+            Value::Int(val) => match i64::try_from(&val) {
+                Ok(val) => int(val),
+                Err(_) => match u64::try_from(&val) {
+                    Ok(val) => Obj::Unsigned(val),
+                    Err(_) => big_int(val)
+                }
+            },
+            Value::F64(val) => Obj::Float(val),
+            Value::Bytes(val) => Obj::from_bytes(val),
+            Value::String(val) => Obj::Str(val),
+            Value::List(list) | Value::Tuple(list) => {
+                let mut builder = ListBuilder::new(list.len());
+                for val in list {
+                    builder.push(Obj::from(val));
+                }
+                builder.finish()
+            },
+            Value::Set(set) | Value::FrozenSet(set) => {
+                let mut builder = ListBuilder::new(set.len());
+                for val in set {
+                    builder.push(Obj::from(val));
+                }
+                builder.finish()
+            },
+            Value::Dict(entries) => {
+                let mut map = MapBuilder::new(entries.len());
+                for (key, val) in entries {
+                    map.insert(Obj::from(key), Obj::from(val));
+                }
+                map.finish()
+            }
+        }
+    }
+}
+
+/// Converts a hashable pickle value into an object like the corresponding `Value`
+impl From<HashableValue> for Obj {
+    #[inline]
+    fn from(val: HashableValue) -> Obj {
+        Obj::from(val.into_value())
+    }
+}
+
+fn int(val: i64) -> Obj {
+    if val >= 0 {
+        Obj::Unsigned(val as u64)
+    } else {
+        Obj::Signed(val)
+    }
+}
+
+#[cfg(feature = "num-bigint")]
+fn big_int(val: ::num_bigint::BigInt) -> Obj {
+    Obj::from_bigint(val)
+}
+
+#[cfg(not(feature = "num-bigint"))]
+fn big_int<T: ::std::fmt::Display>(val: T) -> Obj {
+    Obj::Str(val.to_string())
+}
+
+/// Converts an object into a pickle value
+///
+/// Integers outside of the range of `i64` become `Int`, `Bin` becomes bytes and lists become
+/// lists. `Decimal` values become strings like when serializing them. Python requires map keys to
+/// be hashable, so lists in keys become tuples. Maps can not be used as keys, they are reported
+/// as error with the path of the map containing them.
+impl TryFrom<Obj> for Value {
+    type Error = ExtractError;
+
+    #[inline]
+    fn try_from(obj: Obj) -> Result<Value, ExtractError> {
+        to_pickle(obj, &mut ObjPath::new())
+    }
+}
+
+/// Converts an object into a hashable pickle value
+///
+/// This works like `TryFrom<Obj> for Value` except that lists become tuples. Maps are not
+/// hashable and are reported as error.
+impl TryFrom<Obj> for HashableValue {
+    type Error = ExtractError;
+
+    #[inline]
+    fn try_from(obj: Obj) -> Result<HashableValue, ExtractError> {
+        to_hashable(obj, &ObjPath::new())
+    }
+}
+
+fn to_pickle(obj: Obj, path: &mut ObjPath) -> Result<Value, ExtractError> {
+    Ok(match obj {
+        Obj::Null => Value::None,
+        Obj::Bool(val) => Value::Bool(val),
+        Obj::Signed(val) => Value::I64(val),
+        Obj::Unsigned(val) => match i64::try_from(val) {
+            Ok(val) => Value::I64(val),
+            Err(_) => Value::Int(val.into())
+        },
+        Obj::Float(val) => Value::F64(val),
+        Obj::Str(val) => Value::String(val),
+        Obj::Bin(val) => Value::Bytes(val.into_vec()),
+        Obj::List(_) | Obj::U64Array(_) | Obj::F64Array(_) => {
+            Value::List(convert_list(obj.into_list().unwrap_or_default(), path, to_pickle)?)
+        },
+        Obj::Map(_) => {
+            let mut map = BTreeMap::new();
+            for (key, val) in obj.into_map().unwrap_or_default() {
+                path.push(PathSegment::Key(key.clone()));
+                let val = to_pickle(val, path)?;
+                path.pop();
+                map.insert(to_hashable(key, path)?, val);
+            }
+            Value::Dict(map)
+        },
+        #[cfg(feature = "num-bigint")]
+        Obj::BigInt(val) => Value::Int(val),
+        #[cfg(feature = "rust_decimal")]
+        Obj::Decimal(val) => Value::String(val.to_string())
+    })
+}
+
+fn to_hashable(obj: Obj, path: &ObjPath) -> Result<HashableValue, ExtractError> {
+    Ok(match obj {
+        Obj::Null => HashableValue::None,
+        Obj::Bool(val) => HashableValue::Bool(val),
+        Obj::Signed(val) => HashableValue::I64(val),
+        Obj::Unsigned(val) => match i64::try_from(val) {
+            Ok(val) => HashableValue::I64(val),
+            Err(_) => HashableValue::Int(val.into())
+        },
+        Obj::Float(val) => HashableValue::F64(val),
+        Obj::Str(val) => HashableValue::String(val),
+        Obj::Bin(val) => HashableValue::Bytes(val.into_vec()),
+        Obj::List(_) | Obj::U64Array(_) | Obj::F64Array(_) => {
+            let list = obj.into_list().unwrap_or_default();
+            HashableValue::Tuple(list.into_iter().map(|obj| to_hashable(obj, path)).collect::<Result<_, _>>()?)
+        },
+        Obj::Map(_) => {
+            return Err(unsupported(path, "serde_pickle::HashableValue", &obj, "maps are not hashable in pickle"))
+        },
+        #[cfg(feature = "num-bigint")]
+        Obj::BigInt(val) => HashableValue::Int(val),
+        #[cfg(feature = "rust_decimal")]
+        Obj::Decimal(val) => HashableValue::String(val.to_string())
+    })
+}
@@ -0,0 +1,90 @@
+use std::convert::TryFrom;
+
+use ron::value::{Value, Number, Map};
+
+use generic::{Obj, ListBuilder};
+use map::MapBuilder;
+use path::{ObjPath, PathSegment};
+use extract::ExtractError;
+
+use super::{unsupported, convert_list};
+
+/// Converts a RON value into an object
+///
+/// Integers become `Unsigned` if they are not negative and `Signed` otherwise, characters become
+/// strings. `Some(x)` is converted like `x` and both `None` and `()` become `Null`, just like when
+/// deserializing an object from RON. Maps are sorted by key.
+impl From<Value> for Obj {
+    fn from(val: Value) -> Obj {
+        match val {
+            Value::Unit | Value::Option(None) => Obj::Null,
+            Value::Option(Some(val)) => Obj::from(*val),
+            Value::Bool(val) => Obj::Bool(val),
+            Value::Char(val) => Obj::Str(val.to_string()),
+            Value::Number(Number::Integer(val)) if val >= 0 => Obj::Unsigned(val as u64),
+            Value::Number(Number::Integer(val)) => Obj::Signed(val),
+            Value::Number(Number::Float(val)) => Obj::Float(val.get()),
+            Value::String(val) => Obj::Str(val),
+            Value::Seq(list) => {
+                let mut builder = ListBuilder::new(list.len());
+                for val in list {
+                    builder.push(Obj::from(val));
+                }
+                builder.finish()
+            },
+            Value::Map(entries) => {
+                let mut map = MapBuilder::new(entries.len());
+                for (key, val) in entries {
+                    map.insert(Obj::from(key), Obj::from(val));
+                }
+                map.finish()
+            }
+        }
+    }
+}
+
+/// Converts an object into a RON value
+///
+/// `Null` becomes `()` and `Bin` becomes a sequence of byte values. `Decimal` values become
+/// strings like when serializing them. Integers outside of the range of `i64` cannot be
+/// represented and are reported as error with the path of the value.
+impl TryFrom<Obj> for Value {
+    type Error = ExtractError;
+
+    #[inline]
+    fn try_from(obj: Obj) -> Result<Value, ExtractError> {
+        to_ron(obj, &mut ObjPath::new())
+    }
+}
+
+fn to_ron(obj: Obj, path: &mut ObjPath) -> Result<Value, ExtractError> {
+    Ok(match obj {
+        Obj::Null => Value::Unit,
+        Obj::Bool(val) => Value::Bool(val),
+        Obj::Signed(val) => Value::Number(Number::from(val)),
+        Obj::Unsigned(val) => match i64::try_from(val) {
+            Ok(val) => Value::Number(Number::from(val)),
+            Err(_) => return Err(unsupported(path, "ron::Value", &obj, "integer is too large for RON"))
+        },
+        Obj::Float(val) => Value::Number(Number::from(val)),
+        Obj::Str(val) => Value::String(val),
+        Obj::Bin(val) => Value::Seq(val.iter().map(|&b| Value::Number(Number::from(i64::from(b)))).collect()),
+        Obj::List(_) | Obj::U64Array(_) | Obj::F64Array(_) => {
+            Value::Seq(convert_list(obj.into_list().unwrap_or_default(), path, to_ron)?)
+        },
+        Obj::Map(_) => {
+            let mut map = Map::new();
+            for (key, val) in obj.into_map().unwrap_or_default() {
+                path.push(PathSegment::Key(key.clone()));
+                let val = to_ron(val, path)?;
+                path.pop();
+                map.insert(to_ron(key, path)?, val);
+            }
+            Value::Map(map)
+        },
+        #[cfg(feature = "num-bigint")]
+        Obj::BigInt(_) => return Err(unsupported(path, "ron::Value", &obj, "integer is too large for RON")),
+        #[cfg(feature = "rust_decimal")]
+        Obj::Decimal(val) => Value::String(val.to_string())
+    })
+}
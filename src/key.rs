@@ -0,0 +1,143 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde_bytes::ByteBuf;
+
+/// A type that can be used as key type in [`serde_impl!`](macro.serde_impl!.html)
+///
+/// The associated type `Const` is the type of the key literals written in the macro invocation
+/// and of the key constants generated for the implemented types, e.g. `&'static str` for
+/// `String` keys.
+///
+/// This trait is implemented for `String`, `ByteBuf`, `NormalizedKey`, `bool`, `char` and all
+/// integer types.
+/// Simple enums implemented with `serde_impl!` implement it automatically so that they can be
+/// used as key types as well. Other key types need to implement it manually.
+pub trait KeyType {
+    /// The type of key literals and constants for this key type
+    type Const: 'static;
+}
+
+impl KeyType for String {
+    type Const = &'static str;
+}
+
+impl KeyType for ByteBuf {
+    type Const = &'static [u8];
+}
+
+macro_rules! impl_key_type(
+    ( $( $ty:ty ),* ) => {
+        $(
+            impl KeyType for $ty {
+                type Const = $ty;
+            }
+        )*
+    };
+);
+
+impl_key_type!(bool, char, u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// Information about a field of a struct implemented with [`serde_impl!`](macro.serde_impl!.html)
+///
+/// A list of these is returned by the generated `fields()` function.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FieldInfo<K> {
+    /// The name of the field
+    pub name: &'static str,
+    /// The key of the field, for tuples this is the position of the field
+    pub key: K,
+    /// The type of the field as written in the macro invocation
+    pub type_name: &'static str,
+    /// Whether the field is an `Option`
+    pub optional: bool
+}
+
+/// A normalization that is applied to string keys before comparing them
+///
+/// See [`NormalizedKey`](struct.NormalizedKey.html).
+pub trait Normalize {
+    /// Returns the normalized form of the key
+    fn normalize(key: &str) -> Cow<'_, str>;
+}
+
+/// Normalizes keys by converting them to lowercase
+pub struct Lowercase;
+
+impl Normalize for Lowercase {
+    #[inline]
+    fn normalize(key: &str) -> Cow<'_, str> {
+        if key.chars().any(char::is_uppercase) {
+            Cow::Owned(key.to_lowercase())
+        } else {
+            Cow::Borrowed(key)
+        }
+    }
+}
+
+/// A string key type that compares keys after normalizing them with `N`
+///
+/// Using this type as key type in [`serde_impl!`](macro.serde_impl!.html) makes the
+/// deserialization accept keys that differ from the key literals as long as both are equal
+/// after normalization. Serialization always uses the key literals unchanged.
+///
+/// Custom normalizations can be used by implementing [`Normalize`](trait.Normalize.html) and
+/// defining a type alias, since the key type must be a single identifier.
+pub struct NormalizedKey<N>(String, PhantomData<N>);
+
+/// A string key type that ignores the case of keys
+pub type IgnoreCase = NormalizedKey<Lowercase>;
+
+impl<N> NormalizedKey<N> {
+    /// Creates a new key
+    #[inline]
+    pub fn new<S: Into<String>>(key: S) -> Self {
+        NormalizedKey(key.into(), PhantomData)
+    }
+
+    /// Returns the key as it was deserialized
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl<N: Normalize> PartialEq for NormalizedKey<N> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        N::normalize(&self.0) == N::normalize(&other.0)
+    }
+}
+
+impl<N> Clone for NormalizedKey<N> {
+    #[inline]
+    fn clone(&self) -> Self {
+        NormalizedKey::new(self.0.clone())
+    }
+}
+
+impl<N> fmt::Debug for NormalizedKey<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.0, f)
+    }
+}
+
+impl<N> Serialize for NormalizedKey<N> {
+    #[inline]
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        ser.serialize_str(&self.0)
+    }
+}
+
+impl<'de, N> Deserialize<'de> for NormalizedKey<N> {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        String::deserialize(de).map(NormalizedKey::new)
+    }
+}
+
+impl<N: 'static> KeyType for NormalizedKey<N> {
+    type Const = &'static str;
+}
@@ -5,20 +5,175 @@
 //!
 //! * [`Obj`](enum.Obj.html) - A generic object that can hold any value deserialized via Serde.
 //!
+//! * [`CowObj`](struct.CowObj.html) - A shared generic object that is cloned in constant time and
+//!   copied on mutation.
+//!
+//! * [`ObjRef`](enum.ObjRef.html) - A generic object that borrows strings and bytes from the
+//!   input instead of copying them.
+//!
+//! * [`from_obj`](fn.from_obj.html) - A function to interpret such a generic object as a specific type.
+//!
+//! * [`to_obj`](fn.to_obj.html) - A function to convert any serializable value into such a generic
+//!   object.
+//!
 //! * [`serde_impl!`](macro.serde_impl!.html#using-the-macro) - A macro for implementing (de-)serialization
 //!   via serde in common cases.
 //!
+//! * [`JsonSchema`](trait.JsonSchema.html) - A trait describing the serialized form of a type as
+//!   JSON schema, implemented by the macros on request.
+//!
+//! * [`serialized_size_hint`](fn.serialized_size_hint.html) - A function estimating the size of a
+//!   value in a compact binary format without encoding it.
+//!
+//! * `#[derive(SerdeImpl)]` - A derive macro generating the same implementations as `serde_impl!`
+//!   from attributes on the type definition. It requires the `serde_utils_derive` feature, see
+//!   [below](#the-derive-macro).
+//!
+//! * [`adapters`](adapters/index.html) - Modules for (de-)serializing common types in a specific
+//!   representation, e.g. bytes as base64 strings.
+//!
+//! * Conversions between `Obj` and the value types of other crates via `From` and `TryFrom`,
+//!   each behind a feature: `bson::Bson` and `bson::Document` (`bson`), `ciborium::Value`
+//!   (`ciborium`), `ron::Value` (`ron`), `serde_pickle::Value` and `serde_pickle::HashableValue`
+//!   (`pickle`).
+//!
+//! * [`convert`](convert/index.html) - Conversions between serialization formats, e.g. from
+//!   MessagePack to JSON.
+//!
+//! * [`registry`](registry/index.html) - (De-)serialization of boxed trait objects via a registry of
+//!   their implementations.
+//!
+//! * Helpers for decoding and encoding `Obj` values in common formats, each behind the feature of
+//!   the format: [`from_msgpack_slice`](enum.Obj.html#method.from_msgpack_slice) and
+//!   [`to_msgpack_vec`](enum.Obj.html#method.to_msgpack_vec) (`msgpack` or its alias `rmp`),
+//!   [`from_json_str`](enum.Obj.html#method.from_json_str) and
+//!   [`to_json_string`](enum.Obj.html#method.to_json_string) (`json`),
+//!   [`from_cbor_slice`](enum.Obj.html#method.from_cbor_slice) and
+//!   [`to_cbor_vec`](enum.Obj.html#method.to_cbor_vec) (`cbor`).
+//!
+//! * Export of `Obj` values in the binary encoding of [Avro](https://avro.apache.org/) with an
+//!   inferred or supplied schema, behind the `avro` feature. See
+//!   [`avro_schema`](enum.Obj.html#method.avro_schema) and [`to_avro`](enum.Obj.html#method.to_avro).
+//!
 //! # Using this crate
 //! Since this crate provides macros, it must be included in a special way.
 //!
 //! ```ignore
 //! #[macro_use] extern crate serde_utils;
 //! ```
+//!
+//! The code generated by `serde_impl!` refers to serde through this crate, so callers do not need
+//! to import serde. It also works when the crate is renamed or its macros are re-exported.
+//!
+//! # The derive macro
+//! With the `serde_utils_derive` feature enabled, `#[derive(SerdeImpl)]` can be used instead of
+//! `serde_impl!` so that the field list does not need to be repeated. The derive macro expands to
+//! a `serde_impl!` invocation and is configured with `#[serde_utils(..)]` attributes, which accept
+//! the following comma-separated options:
+//!
+//! * `key = ..` on fields and variants sets the key. If it is missing, the name of the field or
+//!   variant is used as string key.
+//! * `key_type(..)` on the type sets the key type. If it is missing, the key type is inferred
+//!   from the keys: `String` for string literals and `u64` (or the suffix) for integer literals.
+//!   Other keys like byte strings or constants require this option.
+//! * `compressed` on a struct selects [compressed maps](macro.serde_impl!.html#compressed-maps).
+//! * `required` on a struct selects [required fields](macro.serde_impl!.html#required-fields).
+//! * `strict` on a struct rejects unknown keys, see [strict maps](macro.serde_impl!.html#strict-maps).
+//!   It can be combined with `compressed` and `required`.
+//! * `tuple` on a struct encodes it [as tuple](macro.serde_impl!.html#de-serializing-structs-as-tuples).
+//! * `is_default(..)` on a field of a compressed struct sets the predicate that decides whether
+//!   the field is omitted.
+//! * `skip` on a field excludes it from the map, it is set to its default value when
+//!   deserializing. This can not be combined with `required` or `tuple`.
+//! * `roundtrip` on the type generates a [round trip test](macro.serde_impl!.html#round-trip-tests)
+//!   when the `test-util` feature is enabled.
+//! * `crate = ".."` on the type sets the path of this crate, e.g. `crate = "utils"` if it is
+//!   imported via `extern crate serde_utils as utils`. The default is `::serde_utils`.
+//!
+//! ```ignore
+//! #[derive(Default, SerdeImpl)]
+//! #[serde_utils(compressed)]
+//! struct Test {
+//!     #[serde_utils(key = 0)]
+//!     test: String,
+//!     #[serde_utils(key = 1)]
+//!     num: u64,
+//!     #[serde_utils(skip)]
+//!     cache: Option<u64>
+//! }
+//! ```
 
-extern crate serde;
+#[macro_use] extern crate serde;
 extern crate serde_bytes;
+#[cfg(feature = "serde_utils_derive")] extern crate serde_utils_derive;
+#[cfg(feature = "chrono")] extern crate chrono;
+#[cfg(feature = "time")] extern crate time;
+#[cfg(feature = "uuid")] extern crate uuid;
+#[cfg(feature = "num-bigint")] extern crate num_bigint;
+#[cfg(feature = "rust_decimal")] extern crate rust_decimal;
+#[cfg(feature = "msgpack")] extern crate rmp_serde;
+#[cfg(any(feature = "json", feature = "bson"))] extern crate serde_json;
+#[cfg(feature = "cbor")] extern crate serde_cbor;
+#[cfg(feature = "bson")] extern crate bson;
+#[cfg(feature = "ciborium")] extern crate ciborium;
+#[cfg(feature = "ron")] extern crate ron;
+#[cfg(feature = "pickle")] extern crate serde_pickle;
+#[cfg(feature = "test-util")] extern crate arbitrary;
 
 mod generic;
+mod map;
+mod borrowed;
+mod deserializer;
+mod serializer;
+mod key;
+mod path;
+mod extract;
+mod tree;
+mod mutate;
+mod cow;
+mod literal;
+mod dump;
+mod partial;
+mod compat;
+mod policy;
+mod number;
+mod ignore;
+mod diff;
+mod spanned;
+mod context;
+mod intern;
+mod diagnostics;
+mod schema;
+mod size;
+#[cfg(feature = "avro")] mod avro;
+mod interop;
+mod formats;
+pub mod adapters;
+pub mod convert;
+pub mod registry;
+#[cfg(any(feature = "msgpack", feature = "json", feature = "cbor"))] pub mod testing;
 #[macro_use] mod macros;
+#[doc(hidden)] pub mod private;
 
-pub use generic::Obj;
+pub use generic::{Obj, ObjKind};
+pub use map::ObjMap;
+pub use borrowed::ObjRef;
+pub use path::{ObjPath, PathSegment};
+pub use tree::ObjStats;
+pub use cow::CowObj;
+pub use partial::PartialObj;
+pub use compat::JsonCompat;
+pub use number::Number;
+pub use ignore::Ignore;
+pub use context::{DeserializeWith, ContextSeed};
+pub use intern::{Interner, InternerStats};
+pub use schema::JsonSchema;
+pub use size::serialized_size_hint;
+pub use diagnostics::{Diagnostic, set_diagnostics_hook, clear_diagnostics_hook};
+pub use spanned::{Spanned, SpanDeserializer, OffsetReader, ByteCounter};
+pub use policy::{FloatPolicy, NanGreatest, TotalOrder, PolicyObj, TotalOrdObj};
+pub use extract::ExtractError;
+pub use deserializer::{ObjDeserializer, from_obj};
+pub use serializer::{ObjSerializer, to_obj};
+pub use key::{KeyType, FieldInfo, Normalize, Lowercase, NormalizedKey, IgnoreCase};
+#[cfg(feature = "serde_utils_derive")] pub use serde_utils_derive::SerdeImpl;
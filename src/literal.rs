@@ -0,0 +1,98 @@
+use std::fmt::Write;
+
+use generic::Obj;
+
+const PREFIX: &str = "::serde_utils::Obj::";
+
+fn write_float(val: f64, out: &mut String) {
+    if val.is_nan() {
+        out.push_str("f64::NAN");
+    } else if val == f64::INFINITY {
+        out.push_str("f64::INFINITY");
+    } else if val == f64::NEG_INFINITY {
+        out.push_str("f64::NEG_INFINITY");
+    } else {
+        // The debug representation always contains a dot or exponent and round-trips exactly
+        write!(out, "{:?}", val).unwrap();
+    }
+}
+
+fn write_list<T, F: FnMut(&T, &mut String)>(list: &[T], out: &mut String, mut f: F) {
+    out.push_str("vec![");
+    for (i, val) in list.iter().enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        f(val, out);
+    }
+    out.push(']');
+}
+
+fn write_entry(key: &Obj, val: &Obj, out: &mut String) {
+    out.push('(');
+    key.write_rust_literal(out);
+    out.push_str(", ");
+    val.write_rust_literal(out);
+    out.push(')');
+}
+
+impl Obj {
+    /// Returns a Rust expression that evaluates to this value
+    ///
+    /// The expression uses explicit constructors via `::serde_utils::Obj`, so it can be pasted into
+    /// tests of other crates, e.g. to embed a captured payload as fixture. Byte sequences are
+    /// created via [`from_bytes`](#method.from_bytes) so that no other crates are needed. The
+    /// representation is kept, i.e. a `U64Array` is constructed as `U64Array`. The expression is
+    /// written on a single line and can be formatted with `rustfmt`.
+    pub fn to_rust_literal(&self) -> String {
+        let mut out = String::new();
+        self.write_rust_literal(&mut out);
+        out
+    }
+
+    fn write_rust_literal(&self, out: &mut String) {
+        out.push_str(PREFIX);
+        match *self {
+            Obj::Null => out.push_str("Null"),
+            Obj::Bool(val) => write!(out, "Bool({})", val).unwrap(),
+            Obj::Signed(val) => write!(out, "Signed({})", val).unwrap(),
+            Obj::Unsigned(val) => write!(out, "Unsigned({})", val).unwrap(),
+            Obj::Float(val) => {
+                out.push_str("Float(");
+                write_float(val, out);
+                out.push(')');
+            },
+            #[cfg(feature = "num-bigint")]
+            Obj::BigInt(ref val) => write!(out, "BigInt(\"{}\".parse().unwrap())", val).unwrap(),
+            #[cfg(feature = "rust_decimal")]
+            Obj::Decimal(ref val) => write!(out, "Decimal(\"{}\".parse().unwrap())", val).unwrap(),
+            Obj::Str(ref val) => write!(out, "Str({:?}.to_string())", val).unwrap(),
+            Obj::Bin(ref val) => {
+                out.push_str("from_bytes(");
+                write_list(val, out, |val, out| write!(out, "{}", val).unwrap());
+                out.push(')');
+            },
+            Obj::List(ref list) => {
+                out.push_str("List(");
+                write_list(list, out, |val, out| val.write_rust_literal(out));
+                out.push(')');
+            },
+            Obj::U64Array(ref list) => {
+                out.push_str("U64Array(");
+                write_list(list, out, |val, out| write!(out, "{}", val).unwrap());
+                out.push(')');
+            },
+            Obj::F64Array(ref list) => {
+                out.push_str("F64Array(");
+                write_list(list, out, |&val, out| write_float(val, out));
+                out.push(')');
+            },
+            Obj::Map(ref map) => {
+                out.push_str("Map(");
+                let entries: Vec<_> = map.iter().collect();
+                write_list(&entries, out, |&(key, val), out| write_entry(key, val, out));
+                out.push_str(".into_iter().collect())");
+            }
+        }
+    }
+}
@@ -4,7 +4,7 @@
 /// The macro provides implementations for `Serialize` and `Deserialize` for various kinds or
 /// data types. The macro syntax tries to stay as close as possible to the declaration of the
 /// data type.
-/// To use the macro, `serde` must be imported by either `use serde;` or `extern crate serde;`.
+/// The generated code refers to serde through this crate, so `serde` does not need to be imported.
 ///
 /// ## (De-)Serializing `struct`s as maps
 ///
@@ -46,6 +46,8 @@
 /// i.e. `String` instead of `&str` in this example.
 ///
 /// It is also possible to use numeric field keys (when the serialization supports it, JSON does not).
+/// Any integer type can be used as key type, e.g. `u8` for compact encodings or `i64` for signed
+/// keys. The key literals are serialized with the given key type.
 ///
 /// ### Example
 ///
@@ -63,8 +65,99 @@
 /// });
 /// ```
 ///
+/// Binary field keys can be used by choosing `ByteBuf` (from the `serde_bytes` crate) as key type
+/// and byte string literals as keys. The keys are then serialized as byte sequences. Note that
+/// `Vec<u8>` can not be used as key type as serde would encode it as a sequence of numbers.
+///
+/// ### Example
+///
+/// ```ignore
+/// #[derive(Default)]
+/// struct Test {
+///     test: String,
+///     num: u64
+/// }
+/// serde_impl!(Test(ByteBuf) {
+///     test: String => b"test",
+///     num: u64 => b"num"
+/// });
+/// ```
+///
+/// Instead of literals, the keys can also be paths to constants, so that keys can be defined
+/// centrally and shared across the codebase.
+///
+/// ### Example
+///
+/// ```ignore
+/// mod keys {
+///     pub const TEST: u64 = 0;
+///     pub const NUM: u64 = 1;
+/// }
+///
+/// #[derive(Default)]
+/// struct Test {
+///     test: String,
+///     num: u64
+/// }
+/// serde_impl!(Test(u64) {
+///     test: String => keys::TEST,
+///     num: u64 => keys::NUM
+/// });
+/// ```
+///
+/// Note that duplicate keys are detected by comparing the spelling of the keys, so two different
+/// constants with the same value are not detected.
+///
+/// Any other type that implements `Serialize`, `Deserialize`, `PartialEq` and
+/// [`KeyType`](trait.KeyType.html) can be used as key type as well, e.g. a field identifier enum
+/// that is shared by several structs. Simple enums implemented with `serde_impl!` implement
+/// `KeyType` automatically.
+///
+/// ### Example
+///
+/// ```ignore
+/// #[derive(PartialEq)]
+/// enum FieldId {
+///     Test, Num
+/// }
+/// serde_impl!(FieldId(u8) {
+///     Test => 0,
+///     Num => 1
+/// });
+///
+/// #[derive(Default)]
+/// struct Test {
+///     test: String,
+///     num: u64
+/// }
+/// serde_impl!(Test(FieldId) {
+///     test: String => FieldId::Test,
+///     num: u64 => FieldId::Num
+/// });
+/// ```
+///
+/// String keys can also be compared case-insensitively by using the key type
+/// [`IgnoreCase`](type.IgnoreCase.html), or after a custom normalization by using a
+/// [`NormalizedKey`](struct.NormalizedKey.html). This works for maps and enums alike.
+///
+/// ### Example
+///
+/// ```ignore
+/// use serde_utils::IgnoreCase;
+///
+/// enum Status {
+///     Active, Inactive
+/// }
+/// serde_impl!(Status(IgnoreCase) {
+///     Active => "active",
+///     Inactive => "inactive"
+/// });
+/// ```
+///
 /// When deserializing data, the generated implementation will silently ignore all extra fields
 /// and use the default value for all missing fields.
+/// Errors that occur while deserializing a field are prefixed with the name of the type, the name
+/// of the field and its key, e.g. `Test.num (key "num"): invalid type...`.
 ///
 ///
 /// ### Compressed maps
@@ -81,6 +174,231 @@
 /// });
 /// ```
 ///
+/// Fields are compared to the default value of the struct, so their types must implement
+/// `PartialEq`. Alternatively, a field can be prefixed with `#[is_default(predicate)]` where the
+/// predicate is called with a reference to the field value and decides whether it is omitted.
+/// The predicate can be used for types that do not implement `PartialEq` or for omitting other
+/// values than the default.
+///
+/// ```ignore
+/// serde_impl!(Test(String?) {
+///     test: String => "test",
+///     #[is_default(|num: &u64| *num <= 1)]
+///     num: u64 => "num",
+///     #[is_default(Vec::is_empty)]
+///     items: Vec<Item> => "items"
+/// });
+/// ```
+///
+///
+/// ### Strict maps
+///
+/// By default, unknown keys are silently ignored and their values are skipped via
+/// [`Ignore`](struct.Ignore.html) without allocating. Adding `strict` before the field list makes
+/// the deserialization fail on unknown keys instead, e.g. to detect typos in configuration files.
+/// This can be combined with compressed maps and required fields.
+///
+/// ```ignore
+/// serde_impl!(Test(String?) strict {
+///     test: String => "test",
+///     num: u64 => "num"
+/// });
+/// ```
+///
+/// The error lists the expected keys: `Test: unknown key, expected one of: "test", "num"`.
+///
+///
+/// ### Readable keys
+///
+/// Adding `readable` after the key type writes the names of the fields as keys to human-readable
+/// formats like JSON and the given keys to all other formats, so that the same struct can be
+/// served compactly and for debugging. Both kinds of keys are accepted when deserializing,
+/// also keys that are numbers written as strings. This requires a self-describing format. It can
+/// be combined with strict maps.
+///
+/// ```ignore
+/// serde_impl!(Test(u64, readable) {
+///     test: String => 0,
+///     num: u64 => 1
+/// });
+/// ```
+///
+///
+/// ### Maps or tuples
+///
+/// Adding `map_or_tuple` or `tuple_or_map` after the key type accepts both the map encoding and
+/// the tuple encoding of the struct when deserializing, e.g. while migrating from one shape to the
+/// other. The first shape in the name is the one that is written. Tuples contain the fields in
+/// order like [structs as tuples](#de-serializing-structs-as-tuples), also if there is only a single
+/// field, and all of them must be present. This requires a self-describing format and can be
+/// combined with strict maps.
+///
+/// ```ignore
+/// serde_impl!(Test(u64, map_or_tuple) {
+///     test: String => 0,
+///     num: u64 => 1
+/// });
+/// ```
+///
+///
+/// ### Diagnostics
+///
+/// Skipped unknown keys and fields that are missing in the data and keep their default value can
+/// be reported to a hook installed via [`set_diagnostics_hook`](fn.set_diagnostics_hook.html),
+/// e.g. to notice schema drift in production. This applies to all deserializers of structs as
+/// maps, see [`Diagnostic`](enum.Diagnostic.html) for the reported events.
+///
+/// ```ignore
+/// serde_utils::set_diagnostics_hook(|event| eprintln!("{}", event));
+/// // Test: skipped unknown key "typo"
+/// // Test.num (key "num"): missing, using default value
+/// ```
+///
+/// ### Deserializing with a context
+///
+/// Some fields can only be deserialized with access to external state, e.g. ids that need to be
+/// resolved against a session table or strings that should be interned. Adding `context($ctx)`
+/// after the key type generates an implementation of
+/// [`DeserializeWith<$ctx>`](trait.DeserializeWith.html) instead of `Deserialize`. Fields marked
+/// with `#[context]` are deserialized via their own `DeserializeWith<$ctx>` implementation and get
+/// a mutable reference to the context, all other fields are deserialized as usual. The value is
+/// deserialized with a [`ContextSeed`](struct.ContextSeed.html). This can be combined with
+/// compressed and strict maps but not with required fields.
+///
+/// ```ignore
+/// serde_impl!(Message(String) context(Session) {
+///     text: String => "text",
+///     #[context]
+///     author: UserRef => "author",
+///     #[context]
+///     mentions: Vec<UserRef> => "mentions"
+/// });
+///
+/// let message: Message = ContextSeed::new(&mut session).deserialize(&mut de)?;
+/// ```
+///
+/// `DeserializeWith` is implemented for `Option<T>` and `Vec<T>` so that nested values can use the
+/// context as well. Serialization is not affected, all field types must implement `Serialize`.
+///
+///
+/// ### Nested groups
+///
+/// Several fields of a flat struct can be grouped in a nested map in the serialized form by
+/// adding a `nested` block after the normal fields. The syntax for this case is:
+///
+/// ```ignore
+/// serde_impl!($name($ktype) {
+///    $fname: $ftype => $fkey,
+///    ...
+/// } nested {
+///    $gkey => {
+///        $fname: $ftype => $fkey,
+///        ...
+///    },
+///    ...
+/// });
+/// ```
+///
+/// where `$gkey` is the key of the nested map in the outer map. The keys in the nested map use
+/// the same key type as the outer map.
+///
+/// ### Example
+///
+/// ```ignore
+/// #[derive(Default)]
+/// struct Test {
+///     name: String,
+///     cpu: u32,
+///     mem: u64
+/// }
+/// serde_impl!(Test(String) {
+///     name: String => "name"
+/// } nested {
+///     "limits" => {
+///         cpu: u32 => "cpu",
+///         mem: u64 => "mem"
+///     }
+/// });
+/// ```
+///
+/// This struct is serialized as `{"name": ..., "limits": {"cpu": ..., "mem": ...}}`.
+///
+///
+/// ### Required fields
+///
+/// By adding an exclamation mark after the key type, the struct does not need to implement
+/// `Default`. Instead, all fields of the struct must be listed and all fields must be present
+/// when deserializing, otherwise an error is returned. Only fields of type `Option` may be
+/// missing, they are set to `None` in that case. Fields of type
+/// [`Maybe`](adapters/maybe/enum.Maybe.html) may be missing as well, they are set to
+/// `Maybe::Missing`.
+///
+/// ```ignore
+/// struct Test {
+///     test: String,
+///     id: NonZeroU64,
+///     option: Option<bool>
+/// }
+/// serde_impl!(Test(String!) {
+///     test: String => "test",
+///     id: NonZeroU64 => "id",
+///     option: Option<bool> => "option"
+/// });
+/// ```
+///
+///
+/// ### Versioned maps
+///
+/// Structs that change over time can be serialized as maps that contain a version number in
+/// addition to the fields. Payloads of older versions are passed to migration functions that
+/// convert them into the current struct. The macro syntax for this case is:
+///
+/// ```ignore
+/// serde_impl!($name($ktype) version($vkey => $version) {
+///    $fname: $ftype => $fkey,
+///    ...
+/// } migrate {
+///    $mversion => $migrate,
+///    ...
+/// });
+/// ```
+///
+/// where
+///
+/// * `$vkey` is the key of the version entry in the map.
+/// * `$version` is the current version number, which is written when serializing.
+/// * `$mversion` is an older version number.
+/// * `$migrate` is a function (or closure) that converts a payload of version `$mversion` into
+///   the current struct. It is called with the whole map as an [`Obj`](enum.Obj.html) and must
+///   return a `Result<$name, E>` where `E` implements `Display`.
+///
+/// Payloads without a version entry are treated as version `0`. Payloads of the current version
+/// are decoded like normal maps, payloads of versions without migration function are rejected.
+/// The map is read into an [`Obj`](enum.Obj.html) before the version is known, so this requires a
+/// self-describing format like JSON or MessagePack and does not work with e.g. bincode.
+///
+/// ### Example
+///
+/// ```ignore
+/// #[derive(Default)]
+/// struct Test {
+///     name: String,
+///     size: u64
+/// }
+/// serde_impl!(Test(String) version("version" => 2) {
+///     name: String => "name",
+///     size: u64 => "size"
+/// } migrate {
+///     1 => migrate_v1
+/// });
+///
+/// // TestV1 is the old struct, (de-)serialized with serde_impl! as well
+/// fn migrate_v1(obj: Obj) -> Result<Test, serde::de::value::Error> {
+///     let old: TestV1 = serde_utils::from_obj(obj)?;
+///     Ok(Test { name: old.name, size: old.size_kb * 1024 })
+/// }
+/// ```
+///
 ///
 /// ## (De-)Serializing `struct`s as tuples
 ///
@@ -120,6 +438,72 @@
 /// When derserializing a tuple as such a data struct, any missing or extra fields will be treated
 /// as an error. Therefore, the struct does not need to implement `Default`.
 ///
+/// ### Lenient tuples
+///
+/// By adding `..` after the last field, the tuple encoding becomes forward and backward
+/// compatible like the map encoding: extra elements at the end of the tuple are ignored and
+/// missing trailing fields get their default value. Therefore, the struct must implement
+/// `Default` in this case. Since the number of elements is not known when reading, lenient tuples
+/// are written as sequences with their length, also when there is only one field. Skipping extra
+/// elements requires a self-describing format, formats like bincode only support missing fields.
+///
+/// ```ignore
+/// #[derive(Default)]
+/// struct Test {
+///     test: String,
+///     num: u64,
+///     option: Option<bool>,
+/// }
+/// serde_impl!(Test {
+///     test: String,
+///     num: u64,
+///     option: Option<bool>,
+///     ..
+/// });
+/// ```
+///
+/// ## (De-)Serializing flag `struct`s as bitmasks
+///
+/// Structs of `bool` fields can be (de-)serialized as a single integer bitmask. The syntax in this
+/// case is:
+///
+/// ```ignore
+/// serde_impl!($name($ktype) flags {
+///    $fname => $mask,
+///    ...
+///    .. => $unknown
+/// });
+/// ```
+///
+/// where
+///
+/// * `$name` is the name of the type to be implemented.
+/// * `$ktype` is the integer type of the bitmask.
+/// * `$fname` is the name of a `bool` field (on the struct in Rust).
+/// * `$mask` is the mask of bits for the field, masks must not be empty or overlap.
+/// * `$unknown` is the optional name of a field of type `$ktype` that keeps all bits that do not
+///   belong to any field. These bits are also included when serializing, bits of this field that
+///   belong to a mask are ignored.
+///
+/// Without the `$unknown` field, deserializing a bitmask with unknown bits results in an error.
+///
+/// ### Example
+///
+/// ```ignore
+/// struct Permissions {
+///     read: bool,
+///     write: bool,
+///     exec: bool,
+///     other: u8
+/// }
+/// serde_impl!(Permissions(u8) flags {
+///     read => 0x01,
+///     write => 0x02,
+///     exec => 0x04,
+///     .. => other
+/// });
+/// ```
+///
 /// ## (De-)Serializing simple `enums`s
 ///
 /// (De-)serializing enums that do not have parameters, just maps the variants to and from a
@@ -155,7 +539,8 @@
 /// Note that the `$ktype` must be an *owned type* corresponding to the used variant keys,
 /// i.e. `String` instead of `&str` in this example.
 ///
-/// It is also possible to use numeric variant keys.
+/// It is also possible to use numeric or binary (`ByteBuf`) variant keys. Any integer type can be
+/// used, including signed types for negative discriminators.
 ///
 /// ### Example
 ///
@@ -170,6 +555,37 @@
 /// });
 /// ```
 ///
+/// ### Fallback variant
+///
+/// A unit variant can be marked as fallback by adding `_ => $variant` at the end. Deserializing
+/// an unknown discriminator then results in this variant instead of an error, e.g. to handle new
+/// variants added by newer producers. The fallback variant itself can not be serialized.
+///
+/// ```ignore
+/// enum Test {
+///     A, B, Unknown
+/// }
+/// serde_impl!(Test(String) {
+///     A => "a",
+///     B => "b",
+///     _ => Unknown
+/// });
+/// ```
+///
+/// ### Discriminant values as keys
+///
+/// For enums with explicit discriminant values, adding `discriminant` before the variant list
+/// uses these values as keys, so that they do not need to be repeated.
+///
+/// ```ignore
+/// enum Test {
+///     A = 1, B = 5, C = 7
+/// }
+/// serde_impl!(Test(u8) discriminant {
+///     A, B, C
+/// });
+/// ```
+///
 /// ## (De-)Serializing `enums`s with one parameter
 ///
 /// It is also possible to (de-)serialize enums with **exactly one** parameter.
@@ -241,177 +657,2897 @@
 /// }
 /// ```
 ///
-/// ## Limitations
-/// The following things do not work, and most likely will never work:
+/// ### Default payloads
 ///
-/// * Data types with lifetimes
-/// * Parametrized data types
-/// * Enums with multiple parameters
-/// * Enums where different variants have different parameter counts
-/// * Enums with field names
-/// * Tuple structs
-/// * More fancy key types than String and numeric types might not work
-#[macro_export]
-macro_rules! serde_impl(
-    // Serde impl for struct $name($ktype?) { $fname: $ftype } as map
-    ( $name:ident($ktype:ident?) { $( $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
-        impl ::serde::Serialize for $name {
-            fn serialize<S: ::serde::Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
-                use ::serde::ser::SerializeMap;
-                let default: $name = Default::default();
-                let mut len = 0;
-                $(
-                    if self.$fname != default.$fname {
-                        len += 1;
-                    }
-                )*
-                let mut state = ser.serialize_map(Some(len))?;
-                $(
-                    if self.$fname != default.$fname {
-                        state.serialize_entry(&$fkey, &self.$fname)?;
-                    }
-                )*
-                state.end()
-            }
-        }
-        impl<'a> ::serde::Deserialize<'a> for $name {
-            fn deserialize<D: ::serde::Deserializer<'a>>(de: D) -> Result<Self, D::Error> {
-                use serde_utils::Obj as _DummyObjToSkipUnknownFields;
-                struct _Deserializer;
-                impl<'a> ::serde::de::Visitor<'a> for _Deserializer {
-                    type Value = $name;
-                    fn expecting(&self, formatter: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
-                        write!(formatter, "map")
-                    }
-
-                    fn visit_map<V: ::serde::de::MapAccess<'a>>(self, mut visitor: V) -> Result<Self::Value, V::Error> {
-                        let mut obj: $name = Default::default();
-                        while let Some(key) = visitor.next_key::<$ktype>()? {
-                            $(
-                                if key == $fkey {
-                                    obj.$fname = visitor.next_value()?;
-                                    continue
-                                }
-                            )*
-                            let _skip: _DummyObjToSkipUnknownFields = visitor.next_value()?;
-                        }
-                        Ok(obj)
-                    }
-                }
-                Ok(de.deserialize_map(_Deserializer)?)
-            }
-        }
-    };
-    // Serde impl for struct $name($ktype) { $fname: $ftype } as map
-    ( $name:ident($ktype:ident) { $( $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
-        impl ::serde::Serialize for $name {
-            fn serialize<S: ::serde::Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
-                use ::serde::ser::SerializeMap;
-                let mut state = ser.serialize_map(Some( [ $( $fkey ),+ ].len() ))?;
-                $(
-                    state.serialize_entry(&$fkey, &self.$fname)?;
-                )*
-                state.end()
-            }
+/// A variant can declare a default payload with `$variant($ftype = $default)`. The payload may
+/// then be missing in the serialized form, i.e. the tuple contains only the key, and the default
+/// is used instead. Serializing always writes the payload.
+///
+/// ```ignore
+/// serde_impl!(Test(u64) {
+///     A(u64) => 0,
+///     B(Vec<u8> = vec![]) => 1
+/// });
+/// ```
+///
+/// Payloads of type `Option<T>` and `()` do not need a default, a missing payload is read as
+/// `None` or `()`. Variants marked with `#[elide]` also leave out such payloads when writing, so
+/// only the key is written for `None` or `()`. Other variants always write the payload.
+///
+/// ```ignore
+/// serde_impl!(Test(u64) {
+///     A(u64) => 0,
+///     #[elide] B(Option<String>) => 1
+/// });
+/// ```
+///
+/// Default payloads only work in self-describing formats like JSON, MessagePack or CBOR. The enum
+/// is read with `deserialize_tuple(2)` and the payload counts as missing when the sequence ends
+/// after the key. Formats like bincode do not store the length of a tuple and always read both
+/// elements, so they can neither read a tuple that contains only the key nor data written by
+/// `#[elide]` variants. Types used with such formats should not rely on missing payloads.
+///
+/// Default payloads are not supported for single-entry maps, these always contain the payload.
+///
+/// ### Bare keys
+///
+/// Variants that gained a payload after data has been written without it can be marked with
+/// `#[bare]`. Their key is then also accepted on its own, not wrapped in a tuple, and the
+/// payload is the default payload or the `Default` value of its type. Serializing still writes
+/// the tuple.
+///
+/// ```ignore
+/// serde_impl!(Event(String) {
+///     Started(Settings) => "started",
+///     #[bare] Stopped(StopReason) => "stopped"
+/// });
+/// ```
+///
+/// Telling bare keys and tuples apart requires a self-describing format like JSON or
+/// MessagePack, so the enum is read via `deserialize_any` if any variant is marked.
+///
+/// ### Single-entry maps
+///
+/// By default, such enums are encoded as a tuple `(key, payload)`. Adding `map` before the
+/// variant list encodes them as a map with a single entry `{key: payload}` instead, which is
+/// easier to handle for consumers that do not support tuple-encoded unions.
+///
+/// ```ignore
+/// serde_impl!(Test(String) map {
+///     A(u64) => "a",
+///     B(String) => "b",
+///     C(bool) => "c"
+/// });
+/// ```
+///
+/// ## Automatic keys
+///
+/// The keys can be omitted for maps and for both kinds of enums. They are then derived
+/// automatically:
+///
+/// * For `String` and `IgnoreCase` keys, the key is the name of the field or variant.
+/// * For `ByteBuf` keys, the key is the name of the field or variant as bytes.
+/// * For all other key types, the keys are numbered from `0` in the order of the definition. At
+///   most 128 entries are supported this way.
+///
+/// All other options like `?`, `!`, `strict`, `map` and fallback variants can still be used.
+///
+/// ### Example
+///
+/// ```ignore
+/// serde_impl!(Test(String?) {
+///     test: String,
+///     num: u64
+/// });
+///
+/// serde_impl!(Kind(u8) {
+///     A, B, C,
+///     _ => Unknown
+/// });
+/// ```
+///
+/// Note that with numbered keys, reordering or removing entries changes the keys of other entries.
+///
+/// ### Converting the case
+///
+/// Keys derived from names can be converted to another case with `rename_all` after the key type,
+/// e.g. `Test(String, rename_all = "camelCase")` or `Test(String?, rename_all = "kebab-case")`.
+/// The cases are named like in serde: `lowercase`, `UPPERCASE`, `PascalCase`, `camelCase`,
+/// `snake_case`, `SCREAMING_SNAKE_CASE`, `kebab-case` and `SCREAMING-KEBAB-CASE`. Words are split
+/// at underscores and at uppercase letters following lowercase letters or digits, so both field
+/// and variant names work. The keys are computed at compile time and the key constants contain the
+/// converted keys. `rename_all` can not be combined with explicit keys or numbered keys.
+///
+/// ```ignore
+/// serde_impl!(User(String, rename_all = "camelCase") {
+///     user_id: u64,         // "userId"
+///     display_name: String  // "displayName"
+/// });
+/// ```
+///
+/// ## Key constants
+///
+/// For all types with keys, the serialization side also generates associated constants that
+/// expose the keys as they are used on the wire, so that other code does not need to repeat them:
+///
+/// * `KEYS` has one member per field or variant, named like the field or variant and holding its
+///   key, e.g. `Test::KEYS.num`.
+/// * `FIELD_KEYS` (for structs) or `VARIANT_KEYS` (for enums) is a slice of all field or variant
+///   names together with their keys, in the order of the definition.
+///
+/// The keys have the type `<$ktype as KeyType>::Const`, e.g. `&'static str` for `String` keys and
+/// `&'static [u8]` for `ByteBuf` keys. Fields in nested groups are included, version keys are not.
+///
+/// ### Example
+///
+/// ```ignore
+/// serde_impl!(Test(String) {
+///     test: String => "test",
+///     num: u64 => "num"
+/// });
+///
+/// assert_eq!(Test::KEYS.num, "num");
+/// assert_eq!(Test::FIELD_KEYS, &[("test", "test"), ("num", "num")]);
+/// ```
+///
+/// ## Field information
+///
+/// For structs, the serialization side also generates a function
+/// `fields() -> Vec<FieldInfo<K>>` that describes the fields at runtime: their name, key,
+/// type as written in the invocation and whether they are `Option`s. See
+/// [`FieldInfo`](struct.FieldInfo.html) for details. For tuples, the key of a field is its
+/// position. The list is built on each call.
+///
+/// ### Example
+///
+/// ```ignore
+/// for field in Test::fields() {
+///     println!("{} (key {:?}): {}", field.name, field.key, field.type_name);
+/// }
+/// ```
+///
+/// ## Recursive types
+///
+/// Types can contain themselves behind an indirection like `Box`, `Vec` or `Option<Box<_>>`.
+/// The generated code only needs the `Default` of the type itself for maps as described above,
+/// which `#[derive(Default)]` provides since these fields default to empty values. Tuples and
+/// maps with required fields (`!`) need no `Default` at all.
+///
+/// ### Example
+///
+/// ```ignore
+/// #[derive(Default)]
+/// struct Node {
+///     name: String,
+///     children: Vec<Node>,
+///     next: Option<Box<Node>>
+/// }
+/// serde_impl!(Node(String) {
+///     name: String => "name",
+///     children: Vec<Node> => "children",
+///     next: Option<Box<Node>> => "next"
+/// });
+/// ```
+///
+/// ## Borrowed fields
+///
+/// Structs (de-)serialized as maps (also strict ones) or tuples can have a single lifetime
+/// parameter, written after the name as in `$name<'a>($ktype)` or `$name<'a>`. Fields of type
+/// `Cow<'a, str>` and `Cow<'a, [u8]>` then borrow from the input if the deserializer supports it
+/// and own their data otherwise. `Cow<[u8]>` fields are written as bytes instead of a sequence of
+/// numbers. Fields of other types are (de-)serialized as usual.
+///
+/// Fields of type `Cow<'static, str>` work in all structs but always own their data when
+/// deserialized, since the input does not live long enough to borrow from it.
+///
+/// ### Example
+///
+/// ```ignore
+/// #[derive(Default)]
+/// struct Message<'a> {
+///     topic: Cow<'a, str>,
+///     payload: Cow<'a, [u8]>
+/// }
+/// serde_impl!(Message<'a>(String) {
+///     topic: Cow<'a, str> => "topic",
+///     payload: Cow<'a, [u8]> => "payload"
+/// });
+///
+/// let message: Message = rmp_serde::from_slice(&bytes)?;
+/// ```
+///
+/// ## Array fields
+///
+/// Fields of type `[T; N]` are written as tuples of `N` elements like serde does for arrays, but
+/// for any length and not just up to 32 elements. Deserializing fails with an `invalid length`
+/// error if the input has more or fewer elements. The standard library implements `Default` only
+/// for arrays of up to 32 elements, so structs with longer arrays need a manual `Default` impl to
+/// be deserialized as maps, or can use required fields (`!`) instead.
+///
+/// ```ignore
+/// struct Signed {
+///     hash: [u8; 32],
+///     signature: [u8; 64]
+/// }
+/// serde_impl!(Signed(String!) {
+///     hash: [u8; 32] => "hash",
+///     signature: [u8; 64] => "signature"
+/// });
+/// ```
+///
+/// ## Skipped fields
+///
+/// Fields of type `PhantomData<T>` and `()` carry no data, so they are left out of the serialized
+/// form: maps have no entry for them and tuples no element. When deserializing, they are
+/// constructed without reading anything. Their keys still appear in `KEYS` and `fields()`, but
+/// not in the JSON schema. A tuple struct with a single field is still written as that field.
+///
+/// ### Example
+///
+/// ```ignore
+/// #[derive(Default)]
+/// struct Distance {
+///     value: f64,
+///     unit: PhantomData<Meters>
+/// }
+/// serde_impl!(Distance(String) {
+///     value: f64 => "value",
+///     unit: PhantomData<Meters> => "unit"
+/// });
+/// ```
+///
+/// ## Computed fields
+///
+/// Entries of a map with explicit keys can be prefixed with `#[compute($func)]` to write the result
+/// of `$func` under their key instead of a field of the struct, e.g. a checksum or a summary derived
+/// from the other fields. The function is called with a reference to the struct and must return a
+/// value of the given type. The name of the entry does not need to be a field of the struct, it is
+/// only used for `KEYS` and `fields()`. When deserializing, the value under the key is skipped
+/// without reading it, since it is computed from the other fields anyway. This works for maps that
+/// are not compressed and do not have required fields, also strict ones.
+///
+/// ### Example
+///
+/// ```ignore
+/// #[derive(Default)]
+/// struct Block {
+///     data: Vec<u8>
+/// }
+/// impl Block {
+///     fn crc(&self) -> u32 { ... }
+/// }
+/// serde_impl!(Block(String) {
+///     data: Vec<u8> => "data",
+///     #[compute(Block::crc)]
+///     checksum: u32 => "crc"
+/// });
+/// ```
+///
+/// ## Shared values
+///
+/// Fields of type `Rc<T>` and `Arc<T>` are (de-)serialized like `T` with the `rc` feature, which
+/// enables the feature of the same name of serde. Values shared by many fields are then written
+/// once per field and read back as independent copies. The wrapper type
+/// [`Shared`](adapters/shared/struct.Shared.html) writes each value only once and refers back to
+/// it afterwards, so the sharing is restored when reading.
+///
+/// ## Duplicate keys
+///
+/// Mapping two fields or two enum variants to the same key is detected at compile time and
+/// results in an error. Note that keys are compared by their spelling, so `1` and `0x1` are not
+/// detected as duplicates.
+///
+/// ```compile_fail
+/// # #[macro_use] extern crate serde_utils;
+/// # extern crate serde;
+/// #[derive(Default)]
+/// struct Test {
+///     test: String,
+///     num: u64
+/// }
+/// serde_impl!(Test(u64) {
+///     test: String => 0,
+///     num: u64 => 0
+/// });
+/// # fn main() {}
+/// ```
+///
+/// ## Reserved keys
+///
+/// Keys of removed fields can be declared as reserved at the beginning of the field list of a
+/// struct serialized as map with explicit keys, e.g. `reserved 3, 7;`. Values under these keys
+/// are skipped when deserializing, also for strict structs and without reporting them as unknown
+/// keys. Using a reserved key for a field is a compile time error, so a key of a removed field can
+/// not be reused by accident and misread old data. Like duplicate keys, the keys are compared by
+/// their spelling.
+///
+/// ```compile_fail
+/// # #[macro_use] extern crate serde_utils;
+/// # extern crate serde;
+/// #[derive(Default)]
+/// struct Test {
+///     test: String,
+///     num: u64
+/// }
+/// serde_impl!(Test(u64) strict {
+///     reserved 1, 3;
+///     test: String => 0,
+///     num: u64 => 3
+/// });
+/// # fn main() {}
+/// ```
+///
+/// ## Exhaustive structs
+///
+/// A field that is missing in the list is neither serialized nor deserialized, its value is lost
+/// silently. Prefixing the definition of a struct with `exhaustive` checks at compile time that
+/// all fields of the struct are listed. This works for all kinds of structs and can also be used
+/// with multiple types in one invocation.
+///
+/// ```compile_fail
+/// # #[macro_use] extern crate serde_utils;
+/// # extern crate serde;
+/// #[derive(Default)]
+/// struct Test {
+///     test: String,
+///     num: u64
+/// }
+/// serde_impl!(exhaustive Test(u64) {
+///     test: String => 0
+/// });
+/// # fn main() {}
+/// ```
+///
+/// ## Multiple types in one invocation
+///
+/// Several types can be implemented in one invocation by separating their definitions with `;`.
+/// If the definitions start with `key $ktype;`, definitions can use `_` instead of their key type
+/// (also in combination with `?` or `!`) to refer to this shared key type.
+///
+/// ### Example
+///
+/// ```ignore
+/// serde_impl!(key u8;
+///     Test(_) {
+///         test: String => 0,
+///         num: u64 => 1
+///     };
+///     Other(_?) {
+///         option: Option<bool> => 0
+///     };
+///     Kind(_) {
+///         A => 0,
+///         B => 1
+///     };
+///     Pair {
+///         first: u64,
+///         second: u64
+///     }
+/// );
+/// ```
+///
+/// ## Types in other modules
+///
+/// The type can also be referenced by a path, so that the implementations for the types of a
+/// crate can be collected in one module describing the wire format. The fields must be visible
+/// from this module.
+///
+/// ### Example
+///
+/// ```ignore
+/// mod wire {
+///     serde_impl!(super::model::Test(String) {
+///         test: String => "test",
+///         num: u64 => "num"
+///     });
+/// }
+/// ```
+///
+/// ## Generating only one direction
+///
+/// The macros [`serde_impl_ser!`](macro.serde_impl_ser!.html) and
+/// [`serde_impl_de!`](macro.serde_impl_de!.html) accept exactly the same syntax as `serde_impl!`
+/// but only generate the `Serialize` or the `Deserialize` implementation respectively. This is
+/// useful when the other implementation is written by hand.
+///
+/// ### Example
+///
+/// ```ignore
+/// #[derive(Default)]
+/// struct Test {
+///     test: String,
+///     num: u64
+/// }
+/// serde_impl_de!(Test(String) {
+///     test: String => "test",
+///     num: u64 => "num"
+/// });
+/// ```
+///
+/// ## JSON schema
+///
+/// If the definitions start with `schema;`, the macro additionally implements
+/// [`JsonSchema`](trait.JsonSchema.html) via [`serde_impl_schema!`](macro.serde_impl_schema!.html)
+/// so that `$name::json_schema()` returns a description of the serialized form. This requires all
+/// field types to implement `JsonSchema` as well.
+///
+/// ### Example
+///
+/// ```ignore
+/// serde_impl!(schema; key u8;
+///     Test(_) {
+///         test: String => 0,
+///         num: u64 => 1
+///     };
+///     Kind(_) {
+///         A => 0,
+///         B => 1
+///     }
+/// );
+/// ```
+///
+/// ## Conversions to and from `Obj`
+///
+/// If the definitions start with `obj;`, the macro additionally implements `TryFrom<&$name>` for
+/// [`Obj`](enum.Obj.html) and `TryFrom<Obj>` for `$name` via
+/// [`serde_impl_obj!`](macro.serde_impl_obj!.html). The conversions use the same keys as the
+/// serialized form, so typed values and `Obj` values can be exchanged without encoding them in a
+/// format first.
+///
+/// ### Example
+///
+/// ```ignore
+/// serde_impl!(obj; Test(String) {
+///     test: String => "test",
+///     num: u64 => "num"
+/// });
+///
+/// let obj = Obj::try_from(&test)?;
+/// assert_eq!(obj["num"], Obj::Unsigned(1));
+/// let test = Test::try_from(obj)?;
+/// ```
+///
+/// ## Size estimates
+///
+/// If the definitions start with `size;`, the macro additionally generates a method
+/// `serialized_size_hint(&self) -> usize` via [`serde_impl_size!`](macro.serde_impl_size!.html)
+/// that estimates the size of the value in a compact binary format, see
+/// [`serialized_size_hint`](fn.serialized_size_hint.html).
+///
+/// `schema;`, `obj;`, `size;` and `roundtrip;` (see [below](#round-trip-tests)) can be combined in
+/// any order, e.g. `serde_impl!(schema; size; ...)`.
+///
+/// ## Round trip tests
+///
+/// If the definitions start with `roundtrip;` and the `test-util` feature is enabled, the macro
+/// additionally emits a `#[cfg(test)]` test for every type that generates values via
+/// [`arbitrary`](https://docs.rs/arbitrary) and checks that they survive a round trip in all
+/// enabled formats, see
+/// [`assert_arbitrary_roundtrip`](testing/fn.assert_arbitrary_roundtrip.html). The test is named
+/// after the type. The types must implement `Arbitrary`, `PartialEq` and `Debug` in test builds,
+/// usually via `#[cfg_attr(test, derive(Arbitrary, PartialEq, Debug))]`. Without the feature,
+/// `roundtrip;` has no effect.
+///
+/// ```ignore
+/// serde_impl!(roundtrip; schema; Message(u8) {
+///     text: String => 0
+/// });
+/// ```
+///
+/// The feature is meant to be enabled for dev-dependencies only. Types with a lifetime and types
+/// deserialized with a context do not get a test.
+///
+/// ```ignore
+/// [dev-dependencies]
+/// serde_utils = { version = "0.7", features = ["test-util"] }
+/// arbitrary = { version = "1.3", features = ["derive"] }
+/// ```
+///
+/// ## Limitations
+/// The following things do not work, and most likely will never work:
+///
+/// * Data types with lifetimes, except for the maps and tuples described
+///   [above](#borrowed-fields)
+/// * Parametrized data types
+/// * Enums with multiple parameters
+/// * Enums where different variants have different parameter counts
+/// * Enums with field names
+/// * Tuple structs
+/// * Key types must implement `PartialEq` and [`KeyType`](trait.KeyType.html)
+#[macro_export]
+macro_rules! serde_impl(
+    ( schema; $($tt:tt)* ) => {
+        $crate::serde_impl!($($tt)*);
+        $crate::serde_impl_schema!($($tt)*);
+    };
+    ( obj; $($tt:tt)* ) => {
+        $crate::serde_impl!($($tt)*);
+        $crate::serde_impl_obj!($($tt)*);
+    };
+    ( size; $($tt:tt)* ) => {
+        $crate::serde_impl!($($tt)*);
+        $crate::serde_impl_size!($($tt)*);
+    };
+    ( roundtrip; $($tt:tt)* ) => {
+        $crate::serde_impl!($($tt)*);
+        $crate::__serde_impl_roundtrip!($($tt)*);
+    };
+    ( $($tt:tt)* ) => {
+        $crate::serde_impl_ser!($($tt)*);
+        $crate::serde_impl_de!($($tt)*);
+    };
+);
+
+/// Asserts that two values are equal as [`Obj`](enum.Obj.html), reporting the differences per path
+///
+/// The macro is called as `assert_obj_eq!(expected, actual)` with an optional format string and
+/// arguments like `assert_eq!`. Both values can be of any serializable type, they are converted via
+/// [`to_obj`](fn.to_obj.html) before comparing them. So a value can also be compared with an `Obj`
+/// describing its expected serialization.
+///
+/// On failure, the panic message lists every path where the values differ instead of the debug
+/// output of both values:
+///
+/// ```text
+/// assertion `expected == actual` failed:
+/// users/1/name: expected Str("bob"), found Str("alice")
+/// users/1/age: missing, expected Unsigned(42)
+/// ```
+///
+/// ```ignore
+/// assert_obj_eq!(serde_json::from_str::<Obj>(expected_json)?, config, "config {}", path);
+/// ```
+#[macro_export]
+macro_rules! assert_obj_eq(
+    ( $expected:expr, $actual:expr $(,)? ) => {
+        $crate::private::assert_obj_eq(&$expected, &$actual, None)
+    };
+    ( $expected:expr, $actual:expr, $($arg:tt)+ ) => {
+        $crate::private::assert_obj_eq(&$expected, &$actual, Some(format_args!($($arg)+)))
+    };
+);
+
+/// Macro for implementing only serialization via serde
+///
+/// This macro accepts the same syntax as [`serde_impl!`](macro.serde_impl!.html) but only
+/// generates the `Serialize` implementation.
+#[macro_export]
+macro_rules! serde_impl_ser(
+    // Multiple definitions sharing a key type: key $ktype; $def; ...
+    ( key $ktype:ident; $($tt:tt)* ) => {
+        $crate::serde_impl_ser!(@split [$ktype] [;] [] $($tt)*);
+    };
+    // Structs that must list all of their fields
+    ( exhaustive $($rest:tt)* ) => {
+        $crate::__serde_impl_exhaustive!([] $($rest)*);
+        $crate::serde_impl_ser!($($rest)*);
+    };
+    // Types referenced by path are imported into a block, the arms below need a plain name
+    ( $first:ident :: $($rest:tt)* ) => {
+        $crate::serde_impl_ser!(@path [] [$first] $($rest)*);
+    };
+    ( @path [$($def:tt)*] [$($path:ident)::+] $next:ident :: $($rest:tt)* ) => {
+        $crate::serde_impl_ser!(@path [$($def)*] [$($path)::+ :: $next] $($rest)*);
+    };
+    ( @path [$($def:tt)*] [$($path:ident)::+] $name:ident $($rest:tt)* ) => {
+        const _: () = {
+            use $($path::)+$name;
+            $crate::serde_impl_ser!($($def)* $name $($rest)*);
+        };
+    };
+    // Split multiple definitions at `;`
+    ( @split [$($ktype:ident)?] [$($seen:tt)?] [$($acc:tt)*] ; $($rest:tt)* ) => {
+        $crate::serde_impl_ser!(@def [$($ktype)?] $($acc)*);
+        $crate::serde_impl_ser!(@split [$($ktype)?] [;] [] $($rest)*);
+    };
+    ( @split [$($ktype:ident)?] [$($seen:tt)?] [$($acc:tt)*] $next:tt $($rest:tt)* ) => {
+        $crate::serde_impl_ser!(@split [$($ktype)?] [$($seen)?] [$($acc)* $next] $($rest)*);
+    };
+    ( @split [$($ktype:ident)?] [;] [] ) => {};
+    ( @split [$($ktype:ident)?] [;] [$($acc:tt)+] ) => {
+        $crate::serde_impl_ser!(@def [$($ktype)?] $($acc)+);
+    };
+    ( @split [$($ktype:ident)?] [] [$($acc:tt)*] ) => {
+        compile_error!(concat!("invalid syntax for serde_impl!: ", stringify!($($acc)*)));
+    };
+    ( @def [$($ktype:ident)?] exhaustive $($rest:tt)* ) => {
+        $crate::__serde_impl_exhaustive!([] $($rest)*);
+        $crate::serde_impl_ser!(@def [$($ktype)?] $($rest)*);
+    };
+    ( @def [$($ktype:ident)?] $first:ident :: $($rest:tt)* ) => {
+        $crate::serde_impl_ser!(@path [@def [$($ktype)?]] [$first] $($rest)*);
+    };
+    // Substitute the shared key type for `_`
+    ( @def [$ktype:ident] $name:ident(_, rename_all = $case:literal) $($rest:tt)* ) => {
+        $crate::serde_impl_ser!($name($ktype) @$case $($rest)*);
+    };
+    ( @def [$ktype:ident] $name:ident(_ $flag:tt, rename_all = $case:literal) $($rest:tt)* ) => {
+        $crate::serde_impl_ser!($name($ktype $flag) @$case $($rest)*);
+    };
+    ( @def [$ktype:ident] $name:ident(_, $mode:ident) $($rest:tt)* ) => {
+        $crate::serde_impl_ser!($name($ktype, $mode) $($rest)*);
+    };
+    ( @def [$ktype:ident] $name:ident(_ $($flag:tt)?) $($rest:tt)* ) => {
+        $crate::serde_impl_ser!($name($ktype $($flag)?) $($rest)*);
+    };
+    ( @def [$($ktype:ident)?] $($rest:tt)* ) => {
+        $crate::serde_impl_ser!($($rest)*);
+    };
+    // Automatic keys converted to another case, marked with `@$case` for the arms below
+    ( $name:ident($ktype:ident, rename_all = $case:literal) $($rest:tt)* ) => {
+        $crate::serde_impl_ser!($name($ktype) @$case $($rest)*);
+    };
+    ( $name:ident($ktype:ident $flag:tt, rename_all = $case:literal) $($rest:tt)* ) => {
+        $crate::serde_impl_ser!($name($ktype $flag) @$case $($rest)*);
+    };
+    // Serialize impl for struct $name($ktype, readable) { $fname: $ftype } as map, the keys are the names of
+    // the fields for human-readable formats
+    ( $name:ident($ktype:ident, readable) strict { $($body:tt)* } ) => {
+        $crate::serde_impl_ser!($name($ktype, readable) { $($body)* });
+    };
+    ( $name:ident($ktype:ident, readable) { $( $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
+        const _: () = assert!(!$crate::private::has_duplicate_keys(&[ $( stringify!($fkey) ),+ ]),
+            concat!("duplicate key in serde_impl!(", stringify!($name), ")"));
+        $crate::__serde_impl_keys!($name($ktype) FIELD_KEYS { $( $fname => $fkey ),+ });
+        $crate::__serde_impl_fields!($name($ktype) { $( $fname : $ftype => $fkey ),+ });
+        impl $crate::private::serde::Serialize for $name {
+            fn serialize<S: $crate::private::serde::Serializer>(&self, ser: S) -> ::std::result::Result<S::Ok, S::Error> {
+                use $crate::private::serde::ser::SerializeMap;
+                let readable = ser.is_human_readable();
+                let mut state = ser.serialize_map(Some( 0 $( + usize::from(!$crate::__serde_impl_skipped!($ftype)) )+ ))?;
+                $(
+                    if !$crate::__serde_impl_skipped!($ftype) {
+                        if readable {
+                            state.serialize_entry(stringify!($fname), &$crate::__serde_impl_array!(wrap $ftype, &self.$fname))?;
+                        } else {
+                            state.serialize_entry(&$crate::private::key::<$ktype, _>(&$fkey),
+                                &$crate::__serde_impl_array!(wrap $ftype, &self.$fname))?;
+                        }
+                    }
+                )*
+                state.end()
+            }
+        }
+    };
+    // Serialize impl for struct $name($ktype, map_or_tuple) { $fname: $ftype }, written as map
+    ( $name:ident($ktype:ident, map_or_tuple) $($strict:ident)? { $($body:tt)* } ) => {
+        $crate::serde_impl_ser!($name($ktype) { $($body)* });
+    };
+    // Serialize impl for struct $name($ktype, tuple_or_map) { $fname: $ftype }, written as tuple, also with a
+    // single field
+    ( $name:ident($ktype:ident, tuple_or_map) $($strict:ident)? { $( $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
+        const _: () = assert!(!$crate::private::has_duplicate_keys(&[ $( stringify!($fkey) ),+ ]),
+            concat!("duplicate key in serde_impl!(", stringify!($name), ")"));
+        $crate::__serde_impl_keys!($name($ktype) FIELD_KEYS { $( $fname => $fkey ),+ });
+        $crate::__serde_impl_fields!($name($ktype) { $( $fname : $ftype => $fkey ),+ });
+        impl $crate::private::serde::Serialize for $name {
+            fn serialize<S: $crate::private::serde::Serializer>(&self, ser: S) -> ::std::result::Result<S::Ok, S::Error> {
+                use $crate::private::serde::ser::SerializeTuple;
+                let mut state = ser.serialize_tuple(0 $( + usize::from(!$crate::__serde_impl_skipped!($ftype)) )+)?;
+                $(
+                    if !$crate::__serde_impl_skipped!($ftype) {
+                        state.serialize_element(&$crate::__serde_impl_array!(wrap $ftype, &self.$fname))?;
+                    }
+                )+
+                state.end()
+            }
+        }
+    };
+    // Reserved keys are never written, they only need to be checked
+    ( $name:ident($ktype:ident $($flag:tt)?) $($strict:ident)?
+      { reserved $($rkey:expr),+ ; $( $(#[$fattr:ident $(($pred:expr))?])? $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
+        $crate::__serde_impl_reserved!($name [ $($rkey),+ ] [ $($fkey),+ ]);
+        $crate::serde_impl_ser!($name($ktype $($flag)?) {
+            $( $(#[$fattr $(($pred))?])? $fname : $ftype => $fkey ),+
+        });
+    };
+    // Strict handling of unknown keys only affects deserialization
+    ( $name:ident($ktype:ident $($flag:tt)?) strict { $($body:tt)* } ) => {
+        $crate::serde_impl_ser!($name($ktype $($flag)?) { $($body)* });
+    };
+    // The context only affects deserialization
+    ( $name:ident($ktype:ident $($flag:tt)?) context($ctx:ty) $($strict:ident)?
+      { $( $(#[$fattr:ident])? $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
+        $crate::serde_impl_ser!($name($ktype $($flag)?) { $( $fname : $ftype => $fkey ),+ });
+    };
+    // Serialize impl for struct $name<$lt>($ktype) { $fname: $ftype } as map, the fields can borrow
+    ( $name:ident<$lt:lifetime>($ktype:ident) $($strict:ident)? { $( $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
+        const _: () = assert!(!$crate::private::has_duplicate_keys(&[ $( stringify!($fkey) ),+ ]),
+            concat!("duplicate key in serde_impl!(", stringify!($name), ")"));
+        $crate::__serde_impl_keys!($name<$lt>($ktype) FIELD_KEYS { $( $fname => $fkey ),+ });
+        $crate::__serde_impl_fields!($name<$lt>($ktype) { $( $fname : $ftype => $fkey ),+ });
+        impl<$lt> $crate::private::serde::Serialize for $name<$lt> {
+            fn serialize<S: $crate::private::serde::Serializer>(&self, ser: S) -> ::std::result::Result<S::Ok, S::Error> {
+                use $crate::private::serde::ser::SerializeMap;
+                #[allow(unused_imports)]
+                use $crate::private::{ProbeCowBytes, ProbeOwned, ProbeArray};
+                let mut state = ser.serialize_map(Some( 0 $( + usize::from(!$crate::__serde_impl_skipped!($ftype)) )+ ))?;
+                $(
+                    if !$crate::__serde_impl_skipped!($ftype) {
+                        state.serialize_entry(&$crate::private::key::<$ktype, _>(&$fkey),
+                            &(&$crate::private::borrow_probe::<$ftype>()).wrap(&self.$fname))?;
+                    }
+                )*
+                state.end()
+            }
+        }
+    };
+    // Serialize impl for struct $name<$lt> { $fname: $ftype } as tuple, the fields can borrow
+    ( $name:ident<$lt:lifetime> { $( $fname:ident : $ftype:ty ),+ } ) => {
+        $crate::__serde_impl_fields!($name<$lt> { $( $fname : $ftype ),+ });
+        impl<$lt> $crate::private::serde::Serialize for $name<$lt> {
+            fn serialize<S: $crate::private::serde::Serializer>(&self, ser: S) -> ::std::result::Result<S::Ok, S::Error> {
+                use $crate::private::serde::ser::SerializeTuple;
+                #[allow(unused_imports)]
+                use $crate::private::{ProbeCowBytes, ProbeOwned, ProbeArray};
+                if [ $( stringify!($fname) ),+ ].len() == 1 {
+                    return ($( (&$crate::private::borrow_probe::<$ftype>()).wrap(&self.$fname) ),*).serialize(ser)
+                }
+                let mut state = ser.serialize_tuple(0 $( + usize::from(!$crate::__serde_impl_skipped!($ftype)) )+)?;
+                $(
+                    if !$crate::__serde_impl_skipped!($ftype) {
+                        state.serialize_element(&(&$crate::private::borrow_probe::<$ftype>()).wrap(&self.$fname))?;
+                    }
+                )+
+                state.end()
+            }
+        }
+    };
+    // Serialize impl for struct $name($ktype?) { $fname: $ftype } as map
+    ( $name:ident($ktype:ident?) { $( $(#[is_default($pred:expr)])? $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
+        const _: () = assert!(!$crate::private::has_duplicate_keys(&[ $( stringify!($fkey) ),+ ]),
+            concat!("duplicate key in serde_impl!(", stringify!($name), ")"));
+        $crate::__serde_impl_keys!($name($ktype) FIELD_KEYS { $( $fname => $fkey ),+ });
+        $crate::__serde_impl_fields!($name($ktype) { $( $fname : $ftype => $fkey ),+ });
+        impl $crate::private::serde::Serialize for $name {
+            fn serialize<S: $crate::private::serde::Serializer>(&self, ser: S) -> ::std::result::Result<S::Ok, S::Error> {
+                use $crate::private::serde::ser::SerializeMap;
+                #[allow(unused_variables)]
+                let default: $name = Default::default();
+                let mut len = 0;
+                $(
+                    if !$crate::__serde_impl_skipped!($ftype)
+                        && !$crate::__serde_impl_is_default!(self.$fname, default.$fname $(, $pred)?) {
+                        len += 1;
+                    }
+                )*
+                let mut state = ser.serialize_map(Some(len))?;
+                $(
+                    if !$crate::__serde_impl_skipped!($ftype)
+                        && !$crate::__serde_impl_is_default!(self.$fname, default.$fname $(, $pred)?) {
+                        state.serialize_entry(&$crate::private::key::<$ktype, _>(&$fkey),
+                            &$crate::__serde_impl_array!(wrap $ftype, &self.$fname))?;
+                    }
+                )*
+                state.end()
+            }
+        }
+    };
+    // Serialize impl for struct $name($ktype!) { $fname: $ftype } as map
+    ( $name:ident($ktype:ident!) { $( $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
+        $crate::serde_impl_ser!($name($ktype) { $( $fname : $ftype => $fkey ),+ });
+    };
+    // Serialize impl for struct $name($ktype) { $fname: $ftype } as map
+    ( $name:ident($ktype:ident) { $( $(#[compute($func:expr)])? $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
+        const _: () = assert!(!$crate::private::has_duplicate_keys(&[ $( stringify!($fkey) ),+ ]),
+            concat!("duplicate key in serde_impl!(", stringify!($name), ")"));
+        $crate::__serde_impl_keys!($name($ktype) FIELD_KEYS { $( $fname => $fkey ),+ });
+        $crate::__serde_impl_fields!($name($ktype) { $( $fname : $ftype => $fkey ),+ });
+        impl $crate::private::serde::Serialize for $name {
+            fn serialize<S: $crate::private::serde::Serializer>(&self, ser: S) -> ::std::result::Result<S::Ok, S::Error> {
+                use $crate::private::serde::ser::SerializeMap;
+                let mut state = ser.serialize_map(Some( 0 $( + usize::from(!$crate::__serde_impl_skipped!($ftype)) )+ ))?;
+                $(
+                    if !$crate::__serde_impl_skipped!($ftype) {
+                        state.serialize_entry(&$crate::private::key::<$ktype, _>(&$fkey),
+                            &$crate::__serde_impl_array!(wrap $ftype, &$crate::__serde_impl_computed!(get [$($func)?] self.$fname)))?;
+                    }
+                )*
+                state.end()
+            }
+        }
+    };
+    // Serialize impl for struct $name($ktype) { $fname: $ftype } nested { $gkey => { $gfname: $gftype } } as map
+    ( $name:ident($ktype:ident) { $( $fname:ident : $ftype:ty => $fkey:expr ),* }
+      nested { $( $gkey:expr => { $( $gfname:ident : $gftype:ty => $gfkey:expr ),+ } ),+ } ) => {
+        const _: () = assert!(!$crate::private::has_duplicate_keys(&[ $( stringify!($fkey), )* $( stringify!($gkey) ),+ ]),
+            concat!("duplicate key in serde_impl!(", stringify!($name), ")"));
+        $(
+            const _: () = assert!(!$crate::private::has_duplicate_keys(&[ $( stringify!($gfkey) ),+ ]),
+                concat!("duplicate key in serde_impl!(", stringify!($name), ") group ", stringify!($gkey)));
+        )+
+        $crate::__serde_impl_keys!($name($ktype) FIELD_KEYS { $( $fname => $fkey, )* $( $( $gfname => $gfkey ),+ ),+ });
+        $crate::__serde_impl_fields!($name($ktype) { $( $fname : $ftype => $fkey, )* $( $( $gfname : $gftype => $gfkey ),+ ),+ });
+        impl $crate::private::serde::Serialize for $name {
+            fn serialize<S: $crate::private::serde::Serializer>(&self, ser: S) -> ::std::result::Result<S::Ok, S::Error> {
+                use $crate::private::serde::ser::SerializeMap;
+                let mut state = ser.serialize_map(Some(
+                    [ $( stringify!($gkey) ),+ ].len() $( + usize::from(!$crate::__serde_impl_skipped!($ftype)) )*
+                ))?;
+                $(
+                    if !$crate::__serde_impl_skipped!($ftype) {
+                        state.serialize_entry(&$crate::private::key::<$ktype, _>(&$fkey),
+                            &$crate::__serde_impl_array!(wrap $ftype, &self.$fname))?;
+                    }
+                )*
+                $(
+                    {
+                        struct _Group<'a>(&'a $name);
+                        impl<'a> $crate::private::serde::Serialize for _Group<'a> {
+                            fn serialize<S: $crate::private::serde::Serializer>(&self, ser: S) -> ::std::result::Result<S::Ok, S::Error> {
+                                let mut state = ser.serialize_map(Some( 0 $( + usize::from(!$crate::__serde_impl_skipped!($gftype)) )+ ))?;
+                                $(
+                                    if !$crate::__serde_impl_skipped!($gftype) {
+                                        state.serialize_entry(&$crate::private::key::<$ktype, _>(&$gfkey),
+                                            &$crate::__serde_impl_array!(wrap $gftype, &(self.0).$gfname))?;
+                                    }
+                                )+
+                                state.end()
+                            }
+                        }
+                        state.serialize_entry(&$crate::private::key::<$ktype, _>(&$gkey), &_Group(self))?;
+                    }
+                )+
+                state.end()
+            }
+        }
+    };
+    // Serialize impl for struct $name($ktype) version($vkey => $version) { $fname: $ftype } as map
+    ( $name:ident($ktype:ident) version($vkey:expr => $version:expr) { $( $fname:ident : $ftype:ty => $fkey:expr ),+ }
+      migrate { $( $mversion:expr => $migrate:expr ),* } ) => {
+        const _: () = assert!(!$crate::private::has_duplicate_keys(&[ stringify!($vkey), $( stringify!($fkey) ),+ ]),
+            concat!("duplicate key in serde_impl!(", stringify!($name), ")"));
+        $crate::__serde_impl_keys!($name($ktype) FIELD_KEYS { $( $fname => $fkey ),+ });
+        $crate::__serde_impl_fields!($name($ktype) { $( $fname : $ftype => $fkey ),+ });
+        impl $crate::private::serde::Serialize for $name {
+            fn serialize<S: $crate::private::serde::Serializer>(&self, ser: S) -> ::std::result::Result<S::Ok, S::Error> {
+                use $crate::private::serde::ser::SerializeMap;
+                let mut state = ser.serialize_map(Some( 1 $( + usize::from(!$crate::__serde_impl_skipped!($ftype)) )+ ))?;
+                state.serialize_entry(&$crate::private::key::<$ktype, _>(&$vkey), &($version as u64))?;
+                $(
+                    if !$crate::__serde_impl_skipped!($ftype) {
+                        state.serialize_entry(&$crate::private::key::<$ktype, _>(&$fkey),
+                            &$crate::__serde_impl_array!(wrap $ftype, &self.$fname))?;
+                    }
+                )*
+                state.end()
+            }
+        }
+    };
+    // Serialize impl for struct $name { $fname: $ftype, .. } as lenient tuple, written as sequence
+    // since it is read as one
+    ( $name:ident { $( $fname:ident : $ftype:ty ),+ , .. } ) => {
+        $crate::__serde_impl_fields!($name { $( $fname : $ftype ),+ });
+        impl $crate::private::serde::Serialize for $name {
+            fn serialize<S: $crate::private::serde::Serializer>(&self, ser: S) -> ::std::result::Result<S::Ok, S::Error> {
+                use $crate::private::serde::ser::SerializeSeq;
+                let mut state = ser.serialize_seq(Some(0 $( + usize::from(!$crate::__serde_impl_skipped!($ftype)) )+))?;
+                $(
+                    if !$crate::__serde_impl_skipped!($ftype) {
+                        state.serialize_element(&$crate::__serde_impl_array!(wrap $ftype, &self.$fname))?;
+                    }
+                )+
+                state.end()
+            }
+        }
+    };
+    // Serialize impl for struct $name { $fname: $ftype } as tuple
+    ( $name:ident { $( $fname:ident : $ftype:ty ),+ } ) => {
+        $crate::__serde_impl_fields!($name { $( $fname : $ftype ),+ });
+        impl $crate::private::serde::Serialize for $name {
+            fn serialize<S: $crate::private::serde::Serializer>(&self, ser: S) -> ::std::result::Result<S::Ok, S::Error> {
+                use $crate::private::serde::ser::SerializeTuple;
+                if [ $( stringify!($fname) ),+ ].len() == 1 {
+                    return ($( $crate::__serde_impl_array!(wrap $ftype, &self.$fname) ),*).serialize(ser)
+                }
+                let mut state = ser.serialize_tuple(0 $( + usize::from(!$crate::__serde_impl_skipped!($ftype)) )+)?;
+                $(
+                    if !$crate::__serde_impl_skipped!($ftype) {
+                        state.serialize_element(&$crate::__serde_impl_array!(wrap $ftype, &self.$fname))?;
+                    }
+                )+
+                state.end()
+            }
+        }
+    };
+    // Serialize impl for enum $name { $variant }
+    ( $name:ident($ktype:ident) { $( $variant:ident => $fkey:expr ),+ $(, _ => $fallback:ident)? } ) => {
+        const _: () = assert!(!$crate::private::has_duplicate_keys(&[ $( stringify!($fkey) ),+ ]),
+            concat!("duplicate discriminator in serde_impl!(", stringify!($name), ")"));
+        $crate::__serde_impl_keys!($name($ktype) VARIANT_KEYS { $( $variant => $fkey ),+ });
+        impl $crate::KeyType for $name {
+            type Const = $name;
+        }
+        impl $crate::private::serde::Serialize for $name {
+            fn serialize<S: $crate::private::serde::Serializer>(&self, ser: S) -> ::std::result::Result<S::Ok, S::Error> {
+                match self {
+                    $( &$name::$variant => $crate::private::key::<$ktype, _>(&$fkey).serialize(ser), )*
+                    $( &$name::$fallback => Err($crate::private::fallback_variant(stringify!($name), stringify!($fallback))) )?
+                }
+            }
+        }
+    };
+    // Serialize impl for enum $name { $variant($ftype) } as single-entry map
+    ( $name:ident($ktype:ident) map { $( $variant:ident($ftype:ty) => $fkey:expr ),* } ) => {
+        const _: () = assert!(!$crate::private::has_duplicate_keys(&[ $( stringify!($fkey) ),+ ]),
+            concat!("duplicate discriminator in serde_impl!(", stringify!($name), ")"));
+        $crate::__serde_impl_keys!($name($ktype) VARIANT_KEYS { $( $variant => $fkey ),* });
+        impl $crate::private::serde::Serialize for $name {
+            fn serialize<S: $crate::private::serde::Serializer>(&self, ser: S) -> ::std::result::Result<S::Ok, S::Error> {
+                use $crate::private::serde::ser::SerializeMap;
+                let mut state = ser.serialize_map(Some(1))?;
+                match self {
+                    $( &$name::$variant(ref obj) => state.serialize_entry(&$crate::private::key::<$ktype, _>(&$fkey), obj)? ),*
+                }
+                state.end()
+            }
+        }
+    };
+    // Serialize impl for enum $name { $variant($ftype) }, payloads that are `None` or `()` are left out
+    // for variants marked as `#[elide]`
+    ( $name:ident($ktype:ident) { $( $(#[$attr:ident])? $variant:ident($ftype:ty $(= $default:expr)?) => $fkey:expr ),* } ) => {
+        const _: () = assert!(!$crate::private::has_duplicate_keys(&[ $( stringify!($fkey) ),+ ]),
+            concat!("duplicate discriminator in serde_impl!(", stringify!($name), ")"));
+        $crate::__serde_impl_keys!($name($ktype) VARIANT_KEYS { $( $variant => $fkey ),* });
+        impl $crate::private::serde::Serialize for $name {
+            #[inline]
+            fn serialize<S: $crate::private::serde::Serializer>(&self, ser: S) -> ::std::result::Result<S::Ok, S::Error> {
+                match self {
+                    $( &$name::$variant(ref obj) => if $crate::__serde_impl_bare!(@elide [$(#[$attr])?])
+                        && $crate::__serde_impl_payload_default!(@elided $ftype, obj) {
+                        ($crate::private::key::<$ktype, _>(&$fkey),).serialize(ser)
+                    } else {
+                        ($crate::private::key::<$ktype, _>(&$fkey), obj).serialize(ser)
+                    } ),*
+                }
+            }
+        }
+    };
+    // Serialize impl for struct $name($ktype) flags { $fname => $mask } as bitmask
+    ( $name:ident($ktype:ident) flags { $( $fname:ident => $mask:expr ),+ $(, .. => $unknown:ident)? } ) => {
+        const _: () = {
+            let masks: &[$ktype] = &[ $( $mask ),+ ];
+            let mut i = 0;
+            while i < masks.len() {
+                assert!(masks[i] != 0, concat!("empty bit mask in serde_impl!(", stringify!($name), ")"));
+                let mut j = i + 1;
+                while j < masks.len() {
+                    assert!(masks[i] & masks[j] == 0, concat!("overlapping bit masks in serde_impl!(", stringify!($name), ")"));
+                    j += 1;
+                }
+                i += 1;
+            }
+        };
+        $crate::__serde_impl_keys!($name($ktype) FIELD_KEYS { $( $fname => $mask ),+ });
+        impl $crate::private::serde::Serialize for $name {
+            fn serialize<S: $crate::private::serde::Serializer>(&self, ser: S) -> ::std::result::Result<S::Ok, S::Error> {
+                // Only used to mask the unknown bits
+                #[allow(dead_code)]
+                const ALL: $ktype = 0 $( | $mask )+;
+                let mut bits: $ktype = 0;
+                $(
+                    if self.$fname {
+                        bits |= $mask;
+                    }
+                )+
+                $( bits |= self.$unknown & !ALL; )?
+                $crate::private::serde::Serialize::serialize(&bits, ser)
+            }
+        }
+    };
+    // Enum $name { $variant } with discriminant values as keys
+    ( $name:ident($ktype:ident) discriminant { $( $variant:ident ),+ $(, _ => $fallback:ident)? } ) => {
+        $crate::serde_impl_ser!($name($ktype) { $( $variant => $name::$variant as $ktype ),+ $(, _ => $fallback)? });
+    };
+    // Automatic keys for struct $name($ktype) { $fname: $ftype } as map
+    ( $name:ident($ktype:ident $($flag:tt)?) $(@$case:literal)? $($strict:ident)?
+      { $( $(#[is_default($pred:expr)])? $fname:ident : $ftype:ty ),+ } ) => {
+        $crate::__serde_impl_auto!(serde_impl_ser [$name($ktype $($flag)?) $($strict)?] $ktype
+            [ $( ($fname [$(#[is_default($pred)])?] [: $ftype]) )+ ] [] $($case)?);
+    };
+    // Automatic keys for enum $name { $variant }
+    ( $name:ident($ktype:ident) $(@$case:literal)? { $( $variant:ident ),+ $(, _ => $fallback:ident)? } ) => {
+        $crate::__serde_impl_auto!(serde_impl_ser [$name($ktype)] $ktype
+            [ $( ($variant [] []) )+ ] [$(, _ => $fallback)?] $($case)?);
+    };
+    // Automatic keys for enum $name { $variant($ftype) }
+    ( $name:ident($ktype:ident) $(@$case:literal)? $($map:ident)? { $( $(#[$attr:ident])? $variant:ident($ftype:ty $(= $default:expr)?) ),+ } ) => {
+        $crate::__serde_impl_auto!(serde_impl_ser [$name($ktype) $($map)?] $ktype
+            [ $( ($variant [$(#[$attr])?] [($ftype $(= $default)?)]) )+ ] [] $($case)?);
+    };
+    ( $name:ident($ktype:ident $($flag:tt)?) @$case:literal $($rest:tt)* ) => {
+        compile_error!(concat!("rename_all in serde_impl!(", stringify!($name), ") requires automatic keys"));
+    };
+    // Multiple definitions separated by `;`
+    ( $($tt:tt)+ ) => {
+        $crate::serde_impl_ser!(@split [] [] [] $($tt)+);
+    };
+);
+
+/// Macro for implementing only deserialization via serde
+///
+/// This macro accepts the same syntax as [`serde_impl!`](macro.serde_impl!.html) but only
+/// generates the `Deserialize` implementation.
+#[macro_export]
+macro_rules! serde_impl_de(
+    // Multiple definitions sharing a key type: key $ktype; $def; ...
+    ( key $ktype:ident; $($tt:tt)* ) => {
+        $crate::serde_impl_de!(@split [$ktype] [;] [] $($tt)*);
+    };
+    // Structs that must list all of their fields
+    ( exhaustive $($rest:tt)* ) => {
+        $crate::__serde_impl_exhaustive!([] $($rest)*);
+        $crate::serde_impl_de!($($rest)*);
+    };
+    // Types referenced by path are imported into a block, the arms below need a plain name
+    ( $first:ident :: $($rest:tt)* ) => {
+        $crate::serde_impl_de!(@path [] [$first] $($rest)*);
+    };
+    ( @path [$($def:tt)*] [$($path:ident)::+] $next:ident :: $($rest:tt)* ) => {
+        $crate::serde_impl_de!(@path [$($def)*] [$($path)::+ :: $next] $($rest)*);
+    };
+    ( @path [$($def:tt)*] [$($path:ident)::+] $name:ident $($rest:tt)* ) => {
+        const _: () = {
+            use $($path::)+$name;
+            $crate::serde_impl_de!($($def)* $name $($rest)*);
+        };
+    };
+    // Split multiple definitions at `;`
+    ( @split [$($ktype:ident)?] [$($seen:tt)?] [$($acc:tt)*] ; $($rest:tt)* ) => {
+        $crate::serde_impl_de!(@def [$($ktype)?] $($acc)*);
+        $crate::serde_impl_de!(@split [$($ktype)?] [;] [] $($rest)*);
+    };
+    ( @split [$($ktype:ident)?] [$($seen:tt)?] [$($acc:tt)*] $next:tt $($rest:tt)* ) => {
+        $crate::serde_impl_de!(@split [$($ktype)?] [$($seen)?] [$($acc)* $next] $($rest)*);
+    };
+    ( @split [$($ktype:ident)?] [;] [] ) => {};
+    ( @split [$($ktype:ident)?] [;] [$($acc:tt)+] ) => {
+        $crate::serde_impl_de!(@def [$($ktype)?] $($acc)+);
+    };
+    ( @split [$($ktype:ident)?] [] [$($acc:tt)*] ) => {
+        compile_error!(concat!("invalid syntax for serde_impl!: ", stringify!($($acc)*)));
+    };
+    ( @def [$($ktype:ident)?] exhaustive $($rest:tt)* ) => {
+        $crate::__serde_impl_exhaustive!([] $($rest)*);
+        $crate::serde_impl_de!(@def [$($ktype)?] $($rest)*);
+    };
+    ( @def [$($ktype:ident)?] $first:ident :: $($rest:tt)* ) => {
+        $crate::serde_impl_de!(@path [@def [$($ktype)?]] [$first] $($rest)*);
+    };
+    // Substitute the shared key type for `_`
+    ( @def [$ktype:ident] $name:ident(_, rename_all = $case:literal) $($rest:tt)* ) => {
+        $crate::serde_impl_de!($name($ktype) @$case $($rest)*);
+    };
+    ( @def [$ktype:ident] $name:ident(_ $flag:tt, rename_all = $case:literal) $($rest:tt)* ) => {
+        $crate::serde_impl_de!($name($ktype $flag) @$case $($rest)*);
+    };
+    ( @def [$ktype:ident] $name:ident(_, $mode:ident) $($rest:tt)* ) => {
+        $crate::serde_impl_de!($name($ktype, $mode) $($rest)*);
+    };
+    ( @def [$ktype:ident] $name:ident(_ $($flag:tt)?) $($rest:tt)* ) => {
+        $crate::serde_impl_de!($name($ktype $($flag)?) $($rest)*);
+    };
+    ( @def [$($ktype:ident)?] $($rest:tt)* ) => {
+        $crate::serde_impl_de!($($rest)*);
+    };
+    // Automatic keys converted to another case, marked with `@$case` for the arms below
+    ( $name:ident($ktype:ident, rename_all = $case:literal) $($rest:tt)* ) => {
+        $crate::serde_impl_de!($name($ktype) @$case $($rest)*);
+    };
+    ( $name:ident($ktype:ident $flag:tt, rename_all = $case:literal) $($rest:tt)* ) => {
+        $crate::serde_impl_de!($name($ktype $flag) @$case $($rest)*);
+    };
+    // Deserialize impl for struct $name($ktype, readable) { $fname: $ftype } as map, the names of the fields
+    // and the keys are accepted
+    ( $name:ident($ktype:ident, readable) { $( $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
+        $crate::serde_impl_de!(@readable [skip] $name($ktype) { $( $fname : $ftype => $fkey ),+ });
+    };
+    ( $name:ident($ktype:ident, readable) strict { $( $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
+        $crate::serde_impl_de!(@readable [deny] $name($ktype) { $( $fname : $ftype => $fkey ),+ });
+    };
+    ( @readable [$unknown:ident] $name:ident($ktype:ident) { $( $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
+        impl<'a> $crate::private::serde::Deserialize<'a> for $name {
+            fn deserialize<D: $crate::private::serde::Deserializer<'a>>(de: D) -> ::std::result::Result<Self, D::Error> {
+                struct _Deserializer;
+                impl<'a> $crate::private::serde::de::Visitor<'a> for _Deserializer {
+                    type Value = $name;
+                    fn expecting(&self, formatter: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                        write!(formatter, "a map encoding {}", stringify!($name))
+                    }
+
+                    fn visit_map<V: $crate::private::serde::de::MapAccess<'a>>(self, mut visitor: V) -> ::std::result::Result<Self::Value, V::Error> {
+                        let mut obj: $name = Default::default();
+                        $(
+                            let mut $fname = $crate::__serde_impl_skipped!($ftype);
+                        )*
+                        while let Some(key) = visitor.next_key::<$crate::private::ReadableKey<$ktype>>()
+                            .map_err(|err| $crate::private::type_error(err, stringify!($name)))? {
+                            $(
+                                if key.matches(&$fkey, stringify!($fname)) {
+                                    obj.$fname = visitor.next_value_seed($crate::__serde_impl_array!(seed $ftype))
+                                        .map_err(|err| $crate::private::field_error(
+                                            err, stringify!($name), stringify!($fname), stringify!($fkey)
+                                        ))?;
+                                    $fname = true;
+                                    continue
+                                }
+                            )*
+                            $crate::__serde_impl_unknown_key!($unknown, visitor, key, $name, [ $( $fkey ),+ ]);
+                        }
+                        $crate::__serde_impl_report_defaults!($name, [ $( $fname => $fkey ),+ ]);
+                        Ok(obj)
+                    }
+                }
+                de.deserialize_map(_Deserializer)
+            }
+        }
+    };
+    // Deserialize impl for struct $name($ktype, map_or_tuple) { $fname: $ftype }, maps and tuples are accepted
+    ( $name:ident($ktype:ident, map_or_tuple) $($rest:tt)* ) => {
+        $crate::serde_impl_de!(@dual $name($ktype) $($rest)*);
+    };
+    ( $name:ident($ktype:ident, tuple_or_map) $($rest:tt)* ) => {
+        $crate::serde_impl_de!(@dual $name($ktype) $($rest)*);
+    };
+    ( @dual $name:ident($ktype:ident) { $( $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
+        $crate::serde_impl_de!(@dual [skip] $name($ktype) { $( $fname : $ftype => $fkey ),+ });
+    };
+    ( @dual $name:ident($ktype:ident) strict { $( $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
+        $crate::serde_impl_de!(@dual [deny] $name($ktype) { $( $fname : $ftype => $fkey ),+ });
+    };
+    ( @dual [$unknown:ident] $name:ident($ktype:ident) { $( $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
+        impl<'a> $crate::private::serde::Deserialize<'a> for $name {
+            fn deserialize<D: $crate::private::serde::Deserializer<'a>>(de: D) -> ::std::result::Result<Self, D::Error> {
+                struct _Deserializer;
+                impl<'a> $crate::private::serde::de::Visitor<'a> for _Deserializer {
+                    type Value = $name;
+                    fn expecting(&self, formatter: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                        write!(formatter, "a map or tuple encoding {}", stringify!($name))
+                    }
+
+                    fn visit_seq<V: $crate::private::serde::de::SeqAccess<'a>>(self, mut visitor: V) -> ::std::result::Result<Self::Value, V::Error> {
+                        let mut obj: $name = Default::default();
+                        let mut len = 0;
+                        $(
+                            if !$crate::__serde_impl_skipped!($ftype) {
+                                len += 1;
+                                obj.$fname = visitor.next_element_seed($crate::__serde_impl_array!(seed $ftype))
+                                    .map_err(|err| $crate::private::field_error(
+                                        err, stringify!($name), stringify!($fname), stringify!($fkey)
+                                    ))?
+                                    .ok_or_else(|| $crate::private::serde::de::Error::invalid_length(len - 1, &self))?;
+                            }
+                        )+
+                        if visitor.next_element::<$crate::private::serde::de::IgnoredAny>()?.is_some() {
+                            return Err($crate::private::serde::de::Error::invalid_length(len + 1, &self));
+                        }
+                        Ok(obj)
+                    }
+
+                    fn visit_map<V: $crate::private::serde::de::MapAccess<'a>>(self, mut visitor: V) -> ::std::result::Result<Self::Value, V::Error> {
+                        let mut obj: $name = Default::default();
+                        $(
+                            let mut $fname = $crate::__serde_impl_skipped!($ftype);
+                        )*
+                        while let Some(key) = visitor.next_key::<$ktype>()
+                            .map_err(|err| $crate::private::type_error(err, stringify!($name)))? {
+                            $(
+                                if $crate::private::KeyLiteral::<$ktype>::matches(&$fkey, &key) {
+                                    obj.$fname = visitor.next_value_seed($crate::__serde_impl_array!(seed $ftype))
+                                        .map_err(|err| $crate::private::field_error(
+                                            err, stringify!($name), stringify!($fname), stringify!($fkey)
+                                        ))?;
+                                    $fname = true;
+                                    continue
+                                }
+                            )*
+                            $crate::__serde_impl_unknown_key!($unknown, visitor, key, $name, [ $( $fkey ),+ ]);
+                        }
+                        $crate::__serde_impl_report_defaults!($name, [ $( $fname => $fkey ),+ ]);
+                        Ok(obj)
+                    }
+                }
+                de.deserialize_any(_Deserializer)
+            }
+        }
+    };
+    // DeserializeWith impl for struct $name($ktype) context($ctx) { $fname: $ftype } as map
+    ( $name:ident($ktype:ident!) context($ctx:ty) $($rest:tt)* ) => {
+        compile_error!(concat!("serde_impl!(", stringify!($name), "): required fields can not be used with a context"));
+    };
+    ( $name:ident($ktype:ident $($flag:tt)?) context($ctx:ty)
+      { $( $(#[$fattr:ident])? $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
+        $crate::serde_impl_de!(@context [skip] $name($ktype) $ctx { $( $(#[$fattr])? $fname : $ftype => $fkey ),+ });
+    };
+    ( $name:ident($ktype:ident $($flag:tt)?) context($ctx:ty) strict
+      { $( $(#[$fattr:ident])? $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
+        $crate::serde_impl_de!(@context [deny] $name($ktype) $ctx { $( $(#[$fattr])? $fname : $ftype => $fkey ),+ });
+    };
+    ( @context [$unknown:ident] $name:ident($ktype:ident) $ctx:ty
+      { $( $(#[$fattr:ident])? $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
+        const _: () = assert!(!$crate::private::has_duplicate_keys(&[ $( stringify!($fkey) ),+ ]),
+            concat!("duplicate key in serde_impl!(", stringify!($name), ")"));
+        impl<'a> $crate::DeserializeWith<'a, $ctx> for $name {
+            fn deserialize_with<D: $crate::private::serde::Deserializer<'a>>(context: &mut $ctx, de: D) -> ::std::result::Result<Self, D::Error> {
+                struct _Deserializer<'c>(&'c mut $ctx);
+                impl<'a, 'c> $crate::private::serde::de::Visitor<'a> for _Deserializer<'c> {
+                    type Value = $name;
+                    fn expecting(&self, formatter: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                        write!(formatter, "a map encoding {}", stringify!($name))
+                    }
+
+                    fn visit_map<V: $crate::private::serde::de::MapAccess<'a>>(self, mut visitor: V) -> ::std::result::Result<Self::Value, V::Error> {
+                        #[allow(unused_variables)]
+                        let context = self.0;
+                        let mut obj: $name = Default::default();
+                        $(
+                            let mut $fname = $crate::__serde_impl_skipped!($ftype);
+                        )*
+                        while let Some(key) = visitor.next_key::<$ktype>()
+                            .map_err(|err| $crate::private::type_error(err, stringify!($name)))? {
+                            $(
+                                if $crate::private::KeyLiteral::<$ktype>::matches(&$fkey, &key) {
+                                    obj.$fname = $crate::__serde_impl_context_value!([$($fattr)?] $ftype, visitor, context)
+                                        .map_err(|err| $crate::private::field_error(
+                                            err, stringify!($name), stringify!($fname), stringify!($fkey)
+                                        ))?;
+                                    $fname = true;
+                                    continue
+                                }
+                            )*
+                            $crate::__serde_impl_unknown_key!($unknown, visitor, key, $name, [ $( $fkey ),+ ]);
+                        }
+                        $crate::__serde_impl_report_defaults!($name, [ $( $fname => $fkey ),+ ]);
+                        Ok(obj)
+                    }
+                }
+                de.deserialize_map(_Deserializer(context))
+            }
+        }
+    };
+    // Reserved keys of struct $name($ktype) { reserved $rkey; $fname: $ftype } as map, their values are skipped
+    ( $name:ident($ktype:ident $($flag:tt)?) $($strict:ident)?
+      { reserved $($rkey:expr),+ ; $( $(#[$fattr:ident $(($pred:expr))?])? $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
+        $crate::__serde_impl_reserved!($name [ $($rkey),+ ] [ $($fkey),+ ]);
+        $crate::serde_impl_de!(@reserved [ $($rkey),+ ] $name($ktype $($flag)?) $($strict)? {
+            $( $(#[$fattr $(($pred))?])? $fname : $ftype => $fkey ),+
+        });
+    };
+    ( @reserved [ $($rkey:expr),+ ] $name:ident($ktype:ident)
+      { $( $(#[compute($func:expr)])? $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
+        $crate::serde_impl_de!(@map [skip; $($rkey),+] $name($ktype) { $( $(#[compute($func)])? $fname : $ftype => $fkey ),+ });
+    };
+    ( @reserved [ $($rkey:expr),+ ] $name:ident($ktype:ident) strict
+      { $( $(#[compute($func:expr)])? $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
+        $crate::serde_impl_de!(@map [deny; $($rkey),+] $name($ktype) { $( $(#[compute($func)])? $fname : $ftype => $fkey ),+ });
+    };
+    ( @reserved [ $($rkey:expr),+ ] $name:ident($ktype:ident?) $($strict:ident)?
+      { $( $(#[is_default($pred:expr)])? $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
+        $crate::serde_impl_de!(@reserved [ $($rkey),+ ] $name($ktype) $($strict)? { $( $fname : $ftype => $fkey ),+ });
+    };
+    ( @reserved [ $($rkey:expr),+ ] $name:ident($ktype:ident!) { $( $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
+        $crate::serde_impl_de!(@required [skip; $($rkey),+] $name($ktype) { $( $fname : $ftype => $fkey ),+ });
+    };
+    ( @reserved [ $($rkey:expr),+ ] $name:ident($ktype:ident!) strict { $( $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
+        $crate::serde_impl_de!(@required [deny; $($rkey),+] $name($ktype) { $( $fname : $ftype => $fkey ),+ });
+    };
+    // Deserialize impl for struct $name($ktype?) { $fname: $ftype } as map
+    ( $name:ident($ktype:ident?) $($strict:ident)? { $( $(#[is_default($pred:expr)])? $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
+        $crate::serde_impl_de!($name($ktype) $($strict)? { $( $fname : $ftype => $fkey ),+ });
+    };
+    // Deserialize impl for struct $name($ktype!) { $fname: $ftype } as map
+    ( $name:ident($ktype:ident!) { $( $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
+        $crate::serde_impl_de!(@required [skip] $name($ktype) { $( $fname : $ftype => $fkey ),+ });
+    };
+    ( $name:ident($ktype:ident!) strict { $( $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
+        $crate::serde_impl_de!(@required [deny] $name($ktype) { $( $fname : $ftype => $fkey ),+ });
+    };
+    ( @required [$unknown:ident $(; $($rkey:expr),+)?] $name:ident($ktype:ident) { $( $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
+        const _: () = assert!(!$crate::private::has_duplicate_keys(&[ $( stringify!($fkey) ),+ ]),
+            concat!("duplicate key in serde_impl!(", stringify!($name), ")"));
+        impl<'a> $crate::private::serde::Deserialize<'a> for $name {
+            fn deserialize<D: $crate::private::serde::Deserializer<'a>>(de: D) -> ::std::result::Result<Self, D::Error> {
+                struct _Deserializer;
+                impl<'a> $crate::private::serde::de::Visitor<'a> for _Deserializer {
+                    type Value = $name;
+                    fn expecting(&self, formatter: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                        write!(formatter, "a map encoding {}", stringify!($name))
+                    }
+
+                    fn visit_map<V: $crate::private::serde::de::MapAccess<'a>>(self, mut visitor: V) -> ::std::result::Result<Self::Value, V::Error> {
+                        $(
+                            let mut $fname: Option<$ftype> = None;
+                        )*
+                        while let Some(key) = visitor.next_key::<$ktype>()
+                            .map_err(|err| $crate::private::type_error(err, stringify!($name)))? {
+                            $(
+                                if $crate::private::KeyLiteral::<$ktype>::matches(&$fkey, &key) {
+                                    $fname = Some(visitor.next_value_seed($crate::__serde_impl_array!(seed $ftype))
+                                        .map_err(|err| $crate::private::field_error(
+                                            err, stringify!($name), stringify!($fname), stringify!($fkey)
+                                        ))?);
+                                    continue
+                                }
+                            )*
+                            $($(
+                                if $crate::private::KeyLiteral::<$ktype>::matches(&$rkey, &key) {
+                                    let _skip: $crate::Ignore = visitor.next_value()?;
+                                    continue
+                                }
+                            )+)?
+                            $crate::__serde_impl_unknown_key!($unknown, visitor, key, $name, [ $( $fkey ),+ ]);
+                        }
+                        Ok($name {
+                            $(
+                                $fname: match $fname {
+                                    Some(val) => val,
+                                    None => $crate::private::missing_field($crate::__serde_impl_array!(seed $ftype),
+                                        stringify!($name), stringify!($fname), stringify!($fkey)
+                                    )?
+                                }
+                            ),*
+                        })
+                    }
+                }
+                de.deserialize_map(_Deserializer)
+            }
+        }
+    };
+    // Deserialize impl for struct $name<$lt>($ktype) { $fname: $ftype } as map, the fields can borrow
+    ( $name:ident<$lt:lifetime>($ktype:ident) { $( $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
+        $crate::serde_impl_de!(@borrowed_map [skip] $name<$lt>($ktype) { $( $fname : $ftype => $fkey ),+ });
+    };
+    ( $name:ident<$lt:lifetime>($ktype:ident) strict { $( $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
+        $crate::serde_impl_de!(@borrowed_map [deny] $name<$lt>($ktype) { $( $fname : $ftype => $fkey ),+ });
+    };
+    ( @borrowed_map [$unknown:ident] $name:ident<$lt:lifetime>($ktype:ident) { $( $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
+        const _: () = assert!(!$crate::private::has_duplicate_keys(&[ $( stringify!($fkey) ),+ ]),
+            concat!("duplicate key in serde_impl!(", stringify!($name), ")"));
+        impl<'de: $lt, $lt> $crate::private::serde::Deserialize<'de> for $name<$lt> {
+            fn deserialize<D: $crate::private::serde::Deserializer<'de>>(de: D) -> ::std::result::Result<Self, D::Error> {
+                struct _Deserializer<$lt>(::std::marker::PhantomData<&$lt ()>);
+                impl<'de: $lt, $lt> $crate::private::serde::de::Visitor<'de> for _Deserializer<$lt> {
+                    type Value = $name<$lt>;
+                    fn expecting(&self, formatter: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                        write!(formatter, "a map encoding {}", stringify!($name))
+                    }
+
+                    fn visit_map<V: $crate::private::serde::de::MapAccess<'de>>(self, mut visitor: V) -> ::std::result::Result<Self::Value, V::Error> {
+                        #[allow(unused_imports)]
+                        use $crate::private::{ProbeCowStr, ProbeCowBytes, ProbeOwned, ProbeArray};
+                        let mut obj: $name<$lt> = Default::default();
+                        $(
+                            let mut $fname = $crate::__serde_impl_skipped!($ftype);
+                        )*
+                        while let Some(key) = visitor.next_key::<$ktype>()
+                            .map_err(|err| $crate::private::type_error(err, stringify!($name)))? {
+                            $(
+                                if $crate::private::KeyLiteral::<$ktype>::matches(&$fkey, &key) {
+                                    obj.$fname = visitor.next_value_seed((&$crate::private::borrow_probe::<$ftype>()).seed())
+                                        .map_err(|err| $crate::private::field_error(
+                                            err, stringify!($name), stringify!($fname), stringify!($fkey)
+                                        ))?;
+                                    $fname = true;
+                                    continue
+                                }
+                            )*
+                            $crate::__serde_impl_unknown_key!($unknown, visitor, key, $name, [ $( $fkey ),+ ]);
+                        }
+                        $crate::__serde_impl_report_defaults!($name, [ $( $fname => $fkey ),+ ]);
+                        Ok(obj)
+                    }
+                }
+                de.deserialize_map(_Deserializer(::std::marker::PhantomData))
+            }
+        }
+    };
+    // Deserialize impl for struct $name<$lt> { $fname: $ftype } as tuple with a single field, the field can borrow
+    ( $name:ident<$lt:lifetime> { $fname:ident : $ftype:ty } ) => {
+        impl<'de: $lt, $lt> $crate::private::serde::Deserialize<'de> for $name<$lt> {
+            #[inline]
+            fn deserialize<D: $crate::private::serde::Deserializer<'de>>(de: D) -> ::std::result::Result<Self, D::Error> {
+                #[allow(unused_imports)]
+                use $crate::private::serde::de::DeserializeSeed;
+                #[allow(unused_imports)]
+                use $crate::private::{ProbeCowStr, ProbeCowBytes, ProbeOwned, ProbeArray};
+                (&$crate::private::borrow_probe::<$ftype>()).seed().deserialize(de)
+                    .map(|$fname| $name { $fname })
+                    .map_err(|err| $crate::private::type_error(err, stringify!($name)))
+            }
+        }
+    };
+    // Deserialize impl for struct $name<$lt> { $fname: $ftype } as tuple, the fields can borrow
+    ( $name:ident<$lt:lifetime> { $( $fname:ident : $ftype:ty ),+ } ) => {
+        impl<'de: $lt, $lt> $crate::private::serde::Deserialize<'de> for $name<$lt> {
+            fn deserialize<D: $crate::private::serde::Deserializer<'de>>(de: D) -> ::std::result::Result<Self, D::Error> {
+                struct _Deserializer<$lt>(::std::marker::PhantomData<&$lt ()>);
+                impl<'de: $lt, $lt> $crate::private::serde::de::Visitor<'de> for _Deserializer<$lt> {
+                    type Value = $name<$lt>;
+                    fn expecting(&self, formatter: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                        write!(formatter, "a tuple encoding {}", stringify!($name))
+                    }
+
+                    fn visit_seq<V: $crate::private::serde::de::SeqAccess<'de>>(self, mut visitor: V) -> ::std::result::Result<Self::Value, V::Error> {
+                        #[allow(unused_imports)]
+                        use $crate::private::{ProbeCowStr, ProbeCowBytes, ProbeOwned, ProbeArray, ProbeSkip, ProbeKeep};
+                        let mut len = 0;
+                        $(
+                            let probe = $crate::private::skip_probe::<$ftype>();
+                            let $fname: $ftype = if (&probe).is_skipped() {
+                                (&probe).skipped_value()
+                            } else {
+                                len += 1;
+                                visitor.next_element_seed((&$crate::private::borrow_probe::<$ftype>()).seed())?
+                                    .ok_or_else(|| $crate::private::serde::de::Error::invalid_length(len - 1, &self))?
+                            };
+                        )*
+                        Ok($name { $( $fname ),* })
+                    }
+                }
+                let len = 0 $( + usize::from(!$crate::__serde_impl_skipped!($ftype)) )+;
+                de.deserialize_tuple(len, _Deserializer(::std::marker::PhantomData))
+                    .map_err(|err| $crate::private::type_error(err, stringify!($name)))
+            }
+        }
+    };
+    // Deserialize impl for struct $name($ktype) { $fname: $ftype } as map
+    ( $name:ident($ktype:ident) { $( $(#[compute($func:expr)])? $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
+        $crate::serde_impl_de!(@map [skip] $name($ktype) { $( $(#[compute($func)])? $fname : $ftype => $fkey ),+ });
+    };
+    ( $name:ident($ktype:ident) strict { $( $(#[compute($func:expr)])? $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
+        $crate::serde_impl_de!(@map [deny] $name($ktype) { $( $(#[compute($func)])? $fname : $ftype => $fkey ),+ });
+    };
+    ( @map [$unknown:ident $(; $($rkey:expr),+)?] $name:ident($ktype:ident)
+      { $( $(#[compute($func:expr)])? $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
+        const _: () = assert!(!$crate::private::has_duplicate_keys(&[ $( stringify!($fkey) ),+ ]),
+            concat!("duplicate key in serde_impl!(", stringify!($name), ")"));
+        impl<'a> $crate::private::serde::Deserialize<'a> for $name {
+            fn deserialize<D: $crate::private::serde::Deserializer<'a>>(de: D) -> ::std::result::Result<Self, D::Error> {
+                struct _Deserializer;
+                impl<'a> $crate::private::serde::de::Visitor<'a> for _Deserializer {
+                    type Value = $name;
+                    fn expecting(&self, formatter: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                        write!(formatter, "a map encoding {}", stringify!($name))
+                    }
+
+                    fn visit_map<V: $crate::private::serde::de::MapAccess<'a>>(self, mut visitor: V) -> ::std::result::Result<Self::Value, V::Error> {
+                        let mut obj: $name = Default::default();
+                        $(
+                            let mut $fname = $crate::__serde_impl_skipped!($ftype)
+                                || $crate::__serde_impl_computed!(is [$($func)?]);
+                        )*
+                        while let Some(key) = visitor.next_key::<$ktype>()
+                            .map_err(|err| $crate::private::type_error(err, stringify!($name)))? {
+                            $(
+                                if $crate::private::KeyLiteral::<$ktype>::matches(&$fkey, &key) {
+                                    $crate::__serde_impl_computed!(set [$($func)?] visitor, obj.$fname =
+                                        visitor.next_value_seed($crate::__serde_impl_array!(seed $ftype))
+                                            .map_err(|err| $crate::private::field_error(
+                                                err, stringify!($name), stringify!($fname), stringify!($fkey)
+                                            ))?);
+                                    $fname = true;
+                                    continue
+                                }
+                            )*
+                            $($(
+                                if $crate::private::KeyLiteral::<$ktype>::matches(&$rkey, &key) {
+                                    let _skip: $crate::Ignore = visitor.next_value()?;
+                                    continue
+                                }
+                            )+)?
+                            $crate::__serde_impl_unknown_key!($unknown, visitor, key, $name, [ $( $fkey ),+ ]);
+                        }
+                        $crate::__serde_impl_report_defaults!($name, [ $( $fname => $fkey ),+ ]);
+                        Ok(obj)
+                    }
+                }
+                de.deserialize_map(_Deserializer)
+            }
         }
-        impl<'a> ::serde::Deserialize<'a> for $name {
-            fn deserialize<D: ::serde::Deserializer<'a>>(de: D) -> Result<Self, D::Error> {
-                use serde_utils::Obj as _DummyObjToSkipUnknownFields;
+    };
+    // Deserialize impl for struct $name($ktype) { $fname: $ftype } nested { $gkey => { $gfname: $gftype } } as map
+    ( $name:ident($ktype:ident) { $( $fname:ident : $ftype:ty => $fkey:expr ),* }
+      nested { $( $gkey:expr => { $( $gfname:ident : $gftype:ty => $gfkey:expr ),+ } ),+ } ) => {
+        const _: () = assert!(!$crate::private::has_duplicate_keys(&[ $( stringify!($fkey), )* $( stringify!($gkey) ),+ ]),
+            concat!("duplicate key in serde_impl!(", stringify!($name), ")"));
+        $(
+            const _: () = assert!(!$crate::private::has_duplicate_keys(&[ $( stringify!($gfkey) ),+ ]),
+                concat!("duplicate key in serde_impl!(", stringify!($name), ") group ", stringify!($gkey)));
+        )+
+        impl<'a> $crate::private::serde::Deserialize<'a> for $name {
+            fn deserialize<D: $crate::private::serde::Deserializer<'a>>(de: D) -> ::std::result::Result<Self, D::Error> {
                 struct _Deserializer;
-                impl<'a> ::serde::de::Visitor<'a> for _Deserializer {
+                impl<'a> $crate::private::serde::de::Visitor<'a> for _Deserializer {
                     type Value = $name;
                     fn expecting(&self, formatter: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
-                        write!(formatter, "map")
+                        write!(formatter, "a map encoding {}", stringify!($name))
                     }
 
-                    fn visit_map<V: ::serde::de::MapAccess<'a>>(self, mut visitor: V) -> Result<Self::Value, V::Error> {
+                    fn visit_map<V: $crate::private::serde::de::MapAccess<'a>>(self, mut visitor: V) -> ::std::result::Result<Self::Value, V::Error> {
                         let mut obj: $name = Default::default();
-                        while let Some(key) = visitor.next_key::<$ktype>()? {
+                        $(
+                            let mut $fname = $crate::__serde_impl_skipped!($ftype);
+                        )*
+                        $($(
+                            let mut $gfname = $crate::__serde_impl_skipped!($gftype);
+                        )+)+
+                        while let Some(key) = visitor.next_key::<$ktype>()
+                            .map_err(|err| $crate::private::type_error(err, stringify!($name)))? {
                             $(
-                                if key == $fkey {
-                                    obj.$fname = visitor.next_value()?;
+                                if $crate::private::KeyLiteral::<$ktype>::matches(&$fkey, &key) {
+                                    obj.$fname = visitor.next_value_seed($crate::__serde_impl_array!(seed $ftype))
+                                        .map_err(|err| $crate::private::field_error(
+                                            err, stringify!($name), stringify!($fname), stringify!($fkey)
+                                        ))?;
+                                    $fname = true;
                                     continue
                                 }
                             )*
-                            let _skip: _DummyObjToSkipUnknownFields = visitor.next_value()?;
+                            $(
+                                if $crate::private::KeyLiteral::<$ktype>::matches(&$gkey, &key) {
+                                    struct _Group<'b> {
+                                        __obj: &'b mut $name,
+                                        $( $gfname: &'b mut bool ),+
+                                    }
+                                    impl<'a, 'b> $crate::private::serde::de::DeserializeSeed<'a> for _Group<'b> {
+                                        type Value = ();
+                                        fn deserialize<D: $crate::private::serde::Deserializer<'a>>(self, de: D) -> ::std::result::Result<(), D::Error> {
+                                            de.deserialize_map(self)
+                                        }
+                                    }
+                                    impl<'a, 'b> $crate::private::serde::de::Visitor<'a> for _Group<'b> {
+                                        type Value = ();
+                                        fn expecting(&self, formatter: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                                            write!(formatter, "a map encoding {} group {}", stringify!($name), stringify!($gkey))
+                                        }
+
+                                        fn visit_map<V: $crate::private::serde::de::MapAccess<'a>>(self, mut visitor: V) -> ::std::result::Result<(), V::Error> {
+                                            while let Some(key) = visitor.next_key::<$ktype>()
+                                                .map_err(|err| $crate::private::type_error(err, stringify!($name)))? {
+                                                $(
+                                                    if $crate::private::KeyLiteral::<$ktype>::matches(&$gfkey, &key) {
+                                                        self.__obj.$gfname = visitor.next_value_seed($crate::__serde_impl_array!(seed $gftype))
+                                                            .map_err(|err| $crate::private::field_error(
+                                                                err, stringify!($name), stringify!($gfname), stringify!($gfkey)
+                                                            ))?;
+                                                        *self.$gfname = true;
+                                                        continue
+                                                    }
+                                                )+
+                                                $crate::__serde_impl_report_unknown!($name, key);
+                                                let _skip: $crate::Ignore = visitor.next_value()?;
+                                            }
+                                            Ok(())
+                                        }
+                                    }
+                                    visitor.next_value_seed(_Group { __obj: &mut obj, $( $gfname: &mut $gfname ),+ })?;
+                                    continue
+                                }
+                            )+
+                            $crate::__serde_impl_report_unknown!($name, key);
+                            let _skip: $crate::Ignore = visitor.next_value()?;
+                        }
+                        $crate::__serde_impl_report_defaults!($name, [ $( $fname => $fkey, )* $($( $gfname => $gfkey ),+),+ ]);
+                        Ok(obj)
+                    }
+                }
+                de.deserialize_map(_Deserializer)
+            }
+        }
+    };
+    // Deserialize impl for struct $name($ktype) version($vkey => $version) { $fname: $ftype } as map
+    ( $name:ident($ktype:ident) version($vkey:expr => $version:expr) { $( $fname:ident : $ftype:ty => $fkey:expr ),+ }
+      migrate { $( $mversion:expr => $migrate:expr ),* } ) => {
+        const _: () = assert!(!$crate::private::has_duplicate_keys(&[ stringify!($vkey), $( stringify!($fkey) ),+ ]),
+            concat!("duplicate key in serde_impl!(", stringify!($name), ")"));
+        impl<'a> $crate::private::serde::Deserialize<'a> for $name {
+            fn deserialize<D: $crate::private::serde::Deserializer<'a>>(de: D) -> ::std::result::Result<Self, D::Error> {
+                let map = $crate::private::expect_map::<D::Error>(
+                    <$crate::Obj as $crate::private::serde::Deserialize>::deserialize(de)?, stringify!($name)
+                )?;
+                let version = $crate::private::find_version::<$ktype, _, D::Error>(&map, &$vkey)
+                    .map_err(|err| $crate::private::type_error(err, stringify!($name)))?;
+                if version == $version {
+                    let mut obj: $name = Default::default();
+                    $(
+                        let mut $fname = $crate::__serde_impl_skipped!($ftype);
+                    )*
+                    for (key, value) in map {
+                        let key: $ktype = $crate::private::serde::Deserialize::deserialize($crate::ObjDeserializer::<D::Error>::new(key))
+                            .map_err(|err| $crate::private::type_error(err, stringify!($name)))?;
+                        $(
+                            if $crate::private::KeyLiteral::<$ktype>::matches(&$fkey, &key) {
+                                obj.$fname = $crate::private::serde::de::DeserializeSeed::deserialize(
+                                    $crate::__serde_impl_array!(seed $ftype), $crate::ObjDeserializer::new(value)
+                                ).map_err(|err| $crate::private::field_error(
+                                    err, stringify!($name), stringify!($fname), stringify!($fkey)
+                                ))?;
+                                $fname = true;
+                                continue
+                            }
+                        )*
+                        if !$crate::private::KeyLiteral::<$ktype>::matches(&$vkey, &key) {
+                            $crate::__serde_impl_report_unknown!($name, key);
+                        }
+                    }
+                    $crate::__serde_impl_report_defaults!($name, [ $( $fname => $fkey ),+ ]);
+                    return Ok(obj);
+                }
+                $(
+                    if version == $mversion {
+                        return ($migrate)($crate::Obj::Map(map))
+                            .map_err(|err| $crate::private::migration_error(err, stringify!($name), version));
+                    }
+                )*
+                Err($crate::private::unsupported_version(stringify!($name), version))
+            }
+        }
+    };
+    // Deserialize impl for struct $name { $fname: $ftype, .. } as lenient tuple
+    ( $name:ident { $( $fname:ident : $ftype:ty ),+ , .. } ) => {
+        impl<'a> $crate::private::serde::Deserialize<'a> for $name {
+            fn deserialize<D: $crate::private::serde::Deserializer<'a>>(de: D) -> ::std::result::Result<Self, D::Error> {
+                struct _Deserializer;
+                impl<'a> $crate::private::serde::de::Visitor<'a> for _Deserializer {
+                    type Value = $name;
+                    fn expecting(&self, formatter: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                        write!(formatter, "a tuple encoding {}", stringify!($name))
+                    }
+
+                    fn visit_seq<V: $crate::private::serde::de::SeqAccess<'a>>(self, mut visitor: V) -> ::std::result::Result<Self::Value, V::Error> {
+                        let mut obj: $name = Default::default();
+                        let mut pos = 0usize;
+                        $(
+                            if !$crate::__serde_impl_skipped!($ftype) {
+                                pos += 1;
+                                match visitor.next_element_seed($crate::__serde_impl_array!(seed $ftype))
+                                    .map_err(|err| $crate::private::field_error(
+                                        err, stringify!($name), stringify!($fname), &(pos - 1).to_string()
+                                    ))? {
+                                    Some(val) => obj.$fname = val,
+                                    None => return Ok(obj)
+                                }
+                            }
+                        )*
+                        while let Some(_skip) = visitor.next_element::<$crate::Ignore>()? {}
+                        Ok(obj)
+                    }
+                }
+                de.deserialize_seq(_Deserializer)
+            }
+        }
+    };
+    // Deserialize impl for struct $name { $fname: $ftype } as tuple with a single field, written as the field
+    ( $name:ident { $fname:ident : $ftype:ty } ) => {
+        impl<'a> $crate::private::serde::Deserialize<'a> for $name {
+            #[inline]
+            fn deserialize<D: $crate::private::serde::Deserializer<'a>>(de: D) -> ::std::result::Result<Self, D::Error> {
+                $crate::private::serde::de::DeserializeSeed::deserialize($crate::__serde_impl_array!(seed $ftype), de)
+                    .map(|$fname| $name { $fname })
+                    .map_err(|err| $crate::private::type_error(err, stringify!($name)))
+            }
+        }
+    };
+    // Deserialize impl for struct $name { $fname: $ftype } as tuple
+    ( $name:ident { $( $fname:ident : $ftype:ty ),+ } ) => {
+        impl<'a> $crate::private::serde::Deserialize<'a> for $name {
+            fn deserialize<D: $crate::private::serde::Deserializer<'a>>(de: D) -> ::std::result::Result<Self, D::Error> {
+                #[allow(unused_imports)]
+                use $crate::private::{ProbeSkip, ProbeKeep};
+                struct _Deserializer;
+                impl<'a> $crate::private::serde::de::Visitor<'a> for _Deserializer {
+                    type Value = $name;
+                    fn expecting(&self, formatter: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                        write!(formatter, "a tuple encoding {}", stringify!($name))
+                    }
+
+                    fn visit_seq<V: $crate::private::serde::de::SeqAccess<'a>>(self, mut visitor: V) -> ::std::result::Result<Self::Value, V::Error> {
+                        let mut len = 0;
+                        $(
+                            let probe = $crate::private::skip_probe::<$ftype>();
+                            let $fname: $ftype = if (&probe).is_skipped() {
+                                (&probe).skipped_value()
+                            } else {
+                                len += 1;
+                                visitor.next_element_seed($crate::__serde_impl_array!(seed $ftype))?
+                                    .ok_or_else(|| $crate::private::serde::de::Error::invalid_length(len - 1, &self))?
+                            };
+                        )+
+                        Ok($name { $( $fname ),+ })
+                    }
+                }
+                let len = 0 $( + usize::from(!$crate::__serde_impl_skipped!($ftype)) )+;
+                de.deserialize_tuple(len, _Deserializer)
+                    .map_err(|err| $crate::private::type_error(err, stringify!($name)))
+            }
+        }
+    };
+    // Deserialize impl for enum $name { $variant }
+    ( $name:ident($ktype:ident) { $( $variant:ident => $fkey:expr ),+ $(, _ => $fallback:ident)? } ) => {
+        const _: () = assert!(!$crate::private::has_duplicate_keys(&[ $( stringify!($fkey) ),+ ]),
+            concat!("duplicate discriminator in serde_impl!(", stringify!($name), ")"));
+        impl<'a> $crate::private::serde::Deserialize<'a> for $name {
+            fn deserialize<D: $crate::private::serde::Deserializer<'a>>(de: D) -> ::std::result::Result<Self, D::Error> {
+                let key = $ktype::deserialize(de).map_err(|err| $crate::private::type_error(err, stringify!($name)))?;
+                $(
+                    if $crate::private::KeyLiteral::<$ktype>::matches(&$fkey, &key) {
+                        return Ok($name::$variant);
+                    }
+                )*
+                let fallback: Option<$name> = None $( .or(Some($name::$fallback)) )?;
+                fallback.ok_or_else(|| $crate::private::invalid_discriminator(stringify!($name), &[ $( stringify!($fkey) ),+ ]))
+            }
+        }
+    };
+    // Deserialize impl for enum $name { $variant($ftype) } as single-entry map
+    ( $name:ident($ktype:ident) map { $( $variant:ident($ftype:ty) => $fkey:expr ),* } ) => {
+        const _: () = assert!(!$crate::private::has_duplicate_keys(&[ $( stringify!($fkey) ),+ ]),
+            concat!("duplicate discriminator in serde_impl!(", stringify!($name), ")"));
+        impl<'a> $crate::private::serde::Deserialize<'a> for $name {
+            #[inline]
+            fn deserialize<D: $crate::private::serde::Deserializer<'a>>(de: D) -> ::std::result::Result<Self, D::Error> {
+                struct _Deserializer;
+                impl<'a> $crate::private::serde::de::Visitor<'a> for _Deserializer {
+                    type Value = $name;
+                    fn expecting(&self, formatter: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                        write!(formatter, "a single-entry map encoding {}", stringify!($name))
+                    }
+                    fn visit_map<V: $crate::private::serde::de::MapAccess<'a>>(self, mut visitor: V) -> ::std::result::Result<$name, V::Error> {
+                        let key: $ktype = visitor.next_key()
+                            .map_err(|err| $crate::private::type_error(err, stringify!($name)))?
+                            .ok_or_else(|| $crate::private::not_a_single_entry_map(stringify!($name)))?;
+                        $(
+                            if $crate::private::KeyLiteral::<$ktype>::matches(&$fkey, &key) {
+                                let value = $name::$variant(visitor.next_value()
+                                    .map_err(|err| $crate::private::field_error(
+                                        err, stringify!($name), stringify!($variant), stringify!($fkey)
+                                    ))?);
+                                if visitor.next_key::<$crate::private::serde::de::IgnoredAny>()?.is_some() {
+                                    return Err($crate::private::not_a_single_entry_map(stringify!($name)));
+                                }
+                                return Ok(value);
+                            }
+                        )*
+                        Err($crate::private::invalid_discriminator(stringify!($name), &[ $( stringify!($fkey) ),* ]))
+                    }
+                }
+                de.deserialize_map(_Deserializer)
+            }
+        }
+    };
+    // Deserialize impl for enum $name { $variant($ftype) }, missing payloads are the default payloads, `None` or `()`,
+    // variants marked as `#[bare]` also accept the key without tuple
+    ( $name:ident($ktype:ident) { $( $(#[$attr:ident])? $variant:ident($ftype:ty $(= $default:expr)?) => $fkey:expr ),* } ) => {
+        const _: () = assert!(!$crate::private::has_duplicate_keys(&[ $( stringify!($fkey) ),+ ]),
+            concat!("duplicate discriminator in serde_impl!(", stringify!($name), ")"));
+        impl<'a> $crate::private::serde::Deserialize<'a> for $name {
+            #[inline]
+            fn deserialize<D: $crate::private::serde::Deserializer<'a>>(de: D) -> ::std::result::Result<Self, D::Error> {
+                const BARE: bool = false $( || $crate::__serde_impl_bare!(@flag [$(#[$attr])?]) )*;
+                struct _Deserializer;
+                impl _Deserializer {
+                    #[allow(unused_variables)]
+                    fn bare<__E: $crate::private::serde::de::Error>(key: $ktype) -> ::std::result::Result<$name, __E> {
+                        $(
+                            $crate::__serde_impl_bare!([$(#[$attr])?]
+                                if $crate::private::KeyLiteral::<$ktype>::matches(&$fkey, &key) {
+                                    return Ok($name::$variant($crate::__serde_impl_payload_default!(@bare $ftype $(, $default)?)));
+                                }
+                            );
+                        )*
+                        if false $( || $crate::private::KeyLiteral::<$ktype>::matches(&$fkey, &key) )* {
+                            return Err($crate::private::not_a_tuple(stringify!($name)));
+                        }
+                        Err($crate::private::invalid_discriminator(stringify!($name), &[ $( stringify!($fkey) ),* ]))
+                    }
+                }
+                impl<'a> $crate::private::serde::de::Visitor<'a> for _Deserializer {
+                    type Value = $name;
+                    fn expecting(&self, formatter: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                        write!(formatter, "a tuple encoding {}", stringify!($name))
+                    }
+                    fn visit_u64<__E: $crate::private::serde::de::Error>(self, val: u64) -> ::std::result::Result<$name, __E> {
+                        _Deserializer::bare($crate::private::bare_key(val, stringify!($name))?)
+                    }
+                    fn visit_i64<__E: $crate::private::serde::de::Error>(self, val: i64) -> ::std::result::Result<$name, __E> {
+                        _Deserializer::bare($crate::private::bare_key(val, stringify!($name))?)
+                    }
+                    fn visit_str<__E: $crate::private::serde::de::Error>(self, val: &str) -> ::std::result::Result<$name, __E> {
+                        _Deserializer::bare($crate::private::bare_key(val, stringify!($name))?)
+                    }
+                    fn visit_bytes<__E: $crate::private::serde::de::Error>(self, val: &[u8]) -> ::std::result::Result<$name, __E> {
+                        _Deserializer::bare($crate::private::bare_key(val, stringify!($name))?)
+                    }
+                    fn visit_seq<V: $crate::private::serde::de::SeqAccess<'a>>(self, mut visitor: V) -> ::std::result::Result<$name, V::Error> {
+                        let key: $ktype = visitor.next_element()
+                            .map_err(|err| $crate::private::type_error(err, stringify!($name)))?
+                            .ok_or_else(|| $crate::private::not_a_tuple(stringify!($name)))?;
+                        $(
+                            if $crate::private::KeyLiteral::<$ktype>::matches(&$fkey, &key) {
+                                let value: Option<$ftype> = visitor.next_element()
+                                    .map_err(|err| $crate::private::field_error(
+                                        err, stringify!($name), stringify!($variant), stringify!($fkey)
+                                    ))?;
+                                return match value {
+                                    Some(value) => Ok($name::$variant(value)),
+                                    None => $crate::__serde_impl_payload_default!($name, $variant, $ftype $(, $default)?)
+                                };
+                            }
+                        )*
+                        Err($crate::private::invalid_discriminator(stringify!($name), &[ $( stringify!($fkey) ),* ]))
+                    }
+                }
+                // Bare keys can only be told apart from tuples in self-describing formats
+                if BARE {
+                    de.deserialize_any(_Deserializer)
+                } else {
+                    de.deserialize_tuple(2, _Deserializer)
+                }
+            }
+        }
+    };
+    // Deserialize impl for struct $name($ktype) flags { $fname => $mask } as bitmask
+    ( $name:ident($ktype:ident) flags { $( $fname:ident => $mask:expr ),+ $(, .. => $unknown:ident)? } ) => {
+        impl<'a> $crate::private::serde::Deserialize<'a> for $name {
+            fn deserialize<D: $crate::private::serde::Deserializer<'a>>(de: D) -> ::std::result::Result<Self, D::Error> {
+                const ALL: $ktype = 0 $( | $mask )+;
+                let bits = <$ktype as $crate::private::serde::Deserialize>::deserialize(de)
+                    .map_err(|err| $crate::private::type_error(err, stringify!($name)))?;
+                let unknown_bits = bits & !ALL;
+                $crate::__serde_impl_unknown_bits!($name, unknown_bits $(, $unknown)?);
+                Ok($name {
+                    $( $fname: bits & $mask != 0, )+
+                    $( $unknown: unknown_bits )?
+                })
+            }
+        }
+    };
+    // Enum $name { $variant } with discriminant values as keys
+    ( $name:ident($ktype:ident) discriminant { $( $variant:ident ),+ $(, _ => $fallback:ident)? } ) => {
+        impl<'a> $crate::private::serde::Deserialize<'a> for $name {
+            fn deserialize<D: $crate::private::serde::Deserializer<'a>>(de: D) -> ::std::result::Result<Self, D::Error> {
+                let key = <$ktype as $crate::private::serde::Deserialize>::deserialize(de)
+                    .map_err(|err| $crate::private::type_error(err, stringify!($name)))?;
+                $(
+                    if key == $name::$variant as $ktype {
+                        return Ok($name::$variant);
+                    }
+                )+
+                let fallback: Option<$name> = None $( .or(Some($name::$fallback)) )?;
+                // The keys are listed with their values, not the expressions computing them
+                fallback.ok_or_else(|| $crate::private::invalid_discriminator(stringify!($name),
+                    &[ $( &format!("{:?}", $name::$variant as $ktype) ),+ ]))
+            }
+        }
+    };
+    // Automatic keys for struct $name($ktype) { $fname: $ftype } as map
+    ( $name:ident($ktype:ident $($flag:tt)?) $(@$case:literal)? $($strict:ident)?
+      { $( $(#[is_default($pred:expr)])? $fname:ident : $ftype:ty ),+ } ) => {
+        $crate::__serde_impl_auto!(serde_impl_de [$name($ktype $($flag)?) $($strict)?] $ktype
+            [ $( ($fname [$(#[is_default($pred)])?] [: $ftype]) )+ ] [] $($case)?);
+    };
+    // Automatic keys for enum $name { $variant }
+    ( $name:ident($ktype:ident) $(@$case:literal)? { $( $variant:ident ),+ $(, _ => $fallback:ident)? } ) => {
+        $crate::__serde_impl_auto!(serde_impl_de [$name($ktype)] $ktype
+            [ $( ($variant [] []) )+ ] [$(, _ => $fallback)?] $($case)?);
+    };
+    // Automatic keys for enum $name { $variant($ftype) }
+    ( $name:ident($ktype:ident) $(@$case:literal)? $($map:ident)? { $( $(#[$attr:ident])? $variant:ident($ftype:ty $(= $default:expr)?) ),+ } ) => {
+        $crate::__serde_impl_auto!(serde_impl_de [$name($ktype) $($map)?] $ktype
+            [ $( ($variant [$(#[$attr])?] [($ftype $(= $default)?)]) )+ ] [] $($case)?);
+    };
+    ( $name:ident($ktype:ident $($flag:tt)?) @$case:literal $($rest:tt)* ) => {
+        compile_error!(concat!("rename_all in serde_impl!(", stringify!($name), ") requires automatic keys"));
+    };
+    // Multiple definitions separated by `;`
+    ( $($tt:tt)+ ) => {
+        $crate::serde_impl_de!(@split [] [] [] $($tt)+);
+    };
+);
+
+/// Macro for generating a JSON schema of the serialized form
+///
+/// This macro accepts the same syntax as [`serde_impl!`](macro.serde_impl!.html) and implements
+/// [`JsonSchema`](trait.JsonSchema.html) for the type. The schema describes what the `Serialize`
+/// implementation produces: structs as objects with their field keys as properties (keys that are
+/// no strings are formatted), tuples as arrays, enum discriminators as `enum` values and enums
+/// with payload as `oneOf` their variants. The types of all fields must implement `JsonSchema`.
+///
+/// Fields are required in the schema only for structs with required fields (`!`) and if they are
+/// not an `Option`. Strict maps do not allow additional properties. Versioned structs describe
+/// the current version only. In recursive types, the nested occurrences refer to the outer schema
+/// via `{"$ref": "#Name"}` and an `$anchor` named after the type.
+///
+/// Instead of calling this macro separately, `serde_impl!` also generates the schema if the
+/// definitions start with `schema;`.
+///
+/// ### Example
+///
+/// ```ignore
+/// serde_impl!(schema; Test(String!) strict {
+///     test: String => "test",
+///     num: Option<u64> => "num"
+/// });
+///
+/// // {"additionalProperties": false, "properties": {"num": {"anyOf": [...]}, "test": {"type": "string"}},
+/// //  "required": ["test"], "title": "Test", "type": "object"}
+/// let schema = serde_json::to_string(&Test::json_schema())?;
+/// ```
+#[macro_export]
+macro_rules! serde_impl_schema(
+    ( @strict strict ) => { true };
+    ( @strict ) => { false };
+    // The conversions to Obj, the size hint and the round trip tests are generated by their own macros
+    ( obj; $($tt:tt)* ) => {
+        $crate::serde_impl_schema!($($tt)*);
+    };
+    ( size; $($tt:tt)* ) => {
+        $crate::serde_impl_schema!($($tt)*);
+    };
+    ( roundtrip; $($tt:tt)* ) => {
+        $crate::serde_impl_schema!($($tt)*);
+    };
+    // Multiple definitions sharing a key type: key $ktype; $def; ...
+    ( key $ktype:ident; $($tt:tt)* ) => {
+        $crate::serde_impl_schema!(@split [$ktype] [;] [] $($tt)*);
+    };
+    // Structs that must list all of their fields
+    ( exhaustive $($rest:tt)* ) => {
+        $crate::serde_impl_schema!($($rest)*);
+    };
+    // Types referenced by path are imported into a block, the arms below need a plain name
+    ( $first:ident :: $($rest:tt)* ) => {
+        $crate::serde_impl_schema!(@path [] [$first] $($rest)*);
+    };
+    ( @path [$($def:tt)*] [$($path:ident)::+] $next:ident :: $($rest:tt)* ) => {
+        $crate::serde_impl_schema!(@path [$($def)*] [$($path)::+ :: $next] $($rest)*);
+    };
+    ( @path [$($def:tt)*] [$($path:ident)::+] $name:ident $($rest:tt)* ) => {
+        const _: () = {
+            use $($path::)+$name;
+            $crate::serde_impl_schema!($($def)* $name $($rest)*);
+        };
+    };
+    // Split multiple definitions at `;`
+    ( @split [$($ktype:ident)?] [$($seen:tt)?] [$($acc:tt)*] ; $($rest:tt)* ) => {
+        $crate::serde_impl_schema!(@def [$($ktype)?] $($acc)*);
+        $crate::serde_impl_schema!(@split [$($ktype)?] [;] [] $($rest)*);
+    };
+    ( @split [$($ktype:ident)?] [$($seen:tt)?] [$($acc:tt)*] $next:tt $($rest:tt)* ) => {
+        $crate::serde_impl_schema!(@split [$($ktype)?] [$($seen)?] [$($acc)* $next] $($rest)*);
+    };
+    ( @split [$($ktype:ident)?] [;] [] ) => {};
+    ( @split [$($ktype:ident)?] [;] [$($acc:tt)+] ) => {
+        $crate::serde_impl_schema!(@def [$($ktype)?] $($acc)+);
+    };
+    ( @split [$($ktype:ident)?] [] [$($acc:tt)*] ) => {
+        compile_error!(concat!("invalid syntax for serde_impl!: ", stringify!($($acc)*)));
+    };
+    ( @def [$($ktype:ident)?] exhaustive $($rest:tt)* ) => {
+        $crate::serde_impl_schema!(@def [$($ktype)?] $($rest)*);
+    };
+    ( @def [$($ktype:ident)?] $first:ident :: $($rest:tt)* ) => {
+        $crate::serde_impl_schema!(@path [@def [$($ktype)?]] [$first] $($rest)*);
+    };
+    // Substitute the shared key type for `_`
+    ( @def [$ktype:ident] $name:ident(_, rename_all = $case:literal) $($rest:tt)* ) => {
+        $crate::serde_impl_schema!($name($ktype) @$case $($rest)*);
+    };
+    ( @def [$ktype:ident] $name:ident(_ $flag:tt, rename_all = $case:literal) $($rest:tt)* ) => {
+        $crate::serde_impl_schema!($name($ktype $flag) @$case $($rest)*);
+    };
+    ( @def [$ktype:ident] $name:ident(_, $mode:ident) $($rest:tt)* ) => {
+        $crate::serde_impl_schema!($name($ktype, $mode) $($rest)*);
+    };
+    ( @def [$ktype:ident] $name:ident(_ $($flag:tt)?) $($rest:tt)* ) => {
+        $crate::serde_impl_schema!($name($ktype $($flag)?) $($rest)*);
+    };
+    ( @def [$($ktype:ident)?] $($rest:tt)* ) => {
+        $crate::serde_impl_schema!($($rest)*);
+    };
+    // Automatic keys converted to another case, marked with `@$case` for the arms below
+    ( $name:ident($ktype:ident, rename_all = $case:literal) $($rest:tt)* ) => {
+        $crate::serde_impl_schema!($name($ktype) @$case $($rest)*);
+    };
+    ( $name:ident($ktype:ident $flag:tt, rename_all = $case:literal) $($rest:tt)* ) => {
+        $crate::serde_impl_schema!($name($ktype $flag) @$case $($rest)*);
+    };
+    // The schema describes the human-readable form with the names of the fields as keys
+    ( $name:ident($ktype:ident, readable) $($strict:ident)? { $( $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
+        $crate::serde_impl_schema!(@object [$($strict)?] [false] $name(String) { $( $fname : $ftype => stringify!($fname) ),+ });
+    };
+    // The schema describes the written shape of structs that accept maps and tuples
+    ( $name:ident($ktype:ident, map_or_tuple) $($strict:ident)? { $($body:tt)* } ) => {
+        $crate::serde_impl_schema!($name($ktype) $($strict)? { $($body)* });
+    };
+    ( $name:ident($ktype:ident, tuple_or_map) $($strict:ident)? { $( $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
+        impl $crate::JsonSchema for $name {
+            fn json_schema() -> $crate::Obj {
+                $crate::private::recursive_schema::<$name, _>(stringify!($name), || {
+                    let mut items = Vec::new();
+                    $(
+                        if !$crate::__serde_impl_skipped!($ftype) {
+                            items.push(<$ftype as $crate::JsonSchema>::json_schema());
+                        }
+                    )+
+                    $crate::private::tuple_schema(Some(stringify!($name)), items, false)
+                })
+            }
+        }
+    };
+    // Reserved keys are not part of the schema
+    ( $name:ident($ktype:ident $($flag:tt)?) $($strict:ident)?
+      { reserved $($rkey:expr),+ ; $( $(#[$fattr:ident $(($pred:expr))?])? $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
+        $crate::serde_impl_schema!($name($ktype $($flag)?) $($strict)? { $( $fname : $ftype => $fkey ),+ });
+    };
+    // The context does not affect the serialized form
+    ( $name:ident($ktype:ident $($flag:tt)?) context($ctx:ty) $($strict:ident)?
+      { $( $(#[$fattr:ident])? $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
+        $crate::serde_impl_schema!($name($ktype $($flag)?) $($strict)? { $( $fname : $ftype => $fkey ),+ });
+    };
+    // Schema for struct $name($ktype?) { $fname: $ftype } as map
+    ( $name:ident($ktype:ident?) $($strict:ident)? { $( $(#[is_default($pred:expr)])? $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
+        $crate::serde_impl_schema!(@object [$($strict)?] [false] $name($ktype) { $( $fname : $ftype => $fkey ),+ });
+    };
+    // Schema for struct $name($ktype!) { $fname: $ftype } as map
+    ( $name:ident($ktype:ident!) $($strict:ident)? { $( $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
+        $crate::serde_impl_schema!(@object [$($strict)?] [true] $name($ktype) { $( $fname : $ftype => $fkey ),+ });
+    };
+    // Schema for struct $name($ktype) { $fname: $ftype } as map, computed fields are written like the others
+    ( $name:ident($ktype:ident) $($strict:ident)? { $( $(#[compute($func:expr)])? $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
+        $crate::serde_impl_schema!(@object [$($strict)?] [false] $name($ktype) { $( $fname : $ftype => $fkey ),+ });
+    };
+    ( @object [$($strict:ident)?] [$required:tt] $name:ident($ktype:ident) { $( $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
+        impl $crate::JsonSchema for $name {
+            fn json_schema() -> $crate::Obj {
+                $crate::private::recursive_schema::<$name, _>(stringify!($name), || {
+                    #[allow(unused_imports)]
+                    use $crate::private::{ProbeOption, ProbeOther};
+                    #[allow(unused_mut)]
+                    let mut required = Vec::new();
+                    let mut properties = Vec::new();
+                    $(
+                        if !$crate::__serde_impl_skipped!($ftype) {
+                            if $required && !(&$crate::private::option_probe::<$ftype>()).is_option() {
+                                required.push($crate::private::schema_property::<$ktype, _>(&$fkey));
+                            }
+                            properties.push(($crate::private::schema_property::<$ktype, _>(&$fkey),
+                                <$ftype as $crate::JsonSchema>::json_schema()));
+                        }
+                    )+
+                    $crate::private::object_schema(Some(stringify!($name)), properties, required,
+                        $crate::serde_impl_schema!(@strict $($strict)?))
+                })
+            }
+        }
+    };
+    // Schema for struct $name($ktype) { $fname: $ftype } nested { $gkey => { $gfname: $gftype } } as map
+    ( $name:ident($ktype:ident) { $( $fname:ident : $ftype:ty => $fkey:expr ),* }
+      nested { $( $gkey:expr => { $( $gfname:ident : $gftype:ty => $gfkey:expr ),+ } ),+ } ) => {
+        impl $crate::JsonSchema for $name {
+            fn json_schema() -> $crate::Obj {
+                $crate::private::recursive_schema::<$name, _>(stringify!($name), || {
+                    let mut properties = Vec::new();
+                    $(
+                        if !$crate::__serde_impl_skipped!($ftype) {
+                            properties.push(($crate::private::schema_property::<$ktype, _>(&$fkey),
+                                <$ftype as $crate::JsonSchema>::json_schema()));
+                        }
+                    )*
+                    $(
+                        let mut group = Vec::new();
+                        $(
+                            if !$crate::__serde_impl_skipped!($gftype) {
+                                group.push(($crate::private::schema_property::<$ktype, _>(&$gfkey),
+                                    <$gftype as $crate::JsonSchema>::json_schema()));
+                            }
+                        )+
+                        properties.push(($crate::private::schema_property::<$ktype, _>(&$gkey),
+                            $crate::private::object_schema(None, group, Vec::new(), false)));
+                    )+
+                    $crate::private::object_schema(Some(stringify!($name)), properties, Vec::new(), false)
+                })
+            }
+        }
+    };
+    // Schema for struct $name($ktype) version($vkey => $version) { $fname: $ftype } as map
+    ( $name:ident($ktype:ident) version($vkey:expr => $version:expr) { $( $fname:ident : $ftype:ty => $fkey:expr ),+ }
+      migrate { $( $mversion:expr => $migrate:expr ),* } ) => {
+        impl $crate::JsonSchema for $name {
+            fn json_schema() -> $crate::Obj {
+                $crate::private::recursive_schema::<$name, _>(stringify!($name), || {
+                    let version = $crate::private::schema_property::<$ktype, _>(&$vkey);
+                    let mut properties = vec![
+                        (version.clone(), $crate::private::const_schema($crate::Obj::Unsigned($version as u64)))
+                    ];
+                    $(
+                        if !$crate::__serde_impl_skipped!($ftype) {
+                            properties.push(($crate::private::schema_property::<$ktype, _>(&$fkey),
+                                <$ftype as $crate::JsonSchema>::json_schema()));
+                        }
+                    )+
+                    $crate::private::object_schema(Some(stringify!($name)), properties, vec![version], false)
+                })
+            }
+        }
+    };
+    // Schema for struct $name { $fname: $ftype, .. } as lenient tuple
+    ( $name:ident { $( $fname:ident : $ftype:ty ),+ , .. } ) => {
+        impl $crate::JsonSchema for $name {
+            fn json_schema() -> $crate::Obj {
+                $crate::private::recursive_schema::<$name, _>(stringify!($name), || {
+                    let mut items = Vec::new();
+                    $(
+                        if !$crate::__serde_impl_skipped!($ftype) {
+                            items.push(<$ftype as $crate::JsonSchema>::json_schema());
                         }
-                        Ok(obj)
-                    }
-                }
-                Ok(de.deserialize_map(_Deserializer))?
+                    )+
+                    $crate::private::tuple_schema(Some(stringify!($name)), items, true)
+                })
             }
         }
     };
-    // Serde impl for struct $name { $fname: $ftype } as tuple
+    // Schema for struct $name { $fname: $ftype } as tuple
     ( $name:ident { $( $fname:ident : $ftype:ty ),+ } ) => {
-        impl ::serde::Serialize for $name {
-            #[inline]
-            fn serialize<S: ::serde::Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
-                ($( &self.$fname ),*).serialize(ser)
+        impl $crate::JsonSchema for $name {
+            fn json_schema() -> $crate::Obj {
+                $crate::private::recursive_schema::<$name, _>(stringify!($name), || {
+                    let single = [ $( stringify!($fname) ),+ ].len() == 1;
+                    let mut items = Vec::new();
+                    $(
+                        if single || !$crate::__serde_impl_skipped!($ftype) {
+                            items.push(<$ftype as $crate::JsonSchema>::json_schema());
+                        }
+                    )+
+                    $crate::private::tuple_schema(Some(stringify!($name)), items, false)
+                })
             }
         }
-        impl<'a> ::serde::Deserialize<'a> for $name {
-            #[inline]
-            fn deserialize<D: ::serde::Deserializer<'a>>(de: D) -> Result<Self, D::Error> {
-                type T = ( $($ftype),* );
-                T::deserialize(de).map(|( $($fname),* )| $name { $( $fname: $fname ),* })
+    };
+    // Schema for enum $name { $variant }, any key is accepted with a fallback variant
+    ( $name:ident($ktype:ident) { $( $variant:ident => $fkey:expr ),+ , _ => $fallback:ident } ) => {
+        impl $crate::JsonSchema for $name {
+            fn json_schema() -> $crate::Obj {
+                $crate::private::titled(stringify!($name), <$ktype as $crate::JsonSchema>::json_schema())
             }
         }
     };
-    // Serde impl for enum $name { $variant }
     ( $name:ident($ktype:ident) { $( $variant:ident => $fkey:expr ),+ } ) => {
-        impl ::serde::Serialize for $name {
-            fn serialize<S: ::serde::Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
-                match self {
-                    $( &$name::$variant => $fkey ),*
-                }.serialize(ser)
+        impl $crate::JsonSchema for $name {
+            fn json_schema() -> $crate::Obj {
+                $crate::private::enum_schema(stringify!($name),
+                    vec![ $( $crate::private::schema_const::<$ktype, _>(&$fkey) ),+ ])
             }
         }
-        impl<'a> ::serde::Deserialize<'a> for $name {
-            fn deserialize<D: ::serde::Deserializer<'a>>(de: D) -> Result<Self, D::Error> {
-                use ::serde::de::Error as _DummyErrorJustToUseTrait;
-                let key = $ktype::deserialize(de)?;
-                $(
-                    if key == $fkey {
-                        return Ok($name::$variant);
-                    }
-                )*
-                Err(D::Error::custom("Invalid enum discriminator"))
+    };
+    // Schema for enum $name { $variant($ftype) } as single-entry map
+    ( $name:ident($ktype:ident) map { $( $variant:ident($ftype:ty) => $fkey:expr ),* } ) => {
+        impl $crate::JsonSchema for $name {
+            fn json_schema() -> $crate::Obj {
+                $crate::private::recursive_schema::<$name, _>(stringify!($name), || {
+                    $crate::private::one_of_schema(stringify!($name), vec![ $( {
+                        let key = $crate::private::schema_property::<$ktype, _>(&$fkey);
+                        $crate::private::object_schema(None, vec![(key.clone(), <$ftype as $crate::JsonSchema>::json_schema())],
+                            vec![key], true)
+                    } ),* ])
+                })
+            }
+        }
+    };
+    // Schema for enum $name { $variant($ftype) }, the payload is optional for variants with a default,
+    // `Option` or `()` payload
+    ( $name:ident($ktype:ident) { $( $(#[$attr:ident])? $variant:ident($ftype:ty $(= $default:expr)?) => $fkey:expr ),* } ) => {
+        impl $crate::JsonSchema for $name {
+            fn json_schema() -> $crate::Obj {
+                $crate::private::recursive_schema::<$name, _>(stringify!($name), || {
+                    $crate::private::one_of_schema(stringify!($name), vec![ $(
+                        $crate::__serde_impl_payload_default!(@schema $ftype, $crate::private::tuple_schema(None, vec![
+                            $crate::private::const_schema($crate::private::schema_const::<$ktype, _>(&$fkey)),
+                            <$ftype as $crate::JsonSchema>::json_schema()
+                        ], false) $(, $default)?)
+                    ),* ])
+                })
+            }
+        }
+    };
+    // Schema for struct $name($ktype) flags { $fname => $mask } as bitmask
+    ( $name:ident($ktype:ident) flags { $( $fname:ident => $mask:expr ),+ $(, .. => $unknown:ident)? } ) => {
+        impl $crate::JsonSchema for $name {
+            fn json_schema() -> $crate::Obj {
+                $crate::private::titled(stringify!($name), <$ktype as $crate::JsonSchema>::json_schema())
+            }
+        }
+    };
+    // Enum $name { $variant } with discriminant values as keys
+    ( $name:ident($ktype:ident) discriminant { $( $variant:ident ),+ $(, _ => $fallback:ident)? } ) => {
+        $crate::serde_impl_schema!($name($ktype) { $( $variant => $name::$variant as $ktype ),+ $(, _ => $fallback)? });
+    };
+    // Automatic keys for struct $name($ktype) { $fname: $ftype } as map
+    ( $name:ident($ktype:ident $($flag:tt)?) $(@$case:literal)? $($strict:ident)?
+      { $( $(#[is_default($pred:expr)])? $fname:ident : $ftype:ty ),+ } ) => {
+        $crate::__serde_impl_auto!(serde_impl_schema [$name($ktype $($flag)?) $($strict)?] $ktype
+            [ $( ($fname [] [: $ftype]) )+ ] [] $($case)?);
+    };
+    // Automatic keys for enum $name { $variant }
+    ( $name:ident($ktype:ident) $(@$case:literal)? { $( $variant:ident ),+ $(, _ => $fallback:ident)? } ) => {
+        $crate::__serde_impl_auto!(serde_impl_schema [$name($ktype)] $ktype
+            [ $( ($variant [] []) )+ ] [$(, _ => $fallback)?] $($case)?);
+    };
+    // Automatic keys for enum $name { $variant($ftype) }
+    ( $name:ident($ktype:ident) $(@$case:literal)? $($map:ident)? { $( $(#[$attr:ident])? $variant:ident($ftype:ty $(= $default:expr)?) ),+ } ) => {
+        $crate::__serde_impl_auto!(serde_impl_schema [$name($ktype) $($map)?] $ktype
+            [ $( ($variant [$(#[$attr])?] [($ftype $(= $default)?)]) )+ ] [] $($case)?);
+    };
+    ( $name:ident($ktype:ident $($flag:tt)?) @$case:literal $($rest:tt)* ) => {
+        compile_error!(concat!("rename_all in serde_impl!(", stringify!($name), ") requires automatic keys"));
+    };
+    // Multiple definitions separated by `;`
+    ( $($tt:tt)+ ) => {
+        $crate::serde_impl_schema!(@split [] [] [] $($tt)+);
+    };
+);
+
+/// Macro for generating conversions between a type and [`Obj`](enum.Obj.html)
+///
+/// This macro accepts the same syntax as [`serde_impl!`](macro.serde_impl!.html) and implements
+/// `TryFrom<&$name>` for `Obj` and `TryFrom<Obj>` for `$name`. The conversions go through the
+/// `Serialize` and `Deserialize` implementations via [`to_obj`](fn.to_obj.html) and
+/// [`from_obj`](fn.from_obj.html), so the `Obj` has the same keys as the serialized form. For
+/// types deserialized with a context, only the conversion into `Obj` is implemented.
+///
+/// Both conversions fail with an [`ExtractError`](struct.ExtractError.html). The conversion into
+/// `Obj` only fails if the `Serialize` implementation fails, which only happens for fields with
+/// custom implementations that return errors.
+///
+/// Instead of calling this macro separately, `serde_impl!` also generates the conversions if the
+/// definitions start with `obj;`.
+///
+/// ### Example
+///
+/// ```ignore
+/// serde_impl!(Test(u8) {
+///     test: String => 0,
+///     num: u64 => 1
+/// });
+/// serde_impl_obj!(Test(u8) {
+///     test: String => 0,
+///     num: u64 => 1
+/// });
+///
+/// let obj = Obj::try_from(&test)?;
+/// let test = Test::try_from(obj)?;
+/// ```
+#[macro_export]
+macro_rules! serde_impl_obj(
+    // The schema, the size hint and the round trip tests are generated by their own macros
+    ( schema; $($tt:tt)* ) => {
+        $crate::serde_impl_obj!($($tt)*);
+    };
+    ( size; $($tt:tt)* ) => {
+        $crate::serde_impl_obj!($($tt)*);
+    };
+    ( roundtrip; $($tt:tt)* ) => {
+        $crate::serde_impl_obj!($($tt)*);
+    };
+    ( key $ktype:ident; $($tt:tt)* ) => {
+        $crate::__serde_impl_names!(serde_impl_obj [] $($tt)*);
+    };
+    // Types deserialized with a context can only be converted into Obj
+    ( @impl $name:ident [] [ ( $($ktype:tt)* ) context $($rest:tt)* ] ) => {
+        $crate::serde_impl_obj!(@from $name);
+    };
+    ( @impl $name:ident [<$lt:lifetime>] $rest:tt ) => {
+        impl<'r, $lt> ::std::convert::TryFrom<&'r $name<$lt>> for $crate::Obj {
+            type Error = $crate::ExtractError;
+            #[inline]
+            fn try_from(val: &'r $name<$lt>) -> ::std::result::Result<Self, Self::Error> {
+                $crate::private::try_into_obj(val, stringify!($name))
+            }
+        }
+        impl<$lt> ::std::convert::TryFrom<$crate::Obj> for $name<$lt> {
+            type Error = $crate::ExtractError;
+            #[inline]
+            fn try_from(obj: $crate::Obj) -> ::std::result::Result<Self, Self::Error> {
+                $crate::from_obj(obj)
             }
         }
     };
-    // Serde impl for enum $name { $variant($ftype) }
-    ( $name:ident($ktype:ident) { $( $variant:ident($ftype:ty) => $fkey:expr ),* } ) => {
-        impl ::serde::Serialize for $name {
+    ( @impl $name:ident [] $rest:tt ) => {
+        $crate::serde_impl_obj!(@from $name);
+        impl ::std::convert::TryFrom<$crate::Obj> for $name {
+            type Error = $crate::ExtractError;
             #[inline]
-            fn serialize<S: ::serde::Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
-                match self {
-                    $( &$name::$variant(ref obj) => ($fkey, obj).serialize(ser) ),*
-                }
+            fn try_from(obj: $crate::Obj) -> ::std::result::Result<Self, Self::Error> {
+                $crate::from_obj(obj)
             }
         }
-        impl<'a> ::serde::Deserialize<'a> for $name {
+    };
+    ( @from $name:ident ) => {
+        impl<'r> ::std::convert::TryFrom<&'r $name> for $crate::Obj {
+            type Error = $crate::ExtractError;
             #[inline]
-            fn deserialize<D: ::serde::Deserializer<'a>>(de: D) -> Result<Self, D::Error> {
-                struct _Deserializer;
-                impl<'a> ::serde::de::Visitor<'a> for _Deserializer {
-                    type Value = $name;
-                    fn expecting(&self, formatter: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
-                        write!(formatter, "list")
-                    }
-                    fn visit_seq<V: ::serde::de::SeqAccess<'a>>(self, mut visitor: V) -> Result<$name, V::Error> {
-                        use ::serde::de::Error as _DummyErrorJustToUseTrait;
-                        let key: $ktype = visitor.next_element()?.ok_or(V::Error::custom("Enums must be encoded as tuples"))?;
-                        $(
-                            if key == $fkey {
-                                return Ok($name::$variant(visitor.next_element()?.ok_or(V::Error::custom("Enums must be encoded as tuples"))?));
-                            }
-                        )*
-                        Err(V::Error::custom("Invalid enum discriminator"))
+            fn try_from(val: &'r $name) -> ::std::result::Result<Self, Self::Error> {
+                $crate::private::try_into_obj(val, stringify!($name))
+            }
+        }
+    };
+    // Multiple definitions separated by `;`
+    ( $($tt:tt)+ ) => {
+        $crate::__serde_impl_names!(serde_impl_obj [] $($tt)+);
+    };
+);
+
+/// Macro for generating an estimate of the serialized size
+///
+/// This macro accepts the same syntax as [`serde_impl!`](macro.serde_impl!.html) and generates a
+/// method `serialized_size_hint(&self) -> usize` for the type that estimates the size of the value
+/// in a compact binary format via [`serialized_size_hint`](fn.serialized_size_hint.html), e.g. to
+/// pre-allocate buffers or to enforce size limits before encoding.
+///
+/// Instead of calling this macro separately, `serde_impl!` also generates the method if the
+/// definitions start with `size;`.
+///
+/// ### Example
+///
+/// ```ignore
+/// serde_impl!(size; Test(u8) {
+///     test: String => 0,
+///     num: u64 => 1
+/// });
+///
+/// let mut buf = Vec::with_capacity(test.serialized_size_hint());
+/// ```
+#[macro_export]
+macro_rules! serde_impl_size(
+    // The schema, the conversions and the round trip tests are generated by their own macros
+    ( schema; $($tt:tt)* ) => {
+        $crate::serde_impl_size!($($tt)*);
+    };
+    ( obj; $($tt:tt)* ) => {
+        $crate::serde_impl_size!($($tt)*);
+    };
+    ( roundtrip; $($tt:tt)* ) => {
+        $crate::serde_impl_size!($($tt)*);
+    };
+    ( key $ktype:ident; $($tt:tt)* ) => {
+        $crate::__serde_impl_names!(serde_impl_size [] $($tt)*);
+    };
+    ( @impl $name:ident [$(<$lt:lifetime>)?] $rest:tt ) => {
+        impl $(<$lt>)? $name $(<$lt>)? {
+            /// Returns an estimate of the size of the value in a compact binary format
+            #[inline]
+            pub fn serialized_size_hint(&self) -> usize {
+                $crate::serialized_size_hint(self)
+            }
+        }
+    };
+    // Multiple definitions separated by `;`
+    ( $($tt:tt)+ ) => {
+        $crate::__serde_impl_names!(serde_impl_size [] $($tt)+);
+    };
+);
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __serde_impl_names(
+    // Calls $mac!(@impl $name [<$lt>] [$rest]) for every definition, where $rest is the definition
+    // after the name. Types referenced by path are imported into a block.
+    ( $mac:ident [$($acc:tt)*] ; $($rest:tt)* ) => {
+        $crate::__serde_impl_names!(@def $mac $($acc)*);
+        $crate::__serde_impl_names!($mac [] $($rest)*);
+    };
+    ( $mac:ident [$($acc:tt)*] $next:tt $($rest:tt)* ) => {
+        $crate::__serde_impl_names!($mac [$($acc)* $next] $($rest)*);
+    };
+    ( $mac:ident [] ) => {};
+    ( $mac:ident [$($acc:tt)+] ) => {
+        $crate::__serde_impl_names!(@def $mac $($acc)+);
+    };
+    ( @def $mac:ident exhaustive $($rest:tt)* ) => {
+        $crate::__serde_impl_names!(@def $mac $($rest)*);
+    };
+    ( @def $mac:ident $first:ident :: $($rest:tt)* ) => {
+        $crate::__serde_impl_names!(@path $mac [$first] $($rest)*);
+    };
+    ( @def $mac:ident $name:ident < $lt:lifetime > $($rest:tt)* ) => {
+        $crate::$mac!(@impl $name [<$lt>] [$($rest)*]);
+    };
+    ( @def $mac:ident $name:ident $($rest:tt)* ) => {
+        $crate::$mac!(@impl $name [] [$($rest)*]);
+    };
+    ( @path $mac:ident [$($path:ident)::+] $next:ident :: $($rest:tt)* ) => {
+        $crate::__serde_impl_names!(@path $mac [$($path)::+ :: $next] $($rest)*);
+    };
+    // Tests in a block can not be run, so they refer to the type by path
+    ( @path __serde_impl_roundtrip [$($path:ident)::+] $name:ident $($rest:tt)* ) => {
+        $crate::__serde_impl_roundtrip!(@impl $name [$($path::)+] [$($rest)*]);
+    };
+    ( @path $mac:ident [$($path:ident)::+] $name:ident $($rest:tt)* ) => {
+        const _: () = {
+            use $($path::)+$name;
+            $crate::__serde_impl_names!(@def $mac $name $($rest)*);
+        };
+    };
+);
+
+#[cfg(feature = "test-util")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __serde_impl_roundtrip(
+    // The schema, the conversions and the size hint do not change the serialized form
+    ( schema; $($tt:tt)* ) => {
+        $crate::__serde_impl_roundtrip!($($tt)*);
+    };
+    ( obj; $($tt:tt)* ) => {
+        $crate::__serde_impl_roundtrip!($($tt)*);
+    };
+    ( size; $($tt:tt)* ) => {
+        $crate::__serde_impl_roundtrip!($($tt)*);
+    };
+    ( roundtrip; $($tt:tt)* ) => {
+        $crate::__serde_impl_roundtrip!($($tt)*);
+    };
+    ( key $ktype:ident; $($tt:tt)* ) => {
+        $crate::__serde_impl_names!(__serde_impl_roundtrip [] $($tt)*);
+    };
+    // Types with a lifetime or a context can not be deserialized on their own
+    ( @impl $name:ident [<$lt:lifetime>] $rest:tt ) => {};
+    ( @impl $name:ident [$($path:tt)*] [ ( $($ktype:tt)* ) context $($rest:tt)* ] ) => {};
+    ( @impl $name:ident [$($path:tt)*] [ < $($rest:tt)* ] ) => {};
+    ( @impl $name:ident [$($path:tt)*] $rest:tt ) => {
+        #[cfg(test)]
+        #[test]
+        #[allow(non_snake_case)]
+        fn $name() {
+            $crate::testing::assert_arbitrary_roundtrip::<$($path)* $name>($crate::testing::ROUNDTRIP_CASES);
+        }
+    };
+    ( $($tt:tt)* ) => {
+        $crate::__serde_impl_names!(__serde_impl_roundtrip [] $($tt)*);
+    };
+);
+
+#[cfg(not(feature = "test-util"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __serde_impl_roundtrip(
+    ( $($tt:tt)* ) => {};
+);
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __serde_impl_keys(
+    // Key constants KEYS and $list for $name with keys of type $ktype
+    ( $name:ident $(<$lt:lifetime>)? ($ktype:ident) $list:ident { $( $kname:ident => $kkey:expr ),* } ) => {
+        const _: () = {
+            #[allow(non_snake_case)]
+            pub struct _Keys {
+                $(
+                    #[doc = concat!("The key of `", stringify!($kname), "`")]
+                    pub $kname: <$ktype as $crate::KeyType>::Const,
+                )*
+            }
+            impl $(<$lt>)? $name $(<$lt>)? {
+                /// The keys of all fields or variants as named members
+                pub const KEYS: _Keys = _Keys { $( $kname: $kkey ),* };
+                /// The names of all fields or variants together with their keys
+                pub const $list: &'static [(&'static str, <$ktype as $crate::KeyType>::Const)] = &[ $( (stringify!($kname), $kkey) ),* ];
+            }
+        };
+    };
+);
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __serde_impl_fields(
+    // Function fields() for struct $name with keys of type $ktype
+    ( $name:ident $(<$lt:lifetime>)? ($ktype:ident) { $( $fname:ident : $ftype:ty => $fkey:expr ),+ } ) => {
+        impl $(<$lt>)? $name $(<$lt>)? {
+            /// Returns information about all fields of this type
+            pub fn fields() -> Vec<$crate::FieldInfo<<$ktype as $crate::KeyType>::Const>> {
+                #[allow(unused_imports)]
+                use $crate::private::{ProbeOption, ProbeOther};
+                vec![ $(
+                    $crate::FieldInfo {
+                        name: stringify!($fname),
+                        key: $fkey,
+                        type_name: stringify!($ftype),
+                        optional: (&$crate::private::option_probe::<$ftype>()).is_option()
                     }
+                ),+ ]
+            }
+        }
+    };
+    // Function fields() for struct $name as tuple, the keys are the positions
+    ( $name:ident $(<$lt:lifetime>)? { $( $fname:ident : $ftype:ty ),+ } ) => {
+        impl $(<$lt>)? $name $(<$lt>)? {
+            /// Returns information about all fields of this type
+            pub fn fields() -> Vec<$crate::FieldInfo<usize>> {
+                #[allow(unused_imports)]
+                use $crate::private::{ProbeOption, ProbeOther};
+                let fields = [ $( (stringify!($fname), stringify!($ftype), (&$crate::private::option_probe::<$ftype>()).is_option()) ),+ ];
+                fields.iter().enumerate().map(|(key, &(name, type_name, optional))| {
+                    $crate::FieldInfo { name, key, type_name, optional }
+                }).collect()
+            }
+        }
+    };
+);
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __serde_impl_is_default(
+    // Checks whether a field of a compressed map has its default value
+    ( $value:expr, $default:expr ) => {
+        $value == $default
+    };
+    ( $value:expr, $default:expr, $pred:expr ) => {
+        ($pred)(&$value)
+    };
+);
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __serde_impl_skipped(
+    // Checks whether fields of the type are left out of the serialized form, see `SkipProbe`
+    ( $ftype:ty ) => {
+        {
+            #[allow(unused_imports)]
+            use $crate::private::{ProbeSkip, ProbeKeep};
+            (&$crate::private::skip_probe::<$ftype>()).is_skipped()
+        }
+    };
+);
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __serde_impl_array(
+    // Wraps the value of a field for serializing, arrays of any length are written as tuple, see `ArrayProbe`
+    ( wrap $ftype:ty, $val:expr ) => {
+        {
+            #[allow(unused_imports)]
+            use $crate::private::{ProbeArray, ProbeNoArray};
+            (&$crate::private::array_probe::<$ftype>()).wrap($val)
+        }
+    };
+    // Returns the seed for deserializing a field
+    ( seed $ftype:ty ) => {
+        {
+            #[allow(unused_imports)]
+            use $crate::private::{ProbeArray, ProbeNoArray};
+            (&$crate::private::array_probe::<$ftype>()).seed()
+        }
+    };
+);
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __serde_impl_context_value(
+    // Deserializes the next map value, passing the context on for fields marked with #[context]
+    ( [context] $ftype:ty, $visitor:ident, $context:ident ) => {
+        $visitor.next_value_seed($crate::ContextSeed::new(&mut *$context))
+    };
+    ( [] $ftype:ty, $visitor:ident, $context:ident ) => {
+        $visitor.next_value_seed($crate::__serde_impl_array!(seed $ftype))
+    };
+    ( [$fattr:ident] $ftype:ty, $visitor:ident, $context:ident ) => {
+        compile_error!(concat!("unknown field attribute in serde_impl!: #[", stringify!($fattr), "]"))
+    };
+);
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __serde_impl_computed(
+    // The value of a field for serializing, computed fields call their function with the struct
+    ( get [] $obj:ident . $fname:ident ) => {
+        $obj.$fname
+    };
+    ( get [$func:expr] $obj:ident . $fname:ident ) => {
+        ($func)($obj)
+    };
+    // Stores a deserialized value, the values of computed fields are skipped without reading them
+    ( set [] $visitor:ident, $obj:ident . $fname:ident = $value:expr ) => {
+        $obj.$fname = $value
+    };
+    ( set [$func:expr] $visitor:ident, $obj:ident . $fname:ident = $value:expr ) => {
+        {
+            let _skip: $crate::Ignore = $visitor.next_value()?;
+        }
+    };
+    // Whether the field is computed
+    ( is [] ) => { false };
+    ( is [$func:expr] ) => { true };
+);
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __serde_impl_report_unknown(
+    // Reports a skipped key to the diagnostics hook, with its debug formatting if available
+    ( $name:ident, $key:ident ) => {
+        if $crate::private::diagnostics_enabled() {
+            #[allow(unused_imports)]
+            use $crate::private::{DebugKeyText, NoDebugKeyText};
+            $crate::private::report_unknown_key(stringify!($name), (&&$crate::private::DebugKey(&$key)).debug_text());
+        }
+    };
+);
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __serde_impl_report_defaults(
+    // Reports the fields that have not been seen to the diagnostics hook
+    ( $name:ident, [ $( $fname:ident => $fkey:expr ),* ] ) => {
+        if $crate::private::diagnostics_enabled() {
+            $(
+                if !$fname {
+                    $crate::private::report_defaulted_field(stringify!($name), stringify!($fname), stringify!($fkey));
                 }
-                de.deserialize_tuple(2, _Deserializer)
+            )*
+        }
+    };
+);
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __serde_impl_unknown_key(
+    // Handles an unknown key in a map, either by skipping its value or by failing
+    ( skip, $visitor:ident, $key:ident, $name:ident, [ $( $fkey:expr ),+ ] ) => {
+        {
+            $crate::__serde_impl_report_unknown!($name, $key);
+            let _skip: $crate::Ignore = $visitor.next_value()?;
+        }
+    };
+    ( deny, $visitor:ident, $key:ident, $name:ident, [ $( $fkey:expr ),+ ] ) => {
+        return Err($crate::private::unknown_key(stringify!($name), &[ $( stringify!($fkey) ),+ ]))
+    };
+);
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __serde_impl_auto(
+    // Derives the keys of entries without keys and passes the completed definition to $mac.
+    // Entries are given as ($kname [tokens before $kname] [tokens after $kname]).
+    ( $mac:ident [$($head:tt)*] $ktype:ident [$($items:tt)*] [$($tail:tt)*] ) => {
+        $crate::__serde_impl_auto!(@kind $ktype $mac [$($head)*] [$($tail)*] [$($items)*]);
+    };
+    // Keys converted to the case given by rename_all, only for keys derived from names
+    ( $mac:ident [$($head:tt)*] $ktype:ident [$($items:tt)*] [$($tail:tt)*] $case:literal ) => {
+        $crate::__serde_impl_auto!(@rename $ktype [$case] $mac [$($head)*] [$($tail)*] [$($items)*]);
+    };
+    ( @rename String $($rest:tt)* ) => {
+        $crate::__serde_impl_auto!(@munch renamed $($rest)* []);
+    };
+    ( @rename IgnoreCase $($rest:tt)* ) => {
+        $crate::__serde_impl_auto!(@munch renamed $($rest)* []);
+    };
+    ( @rename ByteBuf $($rest:tt)* ) => {
+        $crate::__serde_impl_auto!(@munch renamed_bytes $($rest)* []);
+    };
+    ( @rename $ktype:ident $($rest:tt)* ) => {
+        compile_error!(concat!("rename_all is not supported for keys of type ", stringify!($ktype)));
+    };
+    // String keys are the names of the entries
+    ( @kind String $($rest:tt)* ) => {
+        $crate::__serde_impl_auto!(@munch name [] $($rest)* []);
+    };
+    ( @kind IgnoreCase $($rest:tt)* ) => {
+        $crate::__serde_impl_auto!(@munch name [] $($rest)* []);
+    };
+    // Binary keys are the names of the entries as bytes
+    ( @kind ByteBuf $($rest:tt)* ) => {
+        $crate::__serde_impl_auto!(@munch bytes [] $($rest)* []);
+    };
+    // All other keys are the positions of the entries
+    ( @kind $ktype:ident $($rest:tt)* ) => {
+        $crate::__serde_impl_auto!(@munch index [0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31 32 33 34 35 36 37 38 39 40 41 42 43 44 45 46 47 48 49 50 51 52 53 54 55 56 57 58 59 60 61 62 63 64 65 66 67 68 69 70 71 72 73 74 75 76 77 78 79 80 81 82 83 84 85 86 87 88 89 90 91 92 93 94 95 96 97 98 99 100 101 102 103 104 105 106 107 108 109 110 111 112 113 114 115 116 117 118 119 120 121 122 123 124 125 126 127] $($rest)* []);
+    };
+    ( @munch $kind:ident [$($idx:tt)*] $mac:ident [$($head:tt)*] [$($tail:tt)*] [] [, $($done:tt)*] ) => {
+        $crate::$mac!($($head)* { $($done)* $($tail)* });
+    };
+    ( @munch name [] $mac:ident $head:tt $tail:tt
+      [($kname:ident [$($before:tt)*] [$($after:tt)*]) $($items:tt)*] [$($done:tt)*] ) => {
+        $crate::__serde_impl_auto!(@munch name [] $mac $head $tail [$($items)*]
+            [$($done)* , $($before)* $kname $($after)* => stringify!($kname)]);
+    };
+    ( @munch bytes [] $mac:ident $head:tt $tail:tt
+      [($kname:ident [$($before:tt)*] [$($after:tt)*]) $($items:tt)*] [$($done:tt)*] ) => {
+        $crate::__serde_impl_auto!(@munch bytes [] $mac $head $tail [$($items)*]
+            [$($done)* , $($before)* $kname $($after)* => stringify!($kname).as_bytes()]);
+    };
+    ( @munch renamed [$case:literal] $mac:ident $head:tt $tail:tt
+      [($kname:ident [$($before:tt)*] [$($after:tt)*]) $($items:tt)*] [$($done:tt)*] ) => {
+        $crate::__serde_impl_auto!(@munch renamed [$case] $mac $head $tail [$($items)*]
+            [$($done)* , $($before)* $kname $($after)* => $crate::__serde_impl_rename!($case $kname)]);
+    };
+    ( @munch renamed_bytes [$case:literal] $mac:ident $head:tt $tail:tt
+      [($kname:ident [$($before:tt)*] [$($after:tt)*]) $($items:tt)*] [$($done:tt)*] ) => {
+        $crate::__serde_impl_auto!(@munch renamed_bytes [$case] $mac $head $tail [$($items)*]
+            [$($done)* , $($before)* $kname $($after)* => $crate::__serde_impl_rename!($case $kname).as_bytes()]);
+    };
+    ( @munch index [$key:tt $($idx:tt)*] $mac:ident $head:tt $tail:tt
+      [($kname:ident [$($before:tt)*] [$($after:tt)*]) $($items:tt)*] [$($done:tt)*] ) => {
+        $crate::__serde_impl_auto!(@munch index [$($idx)*] $mac $head $tail [$($items)*]
+            [$($done)* , $($before)* $kname $($after)* => $key]);
+    };
+    ( @munch index [] $($rest:tt)* ) => {
+        compile_error!("too many entries for automatic keys, at most 128 are supported");
+    };
+);
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __serde_impl_payload_default(
+    // The result for a variant without payload, the default payload, `None`, `()` or an error
+    ( $name:ident, $variant:ident, $ftype:ty ) => {
+        {
+            #[allow(unused_imports)]
+            use $crate::private::{ProbeElided, ProbeWritten};
+            match (&$crate::private::payload_probe::<$ftype>()).elided_value() {
+                Some(value) => Ok($name::$variant(value)),
+                None => Err($crate::private::not_a_tuple(stringify!($name)))
+            }
+        }
+    };
+    ( $name:ident, $variant:ident, $ftype:ty, $default:expr ) => {
+        Ok($name::$variant($default))
+    };
+    // Checks whether the payload is left out when serializing, see `PayloadProbe`
+    ( @elided $ftype:ty, $obj:expr ) => {
+        {
+            #[allow(unused_imports)]
+            use $crate::private::{ProbeElided, ProbeWritten};
+            (&$crate::private::payload_probe::<$ftype>()).is_elided($obj)
+        }
+    };
+    // The schema of a variant, the payload can be omitted if there is a default or it can be elided
+    ( @schema $ftype:ty, $schema:expr ) => {
+        {
+            #[allow(unused_imports)]
+            use $crate::private::{ProbeElided, ProbeWritten};
+            let schema = $schema;
+            match (&$crate::private::payload_probe::<$ftype>()).elided_value() {
+                Some(_) => $crate::private::min_items(schema, 1),
+                None => schema
+            }
+        }
+    };
+    ( @schema $ftype:ty, $schema:expr, $default:expr ) => {
+        $crate::private::min_items($schema, 1)
+    };
+    // The payload of a variant read from a bare key, the default payload or the default value
+    ( @bare $ftype:ty ) => {
+        <$ftype as ::std::default::Default>::default()
+    };
+    ( @bare $ftype:ty, $default:expr ) => {
+        $default
+    };
+);
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __serde_impl_bare(
+    // Whether the variant is marked as `#[bare]`
+    ( @flag [] ) => {
+        false
+    };
+    ( @flag [#[bare]] ) => {
+        true
+    };
+    ( @flag [#[elide]] ) => {
+        false
+    };
+    ( @flag [#[$attr:ident]] ) => {
+        compile_error!(concat!("unknown variant attribute #[", stringify!($attr), "], expected #[bare] or #[elide]"))
+    };
+    // Whether the variant is marked as `#[elide]`
+    ( @elide [#[elide]] ) => {
+        true
+    };
+    ( @elide [$($attr:tt)*] ) => {
+        false
+    };
+    // Expands to the tokens only for variants marked as `#[bare]`
+    ( [#[bare]] $($tt:tt)* ) => {
+        $($tt)*
+    };
+    ( [$($attr:tt)*] $($tt:tt)* ) => {};
+);
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __serde_impl_reserved(
+    // Fails to compile if a field uses one of the reserved keys
+    ( $name:ident [ $($rkey:expr),+ ] [ $($fkey:expr),+ ] ) => {
+        const _: () = assert!(!$crate::private::uses_reserved_key(&[ $( stringify!($fkey) ),+ ], &[ $( stringify!($rkey) ),+ ]),
+            concat!("reserved key used by a field in serde_impl!(", stringify!($name), ")"));
+    };
+);
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __serde_impl_exhaustive(
+    // Collects the path of the type
+    ( [$($path:tt)*] $first:ident :: $($rest:tt)* ) => {
+        $crate::__serde_impl_exhaustive!([$($path)* $first ::] $($rest)*);
+    };
+    ( [$($path:tt)*] $name:ident < $lt:lifetime > $($rest:tt)* ) => {
+        $crate::__serde_impl_exhaustive!(@head [$($path)* $name] [<$lt>] $($rest)*);
+    };
+    ( [$($path:tt)*] $name:ident $($rest:tt)* ) => {
+        $crate::__serde_impl_exhaustive!(@head [$($path)* $name] [] $($rest)*);
+    };
+    // Skips the key type and the options up to the fields
+    ( @head $ty:tt $gen:tt flags { $( $fname:ident => $mask:expr ),+ $(, .. => $unknown:ident)? } ) => {
+        $crate::__serde_impl_exhaustive!(@check $ty $gen [ $($fname)+ $($unknown)? ]);
+    };
+    ( @head $ty:tt $gen:tt { $($body:tt)* } $($rest:tt)* ) => {
+        $crate::__serde_impl_exhaustive!(@fields $ty $gen [] [$($rest)*] $($body)*);
+    };
+    ( @head $ty:tt $gen:tt $skip:tt $($rest:tt)* ) => {
+        $crate::__serde_impl_exhaustive!(@head $ty $gen $($rest)*);
+    };
+    // Collects the names of the fields, also of nested groups
+    ( @fields $ty:tt $gen:tt $names:tt $rest:tt reserved $($rkey:expr),+ ; $($body:tt)* ) => {
+        $crate::__serde_impl_exhaustive!(@fields $ty $gen $names $rest $($body)*);
+    };
+    ( @fields $ty:tt $gen:tt $names:tt $rest:tt # [compute $func:tt] $fname:ident : $ftype:ty => $fkey:expr $(, $($body:tt)*)? ) => {
+        $crate::__serde_impl_exhaustive!(@fields $ty $gen $names $rest $($($body)*)?);
+    };
+    ( @fields $ty:tt $gen:tt $names:tt $rest:tt # [$($attr:tt)*] $($body:tt)* ) => {
+        $crate::__serde_impl_exhaustive!(@fields $ty $gen $names $rest $($body)*);
+    };
+    ( @fields $ty:tt $gen:tt [$($names:ident)*] $rest:tt $fname:ident : $ftype:ty $(=> $fkey:expr)? $(, $($body:tt)*)? ) => {
+        $crate::__serde_impl_exhaustive!(@fields $ty $gen [$($names)* $fname] $rest $($($body)*)?);
+    };
+    ( @fields $ty:tt $gen:tt $names:tt $rest:tt .. ) => {
+        $crate::__serde_impl_exhaustive!(@fields $ty $gen $names $rest);
+    };
+    ( @fields $ty:tt $gen:tt $names:tt [] ) => {
+        $crate::__serde_impl_exhaustive!(@check $ty $gen $names);
+    };
+    ( @fields $ty:tt $gen:tt $names:tt [nested { $( $gkey:expr => { $($group:tt)* } ),+ }] ) => {
+        $crate::__serde_impl_exhaustive!(@fields $ty $gen $names [] $( $($group)* , )+);
+    };
+    ( @fields $ty:tt $gen:tt $names:tt $rest:tt $($body:tt)* ) => {
+        compile_error!("exhaustive can only be used for structs");
+    };
+    // Fails to compile if the struct has fields that are not listed
+    ( @check [$($ty:tt)*] [$($gen:tt)*] [$($fname:ident)*] ) => {
+        const _: () = {
+            #[allow(dead_code, unreachable_code)]
+            fn exhaustive $($gen)* () -> $($ty)* $($gen)* {
+                $($ty)* { $( $fname: unreachable!() ),* }
             }
+        };
+    };
+);
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __serde_impl_rename(
+    // The name $kname converted to the case $case of rename_all as string constant
+    ( $case:literal $kname:ident ) => {
+        {
+            const CASE: $crate::private::RenameCase = $crate::private::RenameCase::parse($case);
+            const LEN: usize = $crate::private::renamed_len(stringify!($kname), CASE);
+            const BYTES: [u8; LEN] = $crate::private::rename(stringify!($kname), CASE);
+            const KEY: &str = match ::std::str::from_utf8(&BYTES) {
+                Ok(key) => key,
+                Err(_) => panic!("renamed key is not valid UTF-8")
+            };
+            KEY
+        }
+    };
+);
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __serde_impl_unknown_bits(
+    // Rejects unknown bits of a bitmask unless there is a field to keep them
+    ( $name:ident, $bits:ident ) => {
+        if $bits != 0 {
+            return Err($crate::private::unknown_bits(stringify!($name), $bits));
         }
     };
+    ( $name:ident, $bits:ident, $unknown:ident ) => {};
 );
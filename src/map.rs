@@ -0,0 +1,531 @@
+use std::collections::{btree_map, BTreeMap};
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::FromIterator;
+use std::mem;
+use std::ops::Index;
+use std::slice;
+use std::vec;
+
+use serde::{Serialize, Serializer};
+
+use generic::Obj;
+use policy::{FloatPolicy, NanGreatest};
+
+/// The maximum number of entries of maps that are stored in a sorted vector
+pub(crate) const SMALL_MAP_LIMIT: usize = 16;
+
+/// A map of objects, the value of [`Obj::Map`](enum.Obj.html#variant.Map)
+///
+/// The API resembles that of `BTreeMap<Obj, Obj>`: the keys are unique and all iterators yield
+/// the entries sorted by key. Internally, maps with up to 16 entries are stored as a sorted vector
+/// of pairs, which needs less memory and is faster to search than a `BTreeMap`. Larger maps use a
+/// `BTreeMap`. The representation is switched automatically when the map grows.
+///
+/// `From` and `Into` convert from and to `BTreeMap<Obj, Obj>`.
+#[derive(Clone)]
+pub struct ObjMap(Repr);
+
+#[derive(Clone)]
+enum Repr {
+    Small(Vec<(Obj, Obj)>),
+    Tree(BTreeMap<Obj, Obj>)
+}
+
+impl ObjMap {
+    /// Creates an empty map
+    #[inline]
+    pub fn new() -> Self {
+        ObjMap(Repr::Small(Vec::new()))
+    }
+
+    /// Returns the number of entries
+    #[inline]
+    pub fn len(&self) -> usize {
+        match self.0 {
+            Repr::Small(ref entries) => entries.len(),
+            Repr::Tree(ref map) => map.len()
+        }
+    }
+
+    /// Returns whether the map has no entries
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the value for the key
+    pub fn get(&self, key: &Obj) -> Option<&Obj> {
+        match self.0 {
+            Repr::Small(ref entries) => entries.binary_search_by(|entry| entry.0.cmp(key)).ok().map(|i| &entries[i].1),
+            Repr::Tree(ref map) => map.get(key)
+        }
+    }
+
+    /// Returns a mutable reference to the value for the key
+    pub fn get_mut(&mut self, key: &Obj) -> Option<&mut Obj> {
+        match self.0 {
+            Repr::Small(ref mut entries) => match entries.binary_search_by(|entry| entry.0.cmp(key)) {
+                Ok(i) => Some(&mut entries[i].1),
+                Err(_) => None
+            },
+            Repr::Tree(ref mut map) => map.get_mut(key)
+        }
+    }
+
+    /// Returns whether the map contains the key
+    #[inline]
+    pub fn contains_key(&self, key: &Obj) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Inserts an entry and returns the previous value of the key
+    ///
+    /// Like `BTreeMap::insert`, the key is not updated if it already exists.
+    pub fn insert(&mut self, key: Obj, val: Obj) -> Option<Obj> {
+        let entries = match self.0 {
+            Repr::Tree(ref mut map) => return map.insert(key, val),
+            Repr::Small(ref mut entries) => match entries.binary_search_by(|entry| entry.0.cmp(&key)) {
+                Ok(i) => return Some(mem::replace(&mut entries[i].1, val)),
+                Err(i) => {
+                    entries.insert(i, (key, val));
+                    if entries.len() <= SMALL_MAP_LIMIT {
+                        return None;
+                    }
+                    mem::take(entries)
+                }
+            }
+        };
+        self.0 = Repr::Tree(entries.into_iter().collect());
+        None
+    }
+
+    /// Removes the key and returns its value
+    pub fn remove(&mut self, key: &Obj) -> Option<Obj> {
+        match self.0 {
+            Repr::Small(ref mut entries) => match entries.binary_search_by(|entry| entry.0.cmp(key)) {
+                Ok(i) => Some(entries.remove(i).1),
+                Err(_) => None
+            },
+            Repr::Tree(ref mut map) => map.remove(key)
+        }
+    }
+
+    /// Keeps only the entries for which the predicate returns `true`
+    pub fn retain<F: FnMut(&Obj, &mut Obj) -> bool>(&mut self, mut f: F) {
+        match self.0 {
+            Repr::Small(ref mut entries) => entries.retain_mut(|entry| f(&entry.0, &mut entry.1)),
+            Repr::Tree(ref mut map) => map.retain(|key, val| f(key, val))
+        }
+    }
+
+    /// Removes all entries
+    #[inline]
+    pub fn clear(&mut self) {
+        self.0 = Repr::Small(Vec::new());
+    }
+
+    /// Iterates over the entries sorted by key
+    pub fn iter(&self) -> Iter<'_> {
+        match self.0 {
+            Repr::Small(ref entries) => Iter(IterRepr::Small(entries.iter())),
+            Repr::Tree(ref map) => Iter(IterRepr::Tree(map.iter()))
+        }
+    }
+
+    /// Iterates over the entries sorted by key, with mutable references to the values
+    pub fn iter_mut(&mut self) -> IterMut<'_> {
+        match self.0 {
+            Repr::Small(ref mut entries) => IterMut(IterMutRepr::Small(entries.iter_mut())),
+            Repr::Tree(ref mut map) => IterMut(IterMutRepr::Tree(map.iter_mut()))
+        }
+    }
+
+    /// Iterates over the keys in sorted order
+    #[inline]
+    pub fn keys(&self) -> Keys<'_> {
+        Keys(self.iter())
+    }
+
+    /// Iterates over the values sorted by key
+    #[inline]
+    pub fn values(&self) -> Values<'_> {
+        Values(self.iter())
+    }
+
+    /// Iterates over mutable references to the values sorted by key
+    #[inline]
+    pub fn values_mut(&mut self) -> ValuesMut<'_> {
+        ValuesMut(self.iter_mut())
+    }
+
+    pub(crate) fn eq_with<P: FloatPolicy>(&self, other: &ObjMap) -> bool {
+        self.len() == other.len() && self.iter().zip(other.iter()).all(|((key, val), (okey, oval))| {
+            key.eq_with::<P>(okey) && val.eq_with::<P>(oval)
+        })
+    }
+
+    pub(crate) fn cmp_with<P: FloatPolicy>(&self, other: &ObjMap) -> Ordering {
+        for ((key, val), (okey, oval)) in self.iter().zip(other.iter()) {
+            match key.cmp_with::<P>(okey).then_with(|| val.cmp_with::<P>(oval)) {
+                Ordering::Equal => (),
+                res => return res
+            }
+        }
+        self.len().cmp(&other.len())
+    }
+
+    pub(crate) fn hash_with<P: FloatPolicy, H: Hasher>(&self, state: &mut H) {
+        // Same as hashing a `BTreeMap<Obj, Obj>`
+        state.write_usize(self.len());
+        for (key, val) in self.iter() {
+            key.hash_with::<P, H>(state);
+            val.hash_with::<P, H>(state);
+        }
+    }
+}
+
+impl Default for ObjMap {
+    #[inline]
+    fn default() -> Self {
+        ObjMap::new()
+    }
+}
+
+impl fmt::Debug for ObjMap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl PartialEq for ObjMap {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.eq_with::<NanGreatest>(other)
+    }
+}
+
+impl Eq for ObjMap {}
+
+impl PartialOrd for ObjMap {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ObjMap {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.cmp_with::<NanGreatest>(other)
+    }
+}
+
+impl Hash for ObjMap {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.hash_with::<NanGreatest, H>(state)
+    }
+}
+
+/// Returns the value for the key
+///
+/// # Panics
+/// Panics if the key is not in the map, like `BTreeMap`.
+impl Index<&Obj> for ObjMap {
+    type Output = Obj;
+
+    #[inline]
+    fn index(&self, key: &Obj) -> &Obj {
+        self.get(key).expect("key not found in map")
+    }
+}
+
+impl Serialize for ObjMap {
+    #[inline]
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        ser.collect_map(self.iter())
+    }
+}
+
+impl From<BTreeMap<Obj, Obj>> for ObjMap {
+    fn from(map: BTreeMap<Obj, Obj>) -> Self {
+        if map.len() <= SMALL_MAP_LIMIT {
+            ObjMap(Repr::Small(map.into_iter().collect()))
+        } else {
+            ObjMap(Repr::Tree(map))
+        }
+    }
+}
+
+impl From<ObjMap> for BTreeMap<Obj, Obj> {
+    fn from(map: ObjMap) -> Self {
+        match map.0 {
+            Repr::Small(entries) => entries.into_iter().collect(),
+            Repr::Tree(map) => map
+        }
+    }
+}
+
+/// Collects entries like `BTreeMap`: for duplicate keys, the first key and the last value are kept
+impl FromIterator<(Obj, Obj)> for ObjMap {
+    fn from_iter<I: IntoIterator<Item=(Obj, Obj)>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        let mut builder = MapBuilder::new(iter.size_hint().0);
+        for (key, val) in iter {
+            builder.insert(key, val);
+        }
+        builder.finish_map()
+    }
+}
+
+impl Extend<(Obj, Obj)> for ObjMap {
+    fn extend<I: IntoIterator<Item=(Obj, Obj)>>(&mut self, iter: I) {
+        for (key, val) in iter {
+            self.insert(key, val);
+        }
+    }
+}
+
+impl IntoIterator for ObjMap {
+    type Item = (Obj, Obj);
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> IntoIter {
+        match self.0 {
+            Repr::Small(entries) => IntoIter(IntoIterRepr::Small(entries.into_iter())),
+            Repr::Tree(map) => IntoIter(IntoIterRepr::Tree(map.into_iter()))
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a ObjMap {
+    type Item = (&'a Obj, &'a Obj);
+    type IntoIter = Iter<'a>;
+
+    #[inline]
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut ObjMap {
+    type Item = (&'a Obj, &'a mut Obj);
+    type IntoIter = IterMut<'a>;
+
+    #[inline]
+    fn into_iter(self) -> IterMut<'a> {
+        self.iter_mut()
+    }
+}
+
+/// Iterator over the entries of an [`ObjMap`](struct.ObjMap.html)
+pub struct Iter<'a>(IterRepr<'a>);
+
+enum IterRepr<'a> {
+    Small(slice::Iter<'a, (Obj, Obj)>),
+    Tree(btree_map::Iter<'a, Obj, Obj>)
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (&'a Obj, &'a Obj);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.0 {
+            IterRepr::Small(ref mut iter) => iter.next().map(|entry| (&entry.0, &entry.1)),
+            IterRepr::Tree(ref mut iter) => iter.next()
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.0 {
+            IterRepr::Small(ref iter) => iter.size_hint(),
+            IterRepr::Tree(ref iter) => iter.size_hint()
+        }
+    }
+}
+
+impl<'a> DoubleEndedIterator for Iter<'a> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        match self.0 {
+            IterRepr::Small(ref mut iter) => iter.next_back().map(|entry| (&entry.0, &entry.1)),
+            IterRepr::Tree(ref mut iter) => iter.next_back()
+        }
+    }
+}
+
+impl<'a> ExactSizeIterator for Iter<'a> {}
+
+/// Iterator over the entries of an [`ObjMap`](struct.ObjMap.html) with mutable values
+pub struct IterMut<'a>(IterMutRepr<'a>);
+
+enum IterMutRepr<'a> {
+    Small(slice::IterMut<'a, (Obj, Obj)>),
+    Tree(btree_map::IterMut<'a, Obj, Obj>)
+}
+
+impl<'a> Iterator for IterMut<'a> {
+    type Item = (&'a Obj, &'a mut Obj);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.0 {
+            IterMutRepr::Small(ref mut iter) => iter.next().map(|entry| (&entry.0, &mut entry.1)),
+            IterMutRepr::Tree(ref mut iter) => iter.next()
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.0 {
+            IterMutRepr::Small(ref iter) => iter.size_hint(),
+            IterMutRepr::Tree(ref iter) => iter.size_hint()
+        }
+    }
+}
+
+impl<'a> ExactSizeIterator for IterMut<'a> {}
+
+/// Owning iterator over the entries of an [`ObjMap`](struct.ObjMap.html)
+pub struct IntoIter(IntoIterRepr);
+
+enum IntoIterRepr {
+    Small(vec::IntoIter<(Obj, Obj)>),
+    Tree(btree_map::IntoIter<Obj, Obj>)
+}
+
+impl Iterator for IntoIter {
+    type Item = (Obj, Obj);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.0 {
+            IntoIterRepr::Small(ref mut iter) => iter.next(),
+            IntoIterRepr::Tree(ref mut iter) => iter.next()
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.0 {
+            IntoIterRepr::Small(ref iter) => iter.size_hint(),
+            IntoIterRepr::Tree(ref iter) => iter.size_hint()
+        }
+    }
+}
+
+impl ExactSizeIterator for IntoIter {}
+
+/// Iterator over the keys of an [`ObjMap`](struct.ObjMap.html)
+pub struct Keys<'a>(Iter<'a>);
+
+impl<'a> Iterator for Keys<'a> {
+    type Item = &'a Obj;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a Obj> {
+        self.0.next().map(|(key, _)| key)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+/// Iterator over the values of an [`ObjMap`](struct.ObjMap.html)
+pub struct Values<'a>(Iter<'a>);
+
+impl<'a> Iterator for Values<'a> {
+    type Item = &'a Obj;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a Obj> {
+        self.0.next().map(|(_, val)| val)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+/// Iterator over mutable references to the values of an [`ObjMap`](struct.ObjMap.html)
+pub struct ValuesMut<'a>(IterMut<'a>);
+
+impl<'a> Iterator for ValuesMut<'a> {
+    type Item = &'a mut Obj;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a mut Obj> {
+        self.0.next().map(|(_, val)| val)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+/// Collects map entries in any order, sorting them only once at the end for few entries
+pub(crate) enum MapBuilder {
+    Small(Vec<(Obj, Obj)>),
+    Tree(BTreeMap<Obj, Obj>)
+}
+
+impl MapBuilder {
+    #[inline]
+    pub(crate) fn new(capacity: usize) -> Self {
+        if capacity <= SMALL_MAP_LIMIT {
+            MapBuilder::Small(Vec::with_capacity(capacity))
+        } else {
+            MapBuilder::Tree(BTreeMap::new())
+        }
+    }
+
+    pub(crate) fn insert(&mut self, key: Obj, val: Obj) {
+        match *self {
+            MapBuilder::Tree(ref mut map) => {
+                map.insert(key, val);
+                return;
+            },
+            MapBuilder::Small(ref mut entries) if entries.len() < SMALL_MAP_LIMIT => {
+                entries.push((key, val));
+                return;
+            },
+            MapBuilder::Small(_) => ()
+        }
+        if let MapBuilder::Small(entries) = mem::replace(self, MapBuilder::Tree(BTreeMap::new())) {
+            let mut map: BTreeMap<Obj, Obj> = entries.into_iter().collect();
+            map.insert(key, val);
+            *self = MapBuilder::Tree(map);
+        }
+    }
+
+    pub(crate) fn finish_map(self) -> ObjMap {
+        match self {
+            MapBuilder::Tree(map) => ObjMap::from(map),
+            MapBuilder::Small(mut entries) => {
+                // Like inserting into a `BTreeMap`: the first key and the last value are kept
+                entries.sort_by(|a, b| a.0.cmp(&b.0));
+                let mut res: Vec<(Obj, Obj)> = Vec::with_capacity(entries.len());
+                for (key, val) in entries {
+                    match res.last_mut() {
+                        Some(last) if last.0 == key => last.1 = val,
+                        _ => res.push((key, val))
+                    }
+                }
+                ObjMap(Repr::Small(res))
+            }
+        }
+    }
+
+    #[inline]
+    pub(crate) fn finish(self) -> Obj {
+        Obj::Map(self.finish_map())
+    }
+}
@@ -0,0 +1,302 @@
+use std::borrow::Cow;
+use std::mem;
+
+use generic::{Obj, ObjKind};
+use map::ObjMap;
+use path::{ObjPath, PathSegment};
+use extract::ExtractError;
+
+/// The maximum number of `Null` values that a list is padded with when creating missing values
+const MAX_PADDING: usize = 1 << 16;
+
+fn error<M: ::std::fmt::Display>(path: &[PathSegment], expected: &'static str, found: Option<ObjKind>, message: M) -> ExtractError {
+    ExtractError::new(ObjPath::from(path.to_vec()), expected, found, message)
+}
+
+fn wrong_kind(path: &[PathSegment], segment: &PathSegment, obj: &Obj) -> ExtractError {
+    match *segment {
+        PathSegment::Key(_) => error(path, "map", Some(obj.kind()), format_args!("expected a map, found {}", obj.kind())),
+        PathSegment::Index(_) => error(path, "list", Some(obj.kind()), format_args!("expected a list, found {}", obj.kind()))
+    }
+}
+
+fn not_found(path: &[PathSegment], segment: &PathSegment) -> ExtractError {
+    match *segment {
+        PathSegment::Key(ref key) => error(path, "map", None, format_args!("key {} not found", key)),
+        PathSegment::Index(index) => error(path, "list", None, format_args!("index {} out of range", index))
+    }
+}
+
+/// Fails if padding a list of the given length up to the index would add too many values
+fn check_padding(path: &[PathSegment], index: usize, len: usize) -> Result<(), ExtractError> {
+    if index > len && index - len > MAX_PADDING {
+        return Err(error(path, "list", None, format_args!("index {} is too far beyond the end of the list", index)));
+    }
+    Ok(())
+}
+
+/// Replaces `Null` with an empty map or list depending on the segment that follows
+fn create(obj: &mut Obj, segment: &PathSegment) {
+    if let Obj::Null = *obj {
+        *obj = match *segment {
+            PathSegment::Key(_) => Obj::Map(ObjMap::new()),
+            PathSegment::Index(_) => Obj::List(Vec::new())
+        };
+    }
+}
+
+/// Returns the elements of a list, `U64Array` and `F64Array` values are converted into lists
+fn list_mut(obj: &mut Obj) -> Option<&mut Vec<Obj>> {
+    if let Obj::U64Array(_) | Obj::F64Array(_) = *obj {
+        let list = mem::take(obj).into_list().unwrap_or_default();
+        *obj = Obj::List(list);
+    }
+    match *obj {
+        Obj::List(ref mut list) => Some(list),
+        _ => None
+    }
+}
+
+fn element(obj: &Obj, index: usize) -> Option<Cow<'_, Obj>> {
+    match *obj {
+        Obj::List(ref list) => list.get(index).map(Cow::Borrowed),
+        Obj::U64Array(ref list) => list.get(index).map(|&val| Cow::Owned(Obj::Unsigned(val))),
+        Obj::F64Array(ref list) => list.get(index).map(|&val| Cow::Owned(Obj::Float(val))),
+        _ => None
+    }
+}
+
+fn list_len(obj: &Obj) -> usize {
+    match *obj {
+        Obj::List(ref list) => list.len(),
+        Obj::U64Array(ref list) => list.len(),
+        Obj::F64Array(ref list) => list.len(),
+        _ => 0
+    }
+}
+
+fn entry<'a>(obj: &'a Obj, key: &Obj) -> Option<&'a Obj> {
+    match *obj {
+        Obj::Map(ref map) => map.get(key),
+        _ => None
+    }
+}
+
+fn entry_mut<'a>(obj: &'a mut Obj, key: &Obj) -> Option<&'a mut Obj> {
+    match *obj {
+        Obj::Map(ref mut map) => map.get_mut(key),
+        _ => None
+    }
+}
+
+fn insert_entry(obj: &mut Obj, key: Obj, val: Obj) -> Option<Obj> {
+    match *obj {
+        Obj::Map(ref mut map) => map.insert(key, val),
+        _ => unreachable!()
+    }
+}
+
+fn remove_entry(obj: &mut Obj, key: &Obj) -> Option<Obj> {
+    match *obj {
+        Obj::Map(ref mut map) => map.remove(key),
+        _ => None
+    }
+}
+
+#[inline]
+fn is_map(obj: &Obj) -> bool {
+    matches!(*obj, Obj::Map(_))
+}
+
+#[inline]
+fn is_list(obj: &Obj) -> bool {
+    matches!(*obj, Obj::List(_) | Obj::U64Array(_) | Obj::F64Array(_))
+}
+
+impl Obj {
+    /// Returns a mutable reference to the value at the given path, or `None` if it does not exist
+    ///
+    /// `U64Array` and `F64Array` values along the path are converted into lists so that their
+    /// elements can be modified.
+    pub fn get_path_mut(&mut self, path: &ObjPath) -> Option<&mut Obj> {
+        self.descend(path.segments(), false).ok()
+    }
+
+    /// Sets the value at the given path and returns the previous value
+    ///
+    /// The parent of the value must exist. Map entries are inserted if the key is missing, list
+    /// elements can be appended by using the length of the list as position. The empty path
+    /// replaces the value itself. Fails if the parent does not exist, is not a map or list as
+    /// required by the last segment or if the position is larger than the length of the list.
+    ///
+    /// `U64Array` and `F64Array` values along the path are converted into lists.
+    #[inline]
+    pub fn set_path(&mut self, path: &ObjPath, value: Obj) -> Result<Option<Obj>, ExtractError> {
+        self.set_at(path.segments(), value, false)
+    }
+
+    /// Like [`set_path`](#method.set_path), but creates missing values along the path
+    ///
+    /// Missing map entries and `Null` values are created as maps or lists depending on whether
+    /// the next segment is a key or a position, lists are padded with `Null` up to the position.
+    /// Existing values of other kinds are not replaced, setting a value below them still fails.
+    /// Positions more than 65536 elements beyond the end of a list are rejected as well. The path
+    /// is checked before anything is created, so nothing is changed if setting the value fails.
+    ///
+    /// ```ignore
+    /// let mut obj = Obj::Null;
+    /// obj.set_path_create(&path, Obj::Unsigned(1))?;
+    /// ```
+    #[inline]
+    pub fn set_path_create(&mut self, path: &ObjPath, value: Obj) -> Result<Option<Obj>, ExtractError> {
+        self.set_at(path.segments(), value, true)
+    }
+
+    /// Inserts a value at the given path
+    ///
+    /// In lists, the value is inserted at the position and the following elements are moved back,
+    /// the position may be the length of the list. In maps, the entry is added and the insertion
+    /// fails if the key already exists. The parent of the value must exist and the path must not
+    /// be empty.
+    pub fn insert_path(&mut self, path: &ObjPath, value: Obj) -> Result<(), ExtractError> {
+        let (last, parent_path) = match path.segments().split_last() {
+            Some(split) => split,
+            None => return Err(error(&[], "map", Some(self.kind()), "can not insert at the empty path"))
+        };
+        let parent = self.descend(parent_path, false)?;
+        match *last {
+            PathSegment::Key(ref key) if is_map(parent) => {
+                if entry_mut(parent, key).is_some() {
+                    return Err(error(parent_path, "map", Some(ObjKind::Map), format_args!("key {} already exists", key)));
+                }
+                insert_entry(parent, key.clone(), value);
+                Ok(())
+            },
+            PathSegment::Index(index) if is_list(parent) => match list_mut(parent) {
+                Some(list) if index <= list.len() => {
+                    list.insert(index, value);
+                    Ok(())
+                },
+                _ => Err(not_found(parent_path, last))
+            },
+            _ => Err(wrong_kind(parent_path, last, parent))
+        }
+    }
+
+    /// Removes the value at the given path and returns it, or `None` if it does not exist
+    ///
+    /// Map entries are removed including their key, the following elements of lists are moved
+    /// forward. The empty path removes nothing, the value itself can not be removed.
+    pub fn remove_path(&mut self, path: &ObjPath) -> Option<Obj> {
+        let (last, parent_path) = path.segments().split_last()?;
+        let parent = self.descend(parent_path, false).ok()?;
+        match *last {
+            PathSegment::Key(ref key) => remove_entry(parent, key),
+            PathSegment::Index(index) => {
+                let list = list_mut(parent)?;
+                if index < list.len() {
+                    Some(list.remove(index))
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    fn set_at(&mut self, path: &[PathSegment], value: Obj, create_missing: bool) -> Result<Option<Obj>, ExtractError> {
+        let (last, parent_path) = match path.split_last() {
+            Some(split) => split,
+            None => return Ok(Some(mem::replace(self, value)))
+        };
+        if create_missing {
+            self.check_create(path)?;
+        }
+        let parent = self.descend(parent_path, create_missing)?;
+        if create_missing {
+            create(parent, last);
+        }
+        match *last {
+            PathSegment::Key(ref key) if is_map(parent) => Ok(insert_entry(parent, key.clone(), value)),
+            PathSegment::Index(index) if is_list(parent) => match list_mut(parent) {
+                Some(list) if index < list.len() => Ok(Some(mem::replace(&mut list[index], value))),
+                Some(list) if index == list.len() || create_missing => {
+                    list.resize(index, Obj::Null);
+                    list.push(value);
+                    Ok(None)
+                },
+                _ => Err(not_found(parent_path, last))
+            },
+            _ => Err(wrong_kind(parent_path, last, parent))
+        }
+    }
+
+    /// Checks that `set_at` can create the missing values along the path without failing
+    fn check_create(&self, path: &[PathSegment]) -> Result<(), ExtractError> {
+        // `None` once the rest of the path consists of newly created values
+        let mut obj = Some(Cow::Borrowed(self));
+        for (i, segment) in path.iter().enumerate() {
+            let current = match obj {
+                Some(Cow::Borrowed(&Obj::Null)) | None => None,
+                current => current
+            };
+            obj = match (segment, current) {
+                (PathSegment::Key(key), Some(Cow::Borrowed(map))) if is_map(map) => entry(map, key).map(Cow::Borrowed),
+                (&PathSegment::Index(index), Some(Cow::Borrowed(list))) if is_list(list) => match element(list, index) {
+                    Some(val) => Some(val),
+                    None => {
+                        check_padding(&path[..i], index, list_len(list))?;
+                        None
+                    }
+                },
+                (_, Some(val)) => return Err(wrong_kind(&path[..i], segment, &val)),
+                (PathSegment::Key(_), None) => None,
+                (&PathSegment::Index(index), None) => {
+                    check_padding(&path[..i], index, 0)?;
+                    None
+                }
+            };
+        }
+        Ok(())
+    }
+
+    /// Follows the path, creating missing values if requested
+    fn descend(&mut self, path: &[PathSegment], create_missing: bool) -> Result<&mut Obj, ExtractError> {
+        let mut obj = self;
+        for (i, segment) in path.iter().enumerate() {
+            if create_missing {
+                create(obj, segment);
+            }
+            obj = match *segment {
+                PathSegment::Key(ref key) => {
+                    if !is_map(obj) {
+                        return Err(wrong_kind(&path[..i], segment, obj));
+                    }
+                    if create_missing && entry_mut(obj, key).is_none() {
+                        insert_entry(obj, key.clone(), Obj::Null);
+                    }
+                    match entry_mut(obj, key) {
+                        Some(val) => val,
+                        None => return Err(not_found(&path[..i], segment))
+                    }
+                },
+                PathSegment::Index(index) => {
+                    if !is_list(obj) {
+                        return Err(wrong_kind(&path[..i], segment, obj));
+                    }
+                    let list = match list_mut(obj) {
+                        Some(list) => list,
+                        None => unreachable!()
+                    };
+                    if create_missing && index >= list.len() {
+                        list.resize(index + 1, Obj::Null);
+                    }
+                    match list.get_mut(index) {
+                        Some(val) => val,
+                        None => return Err(not_found(&path[..i], segment))
+                    }
+                }
+            };
+        }
+        Ok(obj)
+    }
+}
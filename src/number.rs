@@ -0,0 +1,211 @@
+use std::fmt;
+
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::de::{Visitor, Error};
+
+use generic::Obj;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum N {
+    PosInt(u64),
+    NegInt(i64),
+    Float(f64)
+}
+
+/// A number stored in an [`Obj`](enum.Obj.html), independent of its variant
+///
+/// `Obj` stores numbers as `Signed`, `Unsigned` or `Float`, and a non-negative integer can be
+/// stored either as `Signed` or `Unsigned`. A `Number` normalizes this, i.e. non-negative integers
+/// are always treated as unsigned and negative integers as signed. It is obtained via
+/// [`Obj::as_number`](enum.Obj.html#method.as_number) and converts back into the canonical
+/// variant.
+///
+/// ```ignore
+/// let id = obj["id"].as_number().and_then(|num| num.as_u64());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Number(N);
+
+impl Number {
+    /// Returns the number as `u64` if it is a non-negative integer
+    #[inline]
+    pub fn as_u64(&self) -> Option<u64> {
+        match self.0 {
+            N::PosInt(val) => Some(val),
+            _ => None
+        }
+    }
+
+    /// Returns the number as `i64` if it is an integer in the range of `i64`
+    #[inline]
+    pub fn as_i64(&self) -> Option<i64> {
+        match self.0 {
+            N::PosInt(val) if val <= i64::MAX as u64 => Some(val as i64),
+            N::NegInt(val) => Some(val),
+            _ => None
+        }
+    }
+
+    /// Returns the number as `f64`, large integers may lose precision
+    #[inline]
+    pub fn as_f64(&self) -> f64 {
+        match self.0 {
+            N::PosInt(val) => val as f64,
+            N::NegInt(val) => val as f64,
+            N::Float(val) => val
+        }
+    }
+
+    /// Returns whether the number is an integer, i.e. not stored as float
+    #[inline]
+    pub fn is_integer(&self) -> bool {
+        !self.is_float()
+    }
+
+    /// Returns whether the number is stored as float
+    #[inline]
+    pub fn is_float(&self) -> bool {
+        matches!(self.0, N::Float(_))
+    }
+}
+
+macro_rules! impl_from_unsigned(
+    ( $( $ty:ty ),* ) => {
+        $(
+            impl From<$ty> for Number {
+                #[inline]
+                fn from(val: $ty) -> Self {
+                    Number(N::PosInt(val as u64))
+                }
+            }
+        )*
+    };
+);
+
+impl_from_unsigned!(u8, u16, u32, u64, usize);
+
+macro_rules! impl_from_signed(
+    ( $( $ty:ty ),* ) => {
+        $(
+            impl From<$ty> for Number {
+                #[inline]
+                fn from(val: $ty) -> Self {
+                    if val >= 0 {
+                        Number(N::PosInt(val as u64))
+                    } else {
+                        Number(N::NegInt(val as i64))
+                    }
+                }
+            }
+        )*
+    };
+);
+
+impl_from_signed!(i8, i16, i32, i64, isize);
+
+impl From<f32> for Number {
+    #[inline]
+    fn from(val: f32) -> Self {
+        Number(N::Float(f64::from(val)))
+    }
+}
+
+impl From<f64> for Number {
+    #[inline]
+    fn from(val: f64) -> Self {
+        Number(N::Float(val))
+    }
+}
+
+impl From<Number> for Obj {
+    #[inline]
+    fn from(num: Number) -> Self {
+        match num.0 {
+            N::PosInt(val) => Obj::Unsigned(val),
+            N::NegInt(val) => Obj::Signed(val),
+            N::Float(val) => Obj::Float(val)
+        }
+    }
+}
+
+impl Obj {
+    /// Returns the value as number if it is `Signed`, `Unsigned` or `Float`
+    #[inline]
+    pub fn as_number(&self) -> Option<Number> {
+        match *self {
+            Obj::Unsigned(val) => Some(Number::from(val)),
+            Obj::Signed(val) => Some(Number::from(val)),
+            Obj::Float(val) => Some(Number::from(val)),
+            _ => None
+        }
+    }
+
+    /// Returns the value as `u64` if it is a non-negative integer, regardless of its variant
+    #[inline]
+    pub fn as_u64(&self) -> Option<u64> {
+        self.as_number().and_then(|num| num.as_u64())
+    }
+
+    /// Returns the value as `i64` if it is an integer in the range of `i64`, regardless of its
+    /// variant
+    #[inline]
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_number().and_then(|num| num.as_i64())
+    }
+
+    /// Returns the value as `f64` if it is a number, large integers may lose precision
+    #[inline]
+    pub fn as_f64(&self) -> Option<f64> {
+        self.as_number().map(|num| num.as_f64())
+    }
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            N::PosInt(val) => write!(f, "{}", val),
+            N::NegInt(val) => write!(f, "{}", val),
+            N::Float(val) => write!(f, "{}", val)
+        }
+    }
+}
+
+impl Serialize for Number {
+    #[inline]
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        match self.0 {
+            N::PosInt(val) => ser.serialize_u64(val),
+            N::NegInt(val) => ser.serialize_i64(val),
+            N::Float(val) => ser.serialize_f64(val)
+        }
+    }
+}
+
+struct NumberVisitor;
+
+impl<'de> Visitor<'de> for NumberVisitor {
+    type Value = Number;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("number")
+    }
+
+    fn visit_u64<E: Error>(self, val: u64) -> Result<Self::Value, E> {
+        Ok(Number::from(val))
+    }
+
+    fn visit_i64<E: Error>(self, val: i64) -> Result<Self::Value, E> {
+        Ok(Number::from(val))
+    }
+
+    fn visit_f64<E: Error>(self, val: f64) -> Result<Self::Value, E> {
+        Ok(Number::from(val))
+    }
+}
+
+impl<'de> Deserialize<'de> for Number {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        de.deserialize_any(NumberVisitor)
+    }
+}
@@ -0,0 +1,195 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::de::{Deserialize, Deserializer, DeserializeSeed, Visitor, SeqAccess, MapAccess, IgnoredAny, Error};
+
+use generic::Obj;
+
+#[derive(Debug, Clone, Default)]
+struct Selection {
+    all: bool,
+    children: BTreeMap<String, Selection>,
+    any: Option<Box<Selection>>
+}
+
+impl Selection {
+    fn insert(&mut self, path: &str) {
+        let mut sel = self;
+        if !path.is_empty() {
+            for segment in path.split('/') {
+                if sel.all {
+                    return;
+                }
+                sel = if segment == "*" {
+                    sel.any.get_or_insert_with(Default::default)
+                } else {
+                    sel.children.entry(segment.to_string()).or_default()
+                };
+            }
+        }
+        sel.all = true;
+    }
+}
+
+/// Collects the selections of all paths that continue with a segment
+fn select<'a, F: Fn(&str) -> bool>(sels: &[&'a Selection], matches: F) -> Vec<&'a Selection> {
+    let mut res = Vec::new();
+    for sel in sels {
+        res.extend(sel.children.iter().filter(|&(segment, _)| matches(segment)).map(|(_, child)| child));
+        if let Some(ref any) = sel.any {
+            res.push(any);
+        }
+    }
+    res
+}
+
+fn matches_key(key: &Obj, segment: &str) -> bool {
+    match *key {
+        Obj::Str(ref key) => key == segment,
+        Obj::Unsigned(key) => segment.parse() == Ok(key),
+        Obj::Signed(key) => segment.parse() == Ok(key),
+        _ => false
+    }
+}
+
+/// A seed that deserializes only selected paths of a document into an [`Obj`](enum.Obj.html)
+///
+/// The paths are given in the syntax of [`Obj::pointer`](enum.Obj.html#method.pointer), e.g.
+/// `users/0/name`, and `*` matches any map key or list position. Map entries outside the selected
+/// paths are skipped without being materialized and list elements outside of them are replaced by
+/// `Null` so that positions are kept. Values that are not lists or maps but where a longer path
+/// was selected are also replaced by `Null`. The result can be queried like the full document.
+///
+/// ```ignore
+/// let seed = PartialObj::new(["id", "users/*/name"]);
+/// let obj = seed.deserialize(&mut deserializer)?;
+/// let name: String = obj.get_as("users/0/name")?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PartialObj {
+    root: Selection
+}
+
+impl PartialObj {
+    /// Creates a seed that selects the given paths
+    pub fn new<I: IntoIterator<Item=S>, S: AsRef<str>>(paths: I) -> Self {
+        let mut root = Selection::default();
+        for path in paths {
+            root.insert(path.as_ref());
+        }
+        PartialObj { root }
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for &PartialObj {
+    type Value = Obj;
+
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(self, de: D) -> Result<Obj, D::Error> {
+        SelectionSeed(vec![&self.root]).deserialize(de)
+    }
+}
+
+struct SelectionSeed<'a>(Vec<&'a Selection>);
+
+impl<'a, 'de> DeserializeSeed<'de> for SelectionSeed<'a> {
+    type Value = Obj;
+
+    fn deserialize<D: Deserializer<'de>>(self, de: D) -> Result<Obj, D::Error> {
+        if self.0.iter().any(|sel| sel.all) {
+            Obj::deserialize(de)
+        } else {
+            de.deserialize_any(self)
+        }
+    }
+}
+
+impl<'a, 'de> Visitor<'de> for SelectionSeed<'a> {
+    type Value = Obj;
+
+    #[inline]
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "any value")
+    }
+
+    #[inline]
+    fn visit_none<E: Error>(self) -> Result<Self::Value, E> {
+        Ok(Obj::Null)
+    }
+
+    #[inline]
+    fn visit_some<D: Deserializer<'de>>(self, de: D) -> Result<Self::Value, D::Error> {
+        self.deserialize(de)
+    }
+
+    #[inline]
+    fn visit_newtype_struct<D: Deserializer<'de>>(self, de: D) -> Result<Self::Value, D::Error> {
+        self.deserialize(de)
+    }
+
+    #[inline]
+    fn visit_bool<E: Error>(self, _val: bool) -> Result<Self::Value, E> {
+        Ok(Obj::Null)
+    }
+
+    #[inline]
+    fn visit_u64<E: Error>(self, _val: u64) -> Result<Self::Value, E> {
+        Ok(Obj::Null)
+    }
+
+    #[inline]
+    fn visit_i64<E: Error>(self, _val: i64) -> Result<Self::Value, E> {
+        Ok(Obj::Null)
+    }
+
+    #[inline]
+    fn visit_f64<E: Error>(self, _val: f64) -> Result<Self::Value, E> {
+        Ok(Obj::Null)
+    }
+
+    #[inline]
+    fn visit_str<E: Error>(self, _val: &str) -> Result<Self::Value, E> {
+        Ok(Obj::Null)
+    }
+
+    #[inline]
+    fn visit_bytes<E: Error>(self, _val: &[u8]) -> Result<Self::Value, E> {
+        Ok(Obj::Null)
+    }
+
+    #[inline]
+    fn visit_unit<E: Error>(self) -> Result<Self::Value, E> {
+        Ok(Obj::Null)
+    }
+
+    fn visit_seq<V: SeqAccess<'de>>(self, mut visitor: V) -> Result<Self::Value, V::Error> {
+        let mut list = Vec::with_capacity(visitor.size_hint().unwrap_or(0));
+        loop {
+            let index = list.len();
+            let sels = select(&self.0, |segment| segment.parse() == Ok(index));
+            let value = if sels.is_empty() {
+                visitor.next_element::<IgnoredAny>()?.map(|_| Obj::Null)
+            } else {
+                visitor.next_element_seed(SelectionSeed(sels))?
+            };
+            match value {
+                Some(value) => list.push(value),
+                None => return Ok(Obj::from_list(list))
+            }
+        }
+    }
+
+    fn visit_map<V: MapAccess<'de>>(self, mut visitor: V) -> Result<Self::Value, V::Error> {
+        let mut map = BTreeMap::new();
+        while let Some(key) = visitor.next_key::<Obj>()? {
+            let sels = select(&self.0, |segment| matches_key(&key, segment));
+            if sels.is_empty() {
+                visitor.next_value::<IgnoredAny>()?;
+            } else {
+                let value = visitor.next_value_seed(SelectionSeed(sels))?;
+                map.insert(key, value);
+            }
+        }
+        Ok(Obj::from_map(map))
+    }
+}
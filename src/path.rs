@@ -0,0 +1,94 @@
+use std::fmt;
+
+use generic::Obj;
+
+/// A single step of an [`ObjPath`](struct.ObjPath.html)
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PathSegment {
+    /// The key of a map entry
+    Key(Obj),
+
+    /// The position of a list element
+    Index(usize)
+}
+
+impl fmt::Display for PathSegment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PathSegment::Key(ref key) => write!(f, "{}", key),
+            PathSegment::Index(index) => write!(f, "{}", index)
+        }
+    }
+}
+
+/// The location of a value inside an [`Obj`](enum.Obj.html) tree
+///
+/// A path consists of the map keys and list positions leading from the root to the value. The
+/// empty path refers to the root itself. Paths are displayed with their segments separated by
+/// slashes, e.g. `users/0/name`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct ObjPath(Vec<PathSegment>);
+
+impl ObjPath {
+    /// Creates an empty path that refers to the root
+    #[inline]
+    pub fn new() -> Self {
+        ObjPath(Vec::new())
+    }
+
+    /// Returns the segments of the path
+    #[inline]
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.0
+    }
+
+    /// Returns the number of segments
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns whether this path refers to the root
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Appends a segment to the path
+    #[inline]
+    pub fn push(&mut self, segment: PathSegment) {
+        self.0.push(segment)
+    }
+
+    /// Removes and returns the last segment of the path
+    #[inline]
+    pub fn pop(&mut self) -> Option<PathSegment> {
+        self.0.pop()
+    }
+}
+
+impl Extend<PathSegment> for ObjPath {
+    #[inline]
+    fn extend<I: IntoIterator<Item=PathSegment>>(&mut self, iter: I) {
+        self.0.extend(iter)
+    }
+}
+
+impl From<Vec<PathSegment>> for ObjPath {
+    #[inline]
+    fn from(segments: Vec<PathSegment>) -> Self {
+        ObjPath(segments)
+    }
+}
+
+impl fmt::Display for ObjPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, segment) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str("/")?;
+            }
+            write!(f, "{}", segment)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,188 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+
+use generic::Obj;
+
+/// A policy for comparing and hashing floats inside an [`Obj`](enum.Obj.html)
+///
+/// Floats do not have a total order, so `NaN` and the sign of zero need special treatment to
+/// implement `Eq`, `Ord`, and `Hash`. Implementations must be consistent, i.e. values that are
+/// equal must have the same ordering and hash.
+pub trait FloatPolicy {
+    /// Compares two floats
+    fn cmp(a: f64, b: f64) -> Ordering;
+
+    /// Hashes a float
+    fn hash<H: Hasher>(val: f64, state: &mut H);
+
+    /// Checks two floats for equality
+    #[inline]
+    fn eq(a: f64, b: f64) -> bool {
+        Self::cmp(a, b) == Ordering::Equal
+    }
+}
+
+/// The float policy of `Obj`: all `NaN` values are equal and greater than all other floats
+///
+/// Otherwise floats are compared by value, so `-0.0` is equal to `0.0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct NanGreatest;
+
+impl FloatPolicy for NanGreatest {
+    fn cmp(a: f64, b: f64) -> Ordering {
+        match (a.is_nan(), b.is_nan()) {
+            (false, false) => a.partial_cmp(&b).unwrap(),
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less
+        }
+    }
+
+    fn hash<H: Hasher>(val: f64, state: &mut H) {
+        let val = if val.is_nan() {
+            f64::NAN
+        } else if val == 0.0 {
+            0.0
+        } else {
+            val
+        };
+        state.write_u64(val.to_bits())
+    }
+}
+
+/// The `totalOrder` predicate of IEEE 754 as implemented by `f64::total_cmp`
+///
+/// Floats are ordered as `-NaN < -Infinity < ... < -0.0 < 0.0 < ... < Infinity < NaN`. Floats are
+/// only equal if they have the same bit pattern, so `-0.0` is different from `0.0` and `NaN` values
+/// with different payloads are different.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct TotalOrder;
+
+impl FloatPolicy for TotalOrder {
+    #[inline]
+    fn cmp(a: f64, b: f64) -> Ordering {
+        a.total_cmp(&b)
+    }
+
+    #[inline]
+    fn hash<H: Hasher>(val: f64, state: &mut H) {
+        state.write_u64(val.to_bits())
+    }
+}
+
+/// A wrapper for [`Obj`](enum.Obj.html) that compares and hashes floats according to a policy
+///
+/// The policy applies to all floats in the tree including map keys. Note that the entries of maps
+/// are still sorted according to the default policy of `Obj`, which only differs in the position
+/// of `NaN` and the sign of zero. Serialization and deserialization are the same as for `Obj`.
+///
+/// ```ignore
+/// let mut keys: Vec<TotalOrdObj> = objs.into_iter().map(TotalOrdObj::new).collect();
+/// keys.sort();
+/// ```
+#[derive(Clone, Default)]
+pub struct PolicyObj<P> {
+    obj: Obj,
+    policy: PhantomData<P>
+}
+
+/// An [`Obj`](enum.Obj.html) that uses the [`TotalOrder`](struct.TotalOrder.html) float policy
+pub type TotalOrdObj = PolicyObj<TotalOrder>;
+
+impl<P> PolicyObj<P> {
+    /// Wraps the value
+    #[inline]
+    pub fn new(obj: Obj) -> Self {
+        PolicyObj { obj, policy: PhantomData }
+    }
+
+    /// Returns the wrapped value
+    #[inline]
+    pub fn into_inner(self) -> Obj {
+        self.obj
+    }
+}
+
+impl<P> From<Obj> for PolicyObj<P> {
+    #[inline]
+    fn from(obj: Obj) -> Self {
+        PolicyObj::new(obj)
+    }
+}
+
+impl<P> Deref for PolicyObj<P> {
+    type Target = Obj;
+
+    #[inline]
+    fn deref(&self) -> &Obj {
+        &self.obj
+    }
+}
+
+impl<P> DerefMut for PolicyObj<P> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Obj {
+        &mut self.obj
+    }
+}
+
+impl<P: FloatPolicy> PartialEq for PolicyObj<P> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.obj.eq_with::<P>(&other.obj)
+    }
+}
+
+impl<P: FloatPolicy> Eq for PolicyObj<P> {}
+
+impl<P: FloatPolicy> PartialOrd for PolicyObj<P> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<P: FloatPolicy> Ord for PolicyObj<P> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.obj.cmp_with::<P>(&other.obj)
+    }
+}
+
+impl<P: FloatPolicy> Hash for PolicyObj<P> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.obj.hash_with::<P, H>(state)
+    }
+}
+
+impl<P> fmt::Debug for PolicyObj<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.obj, f)
+    }
+}
+
+impl<P> fmt::Display for PolicyObj<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.obj, f)
+    }
+}
+
+impl<P> Serialize for PolicyObj<P> {
+    #[inline]
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        self.obj.serialize(ser)
+    }
+}
+
+impl<'de, P> Deserialize<'de> for PolicyObj<P> {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        Obj::deserialize(de).map(PolicyObj::new)
+    }
+}
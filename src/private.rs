@@ -0,0 +1,980 @@
+//! Helpers used by the code generated by the macros of this crate. Not public API.
+
+/// The generated code refers to serde through this path, so the callers do not need to depend on
+/// serde under its own name.
+pub extern crate serde;
+
+use std::marker::PhantomData;
+use std::borrow::Cow;
+use std::convert::TryInto;
+use std::fmt;
+use std::str;
+
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::ser;
+use serde::de::{Error, Unexpected, Visitor, DeserializeSeed, SeqAccess, IntoDeserializer};
+use serde::de::value::BytesDeserializer;
+use serde_bytes::ByteBuf;
+
+use generic::Obj;
+use map::ObjMap;
+use extract::ExtractError;
+use path::ObjPath;
+use ignore::Ignore;
+use key::{Normalize, NormalizedKey};
+use deserializer::ObjDeserializer;
+use serializer::to_obj;
+use diff::diff;
+use diagnostics::{self, Diagnostic};
+
+/// Checks whether any two of the given keys are equal.
+///
+/// The macros pass the stringified keys to this function, so keys are compared by their spelling.
+pub const fn has_duplicate_keys(keys: &[&str]) -> bool {
+    let mut i = 0;
+    while i < keys.len() {
+        let mut j = i + 1;
+        while j < keys.len() {
+            if str_eq(keys[i], keys[j]) {
+                return true;
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+    false
+}
+
+/// Checks whether any of the keys is one of the reserved keys, compared by their spelling as well.
+pub const fn uses_reserved_key(keys: &[&str], reserved: &[&str]) -> bool {
+    let mut i = 0;
+    while i < keys.len() {
+        let mut j = 0;
+        while j < reserved.len() {
+            if str_eq(keys[i], reserved[j]) {
+                return true;
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+    false
+}
+
+const fn str_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// The cases that `rename_all` converts automatic keys to.
+#[derive(Clone, Copy)]
+pub enum RenameCase {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab
+}
+
+impl RenameCase {
+    /// Parses the case given to `rename_all`, using the names of serde.
+    pub const fn parse(case: &str) -> Self {
+        const CASES: [(&str, RenameCase); 8] = [
+            ("lowercase", RenameCase::Lower),
+            ("UPPERCASE", RenameCase::Upper),
+            ("PascalCase", RenameCase::Pascal),
+            ("camelCase", RenameCase::Camel),
+            ("snake_case", RenameCase::Snake),
+            ("SCREAMING_SNAKE_CASE", RenameCase::ScreamingSnake),
+            ("kebab-case", RenameCase::Kebab),
+            ("SCREAMING-KEBAB-CASE", RenameCase::ScreamingKebab)
+        ];
+        let mut i = 0;
+        while i < CASES.len() {
+            if str_eq(CASES[i].0, case) {
+                return CASES[i].1;
+            }
+            i += 1;
+        }
+        panic!("unknown case for rename_all, expected one of: lowercase, UPPERCASE, PascalCase, camelCase, \
+            snake_case, SCREAMING_SNAKE_CASE, kebab-case, SCREAMING-KEBAB-CASE")
+    }
+}
+
+/// Converts the byte at position `i` of a field or variant name to the case.
+///
+/// Words are separated by underscores and start at uppercase letters that follow lowercase letters
+/// or digits, so both `snake_case` field names and `PascalCase` variant names are split correctly.
+/// `words` is the number of words before the byte. Returns the updated number of words, the
+/// separator to write before the byte and the converted byte, where `0` means nothing is written.
+const fn rename_step(name: &[u8], case: RenameCase, i: usize, words: usize) -> (usize, u8, u8) {
+    let (sep, upper_first, upper_rest) = match case {
+        RenameCase::Lower | RenameCase::Upper => (0, false, false),
+        RenameCase::Pascal | RenameCase::Camel => (0, true, false),
+        RenameCase::Snake => (b'_', false, false),
+        RenameCase::ScreamingSnake => (b'_', true, true),
+        RenameCase::Kebab => (b'-', false, false),
+        RenameCase::ScreamingKebab => (b'-', true, true)
+    };
+    let c = name[i];
+    if let RenameCase::Lower | RenameCase::Upper = case {
+        // The name is kept as it is apart from the case of the letters
+        let c = if let RenameCase::Upper = case { c.to_ascii_uppercase() } else { c.to_ascii_lowercase() };
+        return (words, 0, c);
+    }
+    if c == b'_' {
+        return (words, 0, 0);
+    }
+    let word_start = i == 0 || name[i - 1] == b'_'
+        || (c.is_ascii_uppercase() && (name[i - 1].is_ascii_lowercase() || name[i - 1].is_ascii_digit()));
+    if !word_start {
+        return (words, 0, if upper_rest { c.to_ascii_uppercase() } else { c.to_ascii_lowercase() });
+    }
+    let sep = if words > 0 { sep } else { 0 };
+    let upper = upper_first && !(words == 0 && matches!(case, RenameCase::Camel));
+    (words + 1, sep, if upper { c.to_ascii_uppercase() } else { c.to_ascii_lowercase() })
+}
+
+/// Returns the length of the name converted to the case.
+pub const fn renamed_len(name: &str, case: RenameCase) -> usize {
+    let name = name.as_bytes();
+    let mut len = 0;
+    let mut words = 0;
+    let mut i = 0;
+    while i < name.len() {
+        let (next_words, sep, c) = rename_step(name, case, i, words);
+        words = next_words;
+        len += (sep != 0) as usize + (c != 0) as usize;
+        i += 1;
+    }
+    len
+}
+
+/// Converts the name to the case, `N` must be the length returned by `renamed_len`.
+pub const fn rename<const N: usize>(name: &str, case: RenameCase) -> [u8; N] {
+    let name = name.as_bytes();
+    let mut out = [0; N];
+    let mut len = 0;
+    let mut words = 0;
+    let mut i = 0;
+    while i < name.len() {
+        let (next_words, sep, c) = rename_step(name, case, i, words);
+        words = next_words;
+        if sep != 0 {
+            out[len] = sep;
+            len += 1;
+        }
+        if c != 0 {
+            out[len] = c;
+            len += 1;
+        }
+        i += 1;
+    }
+    out
+}
+
+/// Formats a stringified key for error messages.
+///
+/// Automatic keys are written as `stringify!(name)` or `__serde_impl_rename!("case" name)`, they
+/// are shown as string literals instead.
+pub fn key_text(key: &str) -> Cow<'_, str> {
+    if let Some(pos) = key.find("__serde_impl_rename!") {
+        let rest = &key[pos + "__serde_impl_rename!".len()..];
+        let mut parts = rest.split('"');
+        if let (Some(_), Some(case), Some(rest)) = (parts.next(), parts.next(), parts.next()) {
+            if let Some(end) = rest.find(')') {
+                let name = rest[..end].trim();
+                let case = RenameCase::parse(case);
+                let mut out = Vec::with_capacity(renamed_len(name, case));
+                let mut words = 0;
+                for i in 0..name.len() {
+                    let (next_words, sep, c) = rename_step(name.as_bytes(), case, i, words);
+                    words = next_words;
+                    out.extend([sep, c].iter().filter(|&&b| b != 0));
+                }
+                let prefix = if rest[end + 1..].trim() == ".as_bytes()" { "b" } else { "" };
+                return Cow::Owned(format!("{}\"{}\"", prefix, String::from_utf8_lossy(&out)));
+            }
+        }
+    }
+    if let Some(rest) = key.strip_prefix("stringify!") {
+        let rest = rest.trim_start();
+        if let (Some(rest), Some(end)) = (rest.strip_prefix('('), rest.find(')')) {
+            let name = rest[..end - 1].trim();
+            let prefix = if rest[end..].trim() == ".as_bytes()" { "b" } else { "" };
+            return Cow::Owned(format!("{}\"{}\"", prefix, name));
+        }
+    }
+    Cow::Borrowed(key)
+}
+
+/// Wraps an error that occurred while deserializing a field or variant payload.
+pub fn field_error<E: Error>(err: E, ty: &str, field: &str, key: &str) -> E {
+    E::custom(format_args!("{}.{} (key {}): {}", ty, field, key_text(key), err))
+}
+
+/// Wraps an error that occurred while deserializing a value of the given type.
+pub fn type_error<E: Error>(err: E, ty: &str) -> E {
+    E::custom(format_args!("{}: {}", ty, err))
+}
+
+/// Reads the key of an enum from a bare value that is not wrapped in a tuple.
+pub fn bare_key<'de, K: Deserialize<'de>, V: IntoDeserializer<'de, E>, E: Error>(val: V, ty: &str) -> Result<K, E> {
+    K::deserialize(val.into_deserializer()).map_err(|err| type_error(err, ty))
+}
+
+fn key_list(keys: &[&str]) -> String {
+    keys.iter().map(|key| key_text(key)).collect::<Vec<_>>().join(", ")
+}
+
+/// Creates the error for an enum discriminator that matches no variant.
+pub fn invalid_discriminator<E: Error>(ty: &str, expected: &[&str]) -> E {
+    E::custom(format_args!("Invalid enum discriminator for {}, expected one of: {}", ty, key_list(expected)))
+}
+
+/// Creates the error for an unknown key in a strict map.
+pub fn unknown_key<E: Error>(ty: &str, expected: &[&str]) -> E {
+    E::custom(format_args!("{}: unknown key, expected one of: {}", ty, key_list(expected)))
+}
+
+/// Returns whether a diagnostics hook is installed, checked before collecting diagnostics.
+#[inline]
+pub fn diagnostics_enabled() -> bool {
+    diagnostics::enabled()
+}
+
+/// Reports an unknown key that has been skipped to the diagnostics hook.
+pub fn report_unknown_key(ty: &'static str, key: Option<String>) {
+    diagnostics::report(&Diagnostic::UnknownKey { ty, key: key.as_deref() });
+}
+
+/// Reports a field that has been set to its default value to the diagnostics hook.
+pub fn report_defaulted_field(ty: &'static str, field: &'static str, key: &'static str) {
+    diagnostics::report(&Diagnostic::DefaultedField { ty, field, key: &key_text(key) });
+}
+
+/// Wraps a key to format it only if its type implements `Debug`.
+///
+/// The macros call `(&&DebugKey(&key)).debug_text()` with both traits in scope, method resolution
+/// picks `DebugKeyText` if possible and falls back to `NoDebugKeyText` otherwise.
+pub struct DebugKey<'a, K: 'a>(pub &'a K);
+
+pub trait DebugKeyText {
+    fn debug_text(&self) -> Option<String>;
+}
+
+impl<'a, 'b, K: fmt::Debug> DebugKeyText for &'b DebugKey<'a, K> {
+    #[inline]
+    fn debug_text(&self) -> Option<String> {
+        Some(format!("{:?}", self.0))
+    }
+}
+
+pub trait NoDebugKeyText {
+    fn debug_text(&self) -> Option<String>;
+}
+
+impl<'a, K> NoDebugKeyText for DebugKey<'a, K> {
+    #[inline]
+    fn debug_text(&self) -> Option<String> {
+        None
+    }
+}
+
+/// Creates the error for an enum with payload that is not encoded as a tuple.
+pub fn not_a_tuple<E: Error>(ty: &str) -> E {
+    E::custom(format_args!("Enums must be encoded as tuples ({})", ty))
+}
+
+/// Creates the error for an enum with payload that is not encoded as a map with a single entry.
+pub fn not_a_single_entry_map<E: Error>(ty: &str) -> E {
+    E::custom(format_args!("Enums must be encoded as maps with a single entry ({})", ty))
+}
+
+/// Creates the error for serializing the fallback variant of an enum.
+pub fn fallback_variant<E: ser::Error>(ty: &str, variant: &str) -> E {
+    E::custom(format_args!("{}::{} is the fallback variant and can not be serialized", ty, variant))
+}
+
+/// Creates the error for a bitmask with bits that do not belong to any flag.
+pub fn unknown_bits<E: Error, B: fmt::LowerHex>(ty: &str, bits: B) -> E {
+    E::custom(format_args!("{}: unknown bits {:#x}", ty, bits))
+}
+
+/// Relates a key as written in a macro invocation to the key type `K` used on the wire.
+pub trait KeyLiteral<K> {
+    /// Checks whether the deserialized key matches this literal.
+    fn matches(&self, key: &K) -> bool;
+
+    /// Serializes this literal in the same way as the corresponding value of type `K`.
+    fn serialize_key<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error>;
+}
+
+impl<T: Serialize + PartialEq> KeyLiteral<T> for T {
+    #[inline]
+    fn matches(&self, key: &T) -> bool {
+        self == key
+    }
+
+    #[inline]
+    fn serialize_key<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        self.serialize(ser)
+    }
+}
+
+impl KeyLiteral<String> for &str {
+    #[inline]
+    fn matches(&self, key: &String) -> bool {
+        key == self
+    }
+
+    #[inline]
+    fn serialize_key<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        ser.serialize_str(self)
+    }
+}
+
+impl KeyLiteral<ByteBuf> for &[u8] {
+    #[inline]
+    fn matches(&self, key: &ByteBuf) -> bool {
+        &key[..] == *self
+    }
+
+    #[inline]
+    fn serialize_key<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        ser.serialize_bytes(self)
+    }
+}
+
+impl<const N: usize> KeyLiteral<ByteBuf> for &[u8; N] {
+    #[inline]
+    fn matches(&self, key: &ByteBuf) -> bool {
+        key[..] == self[..]
+    }
+
+    #[inline]
+    fn serialize_key<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        ser.serialize_bytes(&self[..])
+    }
+}
+
+impl<N: Normalize> KeyLiteral<NormalizedKey<N>> for &str {
+    #[inline]
+    fn matches(&self, key: &NormalizedKey<N>) -> bool {
+        N::normalize(self) == N::normalize(key.as_str())
+    }
+
+    #[inline]
+    fn serialize_key<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        ser.serialize_str(self)
+    }
+}
+
+/// A key literal that serializes like a value of the key type `K`.
+pub struct Key<'a, K, L: 'a>(&'a L, PhantomData<K>);
+
+impl<'a, K, L: KeyLiteral<K>> Serialize for Key<'a, K, L> {
+    #[inline]
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize_key(ser)
+    }
+}
+
+/// Wraps a key literal so that it serializes like a value of the key type `K`.
+#[inline]
+pub fn key<K, L: KeyLiteral<K>>(lit: &L) -> Key<'_, K, L> {
+    Key(lit, PhantomData)
+}
+
+/// A key of a struct with readable keys, either the name of a field or a key of type `K`.
+///
+/// Names are strings, so a string that is a number can also be a compact key written by a format
+/// that only supports string keys like JSON. Its number is kept to compare it with the keys.
+#[derive(Debug)]
+pub enum ReadableKey<K> {
+    /// A string key and its number if it is one
+    Name(String, Option<Obj>),
+    /// Any other key
+    Key(K)
+}
+
+impl<K> ReadableKey<K> {
+    /// Checks whether the key is the name or the key literal of a field.
+    pub fn matches<L: KeyLiteral<K>>(&self, lit: &L, name: &str) -> bool {
+        match *self {
+            ReadableKey::Name(ref val, ref num) => val == name || match *num {
+                Some(ref num) => to_obj(&key::<K, L>(lit)).ok().as_ref() == Some(num),
+                None => false
+            },
+            ReadableKey::Key(ref val) => lit.matches(val)
+        }
+    }
+}
+
+impl<'de, K: Deserialize<'de>> Deserialize<'de> for ReadableKey<K> {
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        de.deserialize_any(ReadableKeyVisitor(PhantomData))
+    }
+}
+
+struct ReadableKeyVisitor<K>(PhantomData<K>);
+
+impl<K> ReadableKeyVisitor<K> {
+    fn compact<'de, E: Error, D: Deserializer<'de, Error = E>>(de: D) -> Result<ReadableKey<K>, E> where
+        K: Deserialize<'de>
+    {
+        K::deserialize(de).map(ReadableKey::Key)
+    }
+}
+
+impl<'de, K: Deserialize<'de>> Visitor<'de> for ReadableKeyVisitor<K> {
+    type Value = ReadableKey<K>;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("a field name or key")
+    }
+
+    fn visit_str<E: Error>(self, val: &str) -> Result<Self::Value, E> {
+        let num = match val.parse::<u64>() {
+            Ok(num) => Some(Obj::Unsigned(num)),
+            Err(_) => val.parse::<i64>().ok().map(Obj::Signed)
+        };
+        Ok(ReadableKey::Name(val.to_string(), num))
+    }
+
+    fn visit_bool<E: Error>(self, val: bool) -> Result<Self::Value, E> {
+        Self::compact(val.into_deserializer())
+    }
+
+    fn visit_i64<E: Error>(self, val: i64) -> Result<Self::Value, E> {
+        Self::compact(val.into_deserializer())
+    }
+
+    fn visit_u64<E: Error>(self, val: u64) -> Result<Self::Value, E> {
+        Self::compact(val.into_deserializer())
+    }
+
+    fn visit_f64<E: Error>(self, val: f64) -> Result<Self::Value, E> {
+        Self::compact(val.into_deserializer())
+    }
+
+    fn visit_char<E: Error>(self, val: char) -> Result<Self::Value, E> {
+        Self::compact(val.into_deserializer())
+    }
+
+    fn visit_bytes<E: Error>(self, val: &[u8]) -> Result<Self::Value, E> {
+        Self::compact(BytesDeserializer::new(val))
+    }
+}
+
+/// Determines whether `T` is an `Option` via method resolution on `&OptionProbe<T>`.
+pub struct OptionProbe<T>(PhantomData<T>);
+
+/// Creates the probe for the field type `T`.
+#[inline]
+pub fn option_probe<T>() -> OptionProbe<T> {
+    OptionProbe(PhantomData)
+}
+
+/// Selected for `OptionProbe<Option<T>>` since it needs no autoref.
+pub trait ProbeOption {
+    #[inline]
+    fn is_option(&self) -> bool {
+        true
+    }
+}
+
+impl<T> ProbeOption for OptionProbe<Option<T>> {}
+
+/// Selected for all other types.
+pub trait ProbeOther {
+    #[inline]
+    fn is_option(&self) -> bool {
+        false
+    }
+}
+
+impl<T> ProbeOther for &OptionProbe<T> {}
+
+/// Determines whether a field of type `T` is left out of the serialized form via method resolution
+/// on `&SkipProbe<T>`, which is the case for `PhantomData` and `()`.
+pub struct SkipProbe<T: ?Sized>(PhantomData<T>);
+
+/// Creates the probe for the field type `T`.
+#[inline]
+pub fn skip_probe<T: ?Sized>() -> SkipProbe<T> {
+    SkipProbe(PhantomData)
+}
+
+/// Selected for `SkipProbe<PhantomData<T>>` and `SkipProbe<()>` since it needs no autoref.
+pub trait ProbeSkip<T> {
+    #[inline]
+    fn is_skipped(&self) -> bool {
+        true
+    }
+
+    /// Returns the value of a skipped field when deserializing.
+    fn skipped_value(&self) -> T;
+}
+
+impl<T: ?Sized> ProbeSkip<PhantomData<T>> for SkipProbe<PhantomData<T>> {
+    #[inline]
+    fn skipped_value(&self) -> PhantomData<T> {
+        PhantomData
+    }
+}
+
+impl ProbeSkip<()> for SkipProbe<()> {
+    #[inline]
+    fn skipped_value(&self) {}
+}
+
+/// Selected for all other types.
+pub trait ProbeKeep<T> {
+    #[inline]
+    fn is_skipped(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn skipped_value(&self) -> T {
+        unreachable!("field is not skipped")
+    }
+}
+
+impl<T> ProbeKeep<T> for &SkipProbe<T> {}
+
+/// Determines whether the payload of an enum variant of type `T` can be left out of the serialized
+/// form via method resolution on `&PayloadProbe<T>`, which is the case for `Option` and `()`.
+pub struct PayloadProbe<T>(PhantomData<T>);
+
+/// Creates the probe for the payload type `T`.
+#[inline]
+pub fn payload_probe<T>() -> PayloadProbe<T> {
+    PayloadProbe(PhantomData)
+}
+
+/// Selected for `PayloadProbe<Option<T>>` and `PayloadProbe<()>` since it needs no autoref.
+pub trait ProbeElided<T> {
+    /// Returns whether the payload is left out when serializing.
+    fn is_elided(&self, val: &T) -> bool;
+
+    /// Returns the payload of a variant that has been serialized without one.
+    fn elided_value(&self) -> Option<T>;
+}
+
+impl<T> ProbeElided<Option<T>> for PayloadProbe<Option<T>> {
+    #[inline]
+    fn is_elided(&self, val: &Option<T>) -> bool {
+        val.is_none()
+    }
+
+    #[inline]
+    fn elided_value(&self) -> Option<Option<T>> {
+        Some(None)
+    }
+}
+
+impl ProbeElided<()> for PayloadProbe<()> {
+    #[inline]
+    fn is_elided(&self, _val: &()) -> bool {
+        true
+    }
+
+    #[inline]
+    fn elided_value(&self) -> Option<()> {
+        Some(())
+    }
+}
+
+/// Selected for all other types.
+pub trait ProbeWritten<T> {
+    #[inline]
+    fn is_elided(&self, _val: &T) -> bool {
+        false
+    }
+
+    #[inline]
+    fn elided_value(&self) -> Option<T> {
+        None
+    }
+}
+
+impl<T> ProbeWritten<T> for &PayloadProbe<T> {}
+
+/// Selects how a field of a type with a lifetime is (de-)serialized via method resolution on
+/// `&BorrowProbe<T>`: `Cow<str>` and `Cow<[u8]>` borrow from the input, all other types are
+/// handled by their own implementations.
+pub struct BorrowProbe<T>(PhantomData<T>);
+
+/// Creates the probe for the field type `T`.
+#[inline]
+pub fn borrow_probe<T>() -> BorrowProbe<T> {
+    BorrowProbe(PhantomData)
+}
+
+/// Selected for `BorrowProbe<Cow<str>>` since it needs no autoref.
+pub trait ProbeCowStr<'a> {
+    #[inline]
+    fn seed(&self) -> CowStrSeed<'a> {
+        CowStrSeed(PhantomData)
+    }
+}
+
+impl<'a> ProbeCowStr<'a> for BorrowProbe<Cow<'a, str>> {}
+
+/// Selected for `BorrowProbe<Cow<[u8]>>` since it needs no autoref, the bytes are written as bytes
+/// instead of a sequence of numbers.
+pub trait ProbeCowBytes<'a> {
+    #[inline]
+    fn seed(&self) -> CowBytesSeed<'a> {
+        CowBytesSeed(PhantomData)
+    }
+
+    #[inline]
+    fn wrap<'v>(&self, val: &'v [u8]) -> RawBytes<'v> {
+        RawBytes(val)
+    }
+}
+
+impl<'a> ProbeCowBytes<'a> for BorrowProbe<Cow<'a, [u8]>> {}
+
+/// Selected for all other types.
+pub trait ProbeOwned<T> {
+    #[inline]
+    fn seed(&self) -> PhantomData<T> {
+        PhantomData
+    }
+
+    #[inline]
+    fn wrap<'v>(&self, val: &'v T) -> &'v T {
+        val
+    }
+}
+
+impl<T> ProbeOwned<T> for &BorrowProbe<T> {}
+
+/// Bytes that serialize as bytes.
+pub struct RawBytes<'a>(&'a [u8]);
+
+impl<'a> Serialize for RawBytes<'a> {
+    #[inline]
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        ser.serialize_bytes(self.0)
+    }
+}
+
+/// Deserializes a `Cow<str>` that borrows from the input if possible.
+pub struct CowStrSeed<'a>(PhantomData<&'a ()>);
+
+impl<'de: 'a, 'a> DeserializeSeed<'de> for CowStrSeed<'a> {
+    type Value = Cow<'a, str>;
+
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(self, de: D) -> Result<Self::Value, D::Error> {
+        de.deserialize_str(self)
+    }
+}
+
+impl<'de: 'a, 'a> Visitor<'de> for CowStrSeed<'a> {
+    type Value = Cow<'a, str>;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("a string")
+    }
+
+    #[inline]
+    fn visit_borrowed_str<E: Error>(self, val: &'de str) -> Result<Self::Value, E> {
+        Ok(Cow::Borrowed(val))
+    }
+
+    #[inline]
+    fn visit_str<E: Error>(self, val: &str) -> Result<Self::Value, E> {
+        Ok(Cow::Owned(val.to_owned()))
+    }
+
+    #[inline]
+    fn visit_string<E: Error>(self, val: String) -> Result<Self::Value, E> {
+        Ok(Cow::Owned(val))
+    }
+
+    fn visit_borrowed_bytes<E: Error>(self, val: &'de [u8]) -> Result<Self::Value, E> {
+        str::from_utf8(val).map(Cow::Borrowed).map_err(|_| E::invalid_value(Unexpected::Bytes(val), &self))
+    }
+
+    fn visit_bytes<E: Error>(self, val: &[u8]) -> Result<Self::Value, E> {
+        str::from_utf8(val).map(|val| Cow::Owned(val.to_owned())).map_err(|_| E::invalid_value(Unexpected::Bytes(val), &self))
+    }
+}
+
+/// Deserializes a `Cow<[u8]>` that borrows from the input if possible.
+///
+/// Besides bytes, strings and sequences of numbers are accepted as well.
+pub struct CowBytesSeed<'a>(PhantomData<&'a ()>);
+
+impl<'de: 'a, 'a> DeserializeSeed<'de> for CowBytesSeed<'a> {
+    type Value = Cow<'a, [u8]>;
+
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(self, de: D) -> Result<Self::Value, D::Error> {
+        de.deserialize_bytes(self)
+    }
+}
+
+impl<'de: 'a, 'a> Visitor<'de> for CowBytesSeed<'a> {
+    type Value = Cow<'a, [u8]>;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("bytes")
+    }
+
+    #[inline]
+    fn visit_borrowed_bytes<E: Error>(self, val: &'de [u8]) -> Result<Self::Value, E> {
+        Ok(Cow::Borrowed(val))
+    }
+
+    #[inline]
+    fn visit_bytes<E: Error>(self, val: &[u8]) -> Result<Self::Value, E> {
+        Ok(Cow::Owned(val.to_vec()))
+    }
+
+    #[inline]
+    fn visit_byte_buf<E: Error>(self, val: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(Cow::Owned(val))
+    }
+
+    #[inline]
+    fn visit_borrowed_str<E: Error>(self, val: &'de str) -> Result<Self::Value, E> {
+        Ok(Cow::Borrowed(val.as_bytes()))
+    }
+
+    #[inline]
+    fn visit_str<E: Error>(self, val: &str) -> Result<Self::Value, E> {
+        Ok(Cow::Owned(val.as_bytes().to_vec()))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(byte) = seq.next_element()? {
+            bytes.push(byte);
+        }
+        Ok(Cow::Owned(bytes))
+    }
+}
+
+/// Selects how a field of type `T` is (de-)serialized via method resolution on `&ArrayProbe<T>`:
+/// arrays of any length are handled here since serde only implements its traits for arrays of up
+/// to 32 elements, all other types are handled by their own implementations.
+pub struct ArrayProbe<T>(PhantomData<T>);
+
+/// Creates the probe for the field type `T`.
+#[inline]
+pub fn array_probe<T>() -> ArrayProbe<T> {
+    ArrayProbe(PhantomData)
+}
+
+/// Selected for `ArrayProbe<[T; N]>` and `BorrowProbe<[T; N]>` since it needs no autoref.
+pub trait ProbeArray<T, const N: usize> {
+    #[inline]
+    fn seed(&self) -> ArraySeed<T, N> {
+        ArraySeed(PhantomData)
+    }
+
+    #[inline]
+    fn wrap<'v>(&self, val: &'v [T; N]) -> ArrayRef<'v, T> {
+        ArrayRef(val)
+    }
+}
+
+impl<T, const N: usize> ProbeArray<T, N> for ArrayProbe<[T; N]> {}
+
+impl<T, const N: usize> ProbeArray<T, N> for BorrowProbe<[T; N]> {}
+
+/// Selected for all other types.
+pub trait ProbeNoArray<T> {
+    #[inline]
+    fn seed(&self) -> PhantomData<T> {
+        PhantomData
+    }
+
+    #[inline]
+    fn wrap<'v>(&self, val: &'v T) -> &'v T {
+        val
+    }
+}
+
+impl<T> ProbeNoArray<T> for &ArrayProbe<T> {}
+
+/// The elements of an array that serialize as a tuple like serde does for short arrays.
+pub struct ArrayRef<'a, T>(&'a [T]);
+
+impl<'a, T: Serialize> Serialize for ArrayRef<'a, T> {
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeTuple;
+        let mut state = ser.serialize_tuple(self.0.len())?;
+        for val in self.0 {
+            state.serialize_element(val)?;
+        }
+        state.end()
+    }
+}
+
+/// Deserializes an array of exactly `N` elements.
+pub struct ArraySeed<T, const N: usize>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>, const N: usize> DeserializeSeed<'de> for ArraySeed<T, N> {
+    type Value = [T; N];
+
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(self, de: D) -> Result<Self::Value, D::Error> {
+        de.deserialize_tuple(N, self)
+    }
+}
+
+impl<'de, T: Deserialize<'de>, const N: usize> Visitor<'de> for ArraySeed<T, N> {
+    type Value = [T; N];
+
+    fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "an array of length {}", N)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut vals = Vec::with_capacity(N);
+        while vals.len() < N {
+            match seq.next_element()? {
+                Some(val) => vals.push(val),
+                None => return Err(A::Error::invalid_length(vals.len(), &self))
+            }
+        }
+        if seq.next_element::<Ignore>()?.is_some() {
+            return Err(A::Error::invalid_length(N + 1 + seq.size_hint().unwrap_or(0), &self))
+        }
+        Ok(vals.try_into().unwrap_or_else(|_| unreachable!()))
+    }
+}
+
+/// Extracts the entries of an object that must be a map.
+pub fn expect_map<E: Error>(obj: Obj, ty: &str) -> Result<ObjMap, E> {
+    match obj {
+        Obj::Map(map) => Ok(map),
+        _ => Err(type_error(E::invalid_type(Unexpected::Other("non-map value"), &"map"), ty))
+    }
+}
+
+/// Looks up the version entry of a versioned struct. A missing entry is treated as version 0.
+pub fn find_version<'de, K, L, E>(map: &ObjMap, vkey: &L) -> Result<u64, E>
+    where K: Deserialize<'de>, L: KeyLiteral<K>, E: Error
+{
+    for (key, value) in map {
+        if let Ok(key) = K::deserialize(ObjDeserializer::<E>::new(key.clone())) {
+            if vkey.matches(&key) {
+                return u64::deserialize(ObjDeserializer::new(value.clone()));
+            }
+        }
+    }
+    Ok(0)
+}
+
+/// Creates the error for a failed migration of a versioned struct.
+pub fn migration_error<E: Error, M: fmt::Display>(err: M, ty: &str, version: u64) -> E {
+    E::custom(format_args!("{}: failed to migrate from version {}: {}", ty, version, err))
+}
+
+/// Creates the error for a version of a versioned struct that can not be decoded.
+pub fn unsupported_version<E: Error>(ty: &str, version: u64) -> E {
+    E::custom(format_args!("{}: unsupported version {}", ty, version))
+}
+
+pub use schema::{titled, object_schema, tuple_schema, min_items, const_schema, enum_schema, one_of_schema, recursive_schema};
+
+/// Returns the serialized form of a key literal for schemas.
+pub fn schema_const<K, L: KeyLiteral<K>>(lit: &L) -> Obj {
+    to_obj(&key::<K, L>(lit)).unwrap_or(Obj::Null)
+}
+
+/// Returns the property name of a key literal for schemas, keys that are no strings are formatted.
+pub fn schema_property<K, L: KeyLiteral<K>>(lit: &L) -> Obj {
+    match schema_const::<K, L>(lit) {
+        Obj::Str(name) => Obj::Str(name),
+        Obj::Bin(bytes) => Obj::Str(String::from_utf8_lossy(&bytes).into_owned()),
+        other => Obj::Str(other.to_string())
+    }
+}
+
+/// Produces the value of a field that is missing in the input.
+///
+/// Only `Option`, `Maybe` and unit fields like `PhantomData` can be missing, they are set to
+/// `None`, `Maybe::Missing` and the unit value. All other types result in an error.
+pub fn missing_field<'de, S: DeserializeSeed<'de>, E: Error>(seed: S, ty: &str, field: &str, key: &str) -> Result<S::Value, E> {
+    seed.deserialize(MissingField { ty, field, key, marker: PhantomData })
+}
+
+struct MissingField<'a, E> {
+    ty: &'a str,
+    field: &'a str,
+    key: &'a str,
+    marker: PhantomData<E>
+}
+
+impl<'a, 'de, E: Error> Deserializer<'de> for MissingField<'a, E> {
+    type Error = E;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, E> {
+        Err(E::custom(format_args!("{}.{} (key {}): missing field", self.ty, self.field, key_text(self.key))))
+    }
+
+    /// `Option` treats `visit_unit` like `visit_none`, `Maybe` uses it to detect missing fields.
+    #[inline]
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, E> {
+        visitor.visit_unit()
+    }
+
+    /// Unit fields like `PhantomData` are not serialized, so they are always missing.
+    #[inline]
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, E> {
+        visitor.visit_unit()
+    }
+
+    #[inline]
+    fn deserialize_unit_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, E> {
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        newtype_struct seq tuple tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// Converts a value into an object for the `TryFrom` impls of `serde_impl_obj!`.
+pub fn try_into_obj<T: Serialize + ?Sized>(val: &T, name: &str) -> Result<Obj, ExtractError> {
+    to_obj(val).map_err(|err| ExtractError::new(ObjPath::new(), "Obj", None,
+        format_args!("failed to convert {}: {}", name, err)))
+}
+
+/// Compares two values via their objects and panics with the differences, used by `assert_obj_eq!`.
+#[track_caller]
+pub fn assert_obj_eq<L: Serialize + ?Sized, R: Serialize + ?Sized>(expected: &L, actual: &R, msg: Option<fmt::Arguments>) {
+    let expected = to_obj(expected).unwrap_or_else(|err| panic!("failed to convert expected value: {}", err));
+    let actual = to_obj(actual).unwrap_or_else(|err| panic!("failed to convert actual value: {}", err));
+    let diff = diff(&expected, &actual);
+    if !diff.is_empty() {
+        match msg {
+            Some(msg) => panic!("assertion `expected == actual` failed: {}\n{}", msg, diff.join("\n")),
+            None => panic!("assertion `expected == actual` failed:\n{}", diff.join("\n"))
+        }
+    }
+}
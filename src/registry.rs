@@ -0,0 +1,237 @@
+//! Registry for (de-)serializing trait objects
+//!
+//! Enums only cover a closed set of types. For open sets like the messages of plugins, the
+//! implementations of a trait register a discriminator key and a decode function in a
+//! [`Registry`](struct.Registry.html), so that boxed trait objects can be (de-)serialized. The
+//! values are encoded like enums with one parameter by [`serde_impl!`](../macro.serde_impl!.html),
+//! i.e. as tuple `(key, payload)`, so a closed enum and a registry can read each others data.
+//!
+//! The payload is converted via [`Obj`](../enum.Obj.html), so deserializing requires a
+//! self-describing format like JSON or MessagePack.
+//!
+//! The trait object type implements [`Registered`](trait.Registered.html) to provide its
+//! registry. Fields of type `Box<dyn Trait>` can then use
+//! `#[serde(with = "serde_utils::registry")]` or the wrapper type
+//! [`Polymorphic`](struct.Polymorphic.html).
+//!
+//! ```ignore
+//! trait Message: Any {
+//!     fn handle(&self);
+//! }
+//!
+//! impl Registered for dyn Message {
+//!     fn registry() -> &'static Registry<Self> {
+//!         static REGISTRY: Registry<dyn Message> = Registry::new();
+//!         &REGISTRY
+//!     }
+//!
+//!     fn as_any(&self) -> &dyn Any {
+//!         self
+//!     }
+//! }
+//!
+//! // in the plugin
+//! <dyn Message>::registry().register::<Ping>("ping", |obj| Ok(Box::new(from_obj::<Ping>(obj)?)));
+//!
+//! serde_impl!(Envelope(String) {
+//!     sender: String => "sender",
+//!     message: Polymorphic<dyn Message> => "message"
+//! });
+//! ```
+
+use std::any::{Any, TypeId};
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+use std::sync::RwLock;
+
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::ser::{self, SerializeTuple};
+use serde::de::{Error, Visitor, SeqAccess};
+
+use generic::Obj;
+use extract::ExtractError;
+use serializer::to_obj;
+
+/// A function decoding the payload of an implementation into a boxed trait object
+pub type DecodeFn<T> = fn(Obj) -> Result<Box<T>, ExtractError>;
+
+struct Entry<T: ?Sized> {
+    key: &'static str,
+    type_id: TypeId,
+    encode: fn(&dyn Any) -> Result<Obj, String>,
+    decode: DecodeFn<T>
+}
+
+fn encode<S: Serialize + 'static>(val: &dyn Any) -> Result<Obj, String> {
+    match val.downcast_ref::<S>() {
+        Some(val) => to_obj(val).map_err(|err| err.to_string()),
+        None => Err("value does not match its registered type".to_string())
+    }
+}
+
+/// The implementations of a trait that can be (de-)serialized as trait objects
+///
+/// Registries can be created in a `static` via [`new`](#method.new) and are filled at runtime,
+/// e.g. when plugins are loaded.
+pub struct Registry<T: ?Sized> {
+    entries: RwLock<Vec<Entry<T>>>
+}
+
+impl<T: ?Sized> Registry<T> {
+    /// Creates an empty registry
+    #[inline]
+    pub const fn new() -> Self {
+        Registry { entries: RwLock::new(Vec::new()) }
+    }
+
+    /// Registers the implementation `S` under the key with its decode function
+    ///
+    /// Values of type `S` are written with the key and their payload, values with the key are
+    /// read by passing the payload to `decode`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key or the type has already been registered.
+    pub fn register<S: Serialize + 'static>(&self, key: &'static str, decode: DecodeFn<T>) {
+        let mut entries = self.entries.write().unwrap_or_else(|err| err.into_inner());
+        let type_id = TypeId::of::<S>();
+        for entry in entries.iter() {
+            assert!(entry.key != key, "key {:?} is already registered", key);
+            assert!(entry.type_id != type_id, "type is already registered under key {:?}", entry.key);
+        }
+        entries.push(Entry { key, type_id, encode: encode::<S>, decode });
+    }
+
+    /// Returns the keys of all registered implementations in the order of registration
+    pub fn keys(&self) -> Vec<&'static str> {
+        self.entries.read().unwrap_or_else(|err| err.into_inner()).iter().map(|entry| entry.key).collect()
+    }
+
+    /// Returns the key and the payload of a value of the implementation with the given type id
+    fn encode(&self, type_id: TypeId, val: &dyn Any) -> Result<(&'static str, Obj), String> {
+        let entries = self.entries.read().unwrap_or_else(|err| err.into_inner());
+        match entries.iter().find(|entry| entry.type_id == type_id) {
+            Some(entry) => (entry.encode)(val).map(|obj| (entry.key, obj)),
+            None => Err("type is not registered".to_string())
+        }
+    }
+
+    /// Returns the decode function of the implementation with the key
+    fn decoder(&self, key: &str) -> Option<DecodeFn<T>> {
+        let entries = self.entries.read().unwrap_or_else(|err| err.into_inner());
+        entries.iter().find(|entry| entry.key == key).map(|entry| entry.decode)
+    }
+}
+
+impl<T: ?Sized> Default for Registry<T> {
+    #[inline]
+    fn default() -> Self {
+        Registry::new()
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for Registry<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("Registry").field("keys", &self.keys()).finish()
+    }
+}
+
+/// Trait object types with a registry of their implementations, e.g. `dyn Message`
+pub trait Registered: 'static {
+    /// Returns the registry of the implementations
+    fn registry() -> &'static Registry<Self>;
+
+    /// Returns the value as `Any` to find its implementation, implementations simply return `self`
+    fn as_any(&self) -> &dyn Any;
+}
+
+/// Serializes the trait object as tuple of the key of its implementation and its payload
+// `with` passes a reference to the field, so the box can not be taken as `&T`
+#[allow(clippy::borrowed_box)]
+pub fn serialize<T: Registered + ?Sized, S: Serializer>(val: &Box<T>, ser: S) -> Result<S::Ok, S::Error> {
+    let any = (**val).as_any();
+    let (key, payload) = T::registry().encode(any.type_id(), any).map_err(ser::Error::custom)?;
+    let mut tuple = ser.serialize_tuple(2)?;
+    tuple.serialize_element(key)?;
+    tuple.serialize_element(&payload)?;
+    tuple.end()
+}
+
+/// Deserializes the trait object with the decode function registered for its key
+#[inline]
+pub fn deserialize<'de, T: Registered + ?Sized, D: Deserializer<'de>>(de: D) -> Result<Box<T>, D::Error> {
+    de.deserialize_tuple(2, RegistryVisitor(PhantomData))
+}
+
+struct RegistryVisitor<T: ?Sized>(PhantomData<fn() -> Box<T>>);
+
+impl<'de, T: Registered + ?Sized> Visitor<'de> for RegistryVisitor<T> {
+    type Value = Box<T>;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("a tuple of a registered key and a payload")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let key: String = seq.next_element()?.ok_or_else(|| A::Error::invalid_length(0, &self))?;
+        let decode = T::registry().decoder(&key)
+            .ok_or_else(|| A::Error::custom(format_args!("unknown key {:?}, expected one of: {:?}", key, T::registry().keys())))?;
+        let payload: Obj = seq.next_element()?.ok_or_else(|| A::Error::invalid_length(1, &self))?;
+        decode(payload).map_err(|err| A::Error::custom(format_args!("{}: {}", key, err)))
+    }
+}
+
+/// A wrapper around a boxed trait object that (de-)serializes it via its registry
+pub struct Polymorphic<T: Registered + ?Sized>(pub Box<T>);
+
+impl<T: Registered + ?Sized> Polymorphic<T> {
+    /// Returns the boxed trait object
+    #[inline]
+    pub fn into_inner(self) -> Box<T> {
+        self.0
+    }
+}
+
+impl<T: Registered + ?Sized> From<Box<T>> for Polymorphic<T> {
+    #[inline]
+    fn from(val: Box<T>) -> Self {
+        Polymorphic(val)
+    }
+}
+
+impl<T: Registered + ?Sized> Deref for Polymorphic<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Registered + ?Sized> DerefMut for Polymorphic<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: Registered + fmt::Debug + ?Sized> fmt::Debug for Polymorphic<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(fmt)
+    }
+}
+
+impl<T: Registered + ?Sized> Serialize for Polymorphic<T> {
+    #[inline]
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        serialize(&self.0, ser)
+    }
+}
+
+impl<'de, T: Registered + ?Sized> Deserialize<'de> for Polymorphic<T> {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        deserialize(de).map(Polymorphic)
+    }
+}
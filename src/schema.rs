@@ -0,0 +1,337 @@
+use std::any::TypeId;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::marker::PhantomData;
+use std::mem;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use serde_bytes::ByteBuf;
+
+use adapters::shared::{Shared, SharedPointer};
+
+use generic::Obj;
+use map::MapBuilder;
+use key::NormalizedKey;
+
+/// Types that can describe their serialized form as [JSON Schema](https://json-schema.org/)
+///
+/// The trait is implemented for the basic types, for `Option`, collections and tuples and by
+/// [`serde_impl_schema!`](macro.serde_impl_schema!.html) for types implemented via the macros.
+/// The schema is returned as [`Obj`](enum.Obj.html), so it can be serialized in any format.
+///
+/// ```ignore
+/// let schema = serde_json::to_string_pretty(&Test::json_schema())?;
+/// ```
+pub trait JsonSchema {
+    /// Returns the schema of the serialized form of this type
+    fn json_schema() -> Obj;
+}
+
+/// Creates a schema object with the given entries
+fn schema(entries: Vec<(&'static str, Obj)>) -> Obj {
+    let mut map = MapBuilder::new(entries.len());
+    for (key, value) in entries {
+        map.insert(Obj::Str(key.to_string()), value);
+    }
+    map.finish()
+}
+
+fn type_schema(ty: &str) -> Obj {
+    schema(vec![("type", Obj::Str(ty.to_string()))])
+}
+
+/// Adds entries to a schema object, the given entries take precedence
+fn extend(base: Obj, entries: Vec<(&'static str, Obj)>) -> Obj {
+    let mut map = base.into_map().unwrap_or_default();
+    for (key, value) in entries {
+        map.insert(Obj::Str(key.to_string()), value);
+    }
+    Obj::from_map(map)
+}
+
+/// Sets the title of a schema
+pub fn titled(title: &str, base: Obj) -> Obj {
+    extend(base, vec![("title", Obj::Str(title.to_string()))])
+}
+
+/// Creates the schema of an object with the given properties
+pub fn object_schema(title: Option<&str>, properties: Vec<(Obj, Obj)>, required: Vec<Obj>, strict: bool) -> Obj {
+    let mut entries = vec![("type", Obj::Str("object".to_string()))];
+    if let Some(title) = title {
+        entries.push(("title", Obj::Str(title.to_string())));
+    }
+    entries.push(("properties", Obj::from_map(properties.into_iter().collect())));
+    if !required.is_empty() {
+        entries.push(("required", Obj::from_list(required)));
+    }
+    if strict {
+        entries.push(("additionalProperties", Obj::Bool(false)));
+    }
+    schema(entries)
+}
+
+/// Creates the schema of an array with one item per schema in `items`
+///
+/// A lenient tuple also accepts fewer and more items.
+pub fn tuple_schema(title: Option<&str>, items: Vec<Obj>, lenient: bool) -> Obj {
+    let mut entries = vec![("type", Obj::Str("array".to_string()))];
+    if let Some(title) = title {
+        entries.push(("title", Obj::Str(title.to_string())));
+    }
+    if !lenient {
+        entries.push(("minItems", Obj::Unsigned(items.len() as u64)));
+        entries.push(("maxItems", Obj::Unsigned(items.len() as u64)));
+    }
+    entries.push(("prefixItems", Obj::from_list(items)));
+    schema(entries)
+}
+
+thread_local! {
+    /// The types whose schemas are being generated and whether they were referenced recursively
+    static IN_PROGRESS: RefCell<Vec<(TypeId, bool)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Removes the innermost type from `IN_PROGRESS`, also if generating its schema panics
+struct InProgress;
+
+impl InProgress {
+    fn finish(self) -> bool {
+        mem::forget(self);
+        matches!(IN_PROGRESS.with(|stack| stack.borrow_mut().pop()), Some((_, true)))
+    }
+}
+
+impl Drop for InProgress {
+    fn drop(&mut self) {
+        IN_PROGRESS.with(|stack| stack.borrow_mut().pop());
+    }
+}
+
+/// Creates the schema of a type that may contain itself
+///
+/// If the schema of `T` is already being generated further up, a reference to it is returned
+/// instead of recursing endlessly. The referenced schema gets an anchor named `name` then.
+pub fn recursive_schema<T: ?Sized + 'static, F: FnOnce() -> Obj>(name: &str, generate: F) -> Obj {
+    let id = TypeId::of::<T>();
+    let nested = IN_PROGRESS.with(|stack| {
+        let mut stack = stack.borrow_mut();
+        if let Some(entry) = stack.iter_mut().find(|entry| entry.0 == id) {
+            entry.1 = true;
+            return true
+        }
+        stack.push((id, false));
+        false
+    });
+    if nested {
+        return schema(vec![("$ref", Obj::Str(format!("#{}", name)))])
+    }
+    let guard = InProgress;
+    let base = generate();
+    if guard.finish() {
+        extend(base, vec![("$anchor", Obj::Str(name.to_string()))])
+    } else {
+        base
+    }
+}
+
+/// Lowers the number of items a tuple schema requires, so that the trailing items can be omitted
+pub fn min_items(base: Obj, min: usize) -> Obj {
+    extend(base, vec![("minItems", Obj::Unsigned(min as u64))])
+}
+
+/// Creates the schema of a single constant value
+pub fn const_schema(value: Obj) -> Obj {
+    schema(vec![("const", value)])
+}
+
+/// Creates the schema that allows only the given values
+pub fn enum_schema(title: &str, values: Vec<Obj>) -> Obj {
+    schema(vec![("title", Obj::Str(title.to_string())), ("enum", Obj::from_list(values))])
+}
+
+/// Creates the schema that matches exactly one of the given schemas
+pub fn one_of_schema(title: &str, variants: Vec<Obj>) -> Obj {
+    schema(vec![("title", Obj::Str(title.to_string())), ("oneOf", Obj::from_list(variants))])
+}
+
+impl JsonSchema for bool {
+    fn json_schema() -> Obj {
+        type_schema("boolean")
+    }
+}
+
+macro_rules! impl_int_schema(
+    ( $( $ty:ty => $min:expr, $max:expr );* ) => {
+        $(
+            impl JsonSchema for $ty {
+                fn json_schema() -> Obj {
+                    let mut entries = vec![("type", Obj::Str("integer".to_string()))];
+                    let bounds: [Option<Obj>; 2] = [$min, $max];
+                    if let Some(ref min) = bounds[0] {
+                        entries.push(("minimum", min.clone()));
+                    }
+                    if let Some(ref max) = bounds[1] {
+                        entries.push(("maximum", max.clone()));
+                    }
+                    schema(entries)
+                }
+            }
+        )*
+    };
+);
+
+impl_int_schema!(
+    u8 => Some(Obj::Unsigned(0)), Some(Obj::Unsigned(u64::from(u8::MAX)));
+    u16 => Some(Obj::Unsigned(0)), Some(Obj::Unsigned(u64::from(u16::MAX)));
+    u32 => Some(Obj::Unsigned(0)), Some(Obj::Unsigned(u64::from(u32::MAX)));
+    u64 => Some(Obj::Unsigned(0)), None;
+    u128 => Some(Obj::Unsigned(0)), None;
+    usize => Some(Obj::Unsigned(0)), None;
+    i8 => Some(Obj::Signed(i64::from(i8::MIN))), Some(Obj::Signed(i64::from(i8::MAX)));
+    i16 => Some(Obj::Signed(i64::from(i16::MIN))), Some(Obj::Signed(i64::from(i16::MAX)));
+    i32 => Some(Obj::Signed(i64::from(i32::MIN))), Some(Obj::Signed(i64::from(i32::MAX)));
+    i64 => None, None;
+    i128 => None, None;
+    isize => None, None
+);
+
+impl JsonSchema for f32 {
+    fn json_schema() -> Obj {
+        type_schema("number")
+    }
+}
+
+impl JsonSchema for f64 {
+    fn json_schema() -> Obj {
+        type_schema("number")
+    }
+}
+
+impl JsonSchema for char {
+    fn json_schema() -> Obj {
+        extend(type_schema("string"), vec![("minLength", Obj::Unsigned(1)), ("maxLength", Obj::Unsigned(1))])
+    }
+}
+
+impl JsonSchema for String {
+    fn json_schema() -> Obj {
+        type_schema("string")
+    }
+}
+
+impl JsonSchema for str {
+    fn json_schema() -> Obj {
+        type_schema("string")
+    }
+}
+
+impl<N> JsonSchema for NormalizedKey<N> {
+    fn json_schema() -> Obj {
+        type_schema("string")
+    }
+}
+
+impl JsonSchema for ByteBuf {
+    fn json_schema() -> Obj {
+        extend(type_schema("array"), vec![("items", u8::json_schema())])
+    }
+}
+
+impl JsonSchema for () {
+    fn json_schema() -> Obj {
+        type_schema("null")
+    }
+}
+
+impl<T: ?Sized> JsonSchema for PhantomData<T> {
+    fn json_schema() -> Obj {
+        type_schema("null")
+    }
+}
+
+/// `Obj` can hold any value, its schema accepts everything
+impl JsonSchema for Obj {
+    fn json_schema() -> Obj {
+        schema(vec![])
+    }
+}
+
+impl<T: JsonSchema> JsonSchema for Option<T> {
+    fn json_schema() -> Obj {
+        schema(vec![("anyOf", Obj::List(vec![T::json_schema(), type_schema("null")]))])
+    }
+}
+
+impl<T: JsonSchema + ?Sized> JsonSchema for Box<T> {
+    fn json_schema() -> Obj {
+        T::json_schema()
+    }
+}
+
+impl<T: JsonSchema + ?Sized> JsonSchema for Rc<T> {
+    fn json_schema() -> Obj {
+        T::json_schema()
+    }
+}
+
+impl<T: JsonSchema + ?Sized> JsonSchema for Arc<T> {
+    fn json_schema() -> Obj {
+        T::json_schema()
+    }
+}
+
+/// Shared values are written as `[id, value]` or `[id, null]`
+impl<P: SharedPointer> JsonSchema for Shared<P> where P::Value: JsonSchema {
+    fn json_schema() -> Obj {
+        tuple_schema(None, vec![u64::json_schema(), Option::<P::Value>::json_schema()], false)
+    }
+}
+
+impl<T: JsonSchema> JsonSchema for Vec<T> {
+    fn json_schema() -> Obj {
+        extend(type_schema("array"), vec![("items", T::json_schema())])
+    }
+}
+
+impl<T: JsonSchema> JsonSchema for [T] {
+    fn json_schema() -> Obj {
+        Vec::<T>::json_schema()
+    }
+}
+
+/// Arrays are written as tuples, so the length is fixed
+impl<T: JsonSchema, const N: usize> JsonSchema for [T; N] {
+    fn json_schema() -> Obj {
+        extend(Vec::<T>::json_schema(), vec![
+            ("minItems", Obj::Unsigned(N as u64)),
+            ("maxItems", Obj::Unsigned(N as u64))
+        ])
+    }
+}
+
+impl<K, V: JsonSchema> JsonSchema for BTreeMap<K, V> {
+    fn json_schema() -> Obj {
+        extend(type_schema("object"), vec![("additionalProperties", V::json_schema())])
+    }
+}
+
+impl<K, V: JsonSchema, S> JsonSchema for HashMap<K, V, S> {
+    fn json_schema() -> Obj {
+        BTreeMap::<K, V>::json_schema()
+    }
+}
+
+macro_rules! impl_tuple_schema(
+    ( $( ( $( $ty:ident ),+ ) )* ) => {
+        $(
+            impl<$( $ty: JsonSchema ),+> JsonSchema for ( $( $ty, )+ ) {
+                fn json_schema() -> Obj {
+                    tuple_schema(None, vec![ $( $ty::json_schema() ),+ ], false)
+                }
+            }
+        )*
+    };
+);
+
+impl_tuple_schema!((A) (A, B) (A, B, C) (A, B, C, D) (A, B, C, D, E) (A, B, C, D, E, F));
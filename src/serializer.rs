@@ -0,0 +1,387 @@
+use std::marker::PhantomData;
+
+use serde::ser::{self, Serialize, Error};
+use serde::de::{self, Visitor};
+
+use generic::{Obj, GenericVisitor, ListBuilder};
+use map::MapBuilder;
+
+/// A serializer that writes values into an [`Obj`](enum.Obj.html).
+///
+/// The resulting object is the same as when serializing the value in a binary format and
+/// deserializing it as `Obj`: options and unit values become `Null`, enum variants become their
+/// name or a map with the name as single key and newtype structs are transparent. The error type
+/// `E` can be chosen freely so that errors can be passed on from within other serializers.
+pub struct ObjSerializer<E> {
+    marker: PhantomData<E>
+}
+
+impl<E> ObjSerializer<E> {
+    /// Creates a new serializer
+    #[inline]
+    pub fn new() -> Self {
+        ObjSerializer { marker: PhantomData }
+    }
+}
+
+impl<E> Default for ObjSerializer<E> {
+    #[inline]
+    fn default() -> Self {
+        ObjSerializer::new()
+    }
+}
+
+/// Converts a value into an object
+///
+/// This is a shortcut for serializing the value with an
+/// [`ObjSerializer`](struct.ObjSerializer.html) and the counterpart of
+/// [`from_obj`](fn.from_obj.html).
+#[inline]
+pub fn to_obj<T: Serialize + ?Sized>(value: &T) -> Result<Obj, de::value::Error> {
+    value.serialize(ObjSerializer::new())
+}
+
+fn variant(name: &'static str, value: Obj) -> Obj {
+    let mut map = MapBuilder::new(1);
+    map.insert(Obj::Str(name.to_string()), value);
+    map.finish()
+}
+
+impl<E: Error> ser::Serializer for ObjSerializer<E> {
+    type Ok = Obj;
+    type Error = E;
+    type SerializeSeq = SerializeList<E>;
+    type SerializeTuple = SerializeList<E>;
+    type SerializeTupleStruct = SerializeList<E>;
+    type SerializeTupleVariant = SerializeList<E>;
+    type SerializeMap = SerializeMap<E>;
+    type SerializeStruct = SerializeMap<E>;
+    type SerializeStructVariant = SerializeMap<E>;
+
+    #[inline]
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn serialize_bool(self, val: bool) -> Result<Obj, E> {
+        Ok(Obj::Bool(val))
+    }
+
+    #[inline]
+    fn serialize_i8(self, val: i8) -> Result<Obj, E> {
+        self.serialize_i64(i64::from(val))
+    }
+
+    #[inline]
+    fn serialize_i16(self, val: i16) -> Result<Obj, E> {
+        self.serialize_i64(i64::from(val))
+    }
+
+    #[inline]
+    fn serialize_i32(self, val: i32) -> Result<Obj, E> {
+        self.serialize_i64(i64::from(val))
+    }
+
+    #[inline]
+    fn serialize_i64(self, val: i64) -> Result<Obj, E> {
+        Ok(Obj::Signed(val))
+    }
+
+    #[inline]
+    fn serialize_i128(self, val: i128) -> Result<Obj, E> {
+        GenericVisitor.visit_i128::<de::value::Error>(val).map_err(E::custom)
+    }
+
+    #[inline]
+    fn serialize_u8(self, val: u8) -> Result<Obj, E> {
+        self.serialize_u64(u64::from(val))
+    }
+
+    #[inline]
+    fn serialize_u16(self, val: u16) -> Result<Obj, E> {
+        self.serialize_u64(u64::from(val))
+    }
+
+    #[inline]
+    fn serialize_u32(self, val: u32) -> Result<Obj, E> {
+        self.serialize_u64(u64::from(val))
+    }
+
+    #[inline]
+    fn serialize_u64(self, val: u64) -> Result<Obj, E> {
+        Ok(Obj::Unsigned(val))
+    }
+
+    #[inline]
+    fn serialize_u128(self, val: u128) -> Result<Obj, E> {
+        GenericVisitor.visit_u128::<de::value::Error>(val).map_err(E::custom)
+    }
+
+    #[inline]
+    fn serialize_f32(self, val: f32) -> Result<Obj, E> {
+        self.serialize_f64(f64::from(val))
+    }
+
+    #[inline]
+    fn serialize_f64(self, val: f64) -> Result<Obj, E> {
+        Ok(Obj::Float(val))
+    }
+
+    #[inline]
+    fn serialize_char(self, val: char) -> Result<Obj, E> {
+        Ok(Obj::Str(val.to_string()))
+    }
+
+    #[inline]
+    fn serialize_str(self, val: &str) -> Result<Obj, E> {
+        Ok(Obj::Str(val.to_string()))
+    }
+
+    #[inline]
+    fn serialize_bytes(self, val: &[u8]) -> Result<Obj, E> {
+        Ok(Obj::from_bytes(val.to_vec()))
+    }
+
+    #[inline]
+    fn serialize_none(self) -> Result<Obj, E> {
+        Ok(Obj::Null)
+    }
+
+    #[inline]
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Obj, E> {
+        value.serialize(self)
+    }
+
+    #[inline]
+    fn serialize_unit(self) -> Result<Obj, E> {
+        Ok(Obj::Null)
+    }
+
+    #[inline]
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Obj, E> {
+        Ok(Obj::Null)
+    }
+
+    #[inline]
+    fn serialize_unit_variant(self, _name: &'static str, _index: u32, variant: &'static str) -> Result<Obj, E> {
+        Ok(Obj::Str(variant.to_string()))
+    }
+
+    #[inline]
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _name: &'static str, value: &T) -> Result<Obj, E> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(self, _name: &'static str, _index: u32,
+        name: &'static str, value: &T
+    ) -> Result<Obj, E> {
+        Ok(variant(name, value.serialize(self)?))
+    }
+
+    #[inline]
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeList<E>, E> {
+        Ok(SerializeList::new(len.unwrap_or(0), None))
+    }
+
+    #[inline]
+    fn serialize_tuple(self, len: usize) -> Result<SerializeList<E>, E> {
+        Ok(SerializeList::new(len, None))
+    }
+
+    #[inline]
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<SerializeList<E>, E> {
+        Ok(SerializeList::new(len, None))
+    }
+
+    #[inline]
+    fn serialize_tuple_variant(self, _name: &'static str, _index: u32, variant: &'static str, len: usize
+    ) -> Result<SerializeList<E>, E> {
+        Ok(SerializeList::new(len, Some(variant)))
+    }
+
+    #[inline]
+    fn serialize_map(self, len: Option<usize>) -> Result<SerializeMap<E>, E> {
+        Ok(SerializeMap::new(len.unwrap_or(0), None))
+    }
+
+    #[inline]
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<SerializeMap<E>, E> {
+        Ok(SerializeMap::new(len, None))
+    }
+
+    #[inline]
+    fn serialize_struct_variant(self, _name: &'static str, _index: u32, variant: &'static str, len: usize
+    ) -> Result<SerializeMap<E>, E> {
+        Ok(SerializeMap::new(len, Some(variant)))
+    }
+}
+
+#[doc(hidden)]
+pub struct SerializeList<E> {
+    list: ListBuilder,
+    variant: Option<&'static str>,
+    marker: PhantomData<E>
+}
+
+impl<E: Error> SerializeList<E> {
+    #[inline]
+    fn new(len: usize, variant: Option<&'static str>) -> Self {
+        SerializeList { list: ListBuilder::new(len), variant, marker: PhantomData }
+    }
+
+    #[inline]
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), E> {
+        self.list.push(value.serialize(ObjSerializer::new())?);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Obj, E> {
+        let list = self.list.finish();
+        Ok(match self.variant {
+            Some(name) => variant(name, list),
+            None => list
+        })
+    }
+}
+
+impl<E: Error> ser::SerializeSeq for SerializeList<E> {
+    type Ok = Obj;
+    type Error = E;
+
+    #[inline]
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), E> {
+        self.push(value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<Obj, E> {
+        self.finish()
+    }
+}
+
+impl<E: Error> ser::SerializeTuple for SerializeList<E> {
+    type Ok = Obj;
+    type Error = E;
+
+    #[inline]
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), E> {
+        self.push(value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<Obj, E> {
+        self.finish()
+    }
+}
+
+impl<E: Error> ser::SerializeTupleStruct for SerializeList<E> {
+    type Ok = Obj;
+    type Error = E;
+
+    #[inline]
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), E> {
+        self.push(value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<Obj, E> {
+        self.finish()
+    }
+}
+
+impl<E: Error> ser::SerializeTupleVariant for SerializeList<E> {
+    type Ok = Obj;
+    type Error = E;
+
+    #[inline]
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), E> {
+        self.push(value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<Obj, E> {
+        self.finish()
+    }
+}
+
+#[doc(hidden)]
+pub struct SerializeMap<E> {
+    map: MapBuilder,
+    key: Option<Obj>,
+    variant: Option<&'static str>,
+    marker: PhantomData<E>
+}
+
+impl<E: Error> SerializeMap<E> {
+    #[inline]
+    fn new(len: usize, variant: Option<&'static str>) -> Self {
+        SerializeMap { map: MapBuilder::new(len), key: None, variant, marker: PhantomData }
+    }
+
+    #[inline]
+    fn insert<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), E> {
+        self.map.insert(Obj::Str(key.to_string()), value.serialize(ObjSerializer::new())?);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Obj, E> {
+        let map = self.map.finish();
+        Ok(match self.variant {
+            Some(name) => variant(name, map),
+            None => map
+        })
+    }
+}
+
+impl<E: Error> ser::SerializeMap for SerializeMap<E> {
+    type Ok = Obj;
+    type Error = E;
+
+    #[inline]
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), E> {
+        self.key = Some(key.serialize(ObjSerializer::new())?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), E> {
+        let key = self.key.take().ok_or_else(|| E::custom("serialize_value called before serialize_key"))?;
+        self.map.insert(key, value.serialize(ObjSerializer::new())?);
+        Ok(())
+    }
+
+    #[inline]
+    fn end(self) -> Result<Obj, E> {
+        self.finish()
+    }
+}
+
+impl<E: Error> ser::SerializeStruct for SerializeMap<E> {
+    type Ok = Obj;
+    type Error = E;
+
+    #[inline]
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), E> {
+        self.insert(key, value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<Obj, E> {
+        self.finish()
+    }
+}
+
+impl<E: Error> ser::SerializeStructVariant for SerializeMap<E> {
+    type Ok = Obj;
+    type Error = E;
+
+    #[inline]
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), E> {
+        self.insert(key, value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<Obj, E> {
+        self.finish()
+    }
+}
@@ -0,0 +1,397 @@
+use std::convert::TryFrom;
+
+use serde::ser::{self, Serialize};
+use serde::de;
+
+/// Estimates the size of a value in a compact binary format without encoding it
+///
+/// The estimate follows the sizes of MessagePack: small integers take a single byte, strings and
+/// bytes their length plus a header of 1 to 5 bytes, sequences and maps a header plus their
+/// elements. Structs are counted as maps with their field names as keys and enum variants by
+/// their names, like [`to_obj`](fn.to_obj.html) represents them. Other binary formats are usually
+/// close to this, text formats like JSON are larger.
+///
+/// The value is traversed once without allocating, so the estimate can be used to pre-allocate
+/// buffers or to reject values that exceed a size limit before encoding them. Values whose
+/// `Serialize` implementation fails are estimated up to the failure.
+///
+/// ```ignore
+/// if serialized_size_hint(&message) > MAX_MESSAGE_SIZE {
+///     return Err(Error::TooLarge)
+/// }
+/// let mut buf = Vec::with_capacity(serialized_size_hint(&message));
+/// ```
+pub fn serialized_size_hint<T: Serialize + ?Sized>(value: &T) -> usize {
+    let mut size = 0;
+    let _ = value.serialize(SizeCounter(&mut size));
+    size
+}
+
+fn uint_size(val: u64) -> usize {
+    match val {
+        0..=0x7f => 1,
+        0x80..=0xff => 2,
+        0x100..=0xffff => 3,
+        0x1_0000..=0xffff_ffff => 5,
+        _ => 9
+    }
+}
+
+fn int_size(val: i64) -> usize {
+    match val {
+        0.. => uint_size(val as u64),
+        -32..=-1 => 1,
+        -0x80..=-33 => 2,
+        -0x8000..=-0x81 => 3,
+        -0x8000_0000..=-0x8001 => 5,
+        _ => 9
+    }
+}
+
+fn str_size(len: usize) -> usize {
+    len + match len {
+        0..=31 => 1,
+        32..=0xff => 2,
+        0x100..=0xffff => 3,
+        _ => 5
+    }
+}
+
+fn bytes_size(len: usize) -> usize {
+    len + match len {
+        0..=0xff => 2,
+        0x100..=0xffff => 3,
+        _ => 5
+    }
+}
+
+fn header_size(len: usize) -> usize {
+    match len {
+        0..=15 => 1,
+        16..=0xffff => 3,
+        _ => 5
+    }
+}
+
+/// A serializer that only adds up the estimated sizes of the values
+struct SizeCounter<'a>(&'a mut usize);
+
+/// Counts the elements of a sequence or map, the header is added at the end
+struct Compound<'a> {
+    size: &'a mut usize,
+    len: usize
+}
+
+impl<'a> Compound<'a> {
+    fn new(size: &'a mut usize, prefix: usize) -> Self {
+        *size += prefix;
+        Compound { size, len: 0 }
+    }
+
+    fn add<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), de::value::Error> {
+        self.len += 1;
+        value.serialize(SizeCounter(self.size))
+    }
+
+    fn finish(self) -> Result<(), de::value::Error> {
+        *self.size += header_size(self.len);
+        Ok(())
+    }
+}
+
+impl<'a> ser::Serializer for SizeCounter<'a> {
+    type Ok = ();
+    type Error = de::value::Error;
+    type SerializeSeq = Compound<'a>;
+    type SerializeTuple = Compound<'a>;
+    type SerializeTupleStruct = Compound<'a>;
+    type SerializeTupleVariant = Compound<'a>;
+    type SerializeMap = Compound<'a>;
+    type SerializeStruct = Compound<'a>;
+    type SerializeStructVariant = Compound<'a>;
+
+    #[inline]
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn serialize_bool(self, _val: bool) -> Result<(), Self::Error> {
+        *self.0 += 1;
+        Ok(())
+    }
+
+    #[inline]
+    fn serialize_i8(self, val: i8) -> Result<(), Self::Error> {
+        self.serialize_i64(i64::from(val))
+    }
+
+    #[inline]
+    fn serialize_i16(self, val: i16) -> Result<(), Self::Error> {
+        self.serialize_i64(i64::from(val))
+    }
+
+    #[inline]
+    fn serialize_i32(self, val: i32) -> Result<(), Self::Error> {
+        self.serialize_i64(i64::from(val))
+    }
+
+    #[inline]
+    fn serialize_i64(self, val: i64) -> Result<(), Self::Error> {
+        *self.0 += int_size(val);
+        Ok(())
+    }
+
+    #[inline]
+    fn serialize_i128(self, val: i128) -> Result<(), Self::Error> {
+        *self.0 += match i64::try_from(val) {
+            Ok(val) => int_size(val),
+            Err(_) => bytes_size(16)
+        };
+        Ok(())
+    }
+
+    #[inline]
+    fn serialize_u8(self, val: u8) -> Result<(), Self::Error> {
+        self.serialize_u64(u64::from(val))
+    }
+
+    #[inline]
+    fn serialize_u16(self, val: u16) -> Result<(), Self::Error> {
+        self.serialize_u64(u64::from(val))
+    }
+
+    #[inline]
+    fn serialize_u32(self, val: u32) -> Result<(), Self::Error> {
+        self.serialize_u64(u64::from(val))
+    }
+
+    #[inline]
+    fn serialize_u64(self, val: u64) -> Result<(), Self::Error> {
+        *self.0 += uint_size(val);
+        Ok(())
+    }
+
+    #[inline]
+    fn serialize_u128(self, val: u128) -> Result<(), Self::Error> {
+        *self.0 += match u64::try_from(val) {
+            Ok(val) => uint_size(val),
+            Err(_) => bytes_size(16)
+        };
+        Ok(())
+    }
+
+    #[inline]
+    fn serialize_f32(self, _val: f32) -> Result<(), Self::Error> {
+        *self.0 += 5;
+        Ok(())
+    }
+
+    #[inline]
+    fn serialize_f64(self, _val: f64) -> Result<(), Self::Error> {
+        *self.0 += 9;
+        Ok(())
+    }
+
+    #[inline]
+    fn serialize_char(self, val: char) -> Result<(), Self::Error> {
+        *self.0 += str_size(val.len_utf8());
+        Ok(())
+    }
+
+    #[inline]
+    fn serialize_str(self, val: &str) -> Result<(), Self::Error> {
+        *self.0 += str_size(val.len());
+        Ok(())
+    }
+
+    #[inline]
+    fn serialize_bytes(self, val: &[u8]) -> Result<(), Self::Error> {
+        *self.0 += bytes_size(val.len());
+        Ok(())
+    }
+
+    #[inline]
+    fn serialize_none(self) -> Result<(), Self::Error> {
+        self.serialize_unit()
+    }
+
+    #[inline]
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), Self::Error> {
+        value.serialize(self)
+    }
+
+    #[inline]
+    fn serialize_unit(self) -> Result<(), Self::Error> {
+        *self.0 += 1;
+        Ok(())
+    }
+
+    #[inline]
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), Self::Error> {
+        self.serialize_unit()
+    }
+
+    #[inline]
+    fn serialize_unit_variant(self, _name: &'static str, _index: u32, variant: &'static str) -> Result<(), Self::Error> {
+        self.serialize_str(variant)
+    }
+
+    #[inline]
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _name: &'static str, value: &T) -> Result<(), Self::Error> {
+        value.serialize(self)
+    }
+
+    #[inline]
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(self, _name: &'static str, _index: u32, variant: &'static str, value: &T) -> Result<(), Self::Error> {
+        *self.0 += header_size(1) + str_size(variant.len());
+        value.serialize(self)
+    }
+
+    #[inline]
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Compound<'a>, Self::Error> {
+        Ok(Compound::new(self.0, 0))
+    }
+
+    #[inline]
+    fn serialize_tuple(self, _len: usize) -> Result<Compound<'a>, Self::Error> {
+        Ok(Compound::new(self.0, 0))
+    }
+
+    #[inline]
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Compound<'a>, Self::Error> {
+        Ok(Compound::new(self.0, 0))
+    }
+
+    #[inline]
+    fn serialize_tuple_variant(self, _name: &'static str, _index: u32, variant: &'static str, _len: usize) -> Result<Compound<'a>, Self::Error> {
+        Ok(Compound::new(self.0, header_size(1) + str_size(variant.len())))
+    }
+
+    #[inline]
+    fn serialize_map(self, _len: Option<usize>) -> Result<Compound<'a>, Self::Error> {
+        Ok(Compound::new(self.0, 0))
+    }
+
+    #[inline]
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Compound<'a>, Self::Error> {
+        Ok(Compound::new(self.0, 0))
+    }
+
+    #[inline]
+    fn serialize_struct_variant(self, _name: &'static str, _index: u32, variant: &'static str, _len: usize) -> Result<Compound<'a>, Self::Error> {
+        Ok(Compound::new(self.0, header_size(1) + str_size(variant.len())))
+    }
+}
+
+impl<'a> ser::SerializeSeq for Compound<'a> {
+    type Ok = ();
+    type Error = de::value::Error;
+
+    #[inline]
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.add(value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<(), Self::Error> {
+        self.finish()
+    }
+}
+
+impl<'a> ser::SerializeTuple for Compound<'a> {
+    type Ok = ();
+    type Error = de::value::Error;
+
+    #[inline]
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.add(value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<(), Self::Error> {
+        self.finish()
+    }
+}
+
+impl<'a> ser::SerializeTupleStruct for Compound<'a> {
+    type Ok = ();
+    type Error = de::value::Error;
+
+    #[inline]
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.add(value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<(), Self::Error> {
+        self.finish()
+    }
+}
+
+impl<'a> ser::SerializeTupleVariant for Compound<'a> {
+    type Ok = ();
+    type Error = de::value::Error;
+
+    #[inline]
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.add(value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<(), Self::Error> {
+        self.finish()
+    }
+}
+
+impl<'a> ser::SerializeMap for Compound<'a> {
+    type Ok = ();
+    type Error = de::value::Error;
+
+    #[inline]
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Self::Error> {
+        self.add(key)
+    }
+
+    #[inline]
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        value.serialize(SizeCounter(self.size))
+    }
+
+    #[inline]
+    fn end(self) -> Result<(), Self::Error> {
+        self.finish()
+    }
+}
+
+impl<'a> ser::SerializeStruct for Compound<'a> {
+    type Ok = ();
+    type Error = de::value::Error;
+
+    #[inline]
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error> {
+        *self.size += str_size(key.len());
+        self.add(value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<(), Self::Error> {
+        self.finish()
+    }
+}
+
+impl<'a> ser::SerializeStructVariant for Compound<'a> {
+    type Ok = ();
+    type Error = de::value::Error;
+
+    #[inline]
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error> {
+        *self.size += str_size(key.len());
+        self.add(value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<(), Self::Error> {
+        self.finish()
+    }
+}
@@ -0,0 +1,432 @@
+use std::cell::Cell;
+use std::fmt;
+use std::io::{self, Read};
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut, Range};
+use std::rc::Rc;
+
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::de::{Visitor, SeqAccess, MapAccess, EnumAccess, VariantAccess, DeserializeSeed, IntoDeserializer, Error};
+
+const NAME: &str = "$serde_utils::Spanned";
+const START: &str = "$serde_utils::Spanned::start";
+const VALUE: &str = "$serde_utils::Spanned::value";
+const END: &str = "$serde_utils::Spanned::end";
+const FIELDS: &[&str] = &[START, VALUE, END];
+
+/// A value together with the byte offsets where it was found in the input
+///
+/// Spans can only be recorded while deserializing through a
+/// [`SpanDeserializer`](struct.SpanDeserializer.html), other deserializers fail with an error.
+/// Serialization is the same as for the value itself.
+///
+/// ```ignore
+/// #[derive(Deserialize)]
+/// struct Config {
+///     timeout: Spanned<u64>
+/// }
+///
+/// if *config.timeout == 0 {
+///     return Err(format!("error at offset {} (key 'timeout'): must not be 0", config.timeout.start()));
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Spanned<T> {
+    value: T,
+    start: usize,
+    end: usize
+}
+
+impl<T> Spanned<T> {
+    /// Creates a value with the given span
+    #[inline]
+    pub fn new(value: T, span: Range<usize>) -> Self {
+        Spanned { value, start: span.start, end: span.end }
+    }
+
+    /// Returns the offset of the first byte of the value
+    #[inline]
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// Returns the offset after the last byte of the value
+    #[inline]
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    /// Returns the range of offsets of the value
+    #[inline]
+    pub fn span(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    /// Returns the value without the span
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> Deref for Spanned<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for Spanned<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+impl<T: Serialize> Serialize for Spanned<T> {
+    #[inline]
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        self.value.serialize(ser)
+    }
+}
+
+struct SpannedVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for SpannedVisitor<T> {
+    type Value = Spanned<T>;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("a spanned value")
+    }
+
+    fn visit_map<V: MapAccess<'de>>(self, mut visitor: V) -> Result<Self::Value, V::Error> {
+        let start = next_field(&mut visitor, START)?;
+        let value = next_field(&mut visitor, VALUE)?;
+        let end = next_field(&mut visitor, END)?;
+        Ok(Spanned { value, start, end })
+    }
+}
+
+fn next_field<'de, V: MapAccess<'de>, T: Deserialize<'de>>(visitor: &mut V, key: &str) -> Result<T, V::Error> {
+    match visitor.next_key::<String>()? {
+        Some(ref found) if found == key => visitor.next_value(),
+        _ => Err(V::Error::custom("spanned values can only be deserialized via SpanDeserializer"))
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Spanned<T> {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        de.deserialize_struct(NAME, FIELDS, SpannedVisitor(PhantomData))
+    }
+}
+
+/// A shared count of the bytes read by an [`OffsetReader`](struct.OffsetReader.html)
+#[derive(Debug, Clone, Default)]
+pub struct ByteCounter(Rc<Cell<usize>>);
+
+impl ByteCounter {
+    /// Returns the number of bytes read so far
+    #[inline]
+    pub fn get(&self) -> usize {
+        self.0.get()
+    }
+}
+
+/// A reader that counts the bytes read from it
+///
+/// Deserializers reading from it can be wrapped in a
+/// [`SpanDeserializer`](struct.SpanDeserializer.html) using its [counter](#method.counter).
+#[derive(Debug)]
+pub struct OffsetReader<R> {
+    reader: R,
+    counter: ByteCounter
+}
+
+impl<R> OffsetReader<R> {
+    /// Wraps a reader, offsets start at 0
+    #[inline]
+    pub fn new(reader: R) -> Self {
+        OffsetReader { reader, counter: ByteCounter::default() }
+    }
+
+    /// Returns the counter of the bytes read
+    #[inline]
+    pub fn counter(&self) -> ByteCounter {
+        self.counter.clone()
+    }
+
+    /// Returns the wrapped reader
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: Read> Read for OffsetReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.reader.read(buf)?;
+        self.counter.0.set(self.counter.0.get() + len);
+        Ok(len)
+    }
+}
+
+/// A deserializer adapter that records the spans of [`Spanned`](struct.Spanned.html) values
+///
+/// The offsets are taken from the counter of the [`OffsetReader`](struct.OffsetReader.html) that
+/// the wrapped deserializer reads from. They are exact for formats that do not read ahead like
+/// msgpack. Deserializers that peek at the next byte like the one of `serde_json` can report
+/// offsets that are one byte off, e.g. the start after the opening quote of a string or the end
+/// after the comma following a number. Whitespace before a value can also be part of its span.
+///
+/// ```ignore
+/// let reader = OffsetReader::new(File::open(path)?);
+/// let counter = reader.counter();
+/// let mut de = serde_json::Deserializer::from_reader(reader);
+/// let config = Config::deserialize(SpanDeserializer::new(&mut de, counter))?;
+/// ```
+pub struct SpanDeserializer<D> {
+    de: D,
+    counter: ByteCounter
+}
+
+impl<D> SpanDeserializer<D> {
+    /// Wraps a deserializer that reads from the reader of the counter
+    #[inline]
+    pub fn new(de: D, counter: ByteCounter) -> Self {
+        SpanDeserializer { de, counter }
+    }
+}
+
+struct Wrap<T> {
+    inner: T,
+    counter: ByteCounter
+}
+
+impl<T> Wrap<T> {
+    #[inline]
+    fn new(inner: T, counter: &ByteCounter) -> Self {
+        Wrap { inner, counter: counter.clone() }
+    }
+}
+
+macro_rules! forward_deserialize(
+    ( $( $method:ident ( $( $arg:ident : $ty:ty ),* ) ),* ) => {
+        $(
+            #[inline]
+            fn $method<V: Visitor<'de>>(self, $( $arg: $ty, )* visitor: V) -> Result<V::Value, D::Error> {
+                let visitor = Wrap::new(visitor, &self.counter);
+                self.de.$method($( $arg, )* visitor)
+            }
+        )*
+    };
+);
+
+impl<'de, D: Deserializer<'de>> Deserializer<'de> for SpanDeserializer<D> {
+    type Error = D::Error;
+
+    fn deserialize_struct<V: Visitor<'de>>(self, name: &'static str, fields: &'static [&'static str], visitor: V
+    ) -> Result<V::Value, D::Error> {
+        if name == NAME {
+            let start = self.counter.get();
+            return visitor.visit_map(SpanAccess { de: Some(self.de), counter: self.counter, start, field: 0 });
+        }
+        let visitor = Wrap::new(visitor, &self.counter);
+        self.de.deserialize_struct(name, fields, visitor)
+    }
+
+    forward_deserialize!(
+        deserialize_any(), deserialize_bool(), deserialize_i8(), deserialize_i16(), deserialize_i32(),
+        deserialize_i64(), deserialize_i128(), deserialize_u8(), deserialize_u16(), deserialize_u32(),
+        deserialize_u64(), deserialize_u128(), deserialize_f32(), deserialize_f64(), deserialize_char(),
+        deserialize_str(), deserialize_string(), deserialize_bytes(), deserialize_byte_buf(),
+        deserialize_option(), deserialize_unit(), deserialize_unit_struct(name: &'static str),
+        deserialize_newtype_struct(name: &'static str), deserialize_seq(), deserialize_tuple(len: usize),
+        deserialize_tuple_struct(name: &'static str, len: usize), deserialize_map(),
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]), deserialize_identifier(),
+        deserialize_ignored_any()
+    );
+
+    #[inline]
+    fn is_human_readable(&self) -> bool {
+        self.de.is_human_readable()
+    }
+}
+
+struct SpanAccess<D> {
+    de: Option<D>,
+    counter: ByteCounter,
+    start: usize,
+    field: usize
+}
+
+impl<'de, D: Deserializer<'de>> MapAccess<'de> for SpanAccess<D> {
+    type Error = D::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, D::Error> {
+        match FIELDS.get(self.field) {
+            Some(&key) => seed.deserialize(key.into_deserializer()).map(Some),
+            None => Ok(None)
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, D::Error> {
+        self.field += 1;
+        match self.field {
+            1 => seed.deserialize((self.start as u64).into_deserializer()),
+            2 => match self.de.take() {
+                Some(de) => seed.deserialize(SpanDeserializer::new(de, self.counter.clone())),
+                None => Err(D::Error::custom("spanned value deserialized twice"))
+            },
+            _ => seed.deserialize((self.counter.get() as u64).into_deserializer())
+        }
+    }
+}
+
+impl<'de, S: DeserializeSeed<'de>> DeserializeSeed<'de> for Wrap<S> {
+    type Value = S::Value;
+
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(self, de: D) -> Result<S::Value, D::Error> {
+        self.inner.deserialize(SpanDeserializer::new(de, self.counter))
+    }
+}
+
+impl<'de, A: SeqAccess<'de>> SeqAccess<'de> for Wrap<A> {
+    type Error = A::Error;
+
+    #[inline]
+    fn next_element_seed<S: DeserializeSeed<'de>>(&mut self, seed: S) -> Result<Option<S::Value>, A::Error> {
+        let seed = Wrap::new(seed, &self.counter);
+        self.inner.next_element_seed(seed)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+impl<'de, A: MapAccess<'de>> MapAccess<'de> for Wrap<A> {
+    type Error = A::Error;
+
+    #[inline]
+    fn next_key_seed<S: DeserializeSeed<'de>>(&mut self, seed: S) -> Result<Option<S::Value>, A::Error> {
+        let seed = Wrap::new(seed, &self.counter);
+        self.inner.next_key_seed(seed)
+    }
+
+    #[inline]
+    fn next_value_seed<S: DeserializeSeed<'de>>(&mut self, seed: S) -> Result<S::Value, A::Error> {
+        let seed = Wrap::new(seed, &self.counter);
+        self.inner.next_value_seed(seed)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+impl<'de, A: EnumAccess<'de>> EnumAccess<'de> for Wrap<A> {
+    type Error = A::Error;
+    type Variant = Wrap<A::Variant>;
+
+    #[inline]
+    fn variant_seed<S: DeserializeSeed<'de>>(self, seed: S) -> Result<(S::Value, Self::Variant), A::Error> {
+        let counter = self.counter;
+        let (value, variant) = self.inner.variant_seed(Wrap::new(seed, &counter))?;
+        Ok((value, Wrap { inner: variant, counter }))
+    }
+}
+
+impl<'de, A: VariantAccess<'de>> VariantAccess<'de> for Wrap<A> {
+    type Error = A::Error;
+
+    #[inline]
+    fn unit_variant(self) -> Result<(), A::Error> {
+        self.inner.unit_variant()
+    }
+
+    #[inline]
+    fn newtype_variant_seed<S: DeserializeSeed<'de>>(self, seed: S) -> Result<S::Value, A::Error> {
+        let seed = Wrap::new(seed, &self.counter);
+        self.inner.newtype_variant_seed(seed)
+    }
+
+    #[inline]
+    fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, A::Error> {
+        let visitor = Wrap::new(visitor, &self.counter);
+        self.inner.tuple_variant(len, visitor)
+    }
+
+    #[inline]
+    fn struct_variant<V: Visitor<'de>>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value, A::Error> {
+        let visitor = Wrap::new(visitor, &self.counter);
+        self.inner.struct_variant(fields, visitor)
+    }
+}
+
+macro_rules! forward_visit(
+    ( $( $method:ident ( $ty:ty ) ),* ) => {
+        $(
+            #[inline]
+            fn $method<E: Error>(self, val: $ty) -> Result<V::Value, E> {
+                self.inner.$method(val)
+            }
+        )*
+    };
+);
+
+impl<'de, V: Visitor<'de>> Visitor<'de> for Wrap<V> {
+    type Value = V::Value;
+
+    #[inline]
+    fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        self.inner.expecting(fmt)
+    }
+
+    forward_visit!(
+        visit_bool(bool), visit_i8(i8), visit_i16(i16), visit_i32(i32), visit_i64(i64), visit_i128(i128),
+        visit_u8(u8), visit_u16(u16), visit_u32(u32), visit_u64(u64), visit_u128(u128), visit_f32(f32),
+        visit_f64(f64), visit_char(char), visit_str(&str), visit_borrowed_str(&'de str), visit_string(String),
+        visit_bytes(&[u8]), visit_borrowed_bytes(&'de [u8]), visit_byte_buf(Vec<u8>)
+    );
+
+    #[inline]
+    fn visit_none<E: Error>(self) -> Result<V::Value, E> {
+        self.inner.visit_none()
+    }
+
+    #[inline]
+    fn visit_some<D: Deserializer<'de>>(self, de: D) -> Result<V::Value, D::Error> {
+        self.inner.visit_some(SpanDeserializer::new(de, self.counter))
+    }
+
+    #[inline]
+    fn visit_unit<E: Error>(self) -> Result<V::Value, E> {
+        self.inner.visit_unit()
+    }
+
+    #[inline]
+    fn visit_newtype_struct<D: Deserializer<'de>>(self, de: D) -> Result<V::Value, D::Error> {
+        self.inner.visit_newtype_struct(SpanDeserializer::new(de, self.counter))
+    }
+
+    #[inline]
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<V::Value, A::Error> {
+        self.inner.visit_seq(Wrap { inner: seq, counter: self.counter })
+    }
+
+    #[inline]
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<V::Value, A::Error> {
+        self.inner.visit_map(Wrap { inner: map, counter: self.counter })
+    }
+
+    #[inline]
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<V::Value, A::Error> {
+        self.inner.visit_enum(Wrap { inner: data, counter: self.counter })
+    }
+}
@@ -0,0 +1,319 @@
+//! Helpers for testing (de-)serialization
+//!
+//! The helpers serialize values in the formats enabled via features: `msgpack` (using
+//! `rmp-serde`), `json` (using `serde_json`) and `cbor` (using `serde_cbor`). The module is only
+//! available if at least one of these features is enabled. Failures are reported by panicking with
+//! a description of the differences, so the helpers are meant to be used in tests.
+//!
+//! [`assert_roundtrip`](fn.assert_roundtrip.html) checks that a value survives serialization and
+//! deserialization in all enabled formats.
+//!
+//! [`assert_matches_golden`](fn.assert_matches_golden.html) compares the serialization of a value
+//! with a stored file to ensure that the wire format does not change unintentionally, e.g. when
+//! refactoring a [`serde_impl!`](../macro.serde_impl!.html) invocation.
+//!
+//! [`write_corpus`](fn.write_corpus.html) writes a seed corpus of structurally interesting values
+//! for fuzzing decoders.
+//!
+//! With the `test-util` feature, [`assert_arbitrary_roundtrip`](fn.assert_arbitrary_roundtrip.html)
+//! checks the round trip of generated values. [`serde_impl!`](../macro.serde_impl!.html) then also
+//! emits such a test for every type, see
+//! [round trip tests](../macro.serde_impl!.html#round-trip-tests).
+//!
+//! ```ignore
+//! #[test]
+//! fn test_wire_format() {
+//!     assert_roundtrip(&Config::default());
+//!     assert_matches_golden("tests/golden/config.msgpack", &Config::default());
+//! }
+//! ```
+
+use std::any;
+use std::env;
+use std::fmt::Debug;
+use std::fs;
+use std::io;
+use std::iter;
+use std::path::Path;
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+#[cfg(feature = "test-util")] use arbitrary::{Arbitrary, Unstructured};
+
+use generic::Obj;
+use diff::diff;
+
+/// The environment variable that causes [`assert_matches_golden`](fn.assert_matches_golden.html)
+/// to overwrite the golden files instead of comparing them
+pub const UPDATE_GOLDEN_VAR: &str = "SERDE_UTILS_UPDATE_GOLDEN";
+
+/// A serialization format enabled via features
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Format {
+    /// MessagePack using `rmp-serde`, requires the `msgpack` feature
+    #[cfg(feature = "msgpack")]
+    Msgpack,
+
+    /// JSON using `serde_json`, requires the `json` feature
+    #[cfg(feature = "json")]
+    Json,
+
+    /// CBOR using `serde_cbor`, requires the `cbor` feature
+    #[cfg(feature = "cbor")]
+    Cbor
+}
+
+impl Format {
+    /// Returns all enabled formats
+    pub fn all() -> &'static [Format] {
+        &[
+            #[cfg(feature = "msgpack")] Format::Msgpack,
+            #[cfg(feature = "json")] Format::Json,
+            #[cfg(feature = "cbor")] Format::Cbor
+        ]
+    }
+
+    /// Returns the enabled format for a file extension
+    ///
+    /// The extensions are `msgpack` and `mpk` for MessagePack, `json` for JSON and `cbor` for CBOR.
+    pub fn from_extension(ext: &str) -> Option<Format> {
+        match ext {
+            #[cfg(feature = "msgpack")]
+            "msgpack" | "mpk" => Some(Format::Msgpack),
+            #[cfg(feature = "json")]
+            "json" => Some(Format::Json),
+            #[cfg(feature = "cbor")]
+            "cbor" => Some(Format::Cbor),
+            _ => None
+        }
+    }
+
+    /// Returns the name of the format
+    pub fn name(&self) -> &'static str {
+        match *self {
+            #[cfg(feature = "msgpack")]
+            Format::Msgpack => "msgpack",
+            #[cfg(feature = "json")]
+            Format::Json => "JSON",
+            #[cfg(feature = "cbor")]
+            Format::Cbor => "CBOR"
+        }
+    }
+
+    /// Returns the file extension of the format
+    pub fn extension(&self) -> &'static str {
+        match *self {
+            #[cfg(feature = "msgpack")]
+            Format::Msgpack => "msgpack",
+            #[cfg(feature = "json")]
+            Format::Json => "json",
+            #[cfg(feature = "cbor")]
+            Format::Cbor => "cbor"
+        }
+    }
+
+    /// Serializes the value, returning the error message on failure
+    pub fn serialize<T: Serialize>(&self, value: &T) -> Result<Vec<u8>, String> {
+        match *self {
+            #[cfg(feature = "msgpack")]
+            Format::Msgpack => ::rmp_serde::to_vec(value).map_err(|err| err.to_string()),
+            #[cfg(feature = "json")]
+            Format::Json => ::serde_json::to_vec(value).map_err(|err| err.to_string()),
+            #[cfg(feature = "cbor")]
+            Format::Cbor => ::serde_cbor::to_vec(value).map_err(|err| err.to_string())
+        }
+    }
+
+    /// Deserializes a value, returning the error message on failure
+    pub fn deserialize<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, String> {
+        match *self {
+            #[cfg(feature = "msgpack")]
+            Format::Msgpack => ::rmp_serde::from_slice(bytes).map_err(|err| err.to_string()),
+            #[cfg(feature = "json")]
+            Format::Json => ::serde_json::from_slice(bytes).map_err(|err| err.to_string()),
+            #[cfg(feature = "cbor")]
+            Format::Cbor => ::serde_cbor::from_slice(bytes).map_err(|err| err.to_string())
+        }
+    }
+}
+
+fn decode_obj(format: Format, bytes: &[u8]) -> Obj {
+    format.deserialize(bytes).unwrap_or_else(|err| panic!("failed to decode {} data: {}", format.name(), err))
+}
+
+/// Asserts that a value serializes to the contents of a golden file and can be decoded from it
+///
+/// The format is selected by the file extension, see
+/// [`Format::from_extension`](enum.Format.html#method.from_extension). If the file does not exist
+/// or the environment variable [`UPDATE_GOLDEN_VAR`](constant.UPDATE_GOLDEN_VAR.html) is set, the
+/// file is written instead.
+///
+/// The assertion fails if the serialized bytes differ from the file or if the file does not decode
+/// to a value equal to the given one. Differences are reported per path of the decoded data.
+pub fn assert_matches_golden<T, P>(path: P, value: &T)
+    where T: Serialize + DeserializeOwned + PartialEq + Debug, P: AsRef<Path>
+{
+    let path = path.as_ref();
+    let ty = any::type_name::<T>();
+    let format = path.extension().and_then(|ext| ext.to_str()).and_then(Format::from_extension)
+        .unwrap_or_else(|| panic!("no enabled format for golden file {}", path.display()));
+    let bytes = format.serialize(value).unwrap_or_else(|err| panic!("failed to serialize {}: {}", ty, err));
+    if env::var_os(UPDATE_GOLDEN_VAR).is_some() || !path.exists() {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).unwrap_or_else(|err| panic!("failed to create {}: {}", dir.display(), err));
+        }
+        fs::write(path, &bytes).unwrap_or_else(|err| panic!("failed to write {}: {}", path.display(), err));
+        return;
+    }
+    let golden = fs::read(path).unwrap_or_else(|err| panic!("failed to read {}: {}", path.display(), err));
+    if golden != bytes {
+        let diff = diff(&decode_obj(format, &golden), &decode_obj(format, &bytes));
+        if diff.is_empty() {
+            panic!("serialization of {} differs from golden file {} only in encoding details", ty, path.display());
+        }
+        panic!("serialization of {} differs from golden file {}:\n{}", ty, path.display(), diff.join("\n"));
+    }
+    let decoded: T = format.deserialize(&golden)
+        .unwrap_or_else(|err| panic!("failed to decode golden file {} as {}: {}", path.display(), ty, err));
+    if decoded != *value {
+        panic!("golden file {} decodes to a different {}:\n  decoded: {:?}\n  expected: {:?}", path.display(), ty, decoded, value);
+    }
+}
+
+fn roundtrip<T>(format: Format, value: &T) -> Result<(), String>
+    where T: Serialize + DeserializeOwned + PartialEq + Debug
+{
+    let bytes = format.serialize(value).map_err(|err| format!("failed to serialize: {}", err))?;
+    let decoded: T = format.deserialize(&bytes).map_err(|err| format!("failed to deserialize: {}", err))?;
+    if decoded == *value {
+        return Ok(());
+    }
+    let redecoded = format.serialize(&decoded).ok().and_then(|bytes| format.deserialize::<Obj>(&bytes).ok());
+    match (format.deserialize::<Obj>(&bytes), redecoded) {
+        (Ok(expected), Some(actual)) if expected != actual => Err(diff(&expected, &actual).join("\n")),
+        _ => Err(format!("decoded: {:?}\n  expected: {:?}", decoded, value))
+    }
+}
+
+/// Asserts that a value is equal to itself after serializing and deserializing it in every
+/// enabled format
+///
+/// All formats are checked and the assertion reports every format that failed. If possible, the
+/// differences are reported per path of the serialized data.
+pub fn assert_roundtrip<T>(value: &T) where T: Serialize + DeserializeOwned + PartialEq + Debug {
+    let failures: Vec<String> = Format::all().iter()
+        .filter_map(|&format| roundtrip(format, value).err().map(|err| format!("{}: {}", format.name(), err)))
+        .collect();
+    if !failures.is_empty() {
+        panic!("round trip of {} failed:\n{}", any::type_name::<T>(), failures.join("\n"));
+    }
+}
+
+/// The number of generated values checked by the tests that [`serde_impl!`](../macro.serde_impl!.html)
+/// emits with the `test-util` feature
+#[cfg(feature = "test-util")]
+pub const ROUNDTRIP_CASES: usize = 256;
+
+/// Asserts that generated values survive a round trip in every enabled format
+///
+/// The values are generated via `Arbitrary` from pseudo-random data of growing length, so the
+/// first cases are small and the later ones more complex. The data is the same in every run, so
+/// failures can be reproduced. Cases where the data is not enough to generate a value are
+/// skipped.
+#[cfg(feature = "test-util")]
+pub fn assert_arbitrary_roundtrip<T>(cases: usize)
+    where T: for<'a> Arbitrary<'a> + Serialize + DeserializeOwned + PartialEq + Debug
+{
+    let mut state = 0x9e37_79b9_7f4a_7c15u64;
+    let mut data = Vec::new();
+    for case in 0..cases {
+        data.clear();
+        for _ in 0..(case + 1) * 16 {
+            // xorshift64
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            data.push(state as u8);
+        }
+        if let Ok(value) = T::arbitrary(&mut Unstructured::new(&data)) {
+            assert_roundtrip(&value);
+        }
+    }
+}
+
+fn str(val: &str) -> Obj {
+    Obj::Str(val.to_string())
+}
+
+fn single(key: Obj, val: Obj) -> Obj {
+    Obj::Map(iter::once((key, val)).collect())
+}
+
+fn nested(depth: usize, f: fn(Obj) -> Obj) -> Obj {
+    (0..depth).fold(Obj::Null, |obj, _| f(obj))
+}
+
+/// Returns structurally interesting values with a unique name each
+///
+/// The values cover the edges of all variants: extreme and mixed-sign numbers, special floats,
+/// empty, long and unusual strings and byte sequences, empty, large and deeply nested lists and
+/// maps, huge keys and keys of all types.
+pub fn interesting_objs() -> Vec<(&'static str, Obj)> {
+    let long = "x".repeat(1 << 16);
+    vec![
+        ("null", Obj::Null),
+        ("true", Obj::Bool(true)),
+        ("false", Obj::Bool(false)),
+        ("zero", Obj::Unsigned(0)),
+        ("u64_max", Obj::Unsigned(u64::MAX)),
+        ("minus_one", Obj::Signed(-1)),
+        ("i64_min", Obj::Signed(i64::MIN)),
+        ("mixed_sign_numbers", Obj::List(vec![
+            Obj::Signed(i64::MIN), Obj::Signed(-1), Obj::Unsigned(0), Obj::Signed(0), Obj::Unsigned(1),
+            Obj::Unsigned(i64::MAX as u64 + 1), Obj::Unsigned(u64::MAX), Obj::Float(-1.0), Obj::Float(1.5)
+        ])),
+        ("float_zero", Obj::Float(0.0)),
+        ("float_negative_zero", Obj::Float(-0.0)),
+        ("float_nan", Obj::Float(f64::NAN)),
+        ("float_infinity", Obj::Float(f64::INFINITY)),
+        ("float_negative_infinity", Obj::Float(f64::NEG_INFINITY)),
+        ("float_min_positive", Obj::Float(f64::MIN_POSITIVE)),
+        ("float_subnormal", Obj::Float(f64::from_bits(1))),
+        ("float_max", Obj::Float(f64::MAX)),
+        ("float_array", Obj::F64Array(vec![f64::NAN, -0.0, f64::INFINITY, f64::EPSILON])),
+        ("empty_str", str("")),
+        ("long_str", Obj::Str(long.clone())),
+        ("unicode_str", str("\u{0}\u{7f}\u{ff}\u{2028}\u{fffd}\u{1f600}\"\\")),
+        ("empty_bin", Obj::from_bytes(Vec::new())),
+        ("all_bytes", Obj::from_bytes((0..=255).collect())),
+        ("empty_list", Obj::List(Vec::new())),
+        ("empty_map", Obj::Map(Default::default())),
+        ("wide_list", Obj::U64Array((0..10_000).collect())),
+        ("wide_map", Obj::Map((0..10_000u64).map(|i| (Obj::Str(i.to_string()), Obj::Unsigned(i))).collect())),
+        ("nested_lists", nested(100, |obj| Obj::List(vec![obj]))),
+        ("nested_maps", nested(100, |obj| single(str("a"), obj))),
+        ("huge_key", single(Obj::Str(long), Obj::Null)),
+        ("empty_key", single(str(""), Obj::Null)),
+        ("mixed_keys", Obj::Map(vec![
+            Obj::Null, Obj::Bool(true), Obj::Signed(-1), Obj::Unsigned(1), Obj::Float(0.5), str("a"),
+            Obj::from_bytes(vec![0]), Obj::List(vec![Obj::Unsigned(1)]), single(str("a"), Obj::Null)
+        ].into_iter().zip(iter::repeat(Obj::Null)).collect()))
+    ]
+}
+
+/// Writes all [interesting values](fn.interesting_objs.html) that can be serialized in the format
+/// as files named `<name>.<extension>` into the directory
+///
+/// The directory is created if needed. Returns the number of written files.
+pub fn write_corpus<P: AsRef<Path>>(dir: P, format: Format) -> io::Result<usize> {
+    let dir = dir.as_ref();
+    fs::create_dir_all(dir)?;
+    let mut count = 0;
+    for (name, obj) in interesting_objs() {
+        if let Ok(bytes) = format.serialize(&obj) {
+            fs::write(dir.join(format!("{}.{}", name, format.extension())), bytes)?;
+            count += 1;
+        }
+    }
+    Ok(count)
+}
@@ -0,0 +1,275 @@
+use std::borrow::Cow;
+use std::convert::Infallible;
+
+use generic::Obj;
+use map::MapBuilder;
+use path::{ObjPath, PathSegment};
+
+/// Statistics about an [`Obj`](enum.Obj.html) tree as returned by [`Obj::stats`](enum.Obj.html#method.stats)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ObjStats {
+    /// The number of `Null` values
+    pub nulls: usize,
+    /// The number of `Bool` values
+    pub bools: usize,
+    /// The number of `Signed` values, including negative `BigInt` values
+    pub signed: usize,
+    /// The number of `Unsigned` values, including non-negative `BigInt` values
+    pub unsigned: usize,
+    /// The number of `Float` values, including `Decimal` values
+    pub floats: usize,
+    /// The number of `Str` values
+    pub strs: usize,
+    /// The number of `Bin` values
+    pub bins: usize,
+    /// The number of `List` values
+    pub lists: usize,
+    /// The number of `Map` values
+    pub maps: usize,
+    /// The total length of all strings in bytes, including map keys
+    pub str_bytes: usize,
+    /// The total length of all byte sequences, including map keys
+    pub bin_bytes: usize,
+    /// The length of the longest path, i.e. `0` for a single value and `1` for a flat list
+    pub max_depth: usize,
+    /// The number of elements of the largest list
+    pub max_list_len: usize,
+    /// The number of entries of the largest map
+    pub max_map_len: usize
+}
+
+impl ObjStats {
+    /// Returns the total number of values
+    #[inline]
+    pub fn nodes(&self) -> usize {
+        self.nulls + self.bools + self.signed + self.unsigned + self.floats + self.strs + self.bins
+            + self.lists + self.maps
+    }
+
+    fn add(&mut self, obj: &Obj, depth: usize) {
+        if depth > self.max_depth {
+            self.max_depth = depth;
+        }
+        match *obj {
+            Obj::Null => self.nulls += 1,
+            Obj::Bool(_) => self.bools += 1,
+            Obj::Signed(_) => self.signed += 1,
+            Obj::Unsigned(_) => self.unsigned += 1,
+            Obj::Float(_) => self.floats += 1,
+            #[cfg(feature = "num-bigint")]
+            Obj::BigInt(ref val) => if val.sign() == ::num_bigint::Sign::Minus {
+                self.signed += 1
+            } else {
+                self.unsigned += 1
+            },
+            #[cfg(feature = "rust_decimal")]
+            Obj::Decimal(_) => self.floats += 1,
+            Obj::Str(ref val) => {
+                self.strs += 1;
+                self.str_bytes += val.len();
+            },
+            Obj::Bin(ref val) => {
+                self.bins += 1;
+                self.bin_bytes += val.len();
+            },
+            Obj::List(ref list) => {
+                self.lists += 1;
+                self.max_list_len = self.max_list_len.max(list.len());
+                for val in list {
+                    self.add(val, depth + 1);
+                }
+            },
+            Obj::U64Array(ref list) => {
+                self.lists += 1;
+                self.max_list_len = self.max_list_len.max(list.len());
+                self.unsigned += list.len();
+                if !list.is_empty() {
+                    self.max_depth = self.max_depth.max(depth + 1);
+                }
+            },
+            Obj::F64Array(ref list) => {
+                self.lists += 1;
+                self.max_list_len = self.max_list_len.max(list.len());
+                self.floats += list.len();
+                if !list.is_empty() {
+                    self.max_depth = self.max_depth.max(depth + 1);
+                }
+            },
+            Obj::Map(ref map) => {
+                self.maps += 1;
+                self.max_map_len = self.max_map_len.max(map.len());
+                for (key, val) in map.iter() {
+                    self.add(key, depth + 1);
+                    self.add(val, depth + 1);
+                }
+            }
+        }
+    }
+}
+
+impl Obj {
+    /// Rebuilds the tree, replacing every leaf value with the result of the closure
+    ///
+    /// Leaves are all values except lists and maps. The closure is called with the path of each
+    /// leaf and the leaf itself. Lists and maps are rebuilt with the same structure and map keys
+    /// are kept unchanged. The elements of `U64Array` and `F64Array` values are passed to the
+    /// closure as `Unsigned` and `Float` values and the results are collected in the most compact
+    /// representation.
+    pub fn map_values<F: FnMut(&ObjPath, Obj) -> Obj>(self, mut f: F) -> Obj {
+        match self.try_map_values(|path, val| Ok::<_, Infallible>(f(path, val))) {
+            Ok(obj) => obj,
+            Err(err) => match err {}
+        }
+    }
+
+    /// Like [`map_values`](#method.map_values), but stops at the first error of the closure and
+    /// returns it
+    pub fn try_map_values<E, F: FnMut(&ObjPath, Obj) -> Result<Obj, E>>(self, mut f: F) -> Result<Obj, E> {
+        self.try_map_values_at(&mut ObjPath::new(), &mut f)
+    }
+
+    fn try_map_values_at<E, F: FnMut(&ObjPath, Obj) -> Result<Obj, E>>(self, path: &mut ObjPath, f: &mut F) -> Result<Obj, E> {
+        match self {
+            Obj::List(list) => {
+                let mut res = Vec::with_capacity(list.len());
+                for (i, val) in list.into_iter().enumerate() {
+                    path.push(PathSegment::Index(i));
+                    let val = val.try_map_values_at(path, f);
+                    path.pop();
+                    res.push(val?);
+                }
+                Ok(Obj::List(res))
+            },
+            Obj::U64Array(_) | Obj::F64Array(_) => {
+                let list = Obj::List(self.into_list().unwrap_or_default()).try_map_values_at(path, f)?;
+                Ok(Obj::from_list(list.into_list().unwrap_or_default()))
+            },
+            Obj::Map(map) => {
+                let mut res = MapBuilder::new(map.len());
+                for (key, val) in map {
+                    path.push(PathSegment::Key(key));
+                    let val = val.try_map_values_at(path, f);
+                    let key = match path.pop() {
+                        Some(PathSegment::Key(key)) => key,
+                        _ => unreachable!()
+                    };
+                    res.insert(key, val?);
+                }
+                Ok(res.finish())
+            },
+            val => f(path, val)
+        }
+    }
+
+    /// Removes all map entries and list elements for which the predicate returns `false`
+    ///
+    /// The predicate is called with the path and value of every entry and element at any depth,
+    /// parents before their children. Children of removed values are not visited. List positions
+    /// in the paths refer to the positions before any element was removed. The root itself is
+    /// never removed.
+    pub fn retain<F: FnMut(&ObjPath, &Obj) -> bool>(&mut self, mut f: F) {
+        self.retain_at(&mut ObjPath::new(), &mut f)
+    }
+
+    fn retain_at<F: FnMut(&ObjPath, &Obj) -> bool>(&mut self, path: &mut ObjPath, f: &mut F) {
+        match *self {
+            Obj::List(ref mut list) => {
+                let mut i = 0;
+                list.retain_mut(|val| {
+                    path.push(PathSegment::Index(i));
+                    i += 1;
+                    let keep = f(path, val);
+                    if keep {
+                        val.retain_at(path, f);
+                    }
+                    path.pop();
+                    keep
+                });
+            },
+            Obj::U64Array(ref mut list) => {
+                let mut i = 0;
+                list.retain(|&val| {
+                    path.push(PathSegment::Index(i));
+                    i += 1;
+                    let keep = f(path, &Obj::Unsigned(val));
+                    path.pop();
+                    keep
+                });
+            },
+            Obj::F64Array(ref mut list) => {
+                let mut i = 0;
+                list.retain(|&val| {
+                    path.push(PathSegment::Index(i));
+                    i += 1;
+                    let keep = f(path, &Obj::Float(val));
+                    path.pop();
+                    keep
+                });
+            },
+            Obj::Map(ref mut map) => map.retain(|key, val| {
+                path.push(PathSegment::Key(key.clone()));
+                let keep = f(path, val);
+                if keep {
+                    val.retain_at(path, f);
+                }
+                path.pop();
+                keep
+            }),
+            _ => ()
+        }
+    }
+
+    /// Returns the paths and values of all values in the tree that match the predicate
+    ///
+    /// The predicate is called with the path and value of every value including the root, lists and
+    /// maps, parents before their children. Map keys can be matched via the last segment of the
+    /// path. The elements of `U64Array` and `F64Array` values are visited as `Unsigned` and `Float`
+    /// values and returned as owned values, all other values are borrowed.
+    pub fn find_all<F: FnMut(&ObjPath, &Obj) -> bool>(&self, mut f: F) -> Vec<(ObjPath, Cow<'_, Obj>)> {
+        let mut res = Vec::new();
+        self.find_all_at(&mut ObjPath::new(), &mut f, &mut res);
+        res
+    }
+
+    fn find_all_at<'a, F: FnMut(&ObjPath, &Obj) -> bool>(&'a self, path: &mut ObjPath, f: &mut F, res: &mut Vec<(ObjPath, Cow<'a, Obj>)>) {
+        if f(path, self) {
+            res.push((path.clone(), Cow::Borrowed(self)));
+        }
+        match *self {
+            Obj::List(ref list) => for (i, val) in list.iter().enumerate() {
+                path.push(PathSegment::Index(i));
+                val.find_all_at(path, f, res);
+                path.pop();
+            },
+            Obj::U64Array(ref list) => find_all_elements(list.iter().map(|&val| Obj::Unsigned(val)), path, f, res),
+            Obj::F64Array(ref list) => find_all_elements(list.iter().map(|&val| Obj::Float(val)), path, f, res),
+            Obj::Map(ref map) => for (key, val) in map.iter() {
+                path.push(PathSegment::Key(key.clone()));
+                val.find_all_at(path, f, res);
+                path.pop();
+            },
+            _ => ()
+        }
+    }
+
+    /// Returns statistics about the tree like the number of values of each variant and the maximum
+    /// depth
+    ///
+    /// Map keys are counted like values.
+    pub fn stats(&self) -> ObjStats {
+        let mut stats = ObjStats::default();
+        stats.add(self, 0);
+        stats
+    }
+}
+
+/// Calls the predicate of `find_all` for the scalar elements of a compact list
+fn find_all_elements<'a, I: Iterator<Item=Obj>, F: FnMut(&ObjPath, &Obj) -> bool>(elements: I, path: &mut ObjPath, f: &mut F, res: &mut Vec<(ObjPath, Cow<'a, Obj>)>) {
+    for (i, val) in elements.enumerate() {
+        path.push(PathSegment::Index(i));
+        if f(path, &val) {
+            res.push((path.clone(), Cow::Owned(val)));
+        }
+        path.pop();
+    }
+}
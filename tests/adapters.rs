@@ -0,0 +1,547 @@
+#[macro_use] extern crate serde_derive;
+extern crate serde;
+extern crate serde_bytes;
+extern crate serde_json;
+extern crate bincode;
+extern crate rmp_serde;
+#[cfg(feature = "chrono")] extern crate chrono;
+#[cfg(feature = "time")] extern crate time;
+#[cfg(feature = "uuid")] extern crate uuid;
+#[macro_use] extern crate serde_utils;
+
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::OsString;
+use std::fmt::Debug;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4};
+use std::num::{NonZeroU8, NonZeroU32, NonZeroU64};
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde_bytes::ByteBuf;
+
+use serde_utils::adapters::base64::{self, Base64};
+use serde_utils::adapters::default_on_error::DefaultOnError;
+use serde_utils::adapters::duration::{self, HumanDuration};
+use serde_utils::adapters::empty_none::EmptyNone;
+use serde_utils::adapters::hex::{self, Hex};
+use serde_utils::adapters::int_string::IntString;
+use serde_utils::adapters::lenient::Lenient;
+use serde_utils::adapters::lossy_vec::LossyVec;
+use serde_utils::adapters::maybe::{self, Maybe};
+use serde_utils::adapters::net::{Addr, CompactAddr};
+use serde_utils::adapters::non_finite::NonFinite;
+use serde_utils::adapters::os_string::LosslessOsString;
+use serde_utils::adapters::pairs::Pairs;
+use serde_utils::adapters::path::{PortablePath, LossyPath};
+use serde_utils::adapters::shared::{self, Shared};
+use serde_utils::adapters::timestamp::{Secs, Millis, SecsNanos};
+use serde_utils::adapters::zero_none::{self, ZeroNone};
+
+fn test_json<T: serde::Serialize + serde::de::DeserializeOwned + PartialEq + Debug>(obj: &T, json: &str) {
+    assert_eq!(serde_json::to_string(obj).unwrap(), json);
+    assert_eq!(&serde_json::from_str::<T>(json).unwrap(), obj);
+}
+
+fn test_bin<T: serde::Serialize + serde::de::DeserializeOwned + PartialEq + Debug, R: serde::Serialize>(obj: &T, raw: &R) {
+    let serialized = bincode::serialize(obj).unwrap();
+    assert_eq!(serialized, bincode::serialize(raw).unwrap());
+    assert_eq!(&bincode::deserialize::<T>(&serialized).unwrap(), obj);
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct Base64Test {
+    data: Base64,
+    hash: Base64<[u8; 4]>
+}
+serde_impl!(Base64Test(String) {
+    data: Base64 => "data",
+    hash: Base64<[u8; 4]> => "hash"
+});
+
+#[test]
+fn test_base64() {
+    assert_eq!(base64::encode(b""), "");
+    assert_eq!(base64::encode(b"f"), "Zg==");
+    assert_eq!(base64::encode(b"fo"), "Zm8=");
+    assert_eq!(base64::encode(b"foo"), "Zm9v");
+    assert_eq!(base64::encode(b"foob"), "Zm9vYg==");
+    assert_eq!(base64::encode(&[0xfb, 0xff]), "+/8=");
+    assert_eq!(base64::decode("Zm9vYg==").unwrap(), b"foob");
+    assert_eq!(base64::decode("Zm9vYg").unwrap(), b"foob");
+    assert_eq!(base64::decode("+/8").unwrap(), &[0xfb, 0xff]);
+    assert!(base64::decode("Zm9vY").is_err());
+    assert!(base64::decode("Zm9v-g==").is_err());
+    test_json(&Base64(b"foob".to_vec()), "\"Zm9vYg==\"");
+    test_json(&Base64(ByteBuf::from(b"foo".to_vec())), "\"Zm9v\"");
+    test_json(&Base64Test{data: Base64(vec![1, 2, 3]), hash: Base64([1, 2, 3, 4])}, "{\"data\":\"AQID\",\"hash\":\"AQIDBA==\"}");
+    test_bin(&Base64(vec![1, 2, 3]), &ByteBuf::from(vec![1, 2, 3]));
+    test_bin(&Base64([1, 2, 3, 4]), &ByteBuf::from(vec![1, 2, 3, 4]));
+    let obj = Base64Test{data: Base64(vec![1, 2, 3]), hash: Base64([1, 2, 3, 4])};
+    assert_eq!(bincode::deserialize::<Base64Test>(&bincode::serialize(&obj).unwrap()).unwrap(), obj);
+    let err = serde_json::from_str::<Base64<[u8; 4]>>("\"AQID\"").unwrap_err();
+    assert_eq!(err.to_string(), "invalid length 3, expected 4 bytes");
+    assert!(serde_json::from_str::<Base64>("\"A\"").is_err());
+}
+
+#[test]
+fn test_hex() {
+    assert_eq!(hex::encode(&[0x01, 0xab, 0xff]), "01abff");
+    assert_eq!(hex::decode("01abff").unwrap(), &[0x01, 0xab, 0xff]);
+    assert_eq!(hex::decode("01ABFF").unwrap(), &[0x01, 0xab, 0xff]);
+    assert!(hex::decode("01a").is_err());
+    assert!(hex::decode("0g").is_err());
+    test_json(&Hex(vec![0xde, 0xad]), "\"dead\"");
+    test_json(&Hex([0xde, 0xad, 0xbe, 0xef]), "\"deadbeef\"");
+    test_bin(&Hex([0xde, 0xad, 0xbe, 0xef]), &"deadbeef");
+    let err = serde_json::from_str::<Hex<[u8; 4]>>("\"deadbeef00\"").unwrap_err();
+    assert_eq!(err.to_string(), "invalid length 5, expected 4 bytes");
+    let err = serde_json::from_str::<Hex>("\"dea\"").unwrap_err();
+    assert_eq!(err.to_string(), "invalid value: string \"dea\", expected hex string at line 1 column 5");
+}
+
+#[derive(Debug, PartialEq)]
+struct TimestampTest {
+    created: Secs,
+    timeout: Millis<Duration>,
+    exact: SecsNanos
+}
+serde_impl!(TimestampTest(String!) {
+    created: Secs => "created",
+    timeout: Millis<Duration> => "timeout",
+    exact: SecsNanos => "exact"
+});
+
+#[test]
+fn test_timestamp() {
+    let time = UNIX_EPOCH + Duration::new(1_500_000_000, 123_456_789);
+    test_json(&Secs(UNIX_EPOCH + Duration::from_secs(1_500_000_000)), "1500000000");
+    test_json(&Millis(Duration::from_millis(1500)), "1500");
+    test_json(&SecsNanos(time), "[1500000000,123456789]");
+    test_bin(&Millis(Duration::from_millis(1500)), &1500u64);
+    assert_eq!(serde_json::to_string(&Secs(time)).unwrap(), "1500000000");
+    assert_eq!(serde_json::to_string(&Millis(time)).unwrap(), "1500000000123");
+    test_json(&TimestampTest{
+        created: Secs(UNIX_EPOCH + Duration::from_secs(10)),
+        timeout: Millis(Duration::from_millis(250)),
+        exact: SecsNanos(time)
+    }, "{\"created\":10,\"timeout\":250,\"exact\":[1500000000,123456789]}");
+    assert!(serde_json::to_string(&Secs(UNIX_EPOCH - Duration::from_secs(1))).is_err());
+    assert!(serde_json::from_str::<SecsNanos<Duration>>("[1,1000000000]").is_err());
+    let now = SystemTime::now();
+    assert_eq!(serde_json::from_str::<SecsNanos>(&serde_json::to_string(&SecsNanos(now)).unwrap()).unwrap().0, now);
+}
+
+#[test]
+fn test_duration() {
+    assert_eq!(duration::format(Duration::from_secs(5400)), "1h30m");
+    assert_eq!(duration::format(Duration::from_millis(250)), "250ms");
+    assert_eq!(duration::format(Duration::new(90_061, 1_001)), "1d1h1m1s1us1ns");
+    assert_eq!(duration::format(Duration::from_secs(0)), "0s");
+    assert_eq!(duration::parse("1h30m").unwrap(), Duration::from_secs(5400));
+    assert_eq!(duration::parse("1h 30m").unwrap(), Duration::from_secs(5400));
+    assert_eq!(duration::parse("30m1h").unwrap(), Duration::from_secs(5400));
+    assert_eq!(duration::parse("1500ms").unwrap(), Duration::from_millis(1500));
+    assert_eq!(duration::parse("3µs").unwrap(), Duration::from_micros(3));
+    assert!(duration::parse("").is_err());
+    assert!(duration::parse("10").is_err());
+    assert!(duration::parse("10x").is_err());
+    assert!(duration::parse("h").is_err());
+    test_json(&HumanDuration(Duration::from_millis(1500)), "\"1s500ms\"");
+    test_bin(&HumanDuration(Duration::new(1, 500)), &(1u64, 500u32));
+    let err = serde_json::from_str::<HumanDuration>("\"5 minutes\"").unwrap_err();
+    assert_eq!(err.to_string(), "invalid value: string \"5 minutes\", expected duration string like \"1h30m\" at line 1 column 11");
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct LenientTest {
+    amount: Lenient<f64>,
+    count: Lenient<u32>
+}
+serde_impl!(LenientTest(String) {
+    amount: Lenient<f64> => "amount",
+    count: Lenient<u32> => "count"
+});
+
+#[test]
+fn test_lenient_number() {
+    assert_eq!(serde_json::from_str::<Lenient<u8>>("42").unwrap(), Lenient(42));
+    assert_eq!(serde_json::from_str::<Lenient<u8>>("\"42\"").unwrap(), Lenient(42));
+    assert_eq!(serde_json::from_str::<Lenient<u8>>("42.0").unwrap(), Lenient(42));
+    assert_eq!(serde_json::from_str::<Lenient<i64>>("\" -7 \"").unwrap(), Lenient(-7));
+    assert_eq!(serde_json::from_str::<Lenient<f32>>("\"3.5\"").unwrap(), Lenient(3.5));
+    assert_eq!(serde_json::from_str::<Lenient<f64>>("3").unwrap(), Lenient(3.0));
+    assert!(serde_json::from_str::<Lenient<u8>>("256").is_err());
+    assert!(serde_json::from_str::<Lenient<u8>>("\"4.5\"").is_err());
+    assert!(serde_json::from_str::<Lenient<u8>>("-1").is_err());
+    assert!(serde_json::from_str::<Lenient<f64>>("true").is_err());
+    let err = serde_json::from_str::<Lenient<u32>>("\"abc\"").unwrap_err();
+    assert_eq!(err.to_string(), "invalid value: string \"abc\", expected integer or numeric string at line 1 column 5");
+    assert_eq!(serde_json::from_str::<LenientTest>("{\"amount\":\"12.25\",\"count\":\"3\"}").unwrap(),
+        LenientTest{amount: Lenient(12.25), count: Lenient(3)});
+    test_json(&LenientTest{amount: Lenient(12.25), count: Lenient(3)}, "{\"amount\":12.25,\"count\":3}");
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct PairsTest {
+    ports: Pairs<BTreeMap<u16, String>>,
+    flags: Pairs<HashMap<(u8, bool), u8>>
+}
+serde_impl!(PairsTest(String) {
+    ports: Pairs<BTreeMap<u16, String>> => "ports",
+    flags: Pairs<HashMap<(u8, bool), u8>> => "flags"
+});
+
+#[test]
+fn test_pairs() {
+    let mut ports = BTreeMap::new();
+    ports.insert(80, "http".to_string());
+    ports.insert(443, "https".to_string());
+    test_json(&Pairs(ports.clone()), "[[80,\"http\"],[443,\"https\"]]");
+    let mut flags = HashMap::new();
+    flags.insert((1, true), 2);
+    test_json(&PairsTest{ports: Pairs(ports.clone()), flags: Pairs(flags.clone())},
+        "{\"ports\":[[80,\"http\"],[443,\"https\"]],\"flags\":[[[1,true],2]]}");
+    let obj = PairsTest{ports: Pairs(ports), flags: Pairs(flags)};
+    assert_eq!(bincode::deserialize::<PairsTest>(&bincode::serialize(&obj).unwrap()).unwrap(), obj);
+    assert!(serde_json::from_str::<Pairs<BTreeMap<u16, String>>>("[[80]]").is_err());
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct EmptyNoneTest {
+    phone: EmptyNone<String>,
+    age: EmptyNone<u32>
+}
+serde_impl!(EmptyNoneTest(String) {
+    phone: EmptyNone<String> => "phone",
+    age: EmptyNone<u32> => "age"
+});
+
+#[test]
+fn test_empty_none() {
+    test_json(&EmptyNone(Some("test".to_string())), "\"test\"");
+    test_json(&EmptyNone::<String>(None), "\"\"");
+    test_json(&EmptyNone(Some(42u32)), "\"42\"");
+    assert_eq!(serde_json::from_str::<EmptyNone<u32>>("null").unwrap(), EmptyNone(None));
+    assert_eq!(serde_json::from_str::<EmptyNoneTest>("{\"phone\":\"\",\"age\":\"\"}").unwrap(), EmptyNoneTest::default());
+    assert_eq!(serde_json::from_str::<EmptyNoneTest>("{\"phone\":null}").unwrap(), EmptyNoneTest::default());
+    test_json(&EmptyNoneTest{phone: EmptyNone(Some("123".to_string())), age: EmptyNone(None)}, "{\"phone\":\"123\",\"age\":\"\"}");
+    let err = serde_json::from_str::<EmptyNone<u32>>("\"x\"").unwrap_err();
+    assert_eq!(err.to_string(), "invalid value \"x\": invalid digit found in string at line 1 column 3");
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct DefaultOnErrorTest {
+    rating: DefaultOnError<Option<u8>>,
+    tags: DefaultOnError<Vec<String>>,
+    name: String
+}
+serde_impl!(DefaultOnErrorTest(String) {
+    rating: DefaultOnError<Option<u8>> => "rating",
+    tags: DefaultOnError<Vec<String>> => "tags",
+    name: String => "name"
+});
+
+#[test]
+fn test_default_on_error() {
+    let obj: DefaultOnErrorTest = serde_json::from_str("{\"rating\":300,\"tags\":[\"a\",1],\"name\":\"test\"}").unwrap();
+    assert_eq!(*obj.rating, None);
+    assert_eq!(obj.rating.error(), Some("invalid value: integer `300`, expected u8"));
+    assert_eq!(*obj.tags, Vec::<String>::new());
+    assert!(obj.tags.error().is_some());
+    assert_eq!(obj.name, "test");
+    let obj: DefaultOnErrorTest = serde_json::from_str("{\"rating\":3,\"tags\":[\"a\"],\"name\":\"test\"}").unwrap();
+    assert_eq!(obj, DefaultOnErrorTest{rating: DefaultOnError::new(Some(3)), tags: DefaultOnError::new(vec!["a".to_string()]), name: "test".to_string()});
+    assert_eq!(obj.rating.error(), None);
+    test_json(&obj, "{\"rating\":3,\"tags\":[\"a\"],\"name\":\"test\"}");
+    assert!(serde_json::from_str::<DefaultOnError<u8>>("[1,").is_err());
+}
+
+#[test]
+fn test_lossy_vec() {
+    let vec: LossyVec<u8> = serde_json::from_str("[1,\"a\",300,2,null,3]").unwrap();
+    assert_eq!(*vec, vec![1, 2, 3]);
+    assert_eq!(vec.dropped(), 3);
+    let vec: LossyVec<Option<u8>> = serde_json::from_str("[1,null]").unwrap();
+    assert_eq!(vec, LossyVec::new(vec![Some(1), None]));
+    test_json(&LossyVec::new(vec![RecordTest{value: 1}]), "[{\"value\":1}]");
+    let vec: LossyVec<RecordTest> = serde_json::from_str("[{\"value\":1},{\"value\":\"x\"},5]").unwrap();
+    assert_eq!(*vec, vec![RecordTest{value: 1}]);
+    assert_eq!(vec.dropped(), 2);
+    assert!(serde_json::from_str::<LossyVec<u8>>("{}").is_err());
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct RecordTest {
+    value: u8
+}
+serde_impl!(RecordTest(String) {
+    value: u8 => "value"
+});
+
+#[test]
+fn test_lenient_bool() {
+    for &(json, val) in &[("true", true), ("false", false), ("1", true), ("0", false), ("\"yes\"", true),
+            ("\"No\"", false), ("\"TRUE\"", true), ("\"false\"", false), ("\"1\"", true), ("\" 0 \"", false)] {
+        assert_eq!(serde_json::from_str::<Lenient<bool>>(json).unwrap(), Lenient(val));
+    }
+    assert!(serde_json::from_str::<Lenient<bool>>("2").is_err());
+    assert!(serde_json::from_str::<Lenient<bool>>("1.0").is_err());
+    assert!(serde_json::from_str::<Lenient<bool>>("\"maybe\"").is_err());
+    assert!(serde_json::from_str::<Lenient<u8>>("true").is_err());
+    test_json(&Lenient(true), "true");
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct MaybeTest {
+    name: Maybe<String>,
+    email: Maybe<String>
+}
+serde_impl!(MaybeTest(String?) {
+    name: Maybe<String> => "name",
+    email: Maybe<String> => "email"
+});
+
+#[derive(Debug, PartialEq)]
+struct RequiredMaybeTest {
+    name: Maybe<String>,
+    email: Maybe<String>
+}
+serde_impl!(RequiredMaybeTest(String!) {
+    name: Maybe<String> => "name",
+    email: Maybe<String> => "email"
+});
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct DerivedMaybeTest {
+    #[serde(default, with = "maybe", skip_serializing_if = "Option::is_none")]
+    name: Option<Option<String>>
+}
+
+#[test]
+fn test_maybe() {
+    let obj: MaybeTest = serde_json::from_str("{\"name\":null}").unwrap();
+    assert_eq!(obj, MaybeTest{name: Maybe::Null, email: Maybe::Missing});
+    let obj: MaybeTest = serde_json::from_str("{\"email\":\"a@b\"}").unwrap();
+    assert_eq!(obj, MaybeTest{name: Maybe::Missing, email: Maybe::Value("a@b".to_string())});
+    test_json(&MaybeTest{name: Maybe::Null, email: Maybe::Missing}, "{\"name\":null}");
+    test_json(&MaybeTest::default(), "{}");
+    let obj: RequiredMaybeTest = serde_json::from_str("{\"name\":null}").unwrap();
+    assert_eq!(obj, RequiredMaybeTest{name: Maybe::Null, email: Maybe::Missing});
+    let bytes = rmp_serde::to_vec(&MaybeTest{name: Maybe::Null, email: Maybe::Value("x".to_string())}).unwrap();
+    let obj: RequiredMaybeTest = rmp_serde::from_slice(&bytes).unwrap();
+    assert_eq!(obj, RequiredMaybeTest{name: Maybe::Null, email: Maybe::Value("x".to_string())});
+    let mut target = Some("old".to_string());
+    Maybe::Missing.apply_to(&mut target);
+    assert_eq!(target, Some("old".to_string()));
+    Maybe::Null.apply_to(&mut target);
+    assert_eq!(target, None);
+    test_json(&DerivedMaybeTest{name: None}, "{}");
+    test_json(&DerivedMaybeTest{name: Some(None)}, "{\"name\":null}");
+    test_json(&DerivedMaybeTest{name: Some(Some("x".to_string()))}, "{\"name\":\"x\"}");
+}
+
+#[test]
+fn test_net() {
+    let ip4 = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+    let ip6 = IpAddr::V6(Ipv6Addr::LOCALHOST);
+    test_json(&Addr(ip4), "\"10.0.0.1\"");
+    test_json(&Addr(Ipv6Addr::LOCALHOST), "\"::1\"");
+    test_json(&Addr(SocketAddr::new(ip6, 80)), "\"[::1]:80\"");
+    test_json(&CompactAddr(SocketAddrV4::new(Ipv4Addr::new(10, 0, 0, 1), 80)), "\"10.0.0.1:80\"");
+    test_bin(&Addr(ip4), &"10.0.0.1");
+    test_bin(&CompactAddr(ip4), &ByteBuf::from(vec![10, 0, 0, 1]));
+    test_bin(&CompactAddr(ip6), &ByteBuf::from(Ipv6Addr::LOCALHOST.octets().to_vec()));
+    test_bin(&CompactAddr(SocketAddr::new(ip4, 0x1234)), &ByteBuf::from(vec![10, 0, 0, 1, 0x12, 0x34]));
+    test_bin(&CompactAddr(SocketAddr::new(ip6, 80)), &ByteBuf::from({
+        let mut bytes = Ipv6Addr::LOCALHOST.octets().to_vec();
+        bytes.extend_from_slice(&[0, 80]);
+        bytes
+    }));
+    assert!(bincode::deserialize::<CompactAddr<Ipv4Addr>>(&bincode::serialize(&ByteBuf::from(vec![1, 2, 3])).unwrap()).is_err());
+    let err = serde_json::from_str::<Addr<Ipv4Addr>>("\"::1\"").unwrap_err();
+    assert_eq!(err.to_string(), "invalid value: string \"::1\", expected IPv4 address at line 1 column 5");
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct ZeroNoneTest {
+    parent: ZeroNone<NonZeroU64>,
+    session: ZeroNone<NonZeroU32>
+}
+serde_impl!(ZeroNoneTest(String) {
+    parent: ZeroNone<NonZeroU64> => "parent",
+    session: ZeroNone<NonZeroU32> => "session"
+});
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct DerivedZeroNoneTest {
+    #[serde(with = "zero_none")]
+    parent: Option<NonZeroU64>
+}
+
+#[test]
+fn test_zero_none() {
+    test_json(&ZeroNone(NonZeroU64::new(5)), "5");
+    test_json(&ZeroNone::<NonZeroU64>(None), "0");
+    test_json(&ZeroNoneTest{parent: ZeroNone(NonZeroU64::new(7)), session: ZeroNone(None)}, "{\"parent\":7,\"session\":0}");
+    test_json(&DerivedZeroNoneTest{parent: None}, "{\"parent\":0}");
+    test_bin(&ZeroNone::<NonZeroU8>(None), &0u8);
+    test_bin(&ZeroNone(NonZeroU32::new(0x1234)), &0x1234u32);
+    assert!(serde_json::from_str::<ZeroNone<NonZeroU8>>("256").is_err());
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct NonFiniteTest {
+    mean: NonFinite<f64>,
+    max: NonFinite<f32>
+}
+serde_impl!(NonFiniteTest(String) {
+    mean: NonFinite<f64> => "mean",
+    max: NonFinite<f32> => "max"
+});
+
+#[test]
+fn test_non_finite() {
+    test_json(&NonFinite(1.5f64), "1.5");
+    test_json(&NonFinite(f64::INFINITY), "\"Infinity\"");
+    test_json(&NonFinite(f32::NEG_INFINITY), "\"-Infinity\"");
+    test_json(&NonFiniteTest{mean: NonFinite(2.0), max: NonFinite(f32::INFINITY)}, "{\"mean\":2.0,\"max\":\"Infinity\"}");
+    assert_eq!(serde_json::to_string(&NonFinite(f64::NAN)).unwrap(), "\"NaN\"");
+    assert!(serde_json::from_str::<NonFinite<f64>>("\"NaN\"").unwrap().is_nan());
+    assert_eq!(serde_json::from_str::<NonFinite<f32>>("3").unwrap(), NonFinite(3.0));
+    test_bin(&NonFinite(f64::INFINITY), &f64::INFINITY);
+    test_bin(&NonFinite(0.5f32), &0.5f32);
+    let err = serde_json::from_str::<NonFinite<f64>>("\"inf\"").unwrap_err();
+    assert_eq!(err.to_string(), "invalid value: string \"inf\", expected number, \"NaN\", \"Infinity\" or \"-Infinity\" at line 1 column 5");
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn test_chrono() {
+    use chrono::{DateTime, FixedOffset, NaiveDateTime, Utc};
+    use serde_utils::adapters::rfc3339::Rfc3339;
+    let time = DateTime::<Utc>::from_timestamp(1_577_934_245, 500_000_000).unwrap();
+    test_json(&Rfc3339(time), "\"2020-01-02T03:04:05.500Z\"");
+    test_json(&Rfc3339(time.naive_utc()), "\"2020-01-02T03:04:05.500Z\"");
+    test_json(&Millis(time), "1577934245500");
+    test_json(&Secs(DateTime::<Utc>::from_timestamp(1_577_934_245, 0).unwrap().naive_utc()), "1577934245");
+    let offset: Rfc3339<DateTime<FixedOffset>> = serde_json::from_str("\"2020-01-02T04:04:05+01:00\"").unwrap();
+    test_json(&offset, "\"2020-01-02T04:04:05+01:00\"");
+    let utc: Rfc3339<DateTime<Utc>> = serde_json::from_str("\"2020-01-02T04:04:05+01:00\"").unwrap();
+    assert_eq!(*utc, DateTime::<Utc>::from_timestamp(1_577_934_245, 0).unwrap());
+    assert!(serde_json::from_str::<Rfc3339<NaiveDateTime>>("\"2020-01-02\"").is_err());
+    assert!(serde_json::to_string(&Secs(DateTime::<Utc>::from_timestamp(-1, 0).unwrap())).is_err());
+}
+
+#[cfg(feature = "time")]
+#[test]
+fn test_time() {
+    use serde_utils::adapters::rfc3339::Rfc3339;
+    use time::OffsetDateTime;
+    let time = OffsetDateTime::from_unix_timestamp(1_577_934_245).unwrap();
+    test_json(&Rfc3339(time), "\"2020-01-02T03:04:05Z\"");
+    test_json(&Secs(time), "1577934245");
+    test_bin(&SecsNanos(time), &(1_577_934_245u64, 0u32));
+    assert!(serde_json::from_str::<Rfc3339<OffsetDateTime>>("\"yesterday\"").is_err());
+}
+
+#[cfg(feature = "uuid")]
+#[test]
+fn test_uuid() {
+    use serde_utils::adapters::uuid::CompactUuid;
+    use uuid::Uuid;
+    let id = Uuid::parse_str("67e55044-10b1-426f-9247-bb680e5fe0c8").unwrap();
+    test_json(&CompactUuid(id), "\"67e55044-10b1-426f-9247-bb680e5fe0c8\"");
+    test_bin(&CompactUuid(id), &ByteBuf::from(id.as_bytes().to_vec()));
+    assert_eq!(serde_json::from_str::<CompactUuid>("\"67e5504410b1426f9247bb680e5fe0c8\"").unwrap(), CompactUuid(id));
+    assert!(serde_json::from_str::<CompactUuid>("\"67e55044\"").is_err());
+    assert!(bincode::deserialize::<CompactUuid>(&bincode::serialize(&ByteBuf::from(vec![1, 2, 3])).unwrap()).is_err());
+}
+
+#[test]
+fn test_path() {
+    test_json(&PortablePath(PathBuf::from("data/files")), "\"data/files\"");
+    test_bin(&PortablePath(PathBuf::from("/var/log")), &"/var/log");
+    let path: PortablePath = serde_json::from_str("\"C:\\\\Users\\\\test\"").unwrap();
+    assert_eq!(serde_json::to_string(&path).unwrap(), "\"C:/Users/test\"");
+    test_json(&LossyPath(PathBuf::from("a/b")), "\"a/b\"");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_path_invalid_unicode() {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+    let path = PathBuf::from(OsStr::from_bytes(b"a/\xff"));
+    assert!(serde_json::to_string(&PortablePath(path.clone())).is_err());
+    assert_eq!(serde_json::to_string(&LossyPath(path)).unwrap(), "\"a/\u{fffd}\"");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_os_string() {
+    use std::os::unix::ffi::OsStringExt;
+    test_json(&LosslessOsString(OsString::from("ab")), "{\"Unix\":[97,98]}");
+    let invalid = LosslessOsString(OsString::from_vec(vec![b'a', 0xff]));
+    test_json(&invalid, "{\"Unix\":[97,255]}");
+    let bytes = rmp_serde::to_vec(&invalid).unwrap();
+    assert_eq!(rmp_serde::from_slice::<LosslessOsString>(&bytes).unwrap(), invalid);
+    assert_eq!(bincode::deserialize::<LosslessOsString>(&bincode::serialize(&invalid).unwrap()).unwrap(), invalid);
+    let windows: LosslessOsString = serde_json::from_str("{\"Windows\":[97,98]}").unwrap();
+    assert_eq!(windows, LosslessOsString(OsString::from("ab")));
+    assert!(serde_json::from_str::<LosslessOsString>("{\"Windows\":[55296]}").is_err());
+}
+
+#[test]
+fn test_int_string() {
+    test_json(&IntString(9_007_199_254_740_993u64), "\"9007199254740993\"");
+    test_json(&IntString(-5i64), "\"-5\"");
+    test_json(&IntString(u128::MAX), "\"340282366920938463463374607431768211455\"");
+    assert_eq!(serde_json::from_str::<IntString<u64>>("42").unwrap(), IntString(42));
+    test_bin(&IntString(42u64), &42u64);
+    assert!(serde_json::from_str::<IntString<u8>>("\"256\"").is_err());
+    assert!(serde_json::from_str::<IntString<u32>>("-1").is_err());
+    let err = serde_json::from_str::<IntString<u64>>("\"1e3\"").unwrap_err();
+    assert_eq!(err.to_string(), "invalid value: string \"1e3\", expected integer or decimal string at line 1 column 5");
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct SceneTest {
+    nodes: Vec<Shared<Rc<RecordTest>>>
+}
+serde_impl!(SceneTest(String) {
+    nodes: Vec<Shared<Rc<RecordTest>>> => "nodes"
+});
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct SharedWithTest {
+    #[serde(with = "shared")]
+    first: Arc<String>,
+    #[serde(with = "shared")]
+    second: Arc<String>
+}
+
+#[test]
+fn test_shared() {
+    let node = Rc::new(RecordTest{value: 1});
+    let scene = SceneTest { nodes: vec![node.clone().into(), Rc::new(RecordTest{value: 2}).into(), node.into()] };
+    let json = shared::scope(|| serde_json::to_string(&scene).unwrap());
+    assert_eq!(json, "{\"nodes\":[[0,{\"value\":1}],[1,{\"value\":2}],[0,null]]}");
+    let read: SceneTest = shared::scope(|| serde_json::from_str(&json).unwrap());
+    assert_eq!(read, scene);
+    assert!(Rc::ptr_eq(&read.nodes[0], &read.nodes[2]));
+    assert!(!Rc::ptr_eq(&read.nodes[0], &read.nodes[1]));
+    assert_eq!(serde_json::to_string(&scene).unwrap(), "{\"nodes\":[[0,{\"value\":1}],[0,{\"value\":2}],[0,{\"value\":1}]]}");
+    assert!(serde_json::from_str::<SceneTest>(&json).unwrap_err().to_string().contains("unknown shared value 0"));
+    let text = Arc::new("text".to_string());
+    let obj = SharedWithTest { first: text.clone(), second: text };
+    let mut bytes = Vec::new();
+    shared::scope(|| bincode::serialize_into(&mut bytes, &obj).unwrap());
+    assert_eq!(bytes.len(), 8 + 1 + 8 + 4 + 8 + 1);
+    let read: SharedWithTest = shared::scope(|| bincode::deserialize(&bytes).unwrap());
+    assert_eq!(read, obj);
+    assert!(Arc::ptr_eq(&read.first, &read.second));
+    let err = shared::scope(|| serde_json::from_str::<(Shared<Rc<u8>>, Shared<Rc<String>>)>("[[0,1],[0,null]]")).unwrap_err();
+    assert!(err.to_string().contains("shared value 0 has a different type"), "{}", err);
+}
@@ -0,0 +1,97 @@
+#![cfg(feature = "avro")]
+
+extern crate serde_json;
+extern crate serde_utils;
+
+use serde_utils::{Obj, ObjKind};
+
+fn obj(json: &str) -> Obj {
+    serde_json::from_str(json).unwrap()
+}
+
+#[test]
+fn test_avro_encoding() {
+    // Examples from the specification
+    let long = obj("\"long\"");
+    assert_eq!(Obj::Signed(-1).to_avro(&long).unwrap(), vec![0x01]);
+    assert_eq!(Obj::Unsigned(64).to_avro(&long).unwrap(), vec![0x80, 0x01]);
+    assert_eq!(Obj::Str("foo".to_string()).to_avro(&obj("\"string\"")).unwrap(), vec![0x06, b'f', b'o', b'o']);
+    let record = obj(r#"{"type": "record", "name": "test", "fields": [
+        {"name": "a", "type": "long"},
+        {"name": "b", "type": "string"}
+    ]}"#);
+    assert_eq!(obj(r#"{"a": 27, "b": "foo"}"#).to_avro(&record).unwrap(), vec![0x36, 0x06, b'f', b'o', b'o']);
+    let array = obj(r#"{"type": "array", "items": "long"}"#);
+    assert_eq!(obj("[3, 27]").to_avro(&array).unwrap(), vec![0x04, 0x06, 0x36, 0x00]);
+    let union = obj(r#"["null", "string"]"#);
+    assert_eq!(Obj::Null.to_avro(&union).unwrap(), vec![0x00]);
+    assert_eq!(obj("\"a\"").to_avro(&union).unwrap(), vec![0x02, 0x02, b'a']);
+}
+
+#[test]
+fn test_avro_named_types() {
+    let schema = obj(r#"{"type": "record", "name": "Node", "namespace": "test", "fields": [
+        {"name": "kind", "type": {"type": "enum", "name": "Kind", "symbols": ["A", "B"]}},
+        {"name": "id", "type": {"type": "fixed", "name": "Id", "size": 2}},
+        {"name": "ratio", "type": ["long", "double"]},
+        {"name": "next", "type": ["null", "test.Node"], "default": null}
+    ]}"#);
+    let mut value = obj(r#"{"kind": "B", "ratio": 0.5, "next": {"kind": "A", "ratio": 2}}"#).into_map().unwrap();
+    value.insert(Obj::Str("id".to_string()), Obj::from_bytes(vec![1, 2]));
+    let mut next = value[&Obj::Str("next".to_string())].clone().into_map().unwrap();
+    next.insert(Obj::Str("id".to_string()), Obj::from_bytes(vec![3, 4]));
+    value.insert(Obj::Str("next".to_string()), Obj::from_map(next));
+    assert_eq!(Obj::from_map(value).to_avro(&schema).unwrap(), vec![
+        0x02, 1, 2, 0x02, 0, 0, 0, 0, 0, 0, 0xe0, 0x3f,
+        0x02, 0x00, 3, 4, 0x00, 0x04, 0x00
+    ]);
+}
+
+#[test]
+fn test_avro_schema_inference() {
+    let value = obj(r#"{
+        "id": 7,
+        "name": "sensor",
+        "tags": ["a", "b"],
+        "readings": [{"value": 1.5, "unit": "C"}, {"value": 2}],
+        "labels": {"the key": true}
+    }"#);
+    let schema = serde_json::to_value(value.avro_schema("Event").unwrap()).unwrap();
+    assert_eq!(schema["type"], "record");
+    assert_eq!(schema["name"], "Event");
+    let fields = schema["fields"].as_array().unwrap();
+    let field = |name: &str| fields.iter().find(|field| field["name"] == name).unwrap().clone();
+    assert_eq!(field("id")["type"], "long");
+    assert_eq!(field("tags")["type"], serde_json::json!({"type": "array", "items": "string"}));
+    assert_eq!(field("labels")["type"], serde_json::json!({"type": "map", "values": "boolean"}));
+    assert_eq!(field("readings")["type"]["items"], serde_json::json!({
+        "type": "record",
+        "name": "Event_readings_item",
+        "fields": [
+            {"name": "unit", "type": ["null", "string"], "default": null},
+            {"name": "value", "type": ["double", "long"]}
+        ]
+    }));
+    let encoded = value.to_avro(&value.avro_schema("Event").unwrap()).unwrap();
+    // Fields are sorted by name: `id` and then the single entry of `labels`
+    assert_eq!(encoded[..4], [0x0e, 0x02, 0x0e, b't']);
+}
+
+#[test]
+fn test_avro_errors() {
+    let value = obj(r#"{"a": [1, -1]}"#);
+    let schema = obj(r#"{"type": "record", "name": "test", "fields": [
+        {"name": "a", "type": {"type": "array", "items": "int"}},
+        {"name": "b", "type": "string"}
+    ]}"#);
+    let err = obj(r#"{"a": [1, "x"], "b": ""}"#).to_avro(&schema).unwrap_err();
+    assert_eq!(err.path().to_string(), "a/1");
+    assert_eq!(err.found(), Some(ObjKind::Str));
+    let err = value.to_avro(&schema).unwrap_err();
+    assert_eq!(err.path().to_string(), "b");
+    assert_eq!(err.found(), None);
+    let err = Obj::Unsigned(u64::MAX).avro_schema("test").unwrap_err();
+    assert_eq!(err.found(), Some(ObjKind::Unsigned));
+    assert!(Obj::Null.avro_schema("not valid").is_err());
+    assert!(Obj::Unsigned(1).to_avro(&obj("\"Unknown\"")).is_err());
+}
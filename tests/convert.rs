@@ -0,0 +1,42 @@
+extern crate serde_json;
+extern crate serde_cbor;
+extern crate serde_utils;
+
+use serde_utils::Obj;
+use serde_utils::convert::{self, KeyPolicy};
+
+#[test]
+fn test_convert() {
+    let mut cbor = Vec::new();
+    let mut de = serde_json::Deserializer::from_str(r#"{"name":"test","ids":[1,2]}"#);
+    convert::convert(&mut de, &mut serde_cbor::Serializer::new(&mut cbor), KeyPolicy::Keep).unwrap();
+    assert_eq!(serde_cbor::from_slice::<Obj>(&cbor).unwrap(), serde_json::from_str::<Obj>(r#"{"ids":[1,2],"name":"test"}"#).unwrap());
+    // CBOR map {[1]: null} with a list as key
+    let mut json = Vec::new();
+    let mut de = serde_cbor::Deserializer::from_slice(&[0xa1, 0x81, 0x01, 0xf6]);
+    let err = convert::convert(&mut de, &mut serde_json::Serializer::new(&mut json), KeyPolicy::Keep).unwrap_err();
+    assert!(matches!(err, convert::Error::Serialize(_)));
+    assert!(err.to_string().starts_with("failed to write output"));
+    // CBOR map {h'01': h'0102'} with bytes as key and value
+    let mut json = Vec::new();
+    let mut de = serde_cbor::Deserializer::from_slice(&[0xa1, 0x41, 1, 0x42, 1, 2]);
+    convert::convert(&mut de, &mut serde_json::Serializer::new(&mut json), KeyPolicy::Stringify).unwrap();
+    assert_eq!(String::from_utf8(json).unwrap(), r#"{"AQ==":"AQI="}"#);
+    let mut de = serde_json::Deserializer::from_str("[1,");
+    let err = convert::convert(&mut de, &mut serde_json::Serializer::new(Vec::new()), KeyPolicy::Keep).unwrap_err();
+    assert!(matches!(err, convert::Error::Deserialize(_)));
+}
+
+#[cfg(all(feature = "msgpack", feature = "json", feature = "cbor"))]
+#[test]
+fn test_format_shortcuts() {
+    let json = r#"{"ids":[1,2],"name":"test"}"#;
+    let msgpack = convert::json_to_msgpack(json).unwrap();
+    assert_eq!(convert::msgpack_to_json(&msgpack).unwrap(), json);
+    let cbor = convert::msgpack_to_cbor(&msgpack).unwrap();
+    assert_eq!(convert::cbor_to_json(&cbor).unwrap(), json);
+    assert_eq!(convert::cbor_to_msgpack(&cbor).unwrap(), msgpack);
+    assert_eq!(convert::json_to_cbor(json).unwrap(), cbor);
+    assert_eq!(convert::msgpack_to_json(&Obj::from_bytes(vec![1, 2]).to_msgpack_vec().unwrap()).unwrap(), "\"AQI=\"");
+    assert!(matches!(convert::json_to_msgpack("{").unwrap_err(), convert::Error::Deserialize(_)));
+}
@@ -0,0 +1,152 @@
+extern crate serde;
+extern crate serde_bytes;
+extern crate rmp_serde;
+#[macro_use] extern crate serde_utils;
+#[macro_use] extern crate serde_utils_derive;
+
+use std::fmt::Debug;
+use std::io::Cursor;
+
+use serde_bytes::ByteBuf;
+
+fn to_bytes<T: serde::Serialize + Debug>(obj: &T) -> Vec<u8> {
+    let mut serialized = Vec::new();
+    {
+        let mut writer = rmp_serde::Serializer::new(&mut serialized);
+        assert!(obj.serialize(&mut writer).is_ok());
+    }
+    serialized
+}
+
+fn from_bytes<'a, T: serde::Deserialize<'a> + Debug>(bytes: &[u8]) -> T {
+    let cursor = Cursor::new(bytes);
+    let mut reader = rmp_serde::Deserializer::new(cursor);
+    T::deserialize(&mut reader).unwrap()
+}
+
+#[allow(clippy::needless_pass_by_value)]
+fn test_obj<'a, T: serde::Serialize + serde::Deserialize<'a> + PartialEq + Debug>(obj: T) {
+    let serialized = to_bytes(&obj);
+    let deserialized = from_bytes(&serialized);
+    assert_eq!(obj, deserialized);
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct IntMapTest {
+    test: String,
+    num: u64,
+    option: Option<bool>,
+}
+serde_impl!(IntMapTest(u64) {
+    test: String => 0,
+    num: u64 => 1,
+    option: Option<bool> => 2
+});
+
+#[derive(Default, Debug, PartialEq, SerdeImpl)]
+struct DerivedIntMapTest {
+    #[serde_utils(key = 0)]
+    test: String,
+    #[serde_utils(key = 1)]
+    num: u64,
+    #[serde_utils(key = 2)]
+    option: Option<bool>,
+}
+
+#[test]
+fn test_int_map() {
+    test_obj(DerivedIntMapTest{test: "test".to_string(), num: 56, option: Some(true)});
+    let obj = IntMapTest{test: "test".to_string(), num: 56, option: Some(true)};
+    let derived = DerivedIntMapTest{test: "test".to_string(), num: 56, option: Some(true)};
+    assert_eq!(to_bytes(&obj), to_bytes(&derived));
+    assert_eq!(DerivedIntMapTest::KEYS.num, 1);
+}
+
+#[derive(Default, Debug, PartialEq, SerdeImpl)]
+struct DerivedStrMapTest {
+    test: String,
+    #[serde_utils(key = "n")]
+    num: u64,
+    #[serde_utils(skip)]
+    cache: u64,
+}
+
+#[test]
+fn test_str_map() {
+    test_obj(DerivedStrMapTest{test: "test".to_string(), num: 56, cache: 0});
+    let bytes = to_bytes(&DerivedStrMapTest{test: "test".to_string(), num: 56, cache: 7});
+    assert_eq!(DerivedStrMapTest{test: "test".to_string(), num: 56, cache: 0}, from_bytes(&bytes));
+    assert_eq!(DerivedStrMapTest::FIELD_KEYS, &[("test", "test"), ("num", "n")]);
+}
+
+#[derive(Default, Debug, PartialEq, SerdeImpl)]
+#[serde_utils(compressed, key_type(u8))]
+struct DerivedCompressedTest {
+    #[serde_utils(key = 0)]
+    test: String,
+    #[serde_utils(key = 1)]
+    option: Option<bool>,
+    #[serde_utils(key = 2)]
+    #[serde_utils(is_default(Vec::is_empty))]
+    list: Vec<u64>,
+}
+
+#[test]
+fn test_compressed() {
+    test_obj(DerivedCompressedTest{test: "test".to_string(), option: None, list: vec![]});
+    test_obj(DerivedCompressedTest{test: "test".to_string(), option: None, list: vec![1]});
+    assert_eq!(to_bytes(&DerivedCompressedTest::default()).len(), 1);
+}
+
+#[derive(Debug, PartialEq, SerdeImpl)]
+#[serde_utils(required, strict)]
+struct DerivedRequiredTest {
+    test: String,
+    option: Option<bool>,
+}
+
+#[test]
+fn test_required() {
+    test_obj(DerivedRequiredTest{test: "test".to_string(), option: Some(false)});
+    let bytes = to_bytes(&DerivedStrMapTest{test: "test".to_string(), num: 56, cache: 0});
+    let mut reader = rmp_serde::Deserializer::new(Cursor::new(&bytes));
+    assert!(<DerivedRequiredTest as serde::Deserialize>::deserialize(&mut reader).is_err());
+}
+
+#[derive(Debug, PartialEq, SerdeImpl)]
+#[serde_utils(tuple)]
+struct DerivedTupleTest {
+    first: u64,
+    second: String,
+}
+
+#[test]
+fn test_tuple() {
+    test_obj(DerivedTupleTest{first: 1, second: "test".to_string()});
+    assert_eq!(to_bytes(&DerivedTupleTest{first: 1, second: "test".to_string()}), to_bytes(&(1, "test")));
+}
+
+#[derive(Debug, PartialEq, SerdeImpl)]
+enum DerivedEnumTest {
+    A,
+    #[serde_utils(key = "bb")]
+    B
+}
+
+#[derive(Debug, PartialEq, SerdeImpl)]
+#[serde_utils(key_type(ByteBuf))]
+enum DerivedParamEnumTest {
+    #[serde_utils(key = b"a")]
+    A(u64),
+    #[serde_utils(key = b"b")]
+    B(String)
+}
+
+#[test]
+fn test_enums() {
+    test_obj(DerivedEnumTest::A);
+    test_obj(DerivedEnumTest::B);
+    assert_eq!(to_bytes(&DerivedEnumTest::B), to_bytes(&"bb"));
+    test_obj(DerivedParamEnumTest::A(53));
+    test_obj(DerivedParamEnumTest::B("test".to_string()));
+}
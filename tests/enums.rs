@@ -1,9 +1,16 @@
 extern crate serde;
+extern crate serde_bytes;
 extern crate rmp_serde;
+extern crate bincode;
 #[macro_use] extern crate serde_utils;
 
 use std::fmt::Debug;
 use std::io::Cursor;
+use std::collections::BTreeMap;
+use std::borrow::Cow;
+
+use serde_bytes::ByteBuf;
+use serde_utils::{IgnoreCase, Normalize, NormalizedKey};
 
 fn to_bytes<T: serde::Serialize + Debug>(obj: &T) -> Vec<u8> {
     let mut serialized = Vec::new();
@@ -20,7 +27,7 @@ fn from_bytes<'a, T: serde::Deserialize<'a> + Debug>(bytes: &[u8]) -> T {
     T::deserialize(&mut reader).unwrap()
 }
 
-#[allow(unknown_lints,needless_pass_by_value)]
+#[allow(clippy::needless_pass_by_value)]
 fn test_obj<'a, T: serde::Serialize + serde::Deserialize<'a> + PartialEq + Debug>(obj: T) {
     let serialized = to_bytes(&obj);
     let deserialized = from_bytes(&serialized);
@@ -137,6 +144,128 @@ fn test_int_param_enum_reduced() {
     assert_eq!(IntParamEnumTest::C("test".to_string()), from_bytes(&to_bytes(&IntParamEnumTestReduced::C("test".to_string()))));
 }
 
+#[derive(PartialEq, Debug)]
+enum DefaultParamEnumTest {
+    A(u64),
+    B(Vec<u8>),
+    C(String)
+}
+serde_impl!(DefaultParamEnumTest(u64) {
+    A(u64) => 0,
+    B(Vec<u8> = vec![1, 2]) => 1,
+    C(String = String::new()) => 2
+});
+
+#[test]
+fn test_default_param_enum() {
+    test_obj(DefaultParamEnumTest::B(vec![3]));
+    assert_eq!(DefaultParamEnumTest::B(vec![1, 2]), from_bytes(&to_bytes(&(1u64,))));
+    assert_eq!(DefaultParamEnumTest::C(String::new()), from_bytes(&to_bytes(&[2u64])));
+    let msg = error_message::<DefaultParamEnumTest>(&to_bytes(&[0u64]));
+    assert!(msg.contains("Enums must be encoded as tuples"), "{}", msg);
+}
+
+#[derive(PartialEq, Debug)]
+enum ElidedParamEnumTest {
+    A(u64),
+    B(Option<String>),
+    C(())
+}
+serde_impl!(ElidedParamEnumTest(u64) {
+    A(u64) => 0,
+    #[elide] B(Option<String>) => 1,
+    #[elide] C(()) => 2
+});
+
+#[derive(PartialEq, Debug)]
+enum OptionParamEnumTest {
+    A(u64),
+    B(Option<String>),
+    C(())
+}
+serde_impl!(OptionParamEnumTest(u64) {
+    A(u64) => 0,
+    B(Option<String>) => 1,
+    C(()) => 2
+});
+
+#[test]
+fn test_elided_param_enum() {
+    test_obj(ElidedParamEnumTest::B(Some("test".to_string())));
+    test_obj(ElidedParamEnumTest::B(None));
+    test_obj(ElidedParamEnumTest::C(()));
+    assert_eq!(to_bytes(&ElidedParamEnumTest::B(None)), to_bytes(&(1u64,)));
+    assert_eq!(to_bytes(&ElidedParamEnumTest::C(())), to_bytes(&(2u64,)));
+    assert_eq!(ElidedParamEnumTest::B(None), from_bytes(&to_bytes(&(1u64, None::<String>))));
+    assert_eq!(ElidedParamEnumTest::C(()), from_bytes(&to_bytes(&(2u64, ()))));
+    let msg = error_message::<ElidedParamEnumTest>(&to_bytes(&(0u64,)));
+    assert!(msg.contains("Enums must be encoded as tuples"), "{}", msg);
+}
+
+#[test]
+fn test_option_param_enum() {
+    test_obj(OptionParamEnumTest::B(None));
+    test_obj(OptionParamEnumTest::C(()));
+    assert_eq!(to_bytes(&OptionParamEnumTest::B(None)), to_bytes(&(1u64, None::<String>)));
+    assert_eq!(to_bytes(&OptionParamEnumTest::C(())), to_bytes(&(2u64, ())));
+    assert_eq!(OptionParamEnumTest::B(None), from_bytes(&to_bytes(&(1u64,))));
+    for obj in [OptionParamEnumTest::A(3), OptionParamEnumTest::B(None), OptionParamEnumTest::B(Some("test".to_string())), OptionParamEnumTest::C(())] {
+        let serialized = bincode::serialize(&obj).unwrap();
+        assert_eq!(bincode::deserialize::<OptionParamEnumTest>(&serialized).unwrap(), obj);
+    }
+}
+
+#[derive(PartialEq, Debug)]
+enum BareParamEnumTest {
+    A(u64),
+    B(Vec<u8>),
+    C(String)
+}
+serde_impl!(BareParamEnumTest(u64) {
+    A(u64) => 0,
+    #[bare] B(Vec<u8>) => 1,
+    #[bare] C(String = "legacy".to_string()) => 2
+});
+
+#[derive(PartialEq, Debug)]
+enum BareStrParamEnumTest {
+    A(u64),
+    B(Option<u64>)
+}
+serde_impl!(BareStrParamEnumTest(String) {
+    A(u64),
+    #[bare] B(Option<u64>)
+});
+
+// The generated visitor must not shadow a type with a short name
+#[derive(PartialEq, Debug)]
+enum E {
+    A(u64),
+    B(String)
+}
+serde_impl!(E(u64) {
+    A(u64) => 0,
+    #[bare] B(String) => 1
+});
+
+#[test]
+fn test_bare_param_enum() {
+    test_obj(BareParamEnumTest::A(5));
+    test_obj(BareParamEnumTest::B(vec![3]));
+    assert_eq!(to_bytes(&BareParamEnumTest::B(vec![])), to_bytes(&(1u64, Vec::<u8>::new())));
+    assert_eq!(BareParamEnumTest::B(vec![]), from_bytes(&to_bytes(&1u64)));
+    assert_eq!(BareParamEnumTest::C("legacy".to_string()), from_bytes(&to_bytes(&2u8)));
+    let msg = error_message::<BareParamEnumTest>(&to_bytes(&0u64));
+    assert!(msg.contains("Enums must be encoded as tuples"), "{}", msg);
+    let msg = error_message::<BareParamEnumTest>(&to_bytes(&3u64));
+    assert!(msg.contains("Invalid enum discriminator"), "{}", msg);
+    assert_eq!(BareStrParamEnumTest::B(None), from_bytes(&to_bytes(&"B")));
+    assert_eq!(BareStrParamEnumTest::B(Some(1)), from_bytes(&to_bytes(&("B", 1u64))));
+    test_obj(BareStrParamEnumTest::A(1));
+    test_obj(E::A(1));
+    assert_eq!(E::B(String::new()), from_bytes(&to_bytes(&1u64)));
+}
+
 #[test]
 fn test_int_param_enum_extended() {
     assert_eq!(IntParamEnumTestReduced::A(53), from_bytes(&to_bytes(&IntParamEnumTest::A(53))));
@@ -181,3 +310,324 @@ fn test_str_param_enum_extended() {
     assert_eq!(StrParamEnumTestReduced::A(53), from_bytes(&to_bytes(&StrParamEnumTest::A(53))));
     assert_eq!(StrParamEnumTestReduced::C("test".to_string()), from_bytes(&to_bytes(&StrParamEnumTest::C("test".to_string()))));
 }
+
+fn error_message<'a, T: serde::Deserialize<'a> + Debug>(bytes: &[u8]) -> String {
+    let cursor = Cursor::new(bytes);
+    let mut reader = rmp_serde::Deserializer::new(cursor);
+    format!("{:?}", T::deserialize(&mut reader).unwrap_err())
+}
+
+#[test]
+fn test_error_messages() {
+    let msg = error_message::<IntEnumTestReduced>(&to_bytes(&IntEnumTest::B));
+    assert!(msg.contains("IntEnumTestReduced"), "{}", msg);
+    assert!(msg.contains("0, 2"), "{}", msg);
+    let msg = error_message::<StrParamEnumTest>(&to_bytes(&("a", "test")));
+    assert!(msg.contains("StrParamEnumTest.A (key"), "{}", msg);
+}
+
+#[derive(PartialEq, Debug)]
+enum BinEnumTest {
+    A, B
+}
+serde_impl!(BinEnumTest(ByteBuf) {
+    A => b"a",
+    B => b"bb"
+});
+
+#[derive(PartialEq, Debug)]
+enum BinParamEnumTest {
+    A(u64), B(String)
+}
+serde_impl!(BinParamEnumTest(ByteBuf) {
+    A(u64) => b"a",
+    B(String) => b"bb"
+});
+
+#[test]
+fn test_bin_enum() {
+    test_obj(BinEnumTest::A);
+    test_obj(BinEnumTest::B);
+    assert_eq!(to_bytes(&BinEnumTest::B), to_bytes(&ByteBuf::from(b"bb".to_vec())));
+    test_obj(BinParamEnumTest::A(53));
+    test_obj(BinParamEnumTest::B("test".to_string()));
+}
+
+#[derive(PartialEq, Debug)]
+enum SignedEnumTest {
+    A, B, C
+}
+serde_impl!(SignedEnumTest(i8) {
+    A => -1,
+    B => 0,
+    C => 127
+});
+
+#[derive(PartialEq, Debug)]
+enum U16ParamEnumTest {
+    A(u64), B(String)
+}
+serde_impl!(U16ParamEnumTest(u16) {
+    A(u64) => 1000,
+    B(String) => 2
+});
+
+#[test]
+fn test_small_int_enum() {
+    test_obj(SignedEnumTest::A);
+    test_obj(SignedEnumTest::B);
+    test_obj(SignedEnumTest::C);
+    assert_eq!(to_bytes(&SignedEnumTest::A), to_bytes(&-1i8));
+    test_obj(U16ParamEnumTest::A(53));
+    test_obj(U16ParamEnumTest::B("test".to_string()));
+}
+
+mod discriminators {
+    pub const A: u64 = 0;
+    pub const B: u64 = 1;
+}
+
+#[derive(PartialEq, Debug)]
+enum ConstEnumTest {
+    A, B
+}
+serde_impl!(ConstEnumTest(u64) {
+    A => discriminators::A,
+    B => discriminators::B
+});
+
+#[test]
+fn test_const_enum() {
+    test_obj(ConstEnumTest::A);
+    test_obj(ConstEnumTest::B);
+    assert_eq!(IntEnumTest::B, from_bytes(&to_bytes(&ConstEnumTest::B)));
+}
+
+#[derive(PartialEq, Debug)]
+enum BatchEnumTest {
+    A, B
+}
+
+#[derive(PartialEq, Debug)]
+enum BatchParamEnumTest {
+    A(u64), B(String)
+}
+
+serde_impl!(
+    BatchEnumTest(String) {
+        A => "a",
+        B => "b"
+    };
+    BatchParamEnumTest(u64) {
+        A(u64) => 0,
+        B(String) => 1
+    }
+);
+
+#[test]
+fn test_batch_enum() {
+    test_obj(BatchEnumTest::A);
+    test_obj(BatchEnumTest::B);
+    test_obj(BatchParamEnumTest::A(53));
+    test_obj(BatchParamEnumTest::B("test".to_string()));
+}
+
+#[test]
+fn test_key_constants() {
+    assert_eq!(StrEnumTest::KEYS.B, "b");
+    assert_eq!(StrEnumTest::VARIANT_KEYS, &[("A", "a"), ("B", "b"), ("C", "c")]);
+    assert_eq!(IntParamEnumTest::KEYS.C, 2);
+    assert_eq!(BinParamEnumTest::VARIANT_KEYS, &[("A", &b"a"[..]), ("B", &b"bb"[..])]);
+}
+
+#[derive(PartialEq, Debug)]
+enum MapParamEnumTest {
+    A(u64), B(bool), C(String)
+}
+serde_impl!(MapParamEnumTest(String) map {
+    A(u64) => "a",
+    B(bool) => "b",
+    C(String) => "c"
+});
+
+#[test]
+fn test_map_param_enum() {
+    test_obj(MapParamEnumTest::A(53));
+    test_obj(MapParamEnumTest::B(true));
+    test_obj(MapParamEnumTest::C("test".to_string()));
+    let mut map = BTreeMap::new();
+    map.insert("c".to_string(), "test".to_string());
+    assert_eq!(to_bytes(&MapParamEnumTest::C("test".to_string())), to_bytes(&map));
+    map.insert("d".to_string(), "test".to_string());
+    let msg = error_message::<MapParamEnumTest>(&to_bytes(&map));
+    assert!(msg.contains("single entry (MapParamEnumTest)"), "{}", msg);
+    let msg = error_message::<MapParamEnumTest>(&to_bytes(&StrParamEnumTest::A(1)));
+    assert!(msg.contains("MapParamEnumTest"), "{}", msg);
+}
+
+#[derive(PartialEq, Debug)]
+enum FallbackEnumTest {
+    A, B, Unknown
+}
+serde_impl!(FallbackEnumTest(String) {
+    A => "a",
+    B => "b",
+    _ => Unknown
+});
+
+#[test]
+fn test_fallback_enum() {
+    test_obj(FallbackEnumTest::A);
+    test_obj(FallbackEnumTest::B);
+    assert_eq!(FallbackEnumTest::Unknown, from_bytes(&to_bytes(&StrEnumTest::C)));
+    assert_eq!(FallbackEnumTest::B, from_bytes(&to_bytes(&StrEnumTest::B)));
+    let msg = error_message::<FallbackEnumTest>(&to_bytes(&5));
+    assert!(msg.contains("FallbackEnumTest"), "{}", msg);
+    let mut serialized = Vec::new();
+    let err = serde::Serialize::serialize(&FallbackEnumTest::Unknown, &mut rmp_serde::Serializer::new(&mut serialized)).unwrap_err();
+    assert!(format!("{:?}", err).contains("FallbackEnumTest::Unknown"), "{:?}", err);
+}
+
+#[derive(PartialEq, Debug)]
+enum IgnoreCaseEnumTest {
+    Active, Inactive
+}
+serde_impl!(IgnoreCaseEnumTest(IgnoreCase) {
+    Active => "active",
+    Inactive => "inactive"
+});
+
+struct StripDashes;
+
+impl Normalize for StripDashes {
+    fn normalize(key: &str) -> Cow<'_, str> {
+        Cow::Owned(key.chars().filter(|c| *c != '-' && *c != '_').collect())
+    }
+}
+
+type DashlessKey = NormalizedKey<StripDashes>;
+
+#[derive(PartialEq, Debug)]
+enum NormalizedEnumTest {
+    InProgress, Done
+}
+serde_impl!(NormalizedEnumTest(DashlessKey) {
+    InProgress => "in_progress",
+    Done => "done"
+});
+
+#[test]
+fn test_normalized_enum() {
+    test_obj(IgnoreCaseEnumTest::Active);
+    test_obj(IgnoreCaseEnumTest::Inactive);
+    assert_eq!(to_bytes(&IgnoreCaseEnumTest::Active), to_bytes(&"active"));
+    for key in &["Active", "ACTIVE", "active"] {
+        assert_eq!(IgnoreCaseEnumTest::Active, from_bytes(&to_bytes(key)));
+    }
+    assert_eq!(NormalizedEnumTest::InProgress, from_bytes(&to_bytes(&"in-progress")));
+    assert_eq!(NormalizedEnumTest::InProgress, from_bytes(&to_bytes(&"inprogress")));
+    assert_eq!(to_bytes(&NormalizedEnumTest::InProgress), to_bytes(&"in_progress"));
+}
+
+#[derive(PartialEq, Debug)]
+enum AutoStrEnumTest {
+    A, B, C
+}
+serde_impl!(AutoStrEnumTest(String) {
+    A, B, C
+});
+
+#[derive(PartialEq, Debug)]
+enum AutoIntEnumTest {
+    A, B, Unknown
+}
+serde_impl!(AutoIntEnumTest(u8) {
+    A, B,
+    _ => Unknown
+});
+
+#[derive(PartialEq, Debug)]
+enum AutoParamEnumTest {
+    A(u64), B(bool), C(String)
+}
+serde_impl!(AutoParamEnumTest(u64) {
+    A(u64), B(bool), C(String)
+});
+
+#[derive(PartialEq, Debug)]
+enum AutoMapParamEnumTest {
+    A(u64), B(bool), C(String)
+}
+serde_impl!(AutoMapParamEnumTest(String) map {
+    A(u64), B(bool), C(String)
+});
+
+#[test]
+fn test_auto_enum() {
+    test_obj(AutoStrEnumTest::B);
+    assert_eq!(to_bytes(&AutoStrEnumTest::B), to_bytes(&"B"));
+    test_obj(AutoIntEnumTest::B);
+    assert_eq!(to_bytes(&AutoIntEnumTest::B), to_bytes(&1));
+    assert_eq!(AutoIntEnumTest::Unknown, from_bytes(&to_bytes(&5)));
+    test_obj(AutoParamEnumTest::C("test".to_string()));
+    assert_eq!(to_bytes(&AutoParamEnumTest::C("test".to_string())), to_bytes(&IntParamEnumTest::C("test".to_string())));
+    test_obj(AutoMapParamEnumTest::A(53));
+    let msg = error_message::<AutoStrEnumTest>(&to_bytes(&"D"));
+    assert!(msg.contains("\\\"A\\\", \\\"B\\\", \\\"C\\\""), "{}", msg);
+}
+
+#[derive(PartialEq, Debug)]
+enum RenameEnumTest {
+    FirstValue, HTTPServer, Unknown
+}
+serde_impl!(RenameEnumTest(String, rename_all = "snake_case") {
+    FirstValue, HTTPServer,
+    _ => Unknown
+});
+
+#[derive(PartialEq, Debug)]
+enum RenameParamEnumTest {
+    FirstValue(u64), SecondValue(String)
+}
+serde_impl!(RenameParamEnumTest(String, rename_all = "kebab-case") map {
+    FirstValue(u64), SecondValue(String)
+});
+
+#[test]
+fn test_rename_all_enum() {
+    test_obj(RenameEnumTest::FirstValue);
+    assert_eq!(to_bytes(&RenameEnumTest::FirstValue), to_bytes(&"first_value"));
+    assert_eq!(to_bytes(&RenameEnumTest::HTTPServer), to_bytes(&"httpserver"));
+    assert_eq!(RenameEnumTest::Unknown, from_bytes(&to_bytes(&"FirstValue")));
+    test_obj(RenameParamEnumTest::SecondValue("test".to_string()));
+    assert_eq!(RenameParamEnumTest::KEYS.FirstValue, "first-value");
+}
+
+#[derive(PartialEq, Debug)]
+enum DiscriminantEnumTest {
+    A = 1, B = 5, C = 7
+}
+serde_impl!(DiscriminantEnumTest(u8) discriminant {
+    A, B, C
+});
+
+#[derive(PartialEq, Debug)]
+enum DiscriminantFallbackEnumTest {
+    A = 1, B = 5, Unknown = 255
+}
+serde_impl!(DiscriminantFallbackEnumTest(i64) discriminant {
+    A, B,
+    _ => Unknown
+});
+
+#[test]
+fn test_discriminant_enum() {
+    test_obj(DiscriminantEnumTest::A);
+    test_obj(DiscriminantEnumTest::C);
+    assert_eq!(to_bytes(&DiscriminantEnumTest::B), to_bytes(&5));
+    assert_eq!(DiscriminantEnumTest::C, from_bytes(&to_bytes(&7)));
+    let err = rmp_serde::from_slice::<DiscriminantEnumTest>(&to_bytes(&2)).unwrap_err();
+    assert_eq!(err.to_string(), "Invalid enum discriminator for DiscriminantEnumTest, expected one of: 1, 5, 7");
+    assert_eq!(DiscriminantFallbackEnumTest::Unknown, from_bytes(&to_bytes(&7)));
+    assert_eq!(DiscriminantEnumTest::KEYS.B, 5);
+}
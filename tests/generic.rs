@@ -1,15 +1,21 @@
 extern crate serde;
 extern crate serde_bytes;
 extern crate rmp_serde;
-extern crate serde_utils;
+extern crate serde_json;
+extern crate serde_cbor;
+#[macro_use] extern crate serde_derive;
+#[macro_use] extern crate serde_utils;
+#[cfg(feature = "num-bigint")] extern crate num_bigint;
+#[cfg(feature = "rust_decimal")] extern crate rust_decimal;
 
 use std::fmt::Debug;
 use std::io::Cursor;
+use std::borrow::Cow;
 use std::collections::BTreeMap;
 use std::f64;
 
 use serde_bytes::ByteBuf;
-use serde_utils::Obj;
+use serde_utils::{Obj, ObjMap, ObjPath, PathSegment};
 
 fn to_bytes<T: serde::Serialize + Debug>(obj: &T) -> Vec<u8> {
     let mut serialized = Vec::new();
@@ -26,7 +32,7 @@ fn from_bytes<'a, T: serde::Deserialize<'a> + Debug>(bytes: &[u8]) -> T {
     T::deserialize(&mut reader).unwrap()
 }
 
-#[allow(unknown_lints,needless_pass_by_value)]
+#[allow(clippy::needless_pass_by_value)]
 fn test_obj<'a, T: serde::Serialize + serde::Deserialize<'a> + PartialEq + Debug>(obj: T) {
     let serialized = to_bytes(&obj);
     let deserialized = from_bytes(&serialized);
@@ -69,6 +75,15 @@ fn test_binary() {
     test_obj(Obj::Bin(serde_bytes::ByteBuf::from(vec![1,2,3,4])));
     test_obj(Obj::Bin(serde_bytes::ByteBuf::from(vec![])));
     test_obj(Obj::Bin(serde_bytes::ByteBuf::from(vec![0,1,2,3,4])));
+    let obj = Obj::List(vec![Obj::from_bytes(vec![1,2,3,4])]);
+    let json = serde_json::to_string(&obj).unwrap();
+    assert_eq!(json, "[\"AQIDBA==\"]");
+    let parsed: Obj = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, Obj::List(vec![Obj::Str("AQIDBA==".to_string())]));
+    let bytes: Vec<serde_utils::adapters::base64::Base64> = serde_utils::from_obj(parsed).unwrap();
+    assert_eq!(bytes[0].0, vec![1,2,3,4]);
+    let bytes: Vec<serde_utils::adapters::base64::Base64> = serde_utils::from_obj(obj).unwrap();
+    assert_eq!(bytes[0].0, vec![1,2,3,4]);
 }
 
 #[test]
@@ -80,9 +95,9 @@ fn test_list() {
 
 macro_rules! map(
     { $( $key:expr => $val:expr ),* } => {
-        #[allow(unknown_lints,let_and_return)]
+        #[allow(clippy::let_and_return)]
         {
-            let mut _map = BTreeMap::new();
+            let mut _map = ObjMap::new();
             $(
                 _map.insert($key, $val);
             )*
@@ -139,7 +154,7 @@ fn test_eq() {
 
 macro_rules! check_ord(
     [ $( $val:expr ),* ] => {
-        #[allow(unknown_lints,needless_range_loop)]
+        #[allow(clippy::needless_range_loop)]
         {
             let vals = vec![ $( $val ),* ];
             for i in 1..vals.len() {
@@ -164,3 +179,779 @@ fn test_ord() {
         Obj::Map(map!{}), Obj::Map(map!{Obj::Null => Obj::Bool(false)}), Obj::Map(map!{Obj::Null => Obj::Bool(true)})
     ];
 }
+
+#[test]
+fn test_from_obj() {
+    assert_eq!(serde_utils::from_obj::<Option<u8>>(Obj::Null).unwrap(), None);
+    assert_eq!(serde_utils::from_obj::<Option<u8>>(Obj::Unsigned(5)).unwrap(), Some(5));
+    assert_eq!(serde_utils::from_obj::<i16>(Obj::Signed(-5)).unwrap(), -5);
+    assert_eq!(serde_utils::from_obj::<String>(Obj::Str("test".to_string())).unwrap(), "test");
+    assert_eq!(serde_utils::from_obj::<ByteBuf>(Obj::Bin(ByteBuf::from(vec![1, 2]))).unwrap(), ByteBuf::from(vec![1, 2]));
+    assert_eq!(serde_utils::from_obj::<(u8, bool)>(Obj::List(vec![Obj::Unsigned(1), Obj::Bool(true)])).unwrap(), (1, true));
+    let expected: BTreeMap<String, u8> = vec![("a".to_string(), 1)].into_iter().collect();
+    assert_eq!(serde_utils::from_obj::<BTreeMap<String, u8>>(Obj::Map(map!{
+        Obj::Str("a".to_string()) => Obj::Unsigned(1)
+    })).unwrap(), expected);
+    assert!(serde_utils::from_obj::<u8>(Obj::Str("test".to_string())).is_err());
+    assert!(serde_utils::from_obj::<(u8, bool)>(Obj::List(vec![Obj::Unsigned(1)])).is_err());
+}
+
+#[test]
+fn test_map_values() {
+    let obj = Obj::Map(map!{
+        Obj::Str("a".to_string()) => Obj::List(vec![Obj::Unsigned(1), Obj::Str("x".to_string())]),
+        Obj::Str("b".to_string()) => Obj::Unsigned(2)
+    });
+    let mut paths = Vec::new();
+    let mapped = obj.clone().map_values(|path, val| {
+        paths.push(path.to_string());
+        match val {
+            Obj::Unsigned(num) => Obj::Unsigned(num * 10),
+            val => val
+        }
+    });
+    assert_eq!(paths, vec!["a/0", "a/1", "b"]);
+    assert_eq!(mapped, Obj::Map(map!{
+        Obj::Str("a".to_string()) => Obj::List(vec![Obj::Unsigned(10), Obj::Str("x".to_string())]),
+        Obj::Str("b".to_string()) => Obj::Unsigned(20)
+    }));
+    assert_eq!(Obj::Bool(true).map_values(|path, _| { assert!(path.is_empty()); Obj::Null }), Obj::Null);
+    let err = obj.try_map_values(|path, val| match val {
+        Obj::Str(_) => Err(path.clone()),
+        val => Ok(val)
+    }).unwrap_err();
+    assert_eq!(err, ObjPath::from(vec![PathSegment::Key(Obj::Str("a".to_string())), PathSegment::Index(1)]));
+}
+
+#[test]
+fn test_retain() {
+    let mut obj = Obj::Map(map!{
+        Obj::Str("_internal".to_string()) => Obj::Unsigned(1),
+        Obj::Str("items".to_string()) => Obj::List(vec![
+            Obj::Map(map!{Obj::Str("_id".to_string()) => Obj::Unsigned(2), Obj::Str("name".to_string()) => Obj::Null}),
+            Obj::Unsigned(3),
+            Obj::Unsigned(4)
+        ])
+    });
+    let mut paths = Vec::new();
+    obj.retain(|path, val| {
+        paths.push(path.to_string());
+        if let Some(&PathSegment::Key(Obj::Str(ref key))) = path.segments().last() {
+            return !key.starts_with('_');
+        }
+        *val != Obj::Unsigned(3)
+    });
+    assert_eq!(paths, vec!["_internal", "items", "items/0", "items/0/_id", "items/0/name", "items/1", "items/2"]);
+    assert_eq!(obj, Obj::Map(map!{
+        Obj::Str("items".to_string()) => Obj::List(vec![
+            Obj::Map(map!{Obj::Str("name".to_string()) => Obj::Null}),
+            Obj::Unsigned(4)
+        ])
+    }));
+}
+
+#[test]
+fn test_find_all() {
+    let needle = Obj::Str("needle".to_string());
+    let obj = Obj::Map(map!{
+        Obj::Str("a".to_string()) => Obj::List(vec![Obj::Unsigned(1), needle.clone()]),
+        Obj::Str("needle".to_string()) => Obj::Map(map!{Obj::Unsigned(5) => needle.clone()})
+    });
+    let found = obj.find_all(|_, val| *val == needle);
+    let paths: Vec<String> = found.iter().map(|(path, _)| path.to_string()).collect();
+    assert_eq!(paths, vec!["a/1", "needle/5"]);
+    assert!(found.iter().all(|(_, val)| **val == needle));
+    let keys = obj.find_all(|path, _| path.segments().last() == Some(&PathSegment::Key(needle.clone())));
+    assert_eq!(keys.len(), 1);
+    assert_eq!(keys[0].0.to_string(), "needle");
+    assert_eq!(obj.find_all(|path, _| path.is_empty()), vec![(ObjPath::new(), Cow::Borrowed(&obj))]);
+}
+
+#[test]
+fn test_stats() {
+    let obj = Obj::Map(map!{
+        Obj::Str("ab".to_string()) => Obj::List(vec![Obj::Unsigned(1), Obj::Signed(-1), Obj::Float(0.5)]),
+        Obj::Str("c".to_string()) => Obj::Map(map!{Obj::Bin(ByteBuf::from(vec![1, 2])) => Obj::Null})
+    });
+    let stats = obj.stats();
+    assert_eq!(stats.maps, 2);
+    assert_eq!(stats.lists, 1);
+    assert_eq!(stats.strs, 2);
+    assert_eq!(stats.str_bytes, 3);
+    assert_eq!(stats.bins, 1);
+    assert_eq!(stats.bin_bytes, 2);
+    assert_eq!((stats.unsigned, stats.signed, stats.floats, stats.nulls, stats.bools), (1, 1, 1, 1, 0));
+    assert_eq!(stats.nodes(), 10);
+    assert_eq!(stats.max_depth, 2);
+    assert_eq!(stats.max_list_len, 3);
+    assert_eq!(stats.max_map_len, 2);
+    assert_eq!(Obj::Null.stats().max_depth, 0);
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct User {
+    name: String,
+    age: u8
+}
+
+#[test]
+fn test_get_as() {
+    let obj = Obj::Map(map!{
+        Obj::Str("users".to_string()) => Obj::List(vec![Obj::Map(map!{
+            Obj::Str("name".to_string()) => Obj::Str("alice".to_string()),
+            Obj::Str("age".to_string()) => Obj::Unsigned(30)
+        })]),
+        Obj::Unsigned(7) => Obj::Bool(true)
+    });
+    assert_eq!(obj.get_as::<User>("users/0").unwrap(), User{name: "alice".to_string(), age: 30});
+    assert_eq!(obj.get_as::<String>("users/0/name").unwrap(), "alice");
+    assert!(obj.get_as::<bool>("7").unwrap());
+    assert_eq!(obj.pointer("").as_deref(), Some(&obj));
+    assert_eq!(obj.pointer("users/1").as_deref(), None);
+    let path = ObjPath::from(vec![PathSegment::Key(Obj::Str("users".to_string())), PathSegment::Index(0)]);
+    assert_eq!(obj.get_path(&path).as_deref(), obj.pointer("users/0").as_deref());
+    let err = obj.get_as::<u8>("users/0/name").unwrap_err();
+    assert_eq!(err.path().to_string(), "users/0/name");
+    assert_eq!(err.expected(), "u8");
+    assert_eq!(err.to_string(), "invalid type: string \"alice\", expected u8 at path \"users/0/name\"");
+    let err = obj.get_as::<u8>("users/0/email").unwrap_err();
+    assert_eq!(err.path().to_string(), "users/0");
+    assert_eq!(err.message(), "value not found");
+}
+
+fn path(segments: &[&str]) -> ObjPath {
+    ObjPath::from(segments.iter().map(|segment| match segment.parse() {
+        Ok(index) => PathSegment::Index(index),
+        Err(_) => PathSegment::Key(Obj::Str(segment.to_string()))
+    }).collect::<Vec<_>>())
+}
+
+#[test]
+fn test_path_mutation() {
+    let mut obj = Obj::Map(map!{
+        Obj::Str("users".to_string()) => Obj::List(vec![Obj::Map(map!{
+            Obj::Str("name".to_string()) => Obj::Str("alice".to_string())
+        })]),
+        Obj::Str("ids".to_string()) => Obj::U64Array(vec![1, 2, 3])
+    });
+    assert_eq!(obj.set_path(&path(&["users", "0", "name"]), Obj::Str("bob".to_string())).unwrap(), Some(Obj::Str("alice".to_string())));
+    assert_eq!(obj.set_path(&path(&["users", "0", "age"]), Obj::Unsigned(30)).unwrap(), None);
+    assert_eq!(obj.pointer("users/0/age").as_deref(), Some(&Obj::Unsigned(30)));
+    // Lists can be appended to, but not extended beyond their end
+    assert_eq!(obj.set_path(&path(&["users", "1"]), Obj::Null).unwrap(), None);
+    let err = obj.set_path(&path(&["users", "3"]), Obj::Null).unwrap_err();
+    assert_eq!(err.to_string(), "index 3 out of range at path \"users\"");
+    let err = obj.set_path(&path(&["groups", "0"]), Obj::Null).unwrap_err();
+    assert_eq!(err.to_string(), "key groups not found at path \"\"");
+    let err = obj.set_path(&path(&["users", "0", "name", "first"]), Obj::Null).unwrap_err();
+    assert_eq!(err.to_string(), "expected a map, found string at path \"users/0/name\"");
+    // Typed arrays are converted into lists
+    assert_eq!(obj.set_path(&path(&["ids", "1"]), Obj::Str("two".to_string())).unwrap(), Some(Obj::Unsigned(2)));
+    assert_eq!(obj.pointer("ids/1").as_deref(), Some(&Obj::Str("two".to_string())));
+    *obj.get_path_mut(&path(&["ids", "2"])).unwrap() = Obj::Bool(true);
+    assert_eq!(obj.pointer("ids/2").as_deref(), Some(&Obj::Bool(true)));
+    obj.insert_path(&path(&["ids", "0"]), Obj::Unsigned(0)).unwrap();
+    assert_eq!(obj.get_as::<(u64, u64, String, bool)>("ids").unwrap(), (0, 1, "two".to_string(), true));
+    assert!(obj.insert_path(&path(&["users", "0", "age"]), Obj::Null).is_err());
+    assert_eq!(obj.remove_path(&path(&["ids", "0"])), Some(Obj::Unsigned(0)));
+    assert_eq!(obj.remove_path(&path(&["users", "0", "age"])), Some(Obj::Unsigned(30)));
+    assert_eq!(obj.remove_path(&path(&["users", "0", "age"])), None);
+    assert_eq!(obj.remove_path(&ObjPath::new()), None);
+    assert_eq!(obj.pointer("users/0").as_deref(), Some(&Obj::Map(map!{ Obj::Str("name".to_string()) => Obj::Str("bob".to_string()) })));
+    // Missing maps and lists are created along the path
+    let mut obj = Obj::Null;
+    obj.set_path_create(&path(&["a", "b", "2", "c"]), Obj::Bool(true)).unwrap();
+    assert_eq!(obj, Obj::Map(map!{
+        Obj::Str("a".to_string()) => Obj::Map(map!{
+            Obj::Str("b".to_string()) => Obj::List(vec![Obj::Null, Obj::Null, Obj::Map(map!{
+                Obj::Str("c".to_string()) => Obj::Bool(true)
+            })])
+        })
+    }));
+    obj.set_path_create(&path(&["a", "b", "0", "0"]), Obj::Unsigned(1)).unwrap();
+    assert_eq!(obj.pointer("a/b/0/0").as_deref(), Some(&Obj::Unsigned(1)));
+    assert!(obj.set_path_create(&path(&["a", "b", "2", "c", "d"]), Obj::Null).is_err());
+    // Nothing is created if the value can not be set, lists are only padded up to a limit
+    let before = obj.clone();
+    let err = obj.set_path_create(&path(&["a", "x", "y", "18446744073709551615"]), Obj::Null).unwrap_err();
+    assert_eq!(err.to_string(), "index 18446744073709551615 is too far beyond the end of the list at path \"a/x/y\"");
+    assert!(obj.set_path_create(&path(&["a", "new", "2", "c", "d", "100000", "x"]), Obj::Null).is_err());
+    assert!(obj.set_path_create(&path(&["a", "b", "2", "c", "d", "e"]), Obj::Null).is_err());
+    assert_eq!(obj, before);
+    obj.set_path_create(&path(&["a", "b", "65538"]), Obj::Null).unwrap();
+    assert_eq!(obj.set_path(&ObjPath::new(), Obj::Null).unwrap().map(|old| old.kind()), Some(serde_utils::ObjKind::Map));
+    assert_eq!(obj, Obj::Null);
+}
+
+#[test]
+fn test_typed_arrays() {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+    fn hash(obj: &Obj) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        obj.hash(&mut hasher);
+        hasher.finish()
+    }
+    let obj: Obj = from_bytes(&to_bytes(&vec![1u64, 2, 3]));
+    assert!(matches!(obj, Obj::U64Array(_)));
+    let list = Obj::List(vec![Obj::Unsigned(1), Obj::Unsigned(2), Obj::Unsigned(3)]);
+    assert_eq!(obj, list);
+    assert_eq!(hash(&obj), hash(&list));
+    assert!(Obj::U64Array(vec![1, 2]) < list);
+    assert!(Obj::F64Array(vec![0.5]) > Obj::List(vec![Obj::Float(0.25)]));
+    let floats: Obj = from_bytes(&to_bytes(&vec![0.5f64, 1.5]));
+    assert!(matches!(floats, Obj::F64Array(_)));
+    let mixed: Obj = from_bytes(&to_bytes(&(1u64, 0.5f64)));
+    assert_eq!(mixed, Obj::List(vec![Obj::Unsigned(1), Obj::Float(0.5)]));
+    assert!(matches!(mixed, Obj::List(_)));
+    test_obj(Obj::U64Array(vec![1, 2]));
+    assert_eq!(Obj::from_list(vec![Obj::Float(1.0)]), Obj::F64Array(vec![1.0]));
+    assert_eq!(Obj::U64Array(vec![4]).into_list(), Some(vec![Obj::Unsigned(4)]));
+    assert_eq!(serde_utils::from_obj::<Vec<u8>>(obj.clone()).unwrap(), vec![1, 2, 3]);
+    assert_eq!(obj.get_as::<u8>("1").unwrap(), 2);
+    assert_eq!(obj.pointer("1").as_deref(), Some(&Obj::Unsigned(2)));
+    assert_eq!(obj.pointer("1/0").as_deref(), None);
+    assert_eq!(floats.get_path(&ObjPath::from(vec![PathSegment::Index(1)])).as_deref(), Some(&Obj::Float(1.5)));
+    let doc = Obj::Map(map!{
+        Obj::Str("a".to_string()) => obj.clone(),
+        Obj::Str("c".to_string()) => Obj::List(vec![Obj::Map(map!{Obj::Str("x".to_string()) => Obj::Unsigned(1)})])
+    });
+    assert_eq!(doc.pointer("a/0").as_deref(), Some(&Obj::Unsigned(1)));
+    let found = doc.find_all(|_, val| *val == Obj::Unsigned(1));
+    let paths: Vec<String> = found.iter().map(|(path, _)| path.to_string()).collect();
+    assert_eq!(paths, vec!["a/0", "c/0/x"]);
+    let doubled = obj.clone().map_values(|_, val| match val {
+        Obj::Unsigned(num) => Obj::Unsigned(num * 2),
+        val => val
+    });
+    assert_eq!(doubled, Obj::U64Array(vec![2, 4, 6]));
+    let mut retained = obj;
+    retained.retain(|path, _| path.to_string() != "1");
+    assert_eq!(retained, Obj::U64Array(vec![1, 3]));
+    let stats = retained.stats();
+    assert_eq!((stats.lists, stats.unsigned, stats.max_depth), (1, 2, 1));
+}
+
+#[test]
+fn test_obj_map() {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+    fn hash(obj: &Obj) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        obj.hash(&mut hasher);
+        hasher.finish()
+    }
+    let mut record = BTreeMap::new();
+    record.insert("b".to_string(), 2u8);
+    record.insert("a".to_string(), 1u8);
+    let obj: Obj = from_bytes(&to_bytes(&record));
+    let map = Obj::Map(map!{Obj::Str("b".to_string()) => Obj::Unsigned(2), Obj::Str("a".to_string()) => Obj::Unsigned(1)});
+    assert_eq!(obj, map);
+    assert_eq!(hash(&obj), hash(&map));
+    assert_eq!(format!("{}", obj), "{Str(\"a\"): Unsigned(1), Str(\"b\"): Unsigned(2)}");
+    assert!(Obj::Map(map!{Obj::Str("a".to_string()) => Obj::Unsigned(0)}) < map);
+    let large: BTreeMap<u64, u64> = (0..20).map(|i| (i, i)).collect();
+    let large_obj: Obj = from_bytes(&to_bytes(&large));
+    assert_eq!(large_obj, Obj::Map((0..20).rev().map(|i| (Obj::Unsigned(i), Obj::Unsigned(i))).collect()));
+    assert_eq!(large_obj.clone().into_map().map(|map| map.len()), Some(20));
+    assert_eq!(Obj::from_map(map.clone().into_map().unwrap()), map);
+    test_obj(obj.clone());
+    test_obj(large_obj);
+    assert_eq!(serde_utils::from_obj::<BTreeMap<String, u8>>(obj.clone()).unwrap(), record);
+    assert_eq!(obj.pointer("b").as_deref(), Some(&Obj::Unsigned(2)));
+    assert_eq!(obj.get_as::<u8>("a").unwrap(), 1);
+    let doubled = obj.clone().map_values(|_, val| match val {
+        Obj::Unsigned(num) => Obj::Unsigned(num * 2),
+        val => val
+    });
+    assert_eq!(doubled.pointer("b").as_deref(), Some(&Obj::Unsigned(4)));
+    let mut retained = obj.clone();
+    retained.retain(|path, _| path.to_string() != "a");
+    assert!(matches!(retained, Obj::Map(ref entries) if entries.len() == 1));
+    assert_eq!(obj.find_all(|_, val| *val == Obj::Unsigned(2)).len(), 1);
+    let stats = obj.stats();
+    assert_eq!((stats.maps, stats.strs, stats.max_map_len), (1, 2, 2));
+}
+
+#[test]
+fn test_obj_map_api() {
+    let mut map = ObjMap::new();
+    for i in (0..40u64).rev() {
+        assert_eq!(map.insert(Obj::Unsigned(i % 20), Obj::Unsigned(i)), if i < 20 { Some(Obj::Unsigned(i + 20)) } else { None });
+    }
+    assert_eq!(map.len(), 20);
+    assert!(map.keys().cloned().eq((0..20).map(Obj::Unsigned)));
+    assert!(map.values().cloned().eq((0..20).map(Obj::Unsigned)));
+    assert_eq!(map.get(&Obj::Signed(3)), Some(&Obj::Unsigned(3)));
+    assert_eq!(map[&Obj::Unsigned(4)], Obj::Unsigned(4));
+    for i in 2..20 {
+        assert_eq!(map.remove(&Obj::Unsigned(i)), Some(Obj::Unsigned(i)));
+    }
+    assert_eq!(map.remove(&Obj::Unsigned(2)), None);
+    map.insert(Obj::Null, Obj::Null);
+    for val in map.values_mut() {
+        *val = Obj::Bool(true);
+    }
+    assert_eq!(map.iter().next(), Some((&Obj::Null, &Obj::Bool(true))));
+    assert_eq!(map.iter().next_back(), Some((&Obj::Unsigned(1), &Obj::Bool(true))));
+    map.retain(|key, _| *key != Obj::Null);
+    assert!(!map.contains_key(&Obj::Null));
+    let tree: BTreeMap<Obj, Obj> = map.clone().into();
+    assert_eq!(ObjMap::from(tree), map);
+    let dups: ObjMap = vec![(Obj::Signed(1), Obj::Null), (Obj::Unsigned(1), Obj::Bool(false))].into_iter().collect();
+    assert_eq!(dups.iter().collect::<Vec<_>>(), vec![(&Obj::Signed(1), &Obj::Bool(false))]);
+    assert_eq!(format!("{:?}", dups), "{Signed(1): Bool(false)}");
+    map.clear();
+    assert!(map.is_empty());
+}
+
+#[test]
+fn test_cow_obj() {
+    use serde_utils::CowObj;
+    let obj = Obj::List(vec![Obj::Unsigned(1), Obj::Str("a".to_string())]);
+    let shared = CowObj::new(obj.clone());
+    let mut copy = shared.clone();
+    assert!(copy.ptr_eq(&shared));
+    assert_eq!(copy.pointer("1").as_deref(), Some(&Obj::Str("a".to_string())));
+    if let Obj::List(ref mut list) = *copy.make_mut() {
+        list.push(Obj::Null);
+    }
+    assert!(!copy.ptr_eq(&shared));
+    assert_eq!(*shared, obj);
+    assert_eq!(copy.pointer("2").as_deref(), Some(&Obj::Null));
+    let mut unique = CowObj::from(obj.clone());
+    let ptr = unique.make_mut() as *const Obj;
+    assert_eq!(unique.make_mut() as *const Obj, ptr);
+    assert_eq!(shared.clone().into_inner(), obj);
+    assert_eq!(format!("{}", shared), format!("{}", obj));
+    test_obj(shared);
+}
+
+#[test]
+fn test_to_rust_literal() {
+    let obj = ::serde_utils::Obj::Map(vec![(::serde_utils::Obj::Unsigned(1), ::serde_utils::Obj::Map(vec![(::serde_utils::Obj::Unsigned(2), ::serde_utils::Obj::F64Array(vec![1e300, 2.0]))].into_iter().collect())), (::serde_utils::Obj::Str("a\n\"b\"".to_string()), ::serde_utils::Obj::List(vec![::serde_utils::Obj::Null, ::serde_utils::Obj::Bool(true), ::serde_utils::Obj::Signed(-9223372036854775808), ::serde_utils::Obj::Float(-0.5), ::serde_utils::Obj::Float(f64::INFINITY), ::serde_utils::Obj::from_bytes(vec![0, 255])]))].into_iter().collect());
+    assert_eq!(obj.to_rust_literal(), concat!(
+        "::serde_utils::Obj::Map(vec![(::serde_utils::Obj::Unsigned(1), ::serde_utils::Obj::Map(vec![",
+        "(::serde_utils::Obj::Unsigned(2), ::serde_utils::Obj::F64Array(vec![1e300, 2.0]))].into_iter().collect())), ",
+        "(::serde_utils::Obj::Str(\"a\\n\\\"b\\\"\".to_string()), ",
+        "::serde_utils::Obj::List(vec![::serde_utils::Obj::Null, ::serde_utils::Obj::Bool(true), ",
+        "::serde_utils::Obj::Signed(-9223372036854775808), ::serde_utils::Obj::Float(-0.5), ",
+        "::serde_utils::Obj::Float(f64::INFINITY), ::serde_utils::Obj::from_bytes(vec![0, 255])]))].into_iter().collect())"
+    ));
+    assert_eq!(Obj::Float(f64::NAN).to_rust_literal(), "::serde_utils::Obj::Float(f64::NAN)");
+}
+
+#[test]
+fn test_partial_obj() {
+    use serde::de::DeserializeSeed;
+    use serde_utils::PartialObj;
+    fn s(val: &str) -> Obj {
+        Obj::Str(val.to_string())
+    }
+    let doc = Obj::Map(map!{
+        s("id") => Obj::Unsigned(7),
+        s("users") => Obj::List(vec![
+            Obj::Map(map!{s("name") => s("alice"), s("roles") => Obj::List(vec![s("admin")])}),
+            Obj::Map(map!{s("name") => s("bob"), s("roles") => Obj::List(vec![])})
+        ]),
+        s("extra") => Obj::Map(map!{Obj::Unsigned(1) => s("one"), Obj::Unsigned(2) => s("two")}),
+        s("note") => s("ignored")
+    });
+    let bytes = to_bytes(&doc);
+    let seed = PartialObj::new(["id", "users/*/name", "users/0/roles", "extra/2", "note/deeper"]);
+    let obj = seed.deserialize(&mut rmp_serde::Deserializer::new(Cursor::new(&bytes))).unwrap();
+    assert_eq!(obj.get_as::<u64>("id").unwrap(), 7);
+    assert_eq!(obj.get_as::<String>("users/1/name").unwrap(), "bob");
+    assert_eq!(obj.get_as::<Vec<String>>("users/0/roles").unwrap(), vec!["admin".to_string()]);
+    assert_eq!(obj.pointer("users/1/roles").as_deref(), None);
+    assert_eq!(obj.get_as::<String>("extra/2").unwrap(), "two");
+    assert_eq!(obj.pointer("extra/1").as_deref(), None);
+    assert_eq!(obj.pointer("note").as_deref(), Some(&Obj::Null));
+    let seed = PartialObj::new(["users/1"]);
+    let obj = seed.deserialize(&mut rmp_serde::Deserializer::new(Cursor::new(&bytes))).unwrap();
+    assert_eq!(obj.pointer("users/0").as_deref(), Some(&Obj::Null));
+    assert_eq!(obj.get_as::<String>("users/1/name").unwrap(), "bob");
+    let full = PartialObj::new([""]).deserialize(&mut rmp_serde::Deserializer::new(Cursor::new(&bytes))).unwrap();
+    assert_eq!(full, from_bytes::<Obj>(&bytes));
+}
+
+#[test]
+fn test_primitive_eq() {
+    let obj = Obj::Map(map!{
+        Obj::Str("name".to_string()) => Obj::Str("alice".to_string()),
+        Obj::Str("age".to_string()) => Obj::Unsigned(30),
+        Obj::Str("offset".to_string()) => Obj::Signed(-2),
+        Obj::Str("score".to_string()) => Obj::Float(0.5),
+        Obj::Str("admin".to_string()) => Obj::Bool(true),
+        Obj::Str("address".to_string()) => Obj::Map(map!{Obj::Str("city".to_string()) => Obj::Str("Berlin".to_string())})
+    });
+    assert_eq!(obj["name"], "alice");
+    assert_eq!("alice", obj["name"]);
+    assert_eq!(obj["name"], "alice".to_string());
+    assert_eq!(obj["age"], 30);
+    assert_eq!(obj["age"], 30u8);
+    assert_eq!(30i64, obj["age"]);
+    assert_eq!(obj["offset"], -2);
+    assert_ne!(obj["offset"], 2u64);
+    assert_eq!(obj["score"], 0.5);
+    assert_eq!(obj["score"], 0.5f32);
+    assert_eq!(obj["admin"], true);
+    assert_eq!(obj["address"]["city"], "Berlin");
+    assert_eq!(obj["missing"]["city"], Obj::Null);
+    assert_ne!(obj["age"], "30");
+    assert_eq!(Obj::Float(f64::NAN), f64::NAN);
+}
+
+#[test]
+fn test_json_compat() {
+    use serde_utils::JsonCompat;
+    let obj = Obj::Map(map!{
+        Obj::Unsigned(1) => Obj::Str("one".to_string()),
+        Obj::Signed(-1) => Obj::Bool(true),
+        Obj::from_bytes(vec![1, 2]) => Obj::List(vec![Obj::Map(map!{Obj::Null => Obj::Bool(false)})]),
+        Obj::Str("name".to_string()) => Obj::U64Array(vec![1, 2])
+    });
+    assert!(serde_json::to_string(&obj).is_err());
+    assert_eq!(serde_json::to_string(&JsonCompat(&obj)).unwrap(),
+        r#"{"-1":true,"1":"one","name":[1,2],"AQI=":[{"null":false}]}"#);
+    assert_eq!(serde_json::to_string(&JsonCompat(&Obj::Unsigned(3))).unwrap(), "3");
+}
+
+#[test]
+fn test_float_policy() {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+    use serde_utils::{TotalOrdObj, PolicyObj, NanGreatest};
+    fn hash<T: Hash>(obj: &T) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        obj.hash(&mut hasher);
+        hasher.finish()
+    }
+    let neg_zero = Obj::Float(-0.0);
+    let zero = Obj::Float(0.0);
+    assert_eq!(neg_zero, zero);
+    assert_eq!(hash(&neg_zero), hash(&zero));
+    assert_eq!(hash(&Obj::Float(f64::NAN)), hash(&Obj::Float(-f64::NAN)));
+    assert_ne!(TotalOrdObj::new(neg_zero.clone()), TotalOrdObj::new(zero.clone()));
+    assert!(TotalOrdObj::new(neg_zero.clone()) < TotalOrdObj::new(zero.clone()));
+    assert_eq!(PolicyObj::<NanGreatest>::new(neg_zero.clone()), PolicyObj::new(zero.clone()));
+    let mut keys: Vec<TotalOrdObj> = vec![Obj::Float(f64::NAN), Obj::Float(-f64::NAN), Obj::Float(1.0), zero.clone(), neg_zero.clone()]
+        .into_iter().map(TotalOrdObj::new).collect();
+    keys.sort();
+    let sorted: Vec<String> = keys.iter().map(|key| format!("{}", key)).collect();
+    assert_eq!(sorted, vec!["NaN", "-0", "0", "1", "NaN"]);
+    let list = TotalOrdObj::new(Obj::List(vec![neg_zero.clone()]));
+    assert_ne!(list, TotalOrdObj::new(Obj::F64Array(vec![0.0])));
+    assert_eq!(list, TotalOrdObj::new(Obj::F64Array(vec![-0.0])));
+    assert_eq!(hash(&list), hash(&TotalOrdObj::new(Obj::F64Array(vec![-0.0]))));
+    let map = TotalOrdObj::new(Obj::Map(map!{Obj::Str("a".to_string()) => neg_zero}));
+    assert!(map < TotalOrdObj::new(Obj::Map(map!{Obj::Str("a".to_string()) => zero})));
+    test_obj(list);
+}
+
+#[test]
+fn test_number() {
+    use serde_utils::Number;
+    assert_eq!(Obj::Signed(5).as_number(), Obj::Unsigned(5).as_number());
+    assert_eq!(Obj::Signed(5).as_u64(), Some(5));
+    assert_eq!(Obj::Unsigned(u64::MAX).as_i64(), None);
+    assert_eq!(Obj::Signed(-3).as_u64(), None);
+    assert_eq!(Obj::Signed(-3).as_i64(), Some(-3));
+    assert_eq!(Obj::Unsigned(3).as_f64(), Some(3.0));
+    assert_eq!(Obj::Float(1.5).as_i64(), None);
+    assert_eq!(Obj::Str("1".to_string()).as_number(), None);
+    let num = Obj::Float(1.5).as_number().unwrap();
+    assert!(num.is_float() && !num.is_integer());
+    assert!(Number::from(-1).is_integer());
+    assert!(matches!(Obj::from(Number::from(7i64)), Obj::Unsigned(7)));
+    assert!(matches!(Obj::from(Number::from(-7)), Obj::Signed(-7)));
+    assert_eq!(Number::from(-7).to_string(), "-7");
+    assert_eq!(serde_json::from_str::<Number>("12").unwrap(), Number::from(12u8));
+    assert_eq!(serde_json::from_str::<Number>("-0.5").unwrap(), Number::from(-0.5));
+    assert!(serde_json::from_str::<Number>("\"12\"").is_err());
+    assert_eq!(serde_json::to_string(&Number::from(-2)).unwrap(), "-2");
+}
+
+#[test]
+fn test_large_integers() {
+    use serde::Deserialize;
+    use serde::de::IntoDeserializer;
+    use serde::de::value::Error;
+    fn from_u128(val: u128) -> Result<Obj, Error> {
+        Obj::deserialize(IntoDeserializer::<Error>::into_deserializer(val))
+    }
+    fn from_i128(val: i128) -> Result<Obj, Error> {
+        Obj::deserialize(IntoDeserializer::<Error>::into_deserializer(val))
+    }
+    assert_eq!(from_u128(5).unwrap(), Obj::Unsigned(5));
+    assert!(matches!(from_i128(-5).unwrap(), Obj::Signed(-5)));
+    assert!(matches!(from_i128(i128::from(u64::MAX)).unwrap(), Obj::Unsigned(u64::MAX)));
+    #[cfg(not(feature = "num-bigint"))]
+    assert!(from_u128(u128::MAX).is_err());
+    #[cfg(feature = "num-bigint")]
+    {
+        use num_bigint::BigInt;
+        let big = from_u128(u128::MAX).unwrap();
+        assert_eq!(big, Obj::BigInt(BigInt::from(u128::MAX)));
+        assert_eq!(serde_json::to_string(&big).unwrap(), format!("\"{}\"", u128::MAX));
+        assert_eq!(serde_utils::from_obj::<u128>(big.clone()).unwrap(), u128::MAX);
+        assert_eq!(serde_utils::from_obj::<String>(Obj::BigInt(BigInt::from(u128::MAX) * 2)).unwrap(),
+            "680564733841876926926749214863536422910");
+        assert!(matches!(Obj::from_bigint(BigInt::from(-7)), Obj::Signed(-7)));
+        assert_eq!(from_i128(i128::MIN).unwrap(), Obj::from_bigint(BigInt::from(i128::MIN)));
+        assert!(big > Obj::Unsigned(u64::MAX));
+        assert_eq!(big.stats().unsigned, 1);
+    }
+}
+
+#[cfg(feature = "rust_decimal")]
+#[test]
+fn test_decimal() {
+    use rust_decimal::Decimal;
+    let price = Obj::Decimal("12.30".parse().unwrap());
+    assert_eq!(price, Obj::Decimal("12.3".parse().unwrap()));
+    assert_eq!(serde_json::to_string(&price).unwrap(), "\"12.30\"");
+    assert_eq!(serde_utils::from_obj::<String>(price.clone()).unwrap(), "12.30");
+    assert_eq!(price.to_string(), "12.30");
+    assert_eq!(price.to_rust_literal(), "::serde_utils::Obj::Decimal(\"12.30\".parse().unwrap())");
+    assert!(Obj::Decimal(Decimal::new(1, 2)) < price);
+}
+
+#[test]
+fn test_ignore() {
+    use serde_utils::Ignore;
+    let doc = r#"{"a":[1,-2,3.5,null,true,{"b":"c"}],"d":{"e":[[],{}]}}"#;
+    assert_eq!(serde_json::from_str::<Ignore>(doc).unwrap(), Ignore);
+    let obj: Obj = serde_json::from_str(doc).unwrap();
+    assert_eq!(serde_cbor::from_slice::<Ignore>(&serde_cbor::to_vec(&obj).unwrap()).unwrap(), Ignore);
+    assert_eq!(serde_utils::from_obj::<Ignore>(obj).unwrap(), Ignore);
+    assert!(serde_json::from_str::<Ignore>(r#"{"a":[1,2}"#).is_err());
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Shape {
+    Empty,
+    Circle(f64),
+    Rect(u32, u32),
+    Poly { points: Vec<(i8, i8)> }
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Drawing {
+    name: String,
+    data: ByteBuf,
+    parent: Option<u64>,
+    shapes: Vec<Shape>,
+    big: u128
+}
+
+fn drawing() -> Drawing {
+    Drawing {
+        name: "test".to_string(),
+        data: ByteBuf::from(vec![1, 2, 3]),
+        parent: None,
+        shapes: vec![Shape::Empty, Shape::Circle(1.5), Shape::Rect(2, 3), Shape::Poly { points: vec![(-1, 1)] }],
+        big: 1 << 40
+    }
+}
+
+#[test]
+fn test_to_obj() {
+    let obj = serde_utils::to_obj(&drawing()).unwrap();
+    assert_eq!(obj, serde_cbor::from_slice::<Obj>(&serde_cbor::to_vec(&drawing()).unwrap()).unwrap());
+    assert_eq!(obj["data"], Obj::from_bytes(vec![1, 2, 3]));
+    assert_eq!(obj["parent"], Obj::Null);
+    assert_eq!(obj["shapes"].pointer("0").as_deref(), Some(&Obj::Str("Empty".to_string())));
+    assert_eq!(obj["shapes"].pointer("2/Rect").as_deref(), Some(&Obj::List(vec![Obj::Unsigned(2), Obj::Unsigned(3)])));
+    assert_eq!(obj["big"], Obj::Unsigned(1 << 40));
+    assert_eq!(serde_utils::from_obj::<Drawing>(obj.clone()).unwrap(), drawing());
+    assert_eq!(serde_utils::to_obj(&obj).unwrap(), obj);
+    assert!(matches!(serde_utils::to_obj(&[1u64, 2]).unwrap(), Obj::U64Array(_)));
+    #[cfg(not(feature = "num-bigint"))]
+    assert!(serde_utils::to_obj(&u128::MAX).is_err());
+}
+
+#[test]
+fn test_assert_obj_eq() {
+    let expected = serde_utils::to_obj(&drawing()).unwrap();
+    assert_obj_eq!(expected, drawing());
+    assert_obj_eq!(drawing(), drawing(), "same drawing");
+    let mut changed = drawing();
+    changed.name = "other".to_string();
+    changed.shapes.pop();
+    changed.shapes[2] = Shape::Rect(2, 4);
+    let err = std::panic::catch_unwind(|| assert_obj_eq!(drawing(), changed, "drawing {}", 1)).unwrap_err();
+    let msg = err.downcast::<String>().unwrap();
+    assert!(msg.starts_with("assertion `expected == actual` failed: drawing 1\n"), "{}", msg);
+    assert!(msg.contains("\nname: expected Str(\"test\"), found Str(\"other\")"), "{}", msg);
+    assert!(msg.contains("\nshapes/2/Rect/1: expected Unsigned(3), found Unsigned(4)"), "{}", msg);
+    assert!(msg.contains("\nshapes: expected 4 elements, found 3"), "{}", msg);
+    let mut map = expected.into_map().unwrap();
+    map.remove(&Obj::Str("big".to_string()));
+    let expected = Obj::from_map(map);
+    let err = std::panic::catch_unwind(|| assert_obj_eq!(expected, drawing())).unwrap_err();
+    let msg = err.downcast::<String>().unwrap();
+    assert_eq!(*msg, "assertion `expected == actual` failed:\nbig: unexpected Unsigned(1099511627776)");
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct SpannedConfig {
+    name: String,
+    timeout: serde_utils::Spanned<u64>,
+    servers: Vec<serde_utils::Spanned<String>>,
+    shape: Option<serde_utils::Spanned<Shape>>
+}
+
+#[test]
+fn test_spanned() {
+    use serde::Deserialize;
+    use serde_utils::{Spanned, SpanDeserializer, OffsetReader};
+    let json = r#"{"name":"test","timeout":30,"servers":["a","bc"],"shape":{"Rect":[1,2]}}"#;
+    let reader = OffsetReader::new(json.as_bytes());
+    let counter = reader.counter();
+    let mut de = serde_json::Deserializer::from_reader(reader);
+    let config = SpannedConfig::deserialize(SpanDeserializer::new(&mut de, counter)).unwrap();
+    assert_eq!(*config.timeout, 30);
+    assert_eq!(&json[config.timeout.start()..config.timeout.end() - 1], "30");
+    let span = config.servers[1].span();
+    assert!(json[span.start - 1..span.end + 1].contains("\"bc\""));
+    let span = config.shape.as_ref().unwrap().span();
+    assert!(json[span.start - 1..span.end].contains(r#"{"Rect":[1,2]}"#));
+    let bytes = to_bytes(&(&config.name, &config.timeout, &config.servers));
+    let reader = OffsetReader::new(&bytes[..]);
+    let counter = reader.counter();
+    let mut de = rmp_serde::Deserializer::new(reader);
+    let decoded: (String, Spanned<u64>, Vec<Spanned<String>>) =
+        Deserialize::deserialize(SpanDeserializer::new(&mut de, counter)).unwrap();
+    assert_eq!(*decoded.2[1], "bc");
+    assert_eq!(&bytes[decoded.2[1].span()], b"\xa2bc");
+    assert_eq!(decoded.1.span().len(), 1);
+    assert_eq!(Spanned::new(30, 25..28), config.timeout);
+    assert_eq!(serde_json::to_string(&config.timeout).unwrap(), "30");
+    assert!(serde_json::from_str::<Spanned<u64>>("30").is_err());
+}
+
+#[test]
+fn test_extract_error() {
+    use std::convert::TryFrom;
+    use serde_utils::ObjKind;
+    let obj: Obj = serde_json::from_str(r#"{"users":[{"name":"alice","age":30},{"name":"bob","age":"old"}]}"#).unwrap();
+    let err = serde_utils::from_obj::<BTreeMap<String, Vec<User>>>(obj.clone()).unwrap_err();
+    assert_eq!(err.path().to_string(), "users/1/age");
+    assert_eq!(err.found(), Some(ObjKind::Str));
+    assert!(err.expected().contains("BTreeMap"));
+    assert_eq!(err.message(), "invalid type: string \"old\", expected u8");
+    let err = obj.get_as::<Vec<User>>("users").unwrap_err();
+    assert_eq!(err.path().to_string(), "users/1/age");
+    let err = obj.get_as::<u8>("users/2").unwrap_err();
+    assert_eq!(err.found(), None);
+    let err = serde_utils::from_obj::<Vec<u8>>(Obj::U64Array(vec![1, 300])).unwrap_err();
+    assert_eq!(err.path().to_string(), "1");
+    assert_eq!(err.found(), Some(ObjKind::Unsigned));
+    let shapes = Obj::List(vec![Obj::Str("Empty".to_string()), serde_json::from_str(r#"{"Circle":"x"}"#).unwrap()]);
+    let err = serde_utils::from_obj::<Vec<Shape>>(shapes).unwrap_err();
+    assert_eq!(err.path().to_string(), "1/Circle");
+    assert_eq!(u8::try_from(Obj::Unsigned(5)).unwrap(), 5);
+    let err = u8::try_from(Obj::Signed(-1)).unwrap_err();
+    assert!(err.path().is_empty());
+    assert_eq!(err.found(), Some(ObjKind::Signed));
+    assert_eq!(String::try_from(Obj::List(vec![])).unwrap_err().found(), Some(ObjKind::List));
+    assert_eq!(Obj::F64Array(vec![]).kind(), ObjKind::List);
+    assert_eq!(ObjKind::Signed.to_string(), "signed integer");
+    fn user(obj: Obj) -> Result<User, serde::de::value::Error> {
+        Ok(serde_utils::from_obj(obj)?)
+    }
+    assert!(user(obj["users"].pointer("1").unwrap().into_owned()).unwrap_err().to_string().ends_with("at path \"age\""));
+}
+
+#[test]
+fn test_obj_ref() {
+    use std::borrow::Cow;
+    use serde_utils::{ObjKind, ObjRef};
+    let doc = r#"{"name":"plain","escaped":"a\nb","list":[1,-2,null],"name":"again"}"#;
+    let val: ObjRef = serde_json::from_str(doc).unwrap();
+    assert_eq!(val.kind(), ObjKind::Map);
+    assert!(matches!(val.get("name"), Some(&ObjRef::Str(Cow::Borrowed("plain")))));
+    assert!(matches!(val.get("escaped"), Some(&ObjRef::Str(Cow::Owned(_)))));
+    assert_eq!(val.get("escaped").and_then(ObjRef::as_str), Some("a\nb"));
+    assert_eq!(val.get("list"), Some(&ObjRef::List(vec![ObjRef::Unsigned(1), ObjRef::Signed(-2), ObjRef::Null])));
+    assert!(val.is_borrowed());
+    assert_eq!(serde_json::to_string(&val).unwrap(), doc);
+    let obj = Obj::from(val);
+    assert_eq!(obj["name"], "again");
+    assert_eq!(obj, serde_json::from_str::<Obj>(doc).unwrap());
+}
+
+#[cfg(feature = "msgpack")]
+#[test]
+fn test_msgpack_helpers() {
+    use std::borrow::Cow;
+    use serde_utils::ObjRef;
+    let mut map = BTreeMap::new();
+    map.insert(Obj::Str("data".to_string()), Obj::from_bytes(vec![1, 2]));
+    map.insert(Obj::Str("ids".to_string()), Obj::U64Array(vec![1, 300]));
+    map.insert(Obj::Signed(-1), Obj::Float(0.5));
+    let obj = Obj::from_map(map);
+    let bytes = obj.to_msgpack_vec().unwrap();
+    assert_eq!(bytes[..2], [0x83, 0xff]);
+    assert!(bytes.windows(4).any(|part| part == [0xc4, 0x02, 1, 2]));
+    assert_eq!(Obj::from_msgpack_slice(&bytes).unwrap(), obj);
+    assert_eq!(Obj::from_msgpack_slice(&rmp_serde::to_vec(&obj).unwrap()).unwrap(), obj);
+    let val = ObjRef::from_msgpack_slice(&bytes).unwrap();
+    assert!(matches!(val.get("data"), Some(&ObjRef::Bin(Cow::Borrowed(&[1, 2])))));
+    assert_eq!(Obj::from(val), obj);
+    assert!(Obj::from_msgpack_slice(&bytes[..5]).is_err());
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_json_helpers() {
+    let obj = Obj::from_json_str(r#"{"name":"test","ids":[1,2],"nested":{"ok":true}}"#).unwrap();
+    assert_eq!(obj["nested"]["ok"], true);
+    assert_eq!(Obj::from_json_str(&obj.to_json_string()).unwrap(), obj);
+    assert_eq!(obj.to_json_string_pretty().lines().count(), 10);
+    let mut map = BTreeMap::new();
+    map.insert(Obj::Unsigned(1), Obj::from_bytes(vec![1, 2]));
+    map.insert(Obj::Str("nan".to_string()), Obj::Float(f64::NAN));
+    assert_eq!(Obj::from_map(map).to_json_string(), r#"{"1":"AQI=","nan":null}"#);
+    assert!(Obj::from_json_str("{").is_err());
+}
+
+#[cfg(feature = "cbor")]
+#[test]
+fn test_cbor_helpers() {
+    let mut map = BTreeMap::new();
+    map.insert(Obj::Unsigned(1), Obj::from_bytes(vec![1, 2]));
+    map.insert(Obj::Str("temp".to_string()), Obj::Float(21.5));
+    let obj = Obj::from_map(map);
+    let bytes = obj.to_cbor_vec().unwrap();
+    assert_eq!(bytes[..4], [0xa2, 0x01, 0x42, 1]);
+    assert_eq!(Obj::from_cbor_slice(&bytes).unwrap(), obj);
+    // Tagged epoch timestamp as sent by many devices
+    assert_eq!(Obj::from_cbor_slice(&[0xc1, 0x1a, 0x5f, 0x5e, 0x10, 0x00]).unwrap(), Obj::Unsigned(1_600_000_000));
+    assert!(Obj::from_cbor_slice(&bytes[..3]).is_err());
+}
+
+#[test]
+fn test_dump_typed() {
+    let obj: Obj = serde_json::from_str(r#"{"id":7,"tags":["a","b","c"],"nested":{"x":[1.5,-2]},"empty":{}}"#).unwrap();
+    assert_eq!(obj.dump_typed(), r#"Map(4) { "empty": Map(0) {}, "id": Unsigned(7), "nested": Map(1) { "x": List(2)[Float(1.5), Signed(-2)] }, "tags": List(3)[Str("a"), Str("b"), Str("c")] }"#);
+    assert_eq!(obj.dump_typed_limited(1, 2), r#"Map(4) { "empty": Map(0) {...}, "id": Unsigned(7), ... 2 more }"#);
+    assert_eq!(obj["tags"].dump_typed_limited(0, 10), "List(3)[...]");
+    assert_eq!(Obj::U64Array(vec![1, 2, 3]).dump_typed_limited(1, 1), "List(3)[Unsigned(1), ... 2 more]");
+    assert_eq!(Obj::from_bytes(vec![1, 255, 3]).dump_typed_limited(1, 2), "Bin(3)[01 ff ... 1 more]");
+    assert_eq!(Obj::Str("h\u{e9}llo".to_string()).dump_typed_limited(1, 2), r#"Str("hé"... 5 chars)"#);
+    let mut map = BTreeMap::new();
+    map.insert(Obj::Unsigned(1), Obj::Null);
+    assert_eq!(Obj::from_map(map).dump_typed(), "Map(1) { Unsigned(1): Null }");
+}
+
+#[test]
+fn test_serialized_size_hint() {
+    let json = r#"{"name": "test", "ids": [1, 200, 70000, -5, -1000], "nested": {"f": 1.5, "ok": true, "none": null},
+        "long": "0123456789012345678901234567890123456789"}"#;
+    let obj = Obj::from_list(vec![serde_json::from_str(json).unwrap(), Obj::from_bytes(vec![1, 2, 3])]);
+    assert_eq!(serde_utils::serialized_size_hint(&obj), rmp_serde::to_vec(&obj).unwrap().len());
+    assert_eq!(serde_utils::serialized_size_hint(&vec![0u8; 20]), 3 + 20);
+    assert_eq!(serde_utils::serialized_size_hint(&Some(u64::MAX)), 9);
+}
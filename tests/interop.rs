@@ -0,0 +1,224 @@
+extern crate serde_utils;
+#[cfg(feature = "bson")] extern crate bson;
+#[cfg(feature = "ciborium")] extern crate ciborium;
+#[cfg(feature = "ron")] extern crate ron;
+#[cfg(feature = "pickle")] extern crate serde_pickle;
+
+#[cfg(feature = "bson")]
+mod bson_interop {
+    use std::convert::TryFrom;
+
+    use bson::{Bson, Document, doc};
+    use bson::oid::ObjectId;
+    use bson::spec::BinarySubtype;
+    use bson::Binary;
+    use serde_utils::{Obj, ObjKind};
+
+    fn document() -> Document {
+        doc! {
+            "_id": ObjectId::parse_str("5f4e3d2c1b0a998877665544").unwrap(),
+            "name": "sensor",
+            "count": 42i32,
+            "total": 5_000_000_000i64,
+            "offset": -3i32,
+            "ratio": 0.5,
+            "created": bson::DateTime::from_millis(1_600_000_000_000),
+            "raw": Binary { subtype: BinarySubtype::Generic, bytes: vec![1, 2, 3] },
+            "uuid": Binary { subtype: BinarySubtype::Uuid, bytes: vec![0; 16] },
+            "tags": ["a", "b"],
+            "nested": { "ok": true, "none": Bson::Null }
+        }
+    }
+
+    #[test]
+    fn test_bson_to_obj() {
+        let obj = Obj::from(document());
+        assert_eq!(obj.pointer("name").as_deref(), Some(&Obj::Str("sensor".to_string())));
+        assert_eq!(obj.pointer("count").as_deref(), Some(&Obj::Unsigned(42)));
+        assert_eq!(obj.pointer("offset").as_deref(), Some(&Obj::Signed(-3)));
+        assert_eq!(obj.pointer("raw").as_deref(), Some(&Obj::from_bytes(vec![1, 2, 3])));
+        assert_eq!(obj.pointer("_id/$oid").as_deref(), Some(&Obj::Str("5f4e3d2c1b0a998877665544".to_string())));
+        assert_eq!(obj.pointer("created/$date").as_deref(), Some(&Obj::Str("2020-09-13T12:26:40Z".to_string())));
+        assert_eq!(obj.pointer("uuid/$binary/subType").as_deref(), Some(&Obj::Str("04".to_string())));
+        assert_eq!(obj.pointer("nested/ok").as_deref(), Some(&Obj::Bool(true)));
+    }
+
+    #[test]
+    fn test_bson_roundtrip() {
+        let doc = document();
+        let back = Document::try_from(Obj::from(doc.clone())).unwrap();
+        assert_eq!(back.len(), doc.len());
+        for (key, val) in &doc {
+            assert_eq!(back.get(key), Some(val), "{}", key);
+        }
+        assert_eq!(Bson::try_from(Obj::Unsigned(7)).unwrap(), Bson::Int32(7));
+        assert_eq!(Bson::try_from(Obj::Signed(-5_000_000_000)).unwrap(), Bson::Int64(-5_000_000_000));
+        let update = Obj::from(doc! { "$set": { "name": "other" } });
+        assert_eq!(Document::try_from(update).unwrap(), doc! { "$set": { "name": "other" } });
+    }
+
+    #[test]
+    fn test_bson_errors() {
+        let obj = Obj::from(doc! { "list": [1i32, 2i32] });
+        let mut map = obj.into_map().unwrap();
+        map.insert(Obj::Str("big".to_string()), Obj::List(vec![Obj::Null, Obj::Unsigned(u64::MAX)]));
+        let err = Bson::try_from(Obj::from_map(map)).unwrap_err();
+        assert_eq!(err.path().to_string(), "big/1");
+        assert_eq!(err.found(), Some(ObjKind::Unsigned));
+        let mut map = std::collections::BTreeMap::new();
+        map.insert(Obj::Unsigned(1), Obj::Null);
+        let err = Document::try_from(Obj::from_map(map)).unwrap_err();
+        assert!(err.message().contains("not a string"), "{}", err);
+        assert!(Document::try_from(Obj::Unsigned(1)).is_err());
+        let invalid = Obj::from(doc! { "id": { "$oid": "xyz" } });
+        assert_eq!(Document::try_from(invalid).unwrap_err().path().to_string(), "id");
+    }
+}
+
+#[cfg(feature = "ciborium")]
+mod ciborium_interop {
+    use std::convert::TryFrom;
+
+    use ciborium::value::{Value, Integer};
+    use serde_utils::Obj;
+
+    fn value() -> Value {
+        Value::Map(vec![
+            (Value::Text("bytes".to_string()), Value::Bytes(vec![0, 255])),
+            (Value::Integer(7.into()), Value::Array(vec![Value::Integer((-3).into()), Value::Float(1.5)])),
+            (Value::Text("time".to_string()), Value::Tag(1, Box::new(Value::Integer(1_600_000_000.into())))),
+            (Value::Text("min".to_string()), Value::Integer(Integer::try_from(-(1i128 << 64)).unwrap())),
+            (Value::Text("none".to_string()), Value::Null)
+        ])
+    }
+
+    #[test]
+    fn test_ciborium_to_obj() {
+        let obj = Obj::from(value());
+        assert_eq!(obj.pointer("bytes").as_deref(), Some(&Obj::from_bytes(vec![0, 255])));
+        assert_eq!(obj.pointer("7/0").as_deref(), Some(&Obj::Signed(-3)));
+        assert_eq!(obj.pointer("time/$tag").as_deref(), Some(&Obj::Unsigned(1)));
+        assert_eq!(obj.pointer("time/$value").as_deref(), Some(&Obj::Unsigned(1_600_000_000)));
+        #[cfg(not(feature = "num-bigint"))]
+        assert_eq!(obj.pointer("min/$tag").as_deref(), Some(&Obj::Unsigned(3)));
+    }
+
+    #[test]
+    fn test_ciborium_roundtrip() {
+        let back = Value::from(Obj::from(value()));
+        let mut expected = value().into_map().unwrap();
+        let mut entries = back.into_map().unwrap();
+        expected.sort_by_key(|(key, _)| format!("{:?}", key));
+        entries.sort_by_key(|(key, _)| format!("{:?}", key));
+        assert_eq!(entries, expected);
+        let big = Value::Tag(2, Box::new(Value::Bytes(vec![1; 20])));
+        assert_eq!(Value::from(Obj::from(big.clone())), big);
+        let small = Value::Tag(2, Box::new(Value::Bytes(vec![1, 0])));
+        assert_eq!(Value::from(Obj::from(small)), Value::Integer(256.into()));
+    }
+}
+
+#[cfg(feature = "ron")]
+mod ron_interop {
+    use std::convert::TryFrom;
+
+    use ron::value::Value;
+    use serde_utils::{Obj, ObjKind};
+
+    const CONFIG: &str = "(
+        name: \"sensor\",
+        mode: 'a',
+        limits: (cpu: 2, offset: -3, ratio: 0.5),
+        hosts: [\"a\", \"b\"],
+        backup: Some(true),
+        proxy: None,
+    )";
+
+    #[test]
+    fn test_ron_to_obj() {
+        let obj = Obj::from(ron::from_str::<Value>(CONFIG).unwrap());
+        assert_eq!(obj.pointer("name").as_deref(), Some(&Obj::Str("sensor".to_string())));
+        assert_eq!(obj.pointer("mode").as_deref(), Some(&Obj::Str("a".to_string())));
+        assert_eq!(obj.pointer("limits/cpu").as_deref(), Some(&Obj::Unsigned(2)));
+        assert_eq!(obj.pointer("limits/offset").as_deref(), Some(&Obj::Signed(-3)));
+        assert_eq!(obj.pointer("limits/ratio").as_deref(), Some(&Obj::Float(0.5)));
+        assert_eq!(obj.pointer("hosts/1").as_deref(), Some(&Obj::Str("b".to_string())));
+        assert_eq!(obj.pointer("backup").as_deref(), Some(&Obj::Bool(true)));
+        assert_eq!(obj.pointer("proxy").as_deref(), Some(&Obj::Null));
+    }
+
+    #[test]
+    fn test_ron_roundtrip() {
+        let obj = Obj::from(ron::from_str::<Value>(CONFIG).unwrap());
+        let val = Value::try_from(obj.clone()).unwrap();
+        assert_eq!(Obj::from(ron::from_str::<Value>(&ron::to_string(&val).unwrap()).unwrap()), obj);
+        assert_eq!(Value::try_from(Obj::Null).unwrap(), Value::Unit);
+        assert_eq!(Value::try_from(Obj::from_bytes(vec![1, 2])).unwrap(), ron::from_str::<Value>("[1, 2]").unwrap());
+    }
+
+    #[test]
+    fn test_ron_errors() {
+        let mut map = Obj::from(ron::from_str::<Value>(CONFIG).unwrap()).into_map().unwrap();
+        map.insert(Obj::Str("big".to_string()), Obj::List(vec![Obj::Null, Obj::Unsigned(u64::MAX)]));
+        let err = Value::try_from(Obj::from_map(map)).unwrap_err();
+        assert_eq!(err.path().to_string(), "big/1");
+        assert_eq!(err.found(), Some(ObjKind::Unsigned));
+    }
+}
+
+#[cfg(feature = "pickle")]
+mod pickle_interop {
+    use std::collections::{BTreeMap, BTreeSet};
+    use std::convert::TryFrom;
+
+    use serde_pickle::{Value, HashableValue};
+    use serde_utils::{Obj, ObjKind, ObjPath, PathSegment};
+
+    fn value() -> Value {
+        let mut dict = BTreeMap::new();
+        dict.insert(HashableValue::String("name".to_string()), Value::String("sensor".to_string()));
+        dict.insert(HashableValue::String("data".to_string()), Value::Bytes(vec![0, 255]));
+        dict.insert(HashableValue::I64(-3), Value::Tuple(vec![Value::I64(1), Value::F64(0.5), Value::None]));
+        dict.insert(HashableValue::Tuple(vec![HashableValue::I64(1), HashableValue::I64(2)]), Value::Bool(true));
+        dict.insert(HashableValue::String("tags".to_string()),
+            Value::Set(vec![HashableValue::String("b".to_string()), HashableValue::String("a".to_string())].into_iter().collect::<BTreeSet<_>>()));
+        dict.insert(HashableValue::String("big".to_string()), Value::Int(u64::MAX.into()));
+        Value::Dict(dict)
+    }
+
+    #[test]
+    fn test_pickle_to_obj() {
+        let obj = Obj::from(value());
+        assert_eq!(obj.pointer("name").as_deref(), Some(&Obj::Str("sensor".to_string())));
+        assert_eq!(obj.pointer("data").as_deref(), Some(&Obj::from_bytes(vec![0, 255])));
+        assert_eq!(obj.pointer("tags/0").as_deref(), Some(&Obj::Str("a".to_string())));
+        assert_eq!(obj.pointer("big").as_deref(), Some(&Obj::Unsigned(u64::MAX)));
+        let key = |key| ObjPath::from(vec![PathSegment::Key(key)]);
+        assert_eq!(obj.get_path(&key(Obj::Signed(-3))).as_deref(), Some(&Obj::List(vec![Obj::Unsigned(1), Obj::Float(0.5), Obj::Null])));
+        assert_eq!(obj.get_path(&key(Obj::List(vec![Obj::Unsigned(1), Obj::Unsigned(2)]))).as_deref(), Some(&Obj::Bool(true)));
+    }
+
+    #[test]
+    fn test_pickle_roundtrip() {
+        let obj = Obj::from(value());
+        let val = Value::try_from(obj.clone()).unwrap();
+        let bytes = serde_pickle::value_to_vec(&val, Default::default()).unwrap();
+        assert_eq!(Obj::from(serde_pickle::value_from_slice(&bytes, Default::default()).unwrap()), obj);
+        assert_eq!(Value::try_from(Obj::Unsigned(u64::MAX)).unwrap(), Value::Int(u64::MAX.into()));
+        assert_eq!(HashableValue::try_from(Obj::List(vec![Obj::Null])).unwrap(), HashableValue::Tuple(vec![HashableValue::None]));
+    }
+
+    #[test]
+    fn test_pickle_errors() {
+        let mut inner = BTreeMap::new();
+        inner.insert(Obj::Str("a".to_string()), Obj::Null);
+        let mut nested = BTreeMap::new();
+        nested.insert(Obj::List(vec![Obj::from_map(inner)]), Obj::Null);
+        let mut map = BTreeMap::new();
+        map.insert(Obj::Str("nested".to_string()), Obj::from_map(nested));
+        let err = Value::try_from(Obj::from_map(map)).unwrap_err();
+        assert_eq!(err.path().to_string(), "nested");
+        assert_eq!(err.found(), Some(ObjKind::Map));
+        assert!(HashableValue::try_from(Obj::from_map(BTreeMap::new())).is_err());
+    }
+}
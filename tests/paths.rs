@@ -0,0 +1,74 @@
+// The generated code must not depend on the imports of the caller, so this file neither imports
+// serde nor uses the crate under its own name and shadows `Result`.
+#[macro_use] extern crate serde_utils as utils;
+#[macro_use] extern crate serde_utils_derive;
+
+#[allow(dead_code)]
+type Result<T> = std::result::Result<T, String>;
+
+#[derive(Default, Debug, PartialEq)]
+struct Point {
+    x: i64,
+    y: i64,
+    label: Option<String>,
+}
+serde_impl!(Point(String) {
+    x: i64 => "x",
+    y: i64 => "y",
+    label: Option<String> => "label"
+});
+
+#[derive(Debug, PartialEq)]
+struct Pair {
+    first: u64,
+    second: bool,
+}
+serde_impl!(Pair {
+    first: u64,
+    second: bool
+});
+
+#[derive(Debug, PartialEq)]
+enum Color {
+    Red,
+    Green
+}
+serde_impl!(Color(u64) {
+    Red => 1,
+    Green => 2
+});
+
+#[derive(Debug, PartialEq)]
+enum Shape {
+    Circle(f64),
+    Square(f64)
+}
+serde_impl!(Shape(String) {
+    Circle(f64) => "circle",
+    Square(f64) => "square"
+});
+
+// The derive macro refers to the renamed crate via the crate option
+#[derive(Debug, PartialEq, SerdeImpl)]
+#[serde_utils(crate = "utils")]
+enum Derived {
+    #[serde_utils(key = 1)]
+    A(u64),
+    #[serde_utils(key = 2)]
+    B(String)
+}
+
+macro_rules! roundtrip {
+    ($ty:ty, $val:expr) => { utils::from_obj::<$ty>(utils::to_obj(&$val).unwrap()).unwrap() };
+}
+
+#[test]
+fn test_generated_paths() {
+    let point = Point { x: 1, y: -2, label: Some("a".to_string()) };
+    assert_eq!(roundtrip!(Point, point), point);
+    let pair = Pair { first: 3, second: true };
+    assert_eq!(roundtrip!(Pair, pair), pair);
+    assert_eq!(roundtrip!(Color, Color::Green), Color::Green);
+    assert_eq!(roundtrip!(Shape, Shape::Square(2.0)), Shape::Square(2.0));
+    assert_eq!(roundtrip!(Derived, Derived::B("b".to_string())), Derived::B("b".to_string()));
+}
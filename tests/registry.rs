@@ -0,0 +1,99 @@
+extern crate serde_json;
+#[macro_use] extern crate serde_utils;
+
+use std::any::Any;
+use std::fmt::Debug;
+
+use serde_utils::from_obj;
+use serde_utils::registry::{Registry, Registered, Polymorphic};
+
+trait Message: Any + Debug {
+    fn size(&self) -> usize;
+}
+
+impl Registered for dyn Message {
+    fn registry() -> &'static Registry<Self> {
+        static REGISTRY: Registry<dyn Message> = Registry::new();
+        &REGISTRY
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct Ping {
+    seq: u64
+}
+serde_impl!(Ping(String) {
+    seq: u64 => "seq"
+});
+
+impl Message for Ping {
+    fn size(&self) -> usize {
+        8
+    }
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct Text {
+    text: String
+}
+serde_impl!(Text(String) {
+    text: String => "text"
+});
+
+impl Message for Text {
+    fn size(&self) -> usize {
+        self.text.len()
+    }
+}
+
+#[derive(Debug)]
+struct Envelope {
+    sender: String,
+    message: Polymorphic<dyn Message>
+}
+serde_impl!(Envelope(String!) {
+    sender: String => "sender",
+    message: Polymorphic<dyn Message> => "message"
+});
+
+#[derive(Debug, PartialEq)]
+enum Closed {
+    Ping(Ping),
+    Text(Text)
+}
+serde_impl!(Closed(String) {
+    Ping(Ping) => "ping",
+    Text(Text) => "text"
+});
+
+#[test]
+fn test_registry() {
+    let registry = <dyn Message>::registry();
+    registry.register::<Ping>("ping", |obj| Ok(Box::new(from_obj::<Ping>(obj)?)));
+    registry.register::<Text>("text", |obj| Ok(Box::new(from_obj::<Text>(obj)?)));
+    assert_eq!(registry.keys(), vec!["ping", "text"]);
+    let envelope = Envelope { sender: "a".to_string(), message: Polymorphic(Box::new(Text { text: "hello".to_string() })) };
+    let json = serde_json::to_string(&envelope).unwrap();
+    assert_eq!(json, r#"{"sender":"a","message":["text",{"text":"hello"}]}"#);
+    let read: Envelope = serde_json::from_str(&json).unwrap();
+    assert_eq!(read.sender, "a");
+    assert_eq!(read.message.size(), 5);
+    // The encoding is the same as the one of enums
+    let ping: Polymorphic<dyn Message> = serde_json::from_str(&serde_json::to_string(&Closed::Ping(Ping { seq: 3 })).unwrap()).unwrap();
+    assert_eq!(ping.as_any().downcast_ref::<Ping>(), Some(&Ping { seq: 3 }));
+    assert_eq!(serde_json::from_str::<Closed>(&serde_json::to_string(&ping).unwrap()).unwrap(), Closed::Ping(Ping { seq: 3 }));
+    let err = serde_json::from_str::<Polymorphic<dyn Message>>(r#"["pong",{}]"#).unwrap_err().to_string();
+    assert!(err.contains(r#"unknown key "pong""#), "{}", err);
+}
+
+#[test]
+#[should_panic(expected = "already registered")]
+fn test_registry_duplicate() {
+    let registry: Registry<dyn Message> = Registry::new();
+    registry.register::<Ping>("ping", |obj| Ok(Box::new(from_obj::<Ping>(obj)?)));
+    registry.register::<Ping>("other", |obj| Ok(Box::new(from_obj::<Ping>(obj)?)));
+}
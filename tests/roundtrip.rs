@@ -0,0 +1,93 @@
+#![cfg(feature = "test-util")]
+extern crate arbitrary;
+#[macro_use] extern crate serde_utils;
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use arbitrary::Arbitrary;
+use serde_utils::testing::assert_arbitrary_roundtrip;
+
+#[derive(Default, Arbitrary, PartialEq, Debug)]
+struct Message {
+    id: u64,
+    text: String,
+    tags: Vec<String>,
+    attrs: BTreeMap<String, i32>,
+    reply_to: Option<u64>
+}
+
+#[derive(Arbitrary, PartialEq, Debug)]
+enum Payload {
+    Empty(()),
+    Text(String),
+    Message(Message)
+}
+
+#[derive(Arbitrary, PartialEq, Debug)]
+enum Kind {
+    A, B, C
+}
+
+// Each of these types gets a generated test named after it
+serde_impl!(roundtrip; schema; Message(String) {
+    id: u64 => "id",
+    text: String => "text",
+    tags: Vec<String> => "tags",
+    attrs: BTreeMap<String, i32> => "attrs",
+    reply_to: Option<u64> => "reply_to"
+});
+
+serde_impl!(roundtrip; key u8;
+    Payload(_) {
+        Empty(()) => 0,
+        Text(String) => 1,
+        Message(Message) => 2
+    };
+    Kind(_) {
+        A => 0,
+        B => 1,
+        C => 2
+    }
+);
+
+mod nested {
+    use arbitrary::Arbitrary;
+
+    #[derive(Default, Arbitrary, PartialEq, Debug)]
+    pub struct Point {
+        pub x: i64,
+        pub y: i64
+    }
+}
+
+serde_impl!(size; roundtrip; nested::Point(u8) {
+    x: i64 => 0,
+    y: i64 => 1
+});
+
+// Types with a lifetime get no test, they do not implement `DeserializeOwned`
+#[derive(Default)]
+struct Borrowed<'a> {
+    name: Cow<'a, str>
+}
+serde_impl!(roundtrip; Borrowed<'a>(String) {
+    name: Cow<'a, str> => "name"
+});
+
+// Types without the prefix get no test, so they do not need `Arbitrary`
+#[derive(Default, PartialEq, Debug)]
+struct Legacy {
+    data: Vec<u8>
+}
+serde_impl!(schema; Legacy(u8) {
+    data: Vec<u8> => 0
+});
+
+#[test]
+fn test_arbitrary_roundtrip() {
+    assert_arbitrary_roundtrip::<Vec<Message>>(64);
+    let borrowed = Borrowed { name: Cow::Borrowed("a") };
+    assert_eq!(borrowed.name, "a");
+    assert_eq!(Legacy::default(), Legacy { data: vec![] });
+}
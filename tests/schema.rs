@@ -0,0 +1,258 @@
+extern crate serde;
+#[macro_use] extern crate serde_json;
+#[macro_use] extern crate serde_utils;
+
+use serde_json::Value;
+use serde_utils::JsonSchema;
+
+fn schema<T: JsonSchema>() -> Value {
+    serde_json::to_value(T::json_schema()).unwrap()
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct Item {
+    name: String,
+    count: Option<u8>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Kind {
+    A,
+    B
+}
+
+#[derive(Debug, PartialEq)]
+enum Payload {
+    Text(String),
+    Num(i64)
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct Pair {
+    first: u64,
+    second: bool,
+}
+
+serde_impl!(schema; key u8;
+    Item(_!) strict {
+        name: String => 0,
+        count: Option<u8> => 1
+    };
+    Kind(_) {
+        A => 1,
+        B => 2
+    };
+    Payload(_) {
+        Text(String) => 0,
+        Num(i64) => 1
+    };
+    Pair {
+        first: u64,
+        second: bool
+    }
+);
+
+#[derive(Default, Debug, PartialEq)]
+struct Config {
+    name: String,
+    items: Vec<Item>,
+    cpu: u32,
+    mem: u64,
+}
+serde_impl!(Config(String) {
+    name: String => "name",
+    items: Vec<Item> => "items"
+} nested {
+    "limits" => {
+        cpu: u32 => "cpu",
+        mem: u64 => "mem"
+    }
+});
+serde_impl_schema!(Config(String) {
+    name: String => "name",
+    items: Vec<Item> => "items"
+} nested {
+    "limits" => {
+        cpu: u32 => "cpu",
+        mem: u64 => "mem"
+    }
+});
+
+#[derive(Debug, PartialEq)]
+enum Shape {
+    Circle(f64),
+    Label(Option<String>)
+}
+serde_impl!(schema; Shape(String) map {
+    Circle(f64),
+    Label(Option<String>)
+});
+
+#[derive(Default, Debug, PartialEq)]
+struct Versioned {
+    value: char,
+}
+serde_impl!(schema; Versioned(String) version("v" => 2) {
+    value: char => "value"
+} migrate {});
+
+#[test]
+fn test_struct_schema() {
+    assert_eq!(schema::<Item>(), json!({
+        "type": "object",
+        "title": "Item",
+        "properties": {
+            "0": {"type": "string"},
+            "1": {"anyOf": [{"type": "integer", "minimum": 0, "maximum": 255}, {"type": "null"}]}
+        },
+        "required": ["0"],
+        "additionalProperties": false
+    }));
+    assert_eq!(schema::<Pair>(), json!({
+        "type": "array",
+        "title": "Pair",
+        "prefixItems": [{"type": "integer", "minimum": 0}, {"type": "boolean"}],
+        "minItems": 2,
+        "maxItems": 2
+    }));
+    assert_eq!(schema::<Versioned>(), json!({
+        "type": "object",
+        "title": "Versioned",
+        "properties": {
+            "v": {"const": 2},
+            "value": {"type": "string", "minLength": 1, "maxLength": 1}
+        },
+        "required": ["v"]
+    }));
+}
+
+#[test]
+fn test_nested_schema() {
+    let schema = schema::<Config>();
+    assert_eq!(schema["properties"]["items"]["items"]["title"], "Item");
+    assert_eq!(schema["properties"]["limits"], json!({
+        "type": "object",
+        "properties": {
+            "cpu": {"type": "integer", "minimum": 0, "maximum": 4294967295u64},
+            "mem": {"type": "integer", "minimum": 0}
+        }
+    }));
+    assert!(schema.get("required").is_none());
+}
+
+#[test]
+fn test_enum_schema() {
+    assert_eq!(schema::<Kind>(), json!({"title": "Kind", "enum": [1, 2]}));
+    assert_eq!(schema::<Payload>()["oneOf"][1], json!({
+        "type": "array",
+        "prefixItems": [{"const": 1}, {"type": "integer"}],
+        "minItems": 2,
+        "maxItems": 2
+    }));
+    assert_eq!(schema::<Shape>()["oneOf"][0], json!({
+        "type": "object",
+        "properties": {"Circle": {"type": "number"}},
+        "required": ["Circle"],
+        "additionalProperties": false
+    }));
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct Node {
+    name: String,
+    children: Vec<Node>,
+    next: Option<Box<Node>>,
+}
+serde_impl!(schema; Node(String) {
+    name: String => "name",
+    children: Vec<Node> => "children",
+    next: Option<Box<Node>> => "next"
+});
+
+#[derive(Default, Debug, PartialEq)]
+struct Tree {
+    root: Node,
+}
+serde_impl!(schema; Tree(String) {
+    root: Node => "root"
+});
+
+#[test]
+fn test_recursive_schema() {
+    let schema = schema::<Node>();
+    assert_eq!(schema["$anchor"], "Node");
+    assert_eq!(schema["properties"]["children"], json!({"type": "array", "items": {"$ref": "#Node"}}));
+    assert_eq!(schema["properties"]["next"]["anyOf"][0], json!({"$ref": "#Node"}));
+    let schema = self::schema::<Tree>();
+    assert!(schema.get("$anchor").is_none());
+    assert_eq!(schema["properties"]["root"]["$anchor"], "Node");
+    assert_eq!(schema["properties"]["root"]["properties"]["next"]["anyOf"][0], json!({"$ref": "#Node"}));
+    assert!(self::schema::<Item>().get("$anchor").is_none());
+}
+
+#[derive(Default)]
+struct Tagged {
+    value: u64,
+    tag: std::marker::PhantomData<Node>,
+}
+serde_impl!(schema; Tagged(String!) {
+    value: u64 => "value",
+    tag: std::marker::PhantomData<Node> => "tag"
+});
+
+struct TaggedTuple {
+    value: u64,
+    tag: (),
+}
+serde_impl!(schema; TaggedTuple {
+    value: u64,
+    tag: ()
+});
+
+#[test]
+fn test_skipped_field_schema() {
+    let schema = schema::<Tagged>();
+    assert!(schema["properties"].get("tag").is_none());
+    assert_eq!(schema["required"], json!(["value"]));
+    let schema = self::schema::<TaggedTuple>();
+    assert_eq!(schema["maxItems"], 1);
+}
+
+struct Signed {
+    hash: [u8; 32],
+    signature: [u8; 64],
+}
+serde_impl!(schema; Signed {
+    hash: [u8; 32],
+    signature: [u8; 64]
+});
+
+#[test]
+fn test_array_schema() {
+    let schema = schema::<Signed>();
+    assert_eq!(schema["prefixItems"][1]["type"], "array");
+    assert_eq!(schema["prefixItems"][1]["minItems"], 64);
+    assert_eq!(schema["prefixItems"][1]["maxItems"], 64);
+    assert_eq!(schema["prefixItems"][0]["items"], self::schema::<u8>());
+}
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Stop(u64),
+    Restart(bool),
+    Pause(Option<u64>)
+}
+serde_impl!(schema; Command(u8) {
+    Stop(u64) => 1,
+    Restart(bool = false) => 2,
+    Pause(Option<u64>) => 3
+});
+
+#[test]
+fn test_default_payload_schema() {
+    let schema = schema::<Command>();
+    assert_eq!(schema["oneOf"][0]["minItems"], 2);
+    assert_eq!(schema["oneOf"][1]["minItems"], 1);
+    assert_eq!(schema["oneOf"][1]["maxItems"], 2);
+    assert_eq!(schema["oneOf"][2]["minItems"], 1);
+}
@@ -1,10 +1,19 @@
 extern crate serde;
 extern crate serde_bytes;
 extern crate rmp_serde;
+extern crate serde_json;
+extern crate serde_cbor;
+extern crate bincode;
 #[macro_use] extern crate serde_utils;
 
 use std::fmt::Debug;
 use std::io::Cursor;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::num::NonZeroU64;
+
+use serde_bytes::ByteBuf;
+use serde_utils::IgnoreCase;
 
 fn to_bytes<T: serde::Serialize + Debug>(obj: &T) -> Vec<u8> {
     let mut serialized = Vec::new();
@@ -21,7 +30,7 @@ fn from_bytes<'a, T: serde::Deserialize<'a> + Debug>(bytes: &[u8]) -> T {
     T::deserialize(&mut reader).unwrap()
 }
 
-#[allow(unknown_lints,needless_pass_by_value)]
+#[allow(clippy::needless_pass_by_value)]
 fn test_obj<'a, T: serde::Serialize + serde::Deserialize<'a> + PartialEq + Debug>(obj: T) {
     let serialized = to_bytes(&obj);
     let deserialized = from_bytes(&serialized);
@@ -82,6 +91,19 @@ fn test_int_map_extended() {
     assert_eq!(IntMapTestReduced{test: "test".to_string(), option: Some(true)}, obj);
 }
 
+#[test]
+fn test_int_map_extended_nested() {
+    use serde_utils::Obj;
+    let mut nested = BTreeMap::new();
+    nested.insert(Obj::Str("list".to_string()), Obj::List(vec![Obj::Null, Obj::Float(1.5), Obj::Str("x".to_string())]));
+    let mut map = BTreeMap::new();
+    map.insert(Obj::Unsigned(0), Obj::Str("test".to_string()));
+    map.insert(Obj::Unsigned(1), Obj::Map(nested.into()));
+    map.insert(Obj::Unsigned(2), Obj::Bool(true));
+    let obj = from_bytes(&to_bytes(&Obj::Map(map.into())));
+    assert_eq!(IntMapTestReduced{test: "test".to_string(), option: Some(true)}, obj);
+}
+
 #[test]
 fn test_compressed_int_map() {
     test_obj(CompressedIntMapTest{test: "".to_string(), num: 0, option: None});
@@ -174,3 +196,1286 @@ fn test_tuple_extended() {
     let obj = from_bytes(&bytes);
     assert_eq!(TupleTestReduced{test: "test".to_string(), option: Some(true)}, obj);
 }
+
+#[derive(Default, Debug, PartialEq)]
+struct DeOnlyTest {
+    test: String,
+    num: u64,
+}
+serde_impl_de!(DeOnlyTest(String) {
+    test: String => "test",
+    num: u64 => "num"
+});
+
+impl serde::Serialize for DeOnlyTest {
+    fn serialize<S: serde::Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        StrMapTestReduced{test: self.test.clone(), option: Some(self.num > 0)}.serialize(ser)
+    }
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct SerOnlyTest {
+    test: String,
+    num: u64,
+}
+serde_impl_ser!(SerOnlyTest(String) {
+    test: String => "test",
+    num: u64 => "num"
+});
+
+#[test]
+fn test_de_only() {
+    let obj: DeOnlyTest = from_bytes(&to_bytes(&DeOnlyTest{test: "test".to_string(), num: 56}));
+    assert_eq!(DeOnlyTest{test: "test".to_string(), num: 0}, obj);
+}
+
+#[test]
+fn test_ser_only() {
+    let obj: StrMapTest = from_bytes(&to_bytes(&SerOnlyTest{test: "test".to_string(), num: 56}));
+    assert_eq!(StrMapTest{test: "test".to_string(), num: 56, option: None}, obj);
+}
+
+#[test]
+fn test_error_messages() {
+    let cursor = Cursor::new(to_bytes(&StrMapTestReduced{test: "test".to_string(), option: Some(true)}));
+    let mut reader = rmp_serde::Deserializer::new(cursor);
+    let msg = format!("{:?}", <IntMapTest as serde::Deserialize>::deserialize(&mut reader).unwrap_err());
+    assert!(msg.contains("IntMapTest"), "{}", msg);
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct BinMapTest {
+    test: String,
+    num: u64,
+}
+serde_impl!(BinMapTest(ByteBuf) {
+    test: String => b"test",
+    num: u64 => b"n"
+});
+
+#[test]
+fn test_bin_map() {
+    test_obj(BinMapTest{test: "test".to_string(), num: 56});
+    let obj: serde_utils::Obj = from_bytes(&to_bytes(&BinMapTest{test: "test".to_string(), num: 56}));
+    let mut map = BTreeMap::new();
+    map.insert(serde_utils::Obj::Bin(ByteBuf::from(b"test".to_vec())), serde_utils::Obj::Str("test".to_string()));
+    map.insert(serde_utils::Obj::Bin(ByteBuf::from(b"n".to_vec())), serde_utils::Obj::Unsigned(56));
+    assert_eq!(serde_utils::Obj::Map(map.into()), obj);
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct U8MapTest {
+    test: String,
+    num: u64,
+}
+serde_impl!(U8MapTest(u8) {
+    test: String => 0,
+    num: u64 => 255
+});
+
+#[test]
+fn test_u8_map() {
+    test_obj(U8MapTest{test: "test".to_string(), num: 56});
+    let obj: BTreeMap<u8, serde_utils::Obj> = from_bytes(&to_bytes(&U8MapTest{test: "test".to_string(), num: 56}));
+    assert_eq!(obj.keys().cloned().collect::<Vec<_>>(), vec![0, 255]);
+}
+
+#[derive(Debug, PartialEq)]
+enum FieldId {
+    Test, Num, Option
+}
+serde_impl!(FieldId(u8) {
+    Test => 0,
+    Num => 1,
+    Option => 2
+});
+
+#[derive(Default, Debug, PartialEq)]
+struct EnumKeyMapTest {
+    test: String,
+    num: u64,
+    option: Option<bool>,
+}
+serde_impl!(EnumKeyMapTest(FieldId) {
+    test: String => FieldId::Test,
+    num: u64 => FieldId::Num,
+    option: Option<bool> => FieldId::Option
+});
+
+#[test]
+fn test_enum_key_map() {
+    test_obj(EnumKeyMapTest{test: "".to_string(), num: 0, option: None});
+    test_obj(EnumKeyMapTest{test: "test".to_string(), num: 56, option: Some(true)});
+    let bytes = to_bytes(&EnumKeyMapTest{test: "test".to_string(), num: 56, option: Some(true)});
+    assert_eq!(IntMapTest{test: "test".to_string(), num: 56, option: Some(true)}, from_bytes(&bytes));
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct VersionedTestV1 {
+    name: String,
+    size_kb: u64,
+}
+serde_impl!(VersionedTestV1(String) {
+    name: String => "name",
+    size_kb: u64 => "size_kb"
+});
+
+#[derive(Default, Debug, PartialEq)]
+struct VersionedTest {
+    name: String,
+    size: u64,
+}
+serde_impl!(VersionedTest(String) version("version" => 2) {
+    name: String => "name",
+    size: u64 => "size"
+} migrate {
+    1 => migrate_v1,
+    0 => |_| Err("version 0 is not supported")
+});
+
+fn migrate_v1(obj: serde_utils::Obj) -> Result<VersionedTest, serde::de::value::Error> {
+    let old: VersionedTestV1 = serde_utils::from_obj(obj)?;
+    Ok(VersionedTest{name: old.name, size: old.size_kb * 1024})
+}
+
+#[test]
+fn test_versioned() {
+    test_obj(VersionedTest{name: "test".to_string(), size: 56});
+    let obj: BTreeMap<String, serde_utils::Obj> = from_bytes(&to_bytes(&VersionedTest::default()));
+    assert_eq!(obj.get("version"), Some(&serde_utils::Obj::Unsigned(2)));
+}
+
+#[test]
+fn test_versioned_migration() {
+    let mut old = BTreeMap::new();
+    old.insert("version", serde_utils::Obj::Unsigned(1));
+    old.insert("name", serde_utils::Obj::Str("test".to_string()));
+    old.insert("size_kb", serde_utils::Obj::Unsigned(2));
+    assert_eq!(VersionedTest{name: "test".to_string(), size: 2048}, from_bytes(&to_bytes(&old)));
+}
+
+#[test]
+fn test_versioned_unsupported() {
+    let bytes = to_bytes(&VersionedTestV1{name: "test".to_string(), size_kb: 2});
+    let cursor = Cursor::new(&bytes);
+    let mut reader = rmp_serde::Deserializer::new(cursor);
+    let msg = format!("{:?}", <VersionedTest as serde::Deserialize>::deserialize(&mut reader).unwrap_err());
+    assert!(msg.contains("version 0 is not supported"), "{}", msg);
+    let mut newer = BTreeMap::new();
+    newer.insert("version", 3);
+    let cursor = Cursor::new(to_bytes(&newer));
+    let mut reader = rmp_serde::Deserializer::new(cursor);
+    let msg = format!("{:?}", <VersionedTest as serde::Deserialize>::deserialize(&mut reader).unwrap_err());
+    assert!(msg.contains("unsupported version 3"), "{}", msg);
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct LenientTupleTest {
+    test: String,
+    num: u64,
+    option: Option<bool>,
+}
+serde_impl!(LenientTupleTest {
+    test: String,
+    num: u64,
+    option: Option<bool>,
+    ..
+});
+
+#[derive(Default, Debug, PartialEq)]
+struct LenientTupleTestReduced {
+    test: String,
+    num: u64,
+}
+serde_impl!(LenientTupleTestReduced {
+    test: String,
+    num: u64,
+    ..
+});
+
+#[test]
+fn test_lenient_tuple() {
+    test_obj(LenientTupleTest{test: "".to_string(), num: 0, option: None});
+    test_obj(LenientTupleTest{test: "test".to_string(), num: 56, option: Some(true)});
+    assert_eq!(to_bytes(&LenientTupleTest::default()), to_bytes(&TupleTest::default()));
+    let obj = LenientTupleTest{test: "test".to_string(), num: 56, option: Some(true)};
+    let bytes = bincode::serialize(&obj).unwrap();
+    assert_eq!(bincode::deserialize::<LenientTupleTest>(&bytes).unwrap(), obj);
+    let bytes = bincode::serialize(&LenientTupleTestReduced{test: "test".to_string(), num: 56}).unwrap();
+    assert_eq!(bincode::deserialize::<LenientTupleTest>(&bytes).unwrap(), LenientTupleTest{test: "test".to_string(), num: 56, option: None});
+}
+
+#[test]
+fn test_lenient_tuple_reduced() {
+    let bytes = to_bytes(&LenientTupleTestReduced{test: "test".to_string(), num: 56});
+    let obj = from_bytes(&bytes);
+    assert_eq!(LenientTupleTest{test: "test".to_string(), num: 56, option: None}, obj);
+    let msg = serde_json::from_str::<LenientTupleTest>("[\"test\", \"56\"]").unwrap_err().to_string();
+    assert!(msg.contains("LenientTupleTest.num (key 1)"), "{}", msg);
+}
+
+#[test]
+fn test_lenient_tuple_extended() {
+    let bytes = to_bytes(&LenientTupleTest{test: "test".to_string(), num: 56, option: Some(true)});
+    let obj = from_bytes(&bytes);
+    assert_eq!(LenientTupleTestReduced{test: "test".to_string(), num: 56}, obj);
+}
+
+#[derive(Debug, PartialEq)]
+struct RequiredMapTest {
+    test: String,
+    id: NonZeroU64,
+    option: Option<bool>,
+}
+serde_impl!(RequiredMapTest(String!) {
+    test: String => "test",
+    id: NonZeroU64 => "num",
+    option: Option<bool> => "option"
+});
+
+#[test]
+fn test_required_map() {
+    test_obj(RequiredMapTest{test: "test".to_string(), id: NonZeroU64::new(56).unwrap(), option: Some(true)});
+    let bytes = to_bytes(&StrMapTest{test: "test".to_string(), num: 56, option: None});
+    let obj = from_bytes(&bytes);
+    assert_eq!(RequiredMapTest{test: "test".to_string(), id: NonZeroU64::new(56).unwrap(), option: None}, obj);
+}
+
+#[test]
+fn test_required_map_missing() {
+    let bytes = to_bytes(&StrMapTestReduced{test: "test".to_string(), option: Some(true)});
+    let cursor = Cursor::new(&bytes);
+    let mut reader = rmp_serde::Deserializer::new(cursor);
+    let msg = format!("{:?}", <RequiredMapTest as serde::Deserialize>::deserialize(&mut reader).unwrap_err());
+    assert!(msg.contains("RequiredMapTest.id"), "{}", msg);
+    assert!(msg.contains("missing field"), "{}", msg);
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct NestedTest {
+    name: String,
+    cpu: u32,
+    mem: u64,
+}
+serde_impl!(NestedTest(String) {
+    name: String => "name"
+} nested {
+    "limits" => {
+        cpu: u32 => "cpu",
+        mem: u64 => "mem"
+    }
+});
+
+#[test]
+fn test_nested() {
+    test_obj(NestedTest{name: "test".to_string(), cpu: 2, mem: 1024});
+    let obj: BTreeMap<String, serde_utils::Obj> = from_bytes(&to_bytes(&NestedTest{name: "test".to_string(), cpu: 2, mem: 1024}));
+    let mut limits = BTreeMap::new();
+    limits.insert(serde_utils::Obj::Str("cpu".to_string()), serde_utils::Obj::Unsigned(2));
+    limits.insert(serde_utils::Obj::Str("mem".to_string()), serde_utils::Obj::Unsigned(1024));
+    assert_eq!(obj.get("limits"), Some(&serde_utils::Obj::Map(limits.into())));
+    assert_eq!(obj.len(), 2);
+}
+
+mod keys {
+    pub const TEST: &str = "test";
+    pub const NUM: &str = "num";
+    pub const OPTION: u64 = 2;
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct ConstKeyMapTest {
+    test: String,
+    num: u64,
+    option: Option<bool>,
+}
+serde_impl!(ConstKeyMapTest(String) {
+    test: String => keys::TEST,
+    num: u64 => keys::NUM,
+    option: Option<bool> => "option"
+});
+
+#[derive(Default, Debug, PartialEq)]
+struct ConstKeyIntMapTest {
+    test: String,
+    option: Option<bool>,
+}
+serde_impl!(ConstKeyIntMapTest(u64) {
+    test: String => 0,
+    option: Option<bool> => keys::OPTION
+});
+
+#[test]
+fn test_const_key_map() {
+    test_obj(ConstKeyMapTest{test: "test".to_string(), num: 56, option: Some(true)});
+    let bytes = to_bytes(&ConstKeyMapTest{test: "test".to_string(), num: 56, option: Some(true)});
+    assert_eq!(StrMapTest{test: "test".to_string(), num: 56, option: Some(true)}, from_bytes(&bytes));
+    let bytes = to_bytes(&ConstKeyIntMapTest{test: "test".to_string(), option: Some(true)});
+    assert_eq!(IntMapTestReduced{test: "test".to_string(), option: Some(true)}, from_bytes(&bytes));
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct BatchTest {
+    test: String,
+    num: u64,
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct BatchCompressedTest {
+    test: String,
+    option: Option<bool>,
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct BatchTupleTest {
+    first: u64,
+    second: String,
+}
+
+serde_impl!(key u64;
+    BatchTest(_) {
+        test: String => 0,
+        num: u64 => 1
+    };
+    BatchCompressedTest(_?) {
+        test: String => 0,
+        option: Option<bool> => 2
+    };
+    BatchTupleTest {
+        first: u64,
+        second: String
+    };
+);
+
+#[test]
+fn test_batch() {
+    test_obj(BatchTest{test: "test".to_string(), num: 56});
+    test_obj(BatchCompressedTest{test: "test".to_string(), option: None});
+    test_obj(BatchTupleTest{first: 1, second: "test".to_string()});
+    let bytes = to_bytes(&BatchCompressedTest{test: "test".to_string(), option: Some(true)});
+    assert_eq!(IntMapTestReduced{test: "test".to_string(), option: Some(true)}, from_bytes(&bytes));
+    assert_eq!(to_bytes(&BatchCompressedTest::default()).len(), 1);
+}
+
+#[test]
+fn test_key_constants() {
+    assert_eq!(StrMapTest::KEYS.test, "test");
+    assert_eq!(StrMapTest::KEYS.option, "option");
+    assert_eq!(StrMapTest::FIELD_KEYS, &[("test", "test"), ("num", "num"), ("option", "option")]);
+    assert_eq!(IntMapTest::KEYS.num, 1);
+    assert_eq!(BinMapTest::KEYS.test, b"test");
+    assert_eq!(EnumKeyMapTest::KEYS.option, FieldId::Option);
+    assert_eq!(ConstKeyMapTest::KEYS.num, keys::NUM);
+    assert_eq!(BatchCompressedTest::FIELD_KEYS, &[("test", 0), ("option", 2)]);
+    assert_eq!(NestedTest::KEYS.mem, "mem");
+    assert_eq!(NestedTest::FIELD_KEYS, &[("name", "name"), ("cpu", "cpu"), ("mem", "mem")]);
+    assert_eq!(VersionedTest::FIELD_KEYS, &[("name", "name"), ("size", "size")]);
+}
+
+#[test]
+fn test_fields() {
+    let fields = IntMapTest::fields();
+    assert_eq!(fields.len(), 3);
+    assert_eq!(fields[0], serde_utils::FieldInfo{name: "test", key: 0, type_name: "String", optional: false});
+    assert_eq!(fields[2], serde_utils::FieldInfo{name: "option", key: 2, type_name: "Option<bool>", optional: true});
+    assert_eq!(StrMapTest::fields()[1].key, "num");
+    assert_eq!(NestedTest::fields().iter().map(|f| f.name).collect::<Vec<_>>(), vec!["name", "cpu", "mem"]);
+    assert_eq!(TupleTest::fields().iter().map(|f| (f.name, f.key)).collect::<Vec<_>>(), vec![("test", 0), ("num", 1), ("option", 2)]);
+}
+
+#[derive(Default, Debug)]
+struct NoEqPayload {
+    data: Vec<u8>,
+}
+serde_impl!(NoEqPayload {
+    data: Vec<u8>
+});
+
+#[derive(Default, Debug)]
+struct CompressedPredicateTest {
+    test: String,
+    num: u64,
+    payload: NoEqPayload,
+}
+serde_impl!(CompressedPredicateTest(u64?) {
+    test: String => 0,
+    #[is_default(|num: &u64| *num <= 1)]
+    num: u64 => 1,
+    #[is_default(|payload: &NoEqPayload| payload.data.is_empty())]
+    payload: NoEqPayload => 2
+});
+
+#[test]
+fn test_compressed_predicate() {
+    let bytes = to_bytes(&CompressedPredicateTest{test: "test".to_string(), num: 1, payload: NoEqPayload::default()});
+    assert_eq!(bytes, to_bytes(&CompressedIntMapTest{test: "test".to_string(), num: 0, option: None}));
+    let bytes = to_bytes(&CompressedPredicateTest{test: "".to_string(), num: 2, payload: NoEqPayload{data: vec![1]}});
+    let obj: CompressedPredicateTest = from_bytes(&bytes);
+    assert_eq!(obj.num, 2);
+    assert_eq!(obj.payload.data, vec![1]);
+    assert_eq!(to_bytes(&obj), bytes);
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct StrictCompressedTest {
+    test: String,
+    option: Option<bool>,
+}
+serde_impl!(StrictCompressedTest(String?) strict {
+    test: String => "test",
+    option: Option<bool> => "option"
+});
+
+#[derive(Debug, PartialEq)]
+struct StrictRequiredTest {
+    test: String,
+    option: Option<bool>,
+}
+serde_impl!(StrictRequiredTest(String!) strict {
+    test: String => "test",
+    option: Option<bool> => "option"
+});
+
+#[test]
+fn test_strict() {
+    test_obj(StrictCompressedTest{test: "test".to_string(), option: None});
+    assert_eq!(to_bytes(&StrictCompressedTest::default()).len(), 1);
+    test_obj(StrictRequiredTest{test: "test".to_string(), option: Some(true)});
+    let bytes = to_bytes(&StrMapTestReduced{test: "test".to_string(), option: Some(true)});
+    assert_eq!(StrictCompressedTest{test: "test".to_string(), option: Some(true)}, from_bytes(&bytes));
+    let bytes = to_bytes(&StrMapTest{test: "test".to_string(), num: 56, option: Some(true)});
+    let mut reader = rmp_serde::Deserializer::new(Cursor::new(&bytes));
+    let msg = format!("{:?}", <StrictCompressedTest as serde::Deserialize>::deserialize(&mut reader).unwrap_err());
+    assert!(msg.contains("StrictCompressedTest: unknown key, expected one of:"), "{}", msg);
+    let mut reader = rmp_serde::Deserializer::new(Cursor::new(&bytes));
+    assert!(<StrictRequiredTest as serde::Deserialize>::deserialize(&mut reader).is_err());
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct IgnoreCaseMapTest {
+    test: String,
+    num: u64,
+}
+serde_impl!(IgnoreCaseMapTest(IgnoreCase) {
+    test: String => "test",
+    num: u64 => "num"
+});
+
+#[test]
+fn test_ignore_case_map() {
+    test_obj(IgnoreCaseMapTest{test: "test".to_string(), num: 56});
+    let mut map = BTreeMap::new();
+    map.insert("Test", serde_utils::Obj::Str("test".to_string()));
+    map.insert("NUM", serde_utils::Obj::Unsigned(56));
+    assert_eq!(IgnoreCaseMapTest{test: "test".to_string(), num: 56}, from_bytes(&to_bytes(&map)));
+    let bytes = to_bytes(&IgnoreCaseMapTest{test: "test".to_string(), num: 56});
+    assert_eq!(StrMapTestReduced{test: "test".to_string(), option: None}, from_bytes(&bytes));
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct AutoStrMapTest {
+    test: String,
+    num: u64,
+    option: Option<bool>,
+}
+serde_impl!(AutoStrMapTest(String) {
+    test: String,
+    num: u64,
+    option: Option<bool>
+});
+
+#[derive(Default, Debug, PartialEq)]
+struct AutoIntMapTest {
+    test: String,
+    num: u64,
+    option: Option<bool>,
+}
+serde_impl!(AutoIntMapTest(u64?) strict {
+    test: String,
+    num: u64,
+    option: Option<bool>
+});
+
+#[derive(Default, Debug, PartialEq)]
+struct AutoBinMapTest {
+    test: String,
+    num: u64,
+}
+serde_impl!(AutoBinMapTest(ByteBuf) {
+    test: String,
+    num: u64
+});
+
+#[test]
+fn test_auto_keys() {
+    test_obj(AutoStrMapTest{test: "test".to_string(), num: 56, option: Some(true)});
+    let bytes = to_bytes(&AutoStrMapTest{test: "test".to_string(), num: 56, option: Some(true)});
+    assert_eq!(StrMapTest{test: "test".to_string(), num: 56, option: Some(true)}, from_bytes(&bytes));
+    assert_eq!(AutoStrMapTest::KEYS.num, "num");
+    test_obj(AutoIntMapTest{test: "test".to_string(), num: 0, option: Some(true)});
+    let bytes = to_bytes(&AutoIntMapTest{test: "test".to_string(), num: 56, option: Some(true)});
+    assert_eq!(IntMapTest{test: "test".to_string(), num: 56, option: Some(true)}, from_bytes(&bytes));
+    assert_eq!(AutoIntMapTest::FIELD_KEYS, &[("test", 0), ("num", 1), ("option", 2)]);
+    let bytes = to_bytes(&AutoBinMapTest{test: "test".to_string(), num: 56});
+    assert_eq!(BinMapTest{test: "test".to_string(), num: 0}, from_bytes(&bytes));
+    assert_eq!(AutoBinMapTest::KEYS.num, b"num");
+    test_obj(AutoBinMapTest{test: "test".to_string(), num: 56});
+    let mut map = BTreeMap::new();
+    map.insert("num", "test");
+    let bytes = to_bytes(&map);
+    let mut reader = rmp_serde::Deserializer::new(Cursor::new(&bytes));
+    let msg = format!("{:?}", <AutoStrMapTest as serde::Deserialize>::deserialize(&mut reader).unwrap_err());
+    assert!(msg.contains("AutoStrMapTest.num (key \\\"num\\\")"), "{}", msg);
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct RenameMapTest {
+    user_id: u64,
+    display_name: String,
+    is_admin2fa: Option<bool>,
+}
+serde_impl!(RenameMapTest(String?, rename_all = "camelCase") strict {
+    user_id: u64,
+    display_name: String,
+    is_admin2fa: Option<bool>
+});
+
+#[derive(Default, Debug, PartialEq)]
+struct RenameBinMapTest {
+    user_id: u64,
+}
+serde_impl!(RenameBinMapTest(ByteBuf, rename_all = "SCREAMING-KEBAB-CASE") {
+    user_id: u64
+});
+
+#[test]
+fn test_rename_all() {
+    let obj = RenameMapTest{user_id: 5, display_name: "test".to_string(), is_admin2fa: Some(true)};
+    test_obj(RenameMapTest{user_id: 5, display_name: "test".to_string(), is_admin2fa: Some(true)});
+    assert_eq!(serde_json::to_string(&obj).unwrap(), "{\"userId\":5,\"displayName\":\"test\",\"isAdmin2fa\":true}");
+    assert_eq!(RenameMapTest::KEYS.display_name, "displayName");
+    assert_eq!(RenameMapTest::FIELD_KEYS[2], ("is_admin2fa", "isAdmin2fa"));
+    let msg = serde_json::from_str::<RenameMapTest>("{\"userId\":\"x\"}").unwrap_err().to_string();
+    assert!(msg.contains("RenameMapTest.user_id (key \"userId\")"), "{}", msg);
+    let msg = serde_json::from_str::<RenameMapTest>("{\"user_id\":5}").unwrap_err().to_string();
+    assert!(msg.contains("\"userId\", \"displayName\", \"isAdmin2fa\""), "{}", msg);
+    assert_eq!(RenameBinMapTest::KEYS.user_id, b"USER-ID");
+    test_obj(RenameBinMapTest{user_id: 5});
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct ReservedTest {
+    test: String,
+    option: Option<bool>,
+}
+serde_impl!(ReservedTest(u64) strict {
+    reserved 1, 3;
+    test: String => 0,
+    option: Option<bool> => 2
+});
+
+#[derive(Debug, PartialEq)]
+struct ReservedRequiredTest {
+    test: String,
+}
+serde_impl!(ReservedRequiredTest(String!) {
+    reserved "num", "option";
+    test: String => "test"
+});
+
+#[test]
+fn test_reserved_keys() {
+    test_obj(ReservedTest{test: "test".to_string(), option: Some(true)});
+    let bytes = to_bytes(&IntMapTest{test: "test".to_string(), num: 56, option: Some(true)});
+    assert_eq!(ReservedTest{test: "test".to_string(), option: Some(true)}, from_bytes(&bytes));
+    let mut map = BTreeMap::new();
+    map.insert(4u64, "test");
+    let mut reader = rmp_serde::Deserializer::new(Cursor::new(to_bytes(&map)));
+    assert!(<ReservedTest as serde::Deserialize>::deserialize(&mut reader).is_err());
+    let bytes = to_bytes(&StrMapTest{test: "test".to_string(), num: 56, option: None});
+    assert_eq!(ReservedRequiredTest{test: "test".to_string()}, from_bytes(&bytes));
+    assert_eq!(ReservedTest::FIELD_KEYS.len(), 2);
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct FlagsTest {
+    read: bool,
+    write: bool,
+    exec: bool,
+}
+serde_impl!(FlagsTest(u8) flags {
+    read => 0x01,
+    write => 0x02,
+    exec => 0x04
+});
+
+#[derive(Default, Debug, PartialEq)]
+struct LenientFlagsTest {
+    read: bool,
+    write: bool,
+    other: u16,
+}
+serde_impl!(LenientFlagsTest(u16) flags {
+    read => 0x01,
+    write => 0x02,
+    .. => other
+});
+
+#[test]
+fn test_flags() {
+    test_obj(FlagsTest{read: true, write: false, exec: true});
+    test_obj(FlagsTest::default());
+    assert_eq!(to_bytes(&FlagsTest{read: true, write: false, exec: true}), to_bytes(&5u8));
+    assert_eq!(FlagsTest{read: false, write: true, exec: false}, from_bytes(&to_bytes(&2u8)));
+    let mut reader = rmp_serde::Deserializer::new(Cursor::new(to_bytes(&0x0au8)));
+    let msg = format!("{:?}", <FlagsTest as serde::Deserialize>::deserialize(&mut reader).unwrap_err());
+    assert!(msg.contains("FlagsTest: unknown bits 0x8"), "{}", msg);
+    test_obj(LenientFlagsTest{read: true, write: false, other: 0x100});
+    assert_eq!(LenientFlagsTest{read: false, write: true, other: 0x8}, from_bytes(&to_bytes(&0x0au8)));
+    assert_eq!(to_bytes(&LenientFlagsTest{read: false, write: true, other: 0x8}), to_bytes(&0x0au8));
+    assert_eq!(to_bytes(&LenientFlagsTest{read: true, write: false, other: 0x03}), to_bytes(&1u8));
+    assert_eq!(LenientFlagsTest{read: true, write: false, other: 0}, from_bytes(&to_bytes(&LenientFlagsTest{read: true, write: false, other: 0x03})));
+    assert_eq!(FlagsTest::KEYS.exec, 0x04);
+}
+
+struct Session {
+    users: Vec<String>,
+    lookups: usize,
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct UserRef(usize, String);
+
+impl serde::Serialize for UserRef {
+    fn serialize<S: serde::Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        ser.serialize_u64(self.0 as u64)
+    }
+}
+
+impl<'de> serde_utils::DeserializeWith<'de, Session> for UserRef {
+    fn deserialize_with<D: serde::Deserializer<'de>>(session: &mut Session, de: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+        let id = <usize as serde::Deserialize>::deserialize(de)?;
+        session.lookups += 1;
+        match session.users.get(id) {
+            Some(name) => Ok(UserRef(id, name.clone())),
+            None => Err(D::Error::custom(format_args!("unknown user {}", id)))
+        }
+    }
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct ContextTest {
+    text: String,
+    author: UserRef,
+    mentions: Vec<UserRef>,
+    editor: Option<UserRef>,
+}
+serde_impl!(ContextTest(String?) context(Session) strict {
+    text: String => "text",
+    #[context]
+    author: UserRef => "author",
+    #[context]
+    mentions: Vec<UserRef> => "mentions",
+    #[context]
+    editor: Option<UserRef> => "editor"
+});
+
+#[test]
+fn test_context() {
+    use serde::de::DeserializeSeed;
+    use serde_utils::ContextSeed;
+    let mut session = Session { users: vec!["alice".to_string(), "bob".to_string()], lookups: 0 };
+    let obj = ContextTest {
+        text: "hi".to_string(),
+        author: UserRef(1, "bob".to_string()),
+        mentions: vec![UserRef(0, "alice".to_string()), UserRef(1, "bob".to_string())],
+        editor: None
+    };
+    let bytes = to_bytes(&obj);
+    let mut reader = rmp_serde::Deserializer::new(Cursor::new(&bytes));
+    assert_eq!(obj, ContextSeed::new(&mut session).deserialize(&mut reader).unwrap());
+    assert_eq!(session.lookups, 3);
+    let invalid = serde_utils::to_obj(&ContextTest { editor: Some(UserRef(5, String::new())), ..ContextTest::default() }).unwrap();
+    let de = serde_utils::ObjDeserializer::<serde::de::value::Error>::new(invalid);
+    let msg = ContextSeed::<ContextTest, _>::new(&mut session).deserialize(de).unwrap_err().to_string();
+    assert!(msg.contains("ContextTest.editor (key \"editor\"): unknown user 5"), "{}", msg);
+    let bytes = to_bytes(&StrMapTest{test: "test".to_string(), num: 56, option: Some(true)});
+    let mut reader = rmp_serde::Deserializer::new(Cursor::new(&bytes));
+    assert!(ContextSeed::<ContextTest, _>::new(&mut session).deserialize(&mut reader).is_err());
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct InternSource {
+    host: String,
+    tags: Vec<String>,
+    value: u64,
+}
+serde_impl_ser!(InternSource(String) {
+    host: String => "host",
+    tags: Vec<String> => "tags",
+    value: u64 => "value"
+});
+
+#[derive(Default, Debug, PartialEq)]
+struct InternTest {
+    host: std::rc::Rc<str>,
+    tags: Vec<std::rc::Rc<str>>,
+    value: u64,
+}
+serde_impl_de!(InternTest(String) context(serde_utils::Interner) {
+    #[context]
+    host: std::rc::Rc<str> => "host",
+    #[context]
+    tags: Vec<std::rc::Rc<str>> => "tags",
+    value: u64 => "value"
+});
+
+#[test]
+fn test_interner() {
+    use std::rc::Rc;
+    use serde::de::DeserializeSeed;
+    use serde_utils::{ContextSeed, Interner, InternerStats};
+    let mut interner = Interner::new();
+    let records: Vec<InternTest> = (0..10).map(|value| {
+        let bytes = to_bytes(&InternSource { host: "web1".to_string(), tags: vec!["a".to_string(), "b".to_string()], value });
+        let mut reader = rmp_serde::Deserializer::new(Cursor::new(&bytes));
+        ContextSeed::new(&mut interner).deserialize(&mut reader).unwrap()
+    }).collect();
+    assert_eq!(records[3].value, 3);
+    assert_eq!(&*records[3].host, "web1");
+    assert!(Rc::ptr_eq(&records[0].host, &records[9].host));
+    assert!(Rc::ptr_eq(&records[0].tags[1], &records[5].tags[1]));
+    assert_eq!(interner.stats(), InternerStats { strings: 3, bytes: 6, hits: 27, misses: 3 });
+    drop(records);
+    interner.intern("other");
+    interner.shrink();
+    assert_eq!(interner.stats().strings, 0);
+    assert_eq!(interner.stats().bytes, 0);
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct DiagnosticsTest {
+    test: String,
+    count: u64,
+}
+serde_impl!(DiagnosticsTest(String) {
+    test: String => "test",
+    count: u64 => "count"
+});
+
+#[derive(Default, Debug, PartialEq)]
+struct DiagnosticsNestedTest {
+    num: u64,
+    cpu: u32,
+    mem: u64,
+}
+serde_impl!(DiagnosticsNestedTest(String) {
+    num: u64 => "num"
+} nested {
+    "limits" => {
+        cpu: u32 => "cpu",
+        mem: u64 => "mem"
+    }
+});
+
+#[test]
+fn test_diagnostics() {
+    use std::sync::{Arc, Mutex};
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let hook_events = events.clone();
+    serde_utils::set_diagnostics_hook(move |event| {
+        if event.to_string().starts_with("Diagnostics") {
+            hook_events.lock().unwrap().push(event.to_string());
+        }
+    });
+    let bytes = to_bytes(&StrMapTest{test: "test".to_string(), num: 56, option: Some(true)});
+    assert_eq!(DiagnosticsTest{test: "test".to_string(), count: 0}, from_bytes(&bytes));
+    let obj: DiagnosticsNestedTest = from_bytes(&bytes);
+    assert_eq!(obj.num, 56);
+    serde_utils::clear_diagnostics_hook();
+    let _: DiagnosticsTest = from_bytes(&bytes);
+    assert_eq!(*events.lock().unwrap(), vec![
+        "DiagnosticsTest: skipped unknown key \"num\"",
+        "DiagnosticsTest: skipped unknown key \"option\"",
+        "DiagnosticsTest.count (key \"count\"): missing, using default value",
+        "DiagnosticsNestedTest: skipped unknown key \"test\"",
+        "DiagnosticsNestedTest: skipped unknown key \"option\"",
+        "DiagnosticsNestedTest.cpu (key \"cpu\"): missing, using default value",
+        "DiagnosticsNestedTest.mem (key \"mem\"): missing, using default value",
+    ]);
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct TreeNode {
+    name: String,
+    children: Vec<TreeNode>,
+    next: Option<Box<TreeNode>>,
+}
+serde_impl!(TreeNode(String) {
+    name: String => "name",
+    children: Vec<TreeNode> => "children",
+    next: Option<Box<TreeNode>> => "next"
+});
+
+#[derive(Debug, PartialEq)]
+struct TreeNodeTuple {
+    value: u64,
+    children: Vec<TreeNodeTuple>,
+    next: Option<Box<TreeNodeTuple>>,
+}
+serde_impl!(TreeNodeTuple {
+    value: u64,
+    children: Vec<TreeNodeTuple>,
+    next: Option<Box<TreeNodeTuple>>
+});
+
+#[derive(Debug, PartialEq)]
+struct TreeNodeRequired {
+    name: String,
+    children: Vec<TreeNodeRequired>,
+}
+serde_impl!(TreeNodeRequired(String!) {
+    name: String => "name",
+    children: Vec<TreeNodeRequired> => "children"
+});
+
+#[test]
+fn test_recursive() {
+    let leaf = |name: &str| TreeNode { name: name.to_string(), ..Default::default() };
+    test_obj(TreeNode {
+        name: "root".to_string(),
+        children: vec![leaf("a"), TreeNode { children: vec![leaf("b")], ..leaf("c") }],
+        next: Some(Box::new(TreeNode { next: Some(Box::new(leaf("e"))), ..leaf("d") }))
+    });
+    test_obj(TreeNodeTuple {
+        value: 1,
+        children: vec![TreeNodeTuple { value: 2, children: vec![], next: None }],
+        next: Some(Box::new(TreeNodeTuple { value: 3, children: vec![], next: None }))
+    });
+    test_obj(TreeNodeRequired {
+        name: "root".to_string(),
+        children: vec![TreeNodeRequired { name: "child".to_string(), children: vec![] }]
+    });
+}
+
+#[cfg(feature = "rc")]
+#[derive(Default, Debug, PartialEq)]
+struct RcTest {
+    name: std::rc::Rc<str>,
+    child: std::sync::Arc<StrMapTestReduced>,
+}
+#[cfg(feature = "rc")]
+serde_impl!(RcTest(String) {
+    name: std::rc::Rc<str> => "name",
+    child: std::sync::Arc<StrMapTestReduced> => "child"
+});
+
+#[cfg(feature = "rc")]
+#[test]
+fn test_rc_fields() {
+    test_obj(RcTest { name: "node".into(), child: std::sync::Arc::new(StrMapTestReduced { test: "test".to_string(), option: Some(true) }) });
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct BorrowTest<'a> {
+    name: Cow<'a, str>,
+    data: Cow<'a, [u8]>,
+    num: u64,
+}
+serde_impl!(BorrowTest<'a>(String) {
+    name: Cow<'a, str> => "name",
+    data: Cow<'a, [u8]> => "data",
+    num: u64 => "num"
+});
+
+#[derive(Debug, PartialEq)]
+struct BorrowTupleTest<'a> {
+    name: Cow<'a, str>,
+    data: Cow<'a, [u8]>,
+}
+serde_impl!(BorrowTupleTest<'a> {
+    name: Cow<'a, str>,
+    data: Cow<'a, [u8]>
+});
+
+#[derive(Debug, PartialEq)]
+struct BorrowNewtypeTest<'a> {
+    name: Cow<'a, str>,
+}
+serde_impl!(BorrowNewtypeTest<'a> {
+    name: Cow<'a, str>
+});
+
+#[derive(Default, Debug, PartialEq)]
+struct StaticCowTest {
+    name: Cow<'static, str>,
+    data: Cow<'static, [u8]>,
+}
+serde_impl!(StaticCowTest(String) {
+    name: Cow<'static, str> => "name",
+    data: Cow<'static, [u8]> => "data"
+});
+
+#[test]
+fn test_cow_fields() {
+    use serde_utils::Obj;
+    let obj = BorrowTest { name: Cow::Borrowed("name"), data: Cow::Borrowed(&[1, 2, 3]), num: 5 };
+    let bytes = to_bytes(&obj);
+    assert_eq!(from_bytes::<Obj>(&bytes).pointer("data").as_deref(), Some(&Obj::from_bytes(vec![1, 2, 3])));
+    let read: BorrowTest = rmp_serde::from_slice(&bytes).unwrap();
+    assert_eq!(read, obj);
+    assert!(matches!((&read.name, &read.data), (Cow::Borrowed(_), Cow::Borrowed(_))));
+    let read: BorrowTest = serde_json::from_str("{\"name\":\"a\\\"b\",\"data\":[1,2],\"other\":1}").unwrap();
+    assert_eq!(read, BorrowTest { name: Cow::Owned("a\"b".to_string()), data: Cow::Owned(vec![1, 2]), num: 0 });
+    assert!(matches!(read.name, Cow::Owned(_)));
+    assert_eq!(BorrowTest::FIELD_KEYS.len(), 3);
+    let obj = BorrowTupleTest { name: Cow::Borrowed("name"), data: Cow::Borrowed(b"data") };
+    let bytes = to_bytes(&obj);
+    let read: BorrowTupleTest = rmp_serde::from_slice(&bytes).unwrap();
+    assert_eq!(read, obj);
+    assert!(matches!((&read.name, &read.data), (Cow::Borrowed(_), Cow::Borrowed(_))));
+    let err = serde_json::from_str::<BorrowTupleTest>("[\"name\"]").unwrap_err().to_string();
+    assert!(err.starts_with("BorrowTupleTest: invalid length 1"), "{}", err);
+    let read: BorrowNewtypeTest = serde_json::from_str("\"name\"").unwrap();
+    assert!(matches!(read.name, Cow::Borrowed("name")));
+    test_obj(StaticCowTest { name: Cow::Borrowed("name"), data: Cow::Owned(vec![1, 2]) });
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct Meters;
+
+#[derive(Default, Debug, PartialEq)]
+struct SkipTest {
+    value: u64,
+    unit: std::marker::PhantomData<Meters>,
+    marker: (),
+    name: String,
+}
+serde_impl!(SkipTest(String) {
+    value: u64 => "value",
+    unit: std::marker::PhantomData<Meters> => "unit",
+    marker: () => "marker",
+    name: String => "name"
+});
+
+#[derive(Debug, PartialEq)]
+struct SkipTupleTest {
+    value: u64,
+    unit: std::marker::PhantomData<Meters>,
+    name: String,
+}
+serde_impl!(SkipTupleTest {
+    value: u64,
+    unit: std::marker::PhantomData<Meters>,
+    name: String
+});
+
+#[derive(Debug, PartialEq)]
+struct SkipRequiredTest {
+    value: u64,
+    unit: std::marker::PhantomData<Meters>,
+}
+serde_impl!(SkipRequiredTest(String!) {
+    value: u64 => "value",
+    unit: std::marker::PhantomData<Meters> => "unit"
+});
+
+#[test]
+fn test_skipped_fields() {
+    test_obj(SkipTest { value: 5, name: "test".to_string(), ..Default::default() });
+    let obj = SkipTest { value: 5, name: "test".to_string(), ..Default::default() };
+    assert_eq!(serde_json::to_string(&obj).unwrap(), "{\"value\":5,\"name\":\"test\"}");
+    let obj = SkipTupleTest { value: 5, unit: Default::default(), name: "test".to_string() };
+    assert_eq!(serde_json::to_string(&obj).unwrap(), "[5,\"test\"]");
+    assert_eq!(serde_json::from_str::<SkipTupleTest>("[5,\"test\"]").unwrap(), obj);
+    let err = serde_json::from_str::<SkipTupleTest>("[5]").unwrap_err().to_string();
+    assert!(err.starts_with("SkipTupleTest: invalid length 1"), "{}", err);
+    let obj = SkipRequiredTest { value: 5, unit: Default::default() };
+    assert_eq!(serde_json::to_string(&obj).unwrap(), "{\"value\":5}");
+    assert_eq!(serde_json::from_str::<SkipRequiredTest>("{\"value\":5}").unwrap(), obj);
+    assert_eq!(SkipTest::fields().len(), 4);
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct ArrayTest {
+    hash: [u8; 32],
+    points: [(u8, u8); 2],
+}
+serde_impl!(ArrayTest(String) {
+    hash: [u8; 32] => "hash",
+    points: [(u8, u8); 2] => "points"
+});
+
+#[derive(Debug, PartialEq)]
+struct LongArrayTest {
+    signature: [u8; 64],
+    name: String,
+}
+serde_impl!(LongArrayTest(String!) {
+    signature: [u8; 64] => "signature",
+    name: String => "name"
+});
+
+#[derive(Debug, PartialEq)]
+struct ArrayTupleTest {
+    id: u64,
+    key: [u16; 40],
+}
+serde_impl!(ArrayTupleTest {
+    id: u64,
+    key: [u16; 40]
+});
+
+#[derive(Debug, PartialEq)]
+struct ArrayNewtypeTest {
+    key: [u8; 48],
+}
+serde_impl!(ArrayNewtypeTest {
+    key: [u8; 48]
+});
+
+#[test]
+fn test_array_fields() {
+    test_obj(ArrayTest { hash: [7; 32], points: [(1, 2), (3, 4)] });
+    test_obj(LongArrayTest { signature: [3; 64], name: "test".to_string() });
+    test_obj(ArrayTupleTest { id: 5, key: [9; 40] });
+    test_obj(ArrayNewtypeTest { key: [1; 48] });
+    let obj = ArrayTupleTest { id: 5, key: [9; 40] };
+    let json = serde_json::to_string(&obj).unwrap();
+    assert_eq!(serde_json::from_str::<(u64, Vec<u16>)>(&json).unwrap(), (5, vec![9; 40]));
+    let err = serde_json::from_str::<ArrayTupleTest>("[5,[1,2,3]]").unwrap_err().to_string();
+    assert!(err.starts_with("ArrayTupleTest: invalid length 3, expected an array of length 40"), "{}", err);
+    let err = serde_json::from_str::<ArrayTest>("{\"hash\":[1,2]}").unwrap_err().to_string();
+    assert!(err.contains("invalid length 2, expected an array of length 32"), "{}", err);
+    let json = format!("{{\"signature\":{:?},\"name\":\"\"}}", vec![0u8; 65]);
+    let err = serde_json::from_str::<LongArrayTest>(&json).unwrap_err().to_string();
+    assert!(err.contains("expected an array of length 64"), "{}", err);
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct ReadableTest {
+    id: u64,
+    name: String,
+}
+serde_impl!(ReadableTest(u64, readable) strict {
+    id: u64 => 0,
+    name: String => 1
+});
+
+#[test]
+fn test_readable_keys() {
+    // rmp-serde 0.13 claims to be human-readable, so the compact form is tested with CBOR
+    let obj = ReadableTest { id: 5, name: "test".to_string() };
+    let compact = serde_cbor::to_vec(&obj).unwrap();
+    let keys: BTreeMap<u64, serde_utils::Obj> = serde_cbor::from_slice(&compact).unwrap();
+    assert_eq!(keys.keys().collect::<Vec<_>>(), vec![&0, &1]);
+    assert_eq!(serde_cbor::from_slice::<ReadableTest>(&compact).unwrap(), obj);
+    let names = serde_cbor::to_vec(&BTreeMap::from([("id", 5u64)])).unwrap();
+    assert_eq!(serde_cbor::from_slice::<ReadableTest>(&names).unwrap(), ReadableTest { id: 5, name: String::new() });
+    assert_eq!(serde_json::to_string(&obj).unwrap(), "{\"id\":5,\"name\":\"test\"}");
+    assert_eq!(serde_json::from_str::<ReadableTest>("{\"id\":5,\"name\":\"test\"}").unwrap(), obj);
+    assert_eq!(serde_json::from_str::<ReadableTest>("{\"0\":5,\"1\":\"test\"}").unwrap(), obj);
+    assert!(serde_json::from_str::<ReadableTest>("{\"2\":5}").is_err());
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct ExhaustiveTest {
+    test: String,
+    option: Option<bool>,
+    num: u64,
+}
+serde_impl!(exhaustive ExhaustiveTest(u64?) strict {
+    reserved 3;
+    test: String => 0,
+    #[is_default(|num: &u64| *num == 0)]
+    num: u64 => 1,
+    option: Option<bool> => 2
+});
+
+#[derive(Default, Debug, PartialEq)]
+struct ExhaustiveTupleTest {
+    first: u64,
+    second: Vec<(u8, bool)>,
+}
+serde_impl!(key u8;
+    exhaustive ExhaustiveTupleTest {
+        first: u64,
+        second: Vec<(u8, bool)>,
+        ..
+    }
+);
+
+#[test]
+fn test_exhaustive() {
+    test_obj(ExhaustiveTest { test: "test".to_string(), option: Some(true), num: 0 });
+    test_obj(ExhaustiveTupleTest { first: 1, second: vec![(2, true)] });
+}
+
+mod model {
+    #[derive(Default, Debug, PartialEq)]
+    pub struct Remote {
+        pub id: u64,
+        pub name: String,
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub enum RemoteKind {
+        A,
+        B
+    }
+}
+
+mod wire {
+    serde_impl!(super::model::Remote(String) {
+        id: u64 => "id",
+        name: String => "name"
+    });
+
+    serde_impl!(key u8;
+        super::model::RemoteKind(_) {
+            A => 1,
+            B => 2
+        }
+    );
+}
+
+#[test]
+fn test_path_types() {
+    test_obj(model::Remote { id: 7, name: "x".to_string() });
+    assert_eq!(to_bytes(&model::RemoteKind::B), to_bytes(&2u8));
+    assert_eq!(from_bytes::<model::RemoteKind>(&to_bytes(&1u8)), model::RemoteKind::A);
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct ComputedTest {
+    data: Vec<u8>,
+    name: String,
+}
+impl ComputedTest {
+    fn sum(&self) -> u32 {
+        self.data.iter().map(|b| u32::from(*b)).sum()
+    }
+}
+serde_impl!(exhaustive ComputedTest(String) strict {
+    reserved "old";
+    data: Vec<u8> => "data",
+    #[compute(ComputedTest::sum)]
+    sum: u32 => "sum",
+    name: String => "name"
+});
+
+#[test]
+fn test_computed_fields() {
+    let obj = ComputedTest { data: vec![1, 2, 3], name: "test".to_string() };
+    assert_eq!(serde_json::to_string(&obj).unwrap(), "{\"data\":[1,2,3],\"sum\":6,\"name\":\"test\"}");
+    test_obj(ComputedTest { data: vec![4, 5], name: String::new() });
+    let json = "{\"data\":[1,2,3],\"sum\":\"anything\",\"name\":\"test\"}";
+    assert_eq!(serde_json::from_str::<ComputedTest>(json).unwrap(), obj);
+    assert_eq!(ComputedTest::KEYS.sum, "sum");
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct ObjConvTest {
+    name: String,
+    kind: ObjConvKind,
+}
+
+#[derive(Default, Debug, PartialEq)]
+enum ObjConvKind {
+    #[default]
+    A,
+    B
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct ObjConvBorrowed<'a> {
+    topic: Cow<'a, str>,
+}
+
+serde_impl!(obj; schema; size; key u8;
+    ObjConvTest(_) {
+        name: String => 0,
+        kind: ObjConvKind => 1
+    };
+    ObjConvKind(_) {
+        A => 0,
+        B => 1
+    }
+);
+serde_impl!(obj; ObjConvBorrowed<'a>(String) {
+    topic: Cow<'a, str> => "topic"
+});
+
+#[derive(Default, Debug, PartialEq)]
+struct ObjConvPath {
+    path: std::path::PathBuf,
+}
+serde_impl!(obj; ObjConvPath(u8) {
+    path: std::path::PathBuf => 0
+});
+
+#[test]
+fn test_obj_conversions() {
+    use std::convert::TryFrom;
+    let val = ObjConvTest { name: "test".to_string(), kind: ObjConvKind::B };
+    let obj = serde_utils::Obj::try_from(&val).unwrap();
+    assert_eq!(obj, serde_utils::to_obj(&val).unwrap());
+    assert_eq!(ObjConvTest::try_from(obj).unwrap(), val);
+    assert!(ObjConvKind::try_from(serde_utils::Obj::Unsigned(5)).is_err());
+    let val = ObjConvBorrowed { topic: Cow::Borrowed("x") };
+    assert_eq!(ObjConvBorrowed::try_from(serde_utils::Obj::try_from(&val).unwrap()).unwrap(), val);
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        let val = ObjConvPath { path: std::ffi::OsStr::from_bytes(&[0xff]).into() };
+        assert!(serde_utils::Obj::try_from(&val).is_err());
+    }
+}
+
+#[test]
+fn test_serialized_size_hint() {
+    let val = ObjConvTest { name: "test".to_string(), kind: ObjConvKind::B };
+    assert_eq!(val.serialized_size_hint(), rmp_serde::to_vec(&val).unwrap().len());
+    assert_eq!(ObjConvKind::A.serialized_size_hint(), 1);
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct DualTest {
+    id: u64,
+    name: String,
+}
+serde_impl!(key u8;
+    DualTest(_, map_or_tuple) strict {
+        id: u64 => 0,
+        name: String => 1
+    };
+    DualTupleTest(_, tuple_or_map) {
+        id: u64 => 0
+    }
+);
+
+#[derive(Default, Debug, PartialEq)]
+struct DualTupleTest {
+    id: u64,
+}
+
+#[test]
+fn test_map_or_tuple() {
+    let obj = DualTest { id: 5, name: "test".to_string() };
+    let map = serde_cbor::to_vec(&obj).unwrap();
+    assert_eq!(serde_cbor::from_slice::<BTreeMap<u8, serde_utils::Obj>>(&map).unwrap().len(), 2);
+    assert_eq!(serde_cbor::from_slice::<DualTest>(&map).unwrap(), obj);
+    let tuple = serde_cbor::to_vec(&(5u64, "test")).unwrap();
+    assert_eq!(serde_cbor::from_slice::<DualTest>(&tuple).unwrap(), obj);
+    assert!(serde_cbor::from_slice::<DualTest>(&serde_cbor::to_vec(&(5u64,)).unwrap()).is_err());
+    assert!(serde_cbor::from_slice::<DualTest>(&serde_cbor::to_vec(&(5u64, "test", 1u8)).unwrap()).is_err());
+    assert!(serde_cbor::from_slice::<DualTest>(&serde_cbor::to_vec(&BTreeMap::from([(2u8, 1u8)])).unwrap()).is_err());
+    let obj = DualTupleTest { id: 7 };
+    assert_eq!(serde_json::to_string(&obj).unwrap(), "[7]");
+    assert_eq!(serde_json::from_str::<DualTupleTest>("[7]").unwrap(), obj);
+    assert_eq!(serde_json::from_str::<DualTupleTest>("{\"0\":7}").unwrap(), obj);
+    assert!(serde_json::from_str::<DualTupleTest>("[7,8]").is_err());
+    let map = serde_cbor::to_vec(&BTreeMap::from([(0u8, 7u8)])).unwrap();
+    assert_eq!(serde_cbor::from_slice::<DualTupleTest>(&map).unwrap(), obj);
+}
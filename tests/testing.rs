@@ -0,0 +1,121 @@
+#![cfg(all(feature = "msgpack", feature = "json", feature = "cbor"))]
+
+extern crate serde;
+#[macro_use] extern crate serde_utils;
+
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::panic;
+
+use serde_utils::Obj;
+use serde_utils::testing::{assert_matches_golden, assert_roundtrip, interesting_objs, write_corpus, Format};
+
+#[derive(Default, Debug, PartialEq)]
+struct Record {
+    name: String,
+    num: u64,
+    tags: Vec<String>
+}
+serde_impl!(Record(String) {
+    name: String => "name",
+    num: u64 => "num",
+    tags: Vec<String> => "tags"
+});
+
+fn record() -> Record {
+    Record { name: "test".to_string(), num: 42, tags: vec!["a".to_string(), "b".to_string()] }
+}
+
+fn panic_message<F: FnOnce() + panic::UnwindSafe>(f: F) -> String {
+    let err = panic::catch_unwind(f).unwrap_err();
+    match err.downcast::<String>() {
+        Ok(msg) => *msg,
+        Err(err) => err.downcast::<&str>().map(|msg| msg.to_string()).unwrap()
+    }
+}
+
+#[test]
+fn test_formats() {
+    assert_eq!(Format::all(), &[Format::Msgpack, Format::Json, Format::Cbor]);
+    assert_eq!(Format::from_extension("mpk"), Some(Format::Msgpack));
+    assert_eq!(Format::from_extension("txt"), None);
+    let bytes = Format::Json.serialize(&record()).unwrap();
+    assert_eq!(String::from_utf8(bytes.clone()).unwrap(), r#"{"name":"test","num":42,"tags":["a","b"]}"#);
+    assert_eq!(Format::Json.deserialize::<Record>(&bytes).unwrap(), record());
+    assert!(Format::Cbor.deserialize::<Record>(&bytes).is_err());
+}
+
+#[test]
+fn test_golden() {
+    assert_matches_golden(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden/record.msgpack"), &record());
+    assert_matches_golden(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden/record.json"), &record());
+    assert_matches_golden(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden/record.cbor"), &record());
+}
+
+#[test]
+fn test_golden_mismatch() {
+    let dir = env::temp_dir().join(format!("serde_utils_golden_{}", std::process::id()));
+    let path = dir.join("record.json");
+    assert_matches_golden(&path, &record());
+    let mut changed = record();
+    changed.num = 43;
+    changed.tags.pop();
+    let msg = panic_message(|| assert_matches_golden(&path, &changed));
+    assert!(msg.contains("differs from golden file"), "{}", msg);
+    assert!(msg.contains("num: expected Unsigned(42), found Unsigned(43)"), "{}", msg);
+    assert!(msg.contains("tags: expected 2 elements, found 1"), "{}", msg);
+    fs::write(&path, br#"{"name":"test","num":"42","tags":[]}"#).unwrap();
+    let msg = panic_message(|| assert_matches_golden(&path, &record()));
+    assert!(msg.contains("num: expected Str(\"42\"), found Unsigned(42)"), "{}", msg);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[derive(Debug, PartialEq)]
+struct Lossy(f64);
+
+impl serde::Serialize for Lossy {
+    fn serialize<S: serde::Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        ser.serialize_f64(self.0)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Lossy {
+    fn deserialize<D: serde::Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        <f64 as serde::Deserialize>::deserialize(de).map(|val| Lossy(val.floor()))
+    }
+}
+
+#[test]
+fn test_roundtrip() {
+    assert_roundtrip(&record());
+    assert_roundtrip(&vec![Some(1u8), None]);
+    let msg = panic_message(|| assert_roundtrip(&vec![Lossy(1.0), Lossy(2.5)]));
+    assert!(msg.contains("failed:\nmsgpack: 1: expected Float(2.5), found Float(2.0)"), "{}", msg);
+    assert!(msg.contains("\nJSON: 1: expected"), "{}", msg);
+    assert!(msg.contains("\nCBOR: 1: expected"), "{}", msg);
+    let msg = panic_message(|| assert_roundtrip(&f64::NAN));
+    assert!(msg.contains("JSON: failed to deserialize"), "{}", msg);
+}
+
+#[test]
+fn test_corpus() {
+    let objs = interesting_objs();
+    let names: HashSet<&str> = objs.iter().map(|&(name, _)| name).collect();
+    assert_eq!(names.len(), objs.len());
+    assert_eq!(objs.iter().find(|&&(name, _)| name == "nested_maps").unwrap().1.stats().max_depth, 100);
+    let dir = env::temp_dir().join(format!("serde_utils_corpus_{}", std::process::id()));
+    for &format in Format::all() {
+        let count = write_corpus(dir.join(format.name()), format).unwrap();
+        assert!(count > objs.len() / 2);
+        assert!(count <= objs.len());
+        for entry in fs::read_dir(dir.join(format.name())).unwrap() {
+            let path = entry.unwrap().path();
+            assert_eq!(path.extension().unwrap(), format.extension());
+            format.deserialize::<Obj>(&fs::read(&path).unwrap()).unwrap();
+        }
+    }
+    assert!(!dir.join("JSON").join("mixed_keys.json").exists());
+    assert!(dir.join("CBOR").join("mixed_keys.cbor").exists());
+    fs::remove_dir_all(&dir).unwrap();
+}